@@ -1,8 +1,8 @@
 use std::{
-    collections::VecDeque,
+    collections::{BTreeSet, VecDeque},
     error::Error,
     fmt::Display,
-    fs::{File, create_dir},
+    fs::File,
     io::Read,
     path::{Path, PathBuf},
     process::ExitCode,
@@ -17,25 +17,55 @@ use display_error_chain::DisplayErrorChain;
 use file_format::FileFormat;
 use isolang::Language;
 use library::{
-    book::chapter_summaries::ChapterSummaries,
+    book::{
+        book::{Book, ParagraphStorage},
+        chapter_summaries::ChapterSummaries,
+        serialization::{HashAlgorithm, Serializable},
+        strings_dictionary::{DEFAULT_DICTIONARY_SIZE, dictionaries_dir_for},
+        translation::{Translation, split_language_tag},
+    },
     cache::{GEMINI_PROMPT_CACHE_CAPACITY, TranslationsCache},
-    epub_importer::EpubBook,
-    library::Library,
+    epub_importer::{ChapterGranularity, EpubBook},
+    library::{DEFAULT_CHAPTER_HEADING_PATTERN, Library, load_report::BookLoadStatus},
     translator::{
-        ChapterContextProvider, NoChapterContext, TranslationContext, TranslationModel,
-        TranslationProvider, Translator, gemini_cache::GeminiPromptCache, get_translator,
+        NoChapterContext, TranslationContext, TranslationModel, TranslationProvider, Translator,
+        TranslatorConfig, TranslatorServices, book_source_language, check_source_language,
+        fixture::FixtureTranslator, gemini_cache::GeminiPromptCache, get_translator,
+        suggested_worker_count,
     },
 };
+use serde::Deserialize;
 use tokio::time::{Duration, sleep};
 use tokio::{sync::Mutex, task::JoinSet};
 use uuid::Uuid;
 
+/// Paragraph ids buffered per translation worker; the feeder waits once the
+/// channel is full, so memory stays flat however long the book is.
+const QUEUED_PARAGRAPHS_PER_WORKER: usize = 4;
+
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 struct Cli {
     #[arg(short, long, value_name = "FILE")]
     library_path: PathBuf,
 
+    /// Print machine-readable JSON instead of tables
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Only print errors and failures, no progress output
+    #[arg(long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Print per-worker progress (the default)
+    #[arg(long, global = true)]
+    verbose: bool,
+
+    /// Checksum book and translation files with SHA-256 instead of FNV-1
+    /// when they are next saved
+    #[arg(long, global = true)]
+    sha256_checksums: bool,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -47,22 +77,161 @@ enum Commands {
         /// Book title
         #[arg(short, long, value_name = "TITLE")]
         title: String,
-        /// Book language
+        /// Book language (defaults to the configured default source language)
         #[arg(short, long, value_name = "LANG")]
-        language: String,
+        language: Option<String>,
+        /// Start a new chapter at every line matching REGEX (a "Chapter N"
+        /// pattern if no REGEX is given)
+        #[arg(long, value_name = "REGEX", num_args = 0..=1,
+              default_missing_value = DEFAULT_CHAPTER_HEADING_PATTERN)]
+        chapter_headings: Option<String>,
+        /// Split paragraphs on blank lines and keep line breaks inside them
+        /// (poetry, code samples)
+        #[arg(long)]
+        preserve_line_breaks: bool,
+        /// Shelf folder to place the book in, e.g. "Fiction/Sci-Fi"
+        #[arg(long, value_name = "PATH")]
+        folder: Option<String>,
         /// Path to book file
         path: PathBuf,
     },
     /// Add book to library from EPUB
     ImportEpub {
-        /// Book language
+        /// Book language (defaults to the configured default source language)
         #[arg(short, long, value_name = "LANG")]
-        language: String,
+        language: Option<String>,
+        /// Skip malformed chapters instead of aborting the import
+        #[arg(long)]
+        lenient: bool,
+        /// Merge consecutive chapters shorter than CHARS characters
+        #[arg(long, value_name = "CHARS", conflicts_with = "split_on_headings")]
+        merge_chapters_below: Option<usize>,
+        /// Start a chapter at every <h1>/<h2> instead of every spine item
+        #[arg(long)]
+        split_on_headings: bool,
+        /// Store only each paragraph's HTML and derive its text from it
+        #[arg(long, conflicts_with = "text_only")]
+        html_only: bool,
+        /// Store only each paragraph's plain text, dropping its formatting
+        #[arg(long)]
+        text_only: bool,
+        /// Shelf folder to place the book in, e.g. "Fiction/Sci-Fi"
+        #[arg(long, value_name = "PATH")]
+        folder: Option<String>,
         /// Path to EPUB file
         path: PathBuf,
     },
+    /// Add book to library from a directory of text files, one chapter per
+    /// file in filename order
+    ImportDir {
+        /// Book title
+        #[arg(short, long, value_name = "TITLE")]
+        title: String,
+        /// Book language (defaults to the configured default source language)
+        #[arg(short, long, value_name = "LANG")]
+        language: Option<String>,
+        /// Directory containing the chapter files
+        dir: PathBuf,
+    },
+    /// Add several books listed in a JSON manifest: an array of
+    /// `{"path", "title", "language", "folder"}` objects where everything
+    /// but `path` is optional. Relative paths are resolved against the
+    /// manifest's directory; `.epub` files are imported as EPUB, everything
+    /// else as plain text. A failing entry doesn't stop the rest.
+    ImportManifest {
+        /// Path to the manifest file
+        path: PathBuf,
+    },
+    /// Show or change CLI settings
+    Config {
+        /// Source language used by imports when `--language` is omitted
+        #[arg(long, value_name = "LANG")]
+        default_language: Option<String>,
+    },
     /// List books
     List {},
+    /// Check books for structural consistency and invalid UTF-8 in book and
+    /// translation strings
+    Verify {
+        /// Only verify this book
+        id: Option<Uuid>,
+    },
+    /// Load every book directory and report whether it loaded, had
+    /// conflicting copies merged, or failed and why
+    Doctor {},
+    /// Rewrite translation files still in the v1 format as v2
+    Upgrade {},
+    /// Drop string data no longer referenced by any translation version
+    Compact {},
+    /// Train a shared zstd dictionary on the library's books and recompress
+    /// every book.dat with it; helps libraries of many small books
+    TrainDictionary {
+        /// Number of books whose text is used as training samples
+        #[arg(long, value_name = "N", default_value_t = 50)]
+        sample_books: usize,
+        /// Maximum dictionary size in bytes
+        #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_DICTIONARY_SIZE)]
+        max_size: usize,
+    },
+    /// Restore the missing book.dat of an incomplete book from a conflict
+    /// copy
+    Repair {
+        /// Book ID
+        id: Uuid,
+        /// Delete the book directory instead of restoring it
+        #[arg(long)]
+        remove: bool,
+    },
+    /// Write a standalone copy of a book and its translations to a directory
+    Snapshot {
+        /// Book ID
+        id: Uuid,
+        /// Directory to write the copy to
+        out: PathBuf,
+    },
+    /// Export original/translated sentence pairs of a book as CSV
+    ExportSentences {
+        /// Book ID
+        id: Uuid,
+        /// Target language of the translation, optionally with a regional
+        /// variant (e.g. "en", "rus", "por-BR")
+        target_language: String,
+        /// Write to this file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Export original/translated sentence pairs of a book as TMX
+    ExportTmx {
+        /// Book ID
+        id: Uuid,
+        /// Target language of the translation, optionally with a regional
+        /// variant (e.g. "en", "rus", "por-BR")
+        target_language: String,
+        /// Write to this file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Seed untranslated paragraphs of a book from a TMX translation memory
+    ImportTmx {
+        /// Book ID
+        id: Uuid,
+        /// Target language of the translation, optionally with a regional
+        /// variant (e.g. "en", "rus", "por-BR")
+        target_language: String,
+        /// TMX file to read
+        path: PathBuf,
+    },
+    /// Export reading positions and folders of all books as JSON
+    ExportState {
+        /// Write to this file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Restore reading positions and folders from an `export-state` file
+    ImportState {
+        /// JSON file written by `export-state`
+        path: PathBuf,
+    },
     /// Dump chapter summaries for a book
     Summaries {
         /// Book ID
@@ -74,22 +243,105 @@ enum Commands {
         #[arg(long)]
         headers_only: bool,
     },
+    /// List dictionaries (language pairs) with their entry counts
+    Dictionaries {},
+    /// Drop dictionary translations seen fewer than MIN_COUNT times
+    PruneDictionary {
+        /// Source language of the dictionary
+        #[arg(short, long, value_name = "LANG")]
+        source_language: String,
+        /// Target language of the dictionary
+        #[arg(short, long, value_name = "LANG")]
+        target_language: String,
+        /// Keep only translations produced at least this many times
+        #[arg(short, long, value_name = "NUM", default_value_t = 2)]
+        min_count: u32,
+    },
+    /// Recompute a dictionary from the library's translations, e.g. after
+    /// card files were lost; Anki state on existing cards is kept
+    RebuildDictionary {
+        /// Source language of the dictionary
+        #[arg(short, long, value_name = "LANG")]
+        source_language: String,
+        /// Target language of the dictionary
+        #[arg(short, long, value_name = "LANG")]
+        target_language: String,
+    },
+    /// Move the library to a new location
+    Relocate {
+        /// New library root; must be empty or not exist yet
+        new_path: PathBuf,
+    },
     /// Translate book
     Translate {
         /// Book ID
         id: Uuid,
         /// Gemini API key
-        #[arg(short, long, value_name = "KEY")]
-        api_key: String,
-        /// Translation language
-        #[arg(short, long, value_name = "LANG")]
+        #[arg(short, long, value_name = "KEY", required_unless_present = "fixtures")]
+        api_key: Option<String>,
+        /// Replay canned translations from this directory instead of calling
+        /// the API
+        #[arg(long, value_name = "DIR", conflicts_with = "api_key")]
+        fixtures: Option<PathBuf>,
+        /// Translation language, optionally with a regional variant
+        /// (e.g. `por-BR`)
+        #[arg(short, long, value_name = "LANG[-VARIANT]")]
         translation_language: String,
-        /// Number of parallel LLM requests
-        #[arg(short, long, value_name = "NUM")]
-        n_parallel: Option<usize>,
+        /// Number of parallel LLM requests, or `auto` to derive it from
+        /// `--rpm` and `--latency`
+        #[arg(short, long, value_name = "NUM|auto")]
+        n_parallel: Option<Parallelism>,
+        /// Model rate limit in requests per minute, used by `--n-parallel auto`
+        #[arg(long, value_name = "RPM", default_value_t = 60)]
+        rpm: u32,
+        /// Estimated seconds per request, used by `--n-parallel auto`
+        #[arg(long, value_name = "SECS", default_value_t = 20)]
+        latency: u64,
+        /// Sampling temperature (defaults to the model's own)
+        #[arg(long, value_name = "TEMP")]
+        temperature: Option<f32>,
+        /// Thinking budget in tokens, -1 for dynamic (Gemini 2.5 only)
+        #[arg(long, value_name = "TOKENS", allow_negative_numbers = true)]
+        thinking_budget: Option<i32>,
+        /// Append every request and raw response to FILE (API key redacted)
+        #[arg(long, value_name = "FILE")]
+        log_requests: Option<PathBuf>,
+        /// Only retry the paragraphs the last run gave up on, as recorded in
+        /// the book's `failed_paragraphs.json`
+        #[arg(long)]
+        retry_failed: bool,
+        /// Language to translate from (defaults to the book's language)
+        #[arg(long, value_name = "LANG")]
+        source_language: Option<String>,
+        /// Translate even if `--source-language` differs from the book's
+        /// language
+        #[arg(long, requires = "source_language")]
+        allow_language_mismatch: bool,
     },
 }
 
+/// How much progress output long-running commands print. Errors go to
+/// stderr regardless.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Verbosity {
+    Quiet,
+    Verbose,
+}
+
+impl Verbosity {
+    fn from_cli(cli: &Cli) -> Self {
+        if cli.quiet {
+            Verbosity::Quiet
+        } else {
+            Verbosity::Verbose
+        }
+    }
+
+    fn is_verbose(self) -> bool {
+        self == Verbosity::Verbose
+    }
+}
+
 #[derive(Debug)]
 enum CliError {
     UnsupportedFormat(String),
@@ -105,11 +357,35 @@ impl Display for CliError {
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+enum Parallelism {
+    Auto,
+    Fixed(usize),
+}
+
+impl FromStr for Parallelism {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("auto") {
+            return Ok(Parallelism::Auto);
+        }
+        match s.parse::<usize>() {
+            Ok(0) => Err("must be at least 1".to_owned()),
+            Ok(n) => Ok(Parallelism::Fixed(n)),
+            Err(_) => Err(format!("expected a number or 'auto', got '{s}'")),
+        }
+    }
+}
+
 async fn add_book(
     library: &Arc<Library>,
     title: &str,
     path: &PathBuf,
     lang: &str,
+    chapter_headings: Option<&str>,
+    preserve_line_breaks: bool,
+    folder: Option<&str>,
 ) -> anyhow::Result<()> {
     let fmt = FileFormat::from_file(path)?;
 
@@ -118,12 +394,25 @@ async fn add_book(
         let mut text = String::new();
         data.read_to_string(&mut text)?;
 
-        let book_id = library
-            .create_book_plain(title, &text, &Language::from_str(lang)?)
+        let language = Language::from_str(lang)?;
+        let created = library
+            .create_book_plain_with_options(
+                title,
+                &text,
+                &language,
+                chapter_headings,
+                preserve_line_breaks,
+            )
             .await?;
-        let book = library.get_book(&book_id).await?;
-        let book = book.lock().await;
-        println!("Created book {} (id: {})", book.book.title, book.book.id);
+        if let Some(folder) = folder {
+            let book = library.get_book(&created.id).await?;
+            let mut book = book.lock().await;
+            book.update_folder_path(folder_path(folder)).await?;
+        }
+        println!(
+            "Created book {} (id: {}, language: {}, {} chapters, {} paragraphs)",
+            title, created.id, created.language, created.chapters_count, created.paragraphs_count
+        );
     } else {
         Err(CliError::UnsupportedFormat(fmt.media_type().to_owned()))?
     }
@@ -131,19 +420,218 @@ async fn add_book(
     Ok(())
 }
 
-async fn add_epub(library: &Arc<Library>, path: &Path, lang: &str) -> anyhow::Result<()> {
-    let epub = EpubBook::load(path)?;
+/// How `add` turns an EPUB into a book.
+struct EpubImportOptions {
+    lenient: bool,
+    granularity: ChapterGranularity,
+    storage: ParagraphStorage,
+}
+
+impl Default for EpubImportOptions {
+    fn default() -> Self {
+        Self {
+            lenient: false,
+            granularity: ChapterGranularity::SpineItems,
+            storage: ParagraphStorage::Both,
+        }
+    }
+}
+
+async fn add_epub(
+    library: &Arc<Library>,
+    path: &Path,
+    lang: &str,
+    options: EpubImportOptions,
+    title: Option<&str>,
+    folder: Option<&str>,
+) -> anyhow::Result<()> {
+    let EpubImportOptions {
+        lenient,
+        granularity,
+        storage,
+    } = options;
+    let (mut epub, skipped) = EpubBook::load_with(path, lenient)?;
+    epub.regroup_chapters(granularity);
+    if let Some(title) = title {
+        epub.title = title.to_owned();
+    }
+    if skipped > 0 {
+        println!("Skipped {skipped} malformed chapter(s)");
+    }
 
     let book_id = library
-        .create_book_epub(&epub, &Language::from_str(lang)?)
+        .create_book_epub(&epub, &Language::from_str(lang)?, storage)
         .await?;
     let book = library.get_book(&book_id).await?;
-    let book = book.lock().await;
+    let mut book = book.lock().await;
+    if let Some(folder) = folder {
+        book.update_folder_path(folder_path(folder)).await?;
+    }
     println!("Created book {} (id: {})", book.book.title, book.book.id);
+    if storage != ParagraphStorage::Both {
+        println!(
+            "Paragraph strings: {} bytes",
+            book.book.strings_blob().len()
+        );
+    }
+
+    Ok(())
+}
+
+async fn add_dir(
+    library: &Arc<Library>,
+    title: &str,
+    dir: &Path,
+    lang: &str,
+) -> anyhow::Result<()> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort_by_cached_key(|path| chapter_sort_key(path));
+
+    let mut chapters = Vec::new();
+    for path in paths {
+        let fmt = FileFormat::from_file(&path)?;
+        if fmt.media_type() != "text/plain" {
+            eprintln!(
+                "Skipping {}: not a text file ({})",
+                path.display(),
+                fmt.media_type()
+            );
+            continue;
+        }
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        chapters.push((name, std::fs::read_to_string(&path)?));
+    }
+
+    let chapters: Vec<_> = chapters
+        .iter()
+        .map(|(name, text)| (name.as_str(), text.as_str()))
+        .collect();
+    let book_id = library
+        .create_book_from_chapters(title, &chapters, &Language::from_str(lang)?)
+        .await?;
+    let book = library.get_book(&book_id).await?;
+    let book = book.lock().await;
+    println!(
+        "Created book {} with {} chapter(s) (id: {})",
+        book.book.title,
+        chapters.len(),
+        book.book.id
+    );
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct ManifestEntry {
+    path: PathBuf,
+    title: Option<String>,
+    language: Option<String>,
+    folder: Option<String>,
+}
+
+fn parse_manifest(json: &str, base_dir: &Path) -> anyhow::Result<Vec<ManifestEntry>> {
+    let mut entries: Vec<ManifestEntry> =
+        serde_json::from_str(json).map_err(|err| anyhow::anyhow!("Invalid manifest: {err}"))?;
+    for entry in &mut entries {
+        entry.path = base_dir.join(&entry.path);
+    }
+    Ok(entries)
+}
+
+async fn import_manifest_entry(
+    library: &Arc<Library>,
+    entry: &ManifestEntry,
+) -> anyhow::Result<()> {
+    let lang = source_language(&entry.language)?;
+    let is_epub = entry
+        .path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("epub"));
+    if is_epub {
+        return add_epub(
+            library,
+            &entry.path,
+            &lang,
+            EpubImportOptions::default(),
+            entry.title.as_deref(),
+            entry.folder.as_deref(),
+        )
+        .await;
+    }
+
+    let title = match &entry.title {
+        Some(title) => title.clone(),
+        None => entry
+            .path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    };
+    add_book(
+        library,
+        &title,
+        &entry.path,
+        &lang,
+        None,
+        false,
+        entry.folder.as_deref(),
+    )
+    .await
+}
+
+async fn import_manifest(library: &Arc<Library>, path: &Path) -> anyhow::Result<()> {
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    let entries = parse_manifest(&std::fs::read_to_string(path)?, base_dir)?;
+
+    let mut results = Vec::with_capacity(entries.len());
+    for entry in &entries {
+        results.push(import_manifest_entry(library, entry).await);
+    }
+
+    println!();
+    println!("status\tpath\tdetail");
+    for (entry, result) in entries.iter().zip(&results) {
+        match result {
+            Ok(()) => println!("ok\t{}\t", entry.path.display()),
+            Err(err) => println!("failed\t{}\t{err:#}", entry.path.display()),
+        }
+    }
+    let imported = results.iter().filter(|result| result.is_ok()).count();
+    println!("Imported {imported} of {} book(s)", entries.len());
 
     Ok(())
 }
 
+/// Orders "2.txt" before "10.txt": files are compared by their leading
+/// number first, then by name. Files without a leading number sort last.
+fn chapter_sort_key(path: &Path) -> (u64, String) {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let digits: String = name.chars().take_while(char::is_ascii_digit).collect();
+    (digits.parse().unwrap_or(u64::MAX), name)
+}
+
+/// Splits a `--folder` argument like "Fiction/Sci-Fi" into shelf segments.
+fn folder_path(folder: &str) -> Vec<String> {
+    folder
+        .split('/')
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
 async fn dump_summaries(
     library: &Arc<Library>,
     book_id: Uuid,
@@ -221,21 +709,33 @@ async fn dump_summaries(
     Ok(())
 }
 
-async fn list_books(library: &Arc<Library>) -> anyhow::Result<()> {
+async fn list_books(library: &Arc<Library>, json: bool) -> anyhow::Result<()> {
     let books = library.list_books().await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&books)?);
+        return Ok(());
+    }
+
     println!("id                                \ttitle\tchapters\tparagraphs");
     for book in books {
         println!(
             "{}\t{}\t{}\t{}",
             book.id, book.title, book.chapters_count, book.paragraphs_count
         );
+        if book.incomplete {
+            println!("\tIncomplete: book.dat is missing, see `repair`");
+        }
         if !book.translations_metadata.is_empty() {
             println!("\tTranslations:");
             println!("\tid                                \tsrc\ttgt\tparagraphs");
             for t in book.translations_metadata {
+                let target = match &t.target_variant {
+                    Some(variant) => format!("{}-{variant}", t.target_language),
+                    None => t.target_language.clone(),
+                };
                 println!(
                     "\t{}\t{}\t{}\t{}",
-                    t.id, t.source_langugage, t.target_language, t.translated_paragraphs_count
+                    t.id, t.source_langugage, target, t.translated_paragraphs_count
                 );
             }
         }
@@ -244,41 +744,189 @@ async fn list_books(library: &Arc<Library>) -> anyhow::Result<()> {
     Ok(())
 }
 
+async fn list_dictionaries(library: &Arc<Library>, json: bool) -> anyhow::Result<()> {
+    let dictionaries = library.list_dictionaries().await?;
+    if json {
+        let rows: Vec<_> = dictionaries
+            .iter()
+            .map(|d| {
+                serde_json::json!({
+                    "sourceLanguage": d.source_language.to_639_3(),
+                    "targetLanguage": d.target_language.to_639_3(),
+                    "entries": d.entries,
+                    "conflicts": d.conflicts,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+
+    println!("src\ttgt\tentries");
+    for d in dictionaries {
+        println!(
+            "{}\t{}\t{}",
+            d.source_language.to_639_3(),
+            d.target_language.to_639_3(),
+            d.entries
+        );
+        if d.conflicts > 0 {
+            println!(
+                "\t{} unmerged sync conflict file(s), merged when the card is next loaded",
+                d.conflicts
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn verify_books(library: &Arc<Library>, book_id: Option<Uuid>) -> anyhow::Result<()> {
+    let books = library.list_books().await?;
+    let mut checked = 0usize;
+    let mut failed = 0usize;
+    for meta in books {
+        if let Some(id) = book_id
+            && id != meta.id
+        {
+            continue;
+        }
+        checked += 1;
+
+        let result = std::fs::read(&meta.main_path)
+            .map_err(anyhow::Error::from)
+            .and_then(|data| {
+                let dictionaries = dictionaries_dir_for(&meta.main_path);
+                Ok(Book::deserialize_with_dictionaries(
+                    &mut std::io::Cursor::new(data),
+                    dictionaries.as_deref(),
+                )?)
+            })
+            .and_then(|book| Ok(book.validate()?))
+            .and_then(|()| {
+                meta.translations_metadata.iter().try_for_each(|t| {
+                    let data = std::fs::read(&t.main_path)?;
+                    let translation = Translation::deserialize(&mut std::io::Cursor::new(data))?;
+                    match translation.first_invalid_utf8_paragraph() {
+                        Some(paragraph) => anyhow::bail!(
+                            "Translation to {}: paragraph {paragraph} has text that isn't valid UTF-8",
+                            t.target_language
+                        ),
+                        None => Ok(()),
+                    }
+                })
+            });
+        match result {
+            Ok(()) => println!("{}\t{}\tOK", meta.id, meta.title),
+            Err(err) => {
+                failed += 1;
+                println!("{}\t{}\tINVALID: {err}", meta.id, meta.title);
+            }
+        }
+    }
+
+    if let Some(id) = book_id
+        && checked == 0
+    {
+        anyhow::bail!("Book {id} not found in library");
+    }
+    if failed > 0 {
+        anyhow::bail!("{failed} of {checked} books failed verification");
+    }
+
+    Ok(())
+}
+
+async fn doctor(library: &Arc<Library>) -> anyhow::Result<()> {
+    let reports = library.load_report().await?;
+    let mut failed = 0usize;
+    for report in &reports {
+        let id = report
+            .id
+            .map(|id| id.to_string())
+            .unwrap_or_else(|| "-".into());
+        let title = report.title.as_deref().unwrap_or("-");
+        let status = match &report.status {
+            BookLoadStatus::Loaded => "OK".to_string(),
+            BookLoadStatus::ConflictsMerged { files } => {
+                format!("OK, merged {files} conflicting file(s)")
+            }
+            BookLoadStatus::ConflictsPending { files } => {
+                format!("OK, {files} conflicting file(s) left unmerged")
+            }
+            BookLoadStatus::Incomplete => "INCOMPLETE: book.dat is missing, see `repair`".into(),
+            BookLoadStatus::Failed { error } => {
+                failed += 1;
+                format!("FAILED: {error}")
+            }
+        };
+        println!("{id}\t{title}\t{}\t{status}", report.path.display());
+    }
+
+    println!("{} book(s), {failed} failed to load", reports.len());
+    if failed > 0 {
+        anyhow::bail!("{failed} of {} books failed to load", reports.len());
+    }
+
+    Ok(())
+}
+
+/// Target language of a `translate` run, with its variant if there is one.
+#[derive(Clone)]
+struct TranslationTarget {
+    language: Language,
+    variant: Option<String>,
+}
+
 async fn translate_paragraph(
     library: Arc<Library>,
     translator: &dyn Translator,
     book_id: Uuid,
-    tgt_lang: &Language,
+    target: &TranslationTarget,
     paragraph_id: usize,
     worker_id: usize,
+    verbosity: Verbosity,
 ) -> anyhow::Result<()> {
-    let (translation, paragraph_text, chapter_id) = {
-        let book = library.get_book(&book_id).await?;
-        let mut book = book.lock().await;
-        let translation = book.get_or_create_translation(tgt_lang).await;
+    let (translation, paragraph_text, chapter_id, book) = {
+        let book_handle = library.get_book(&book_id).await?;
+        let mut book = book_handle.lock().await;
+        let translation = book
+            .get_or_create_translation_variant(&target.language, target.variant.as_deref())
+            .await?;
         let paragraph = book.book.paragraph_view(paragraph_id);
         let chapter_id = book.book.chapter_for_paragraph(paragraph_id).unwrap_or(0);
         (
             translation,
             paragraph.original_text.to_string(),
             chapter_id,
+            book_handle.clone(),
         )
     };
-    println!(
-        "Worker {worker_id}: Translating paragraph {}: \"{}...\"",
-        paragraph_id,
-        String::from_iter(paragraph_text.chars().take(40))
-    );
-    let p_translation = translator
+    if verbosity.is_verbose() {
+        println!(
+            "Worker {worker_id}: Translating paragraph {}: \"{}...\"",
+            paragraph_id,
+            String::from_iter(paragraph_text.chars().take(40))
+        );
+    }
+    let mut p_translation = translator
         .get_translation(TranslationContext {
             paragraph_text: &paragraph_text,
             book_id,
             chapter_id,
             use_cache: true,
             callback: None,
+            token_sink: None,
         })
         .await?;
-    println!("Worker {worker_id}: Translated paragraph {}", paragraph_id);
+    if verbosity.is_verbose() {
+        println!("Worker {worker_id}: Translated paragraph {}", paragraph_id);
+    }
+
+    book.lock()
+        .await
+        .apply_footnotes(paragraph_id, &mut p_translation)
+        .await?;
 
     translation.lock().await.add_paragraph_translation(
         paragraph_id,
@@ -287,7 +935,7 @@ async fn translate_paragraph(
     );
 
     library
-        .apply_paragraph_to_cards(book_id, paragraph_id, &p_translation, *tgt_lang)
+        .apply_paragraph_to_cards(book_id, paragraph_id, &p_translation, target.language)
         .await?;
 
     Ok(())
@@ -321,39 +969,90 @@ async fn run_saver(library: Arc<Library>, book_id: Uuid, rx: flume::Receiver<()>
     }
 }
 
-async fn translate_book(
-    library: Arc<Library>,
-    cache: Arc<TranslationsCache>,
-    gemini_prompt_cache: Arc<GeminiPromptCache>,
-    api_key: &str,
+/// What to translate and how, as given on the `translate` command line.
+struct TranslateOptions<'a> {
+    api_key: Option<&'a str>,
+    fixtures: Option<&'a Path>,
     book_id: Uuid,
-    tgt_lang: &str,
+    target_language: &'a str,
     n_workers: usize,
+    translator_config: TranslatorConfig,
+    retry_failed: bool,
+    source_language: Option<&'a str>,
+    allow_language_mismatch: bool,
+}
+
+async fn translate_book(
+    library: Arc<Library>,
+    services: TranslatorServices,
+    options: TranslateOptions<'_>,
+    verbosity: Verbosity,
 ) -> anyhow::Result<()> {
-    let target_lang = isolang::Language::from_str(tgt_lang)?;
+    let TranslateOptions {
+        api_key,
+        fixtures,
+        book_id,
+        target_language: tgt_lang,
+        n_workers,
+        translator_config,
+        retry_failed,
+        source_language,
+        allow_language_mismatch,
+    } = options;
+    let (tgt_lang, tgt_variant) = split_language_tag(tgt_lang);
+    let target_lang = isolang::Language::from_str(&tgt_lang)?;
+    let target_tag = match &tgt_variant {
+        Some(variant) => format!("{}-{variant}", target_lang.to_639_3()),
+        None => target_lang.to_639_3().to_owned(),
+    };
 
     let queue = Arc::new(Mutex::new(VecDeque::new()));
+    // Paragraphs given up on after the last attempt.
+    let dead_letter = Arc::new(Mutex::new(BTreeSet::new()));
 
-    let source_lang = {
+    let (source_lang, progress) = {
         let book = library.get_book(&book_id).await?;
         let mut book = book.lock().await;
-        let source_lang = Language::from_639_3(&book.book.language).unwrap();
+        let source_lang = match source_language {
+            Some(language) => Language::from_str(language)?,
+            None => book_source_language(&book.book.language)?,
+        };
+        if let Err(err) = check_source_language(&book.book.language, &source_lang) {
+            if !allow_language_mismatch {
+                return Err(err.context("Pass --allow-language-mismatch to translate anyway"));
+            }
+            eprintln!("Warning: {err}");
+        }
 
         let paragraph_count = book.book.paragraphs_count();
 
-        let translation = book.get_or_create_translation(&target_lang).await;
+        let translation = book
+            .get_or_create_translation_variant(&target_lang, tgt_variant.as_deref())
+            .await?;
         let untranslated_paragraphs_count =
             paragraph_count - translation.lock().await.translated_paragraphs_count();
-        println!(
-            "Translating book {} from {} to {}",
-            book.book.title,
-            source_lang.to_name(),
-            target_lang.to_name()
-        );
-        println!(
-            "Found {untranslated_paragraphs_count} untranslated paragraphs out of {}",
-            paragraph_count
-        );
+        if verbosity.is_verbose() {
+            println!(
+                "Translating book {} from {} to {}",
+                book.book.title,
+                source_lang.to_name(),
+                target_lang.to_name()
+            );
+            println!(
+                "Found {untranslated_paragraphs_count} untranslated paragraphs out of {}",
+                paragraph_count
+            );
+        }
+
+        let failed = if retry_failed {
+            let failed = book.failed_paragraphs(&target_tag).await?;
+            if verbosity.is_verbose() {
+                println!("Retrying {} previously failed paragraph(s)", failed.len());
+            }
+            Some(failed)
+        } else {
+            None
+        };
 
         // Collect untranslated paragraph IDs with minimal lock contention
         let untranslated_ids: Vec<usize> = {
@@ -361,13 +1060,21 @@ async fn translate_book(
             let mut ids = Vec::new();
             for chapter in book.book.chapter_views() {
                 for paragraph in chapter.paragraphs() {
-                    if t.paragraph_view(paragraph.id).is_none() {
+                    if t.paragraph_view(paragraph.id).is_none()
+                        && failed.as_ref().is_none_or(|f| f.contains(&paragraph.id))
+                    {
                         ids.push(paragraph.id);
                     }
                 }
             }
             ids
         };
+        let progress = book.translation_progress(&target_tag).await?;
+        let interrupted = progress.snapshot().await.in_flight.len();
+        if interrupted > 0 && verbosity.is_verbose() {
+            println!("Resuming: retrying {interrupted} interrupted paragraph(s) first");
+        }
+        let untranslated_ids = progress.plan(untranslated_ids).await?;
         {
             let mut q = queue.lock().await;
             for id in untranslated_ids {
@@ -375,15 +1082,16 @@ async fn translate_book(
             }
         }
 
-        source_lang
+        (source_lang, Arc::new(progress))
     };
 
     let start_time = Instant::now();
 
-    let (tx, rx) = flume::unbounded();
+    let (tx, rx) = flume::bounded(n_workers.max(1) * QUEUED_PARAGRAPHS_PER_WORKER);
 
-    // Channel to notify saver about new changes
-    let (tx_save, rx_save) = flume::unbounded::<()>();
+    // Channel to notify saver about new changes. One slot is enough: while a
+    // notification is pending, further ones are redundant and dropped.
+    let (tx_save, rx_save) = flume::bounded::<()>(1);
     let saver_library = library.clone();
     let saver_handle = tokio::spawn(async move {
         run_saver(saver_library, book_id, rx_save).await;
@@ -394,22 +1102,34 @@ async fn translate_book(
         let library1 = library.clone();
         let rx = rx.clone();
         let tx_save_w = tx_save.clone();
-        let context_provider: Arc<dyn ChapterContextProvider> = Arc::new(NoChapterContext);
-        let translator = get_translator(
-            cache.clone(),
-            context_provider,
-            gemini_prompt_cache.clone(),
-            TranslationProvider::Google,
-            TranslationModel::Gemini25Flash,
-            api_key.to_owned(),
-            source_lang,
-            target_lang,
-        )?;
+        let progress = progress.clone();
+        let dead_letter = dead_letter.clone();
+        let translator: Box<dyn Translator> = match (fixtures, api_key) {
+            (Some(dir), _) => Box::new(FixtureTranslator::new(dir)),
+            (None, Some(api_key)) => get_translator(
+                services.clone(),
+                TranslationProvider::Google,
+                TranslationModel::Gemini25Flash,
+                api_key.to_owned(),
+                source_lang,
+                target_lang,
+                translator_config.clone(),
+            )?,
+            (None, None) => anyhow::bail!("Either an API key or a fixtures directory is required"),
+        };
+        let target = TranslationTarget {
+            language: target_lang,
+            variant: tgt_variant.clone(),
+        };
         set.spawn(async move {
-            println!("Worker {}: spawning...", i);
-            let target_lang1 = target_lang;
+            if verbosity.is_verbose() {
+                println!("Worker {}: spawning...", i);
+            }
             // Receive until the channel is closed (all senders dropped)
             while let Ok(p_id) = rx.recv_async().await {
+                if let Err(err) = progress.mark_in_flight(p_id).await {
+                    eprintln!("Worker {i}: Failed to record progress: {err}");
+                }
                 // Bounded retry inside the worker instead of re-queuing
                 let mut attempt = 1u32;
                 loop {
@@ -417,16 +1137,20 @@ async fn translate_book(
                         library1.clone(),
                         translator.as_ref(),
                         book_id,
-                        &target_lang1,
+                        &target,
                         p_id,
                         i,
+                        verbosity,
                     )
                     .await;
 
                     match result {
                         Ok(_) => {
                             // Notify saver that new data is available
-                            let _ = tx_save_w.send_async(()).await;
+                            let _ = tx_save_w.try_send(());
+                            if let Err(err) = progress.mark_done(p_id).await {
+                                eprintln!("Worker {i}: Failed to record progress: {err}");
+                            }
                             break
                         },
                         Err(err) => {
@@ -438,41 +1162,117 @@ async fn translate_book(
                                 eprintln!(
                                     "Worker {i}: Giving up on paragraph {p_id} after {attempt} attempts"
                                 );
+                                if let Err(err) = progress.mark_failed(p_id).await {
+                                    eprintln!("Worker {i}: Failed to record progress: {err}");
+                                }
+                                dead_letter.lock().await.insert(p_id);
                                 break;
                             }
                             let backoff = Duration::from_secs((attempt * 2) as u64);
-                            println!(
-                                "Worker {i}: Backing off {backoff:?} before retrying paragraph {p_id}"
-                            );
+                            if verbosity.is_verbose() {
+                                println!(
+                                    "Worker {i}: Backing off {backoff:?} before retrying paragraph {p_id}"
+                                );
+                            }
                             sleep(backoff).await;
                             attempt += 1;
                         }
                     }
                 }
             }
-            println!("Worker {i}: terminated");
+            if verbosity.is_verbose() {
+                println!("Worker {i}: terminated");
+            }
         });
     }
 
-    while let Some(p_id) = queue.lock().await.pop_front() {
-        tx.send_async(p_id).await?;
-    }
+    // Only the workers keep the channel open, so the feeder can't block
+    // forever on a full channel if they all stop.
+    drop(rx);
+
+    // Feed the workers concurrently; dropping `tx` when the queue is empty
+    // closes the channel so they exit once it drains.
+    let feeder = tokio::spawn(async move {
+        while let Some(p_id) = queue.lock().await.pop_front() {
+            tx.send_async(p_id).await?;
+        }
+        anyhow::Ok(())
+    });
 
-    drop(tx);
     // Drop main saver sender so channel can close when workers finish
     drop(tx_save);
 
     set.join_all().await;
+    let fed = feeder.await;
 
-    // Wait for saver to flush any pending changes
+    // Wait for saver to flush any pending changes, even if feeding failed
     let _ = saver_handle.await;
+    fed??;
+
+    progress.finish().await?;
+    let failed = std::mem::take(&mut *dead_letter.lock().await);
+    {
+        let book = library.get_book(&book_id).await?;
+        let book = book.lock().await;
+        book.set_failed_paragraphs(&target_tag, &failed).await?;
+    }
+    if !failed.is_empty() {
+        let ids: Vec<String> = failed.iter().map(|id| id.to_string()).collect();
+        println!(
+            "{} paragraph(s) failed: {}; run again with --retry-failed to retry them",
+            failed.len(),
+            ids.join(", ")
+        );
+    }
 
     let elapsed_time = start_time.elapsed();
-    println!("Translated in: {:?}", elapsed_time);
+    if verbosity.is_verbose() {
+        println!("Translated in: {:?}", elapsed_time);
+    }
 
     Ok(())
 }
 
+fn cli_config_path() -> PathBuf {
+    let dirs = ProjectDirs::from("", "TS", "FLTS").unwrap();
+    dirs.config_dir().join("cli.json")
+}
+
+fn load_default_source_language() -> anyhow::Result<Option<String>> {
+    let path = cli_config_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+    let config: serde_json::Value = serde_json::from_reader(File::open(path)?)?;
+    Ok(config["defaultSourceLanguageId"]
+        .as_str()
+        .map(str::to_owned))
+}
+
+fn save_default_source_language(language: &str) -> anyhow::Result<()> {
+    let language = Language::from_str(language)?.to_639_3();
+    let path = cli_config_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let config = serde_json::json!({ "defaultSourceLanguageId": language });
+    serde_json::to_writer_pretty(File::create(path)?, &config)?;
+    Ok(())
+}
+
+/// The `--language` argument, or the configured default when it's omitted.
+fn source_language(language: &Option<String>) -> anyhow::Result<String> {
+    match language {
+        Some(language) => Ok(language.clone()),
+        None => load_default_source_language()?.ok_or_else(|| {
+            anyhow::anyhow!(
+                "No --language given and no default source language configured \
+                 (set one with `config --default-language LANG`)"
+            )
+        }),
+    }
+}
+
 async fn get_cache() -> anyhow::Result<TranslationsCache> {
     let dirs = ProjectDirs::from("", "TS", "FLTS").unwrap();
     let cache_dir = dirs.cache_dir();
@@ -502,12 +1302,11 @@ async fn main() -> ExitCode {
 async fn do_main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
-    let library_path = cli.library_path.clone();
-    if !library_path.exists() {
-        create_dir(library_path.clone())?;
+    let library = Arc::new(Library::open(cli.library_path.clone()).await?);
+    if cli.sha256_checksums {
+        library.set_hash_algorithm(HashAlgorithm::Sha256);
     }
-
-    let library = Arc::new(Library::open(library_path).await?);
+    let verbosity = Verbosity::from_cli(&cli);
 
     match &cli.command {
         Some(cmd) => match cmd {
@@ -515,14 +1314,217 @@ async fn do_main() -> anyhow::Result<()> {
                 title,
                 path,
                 language,
+                chapter_headings,
+                preserve_line_breaks,
+                folder,
             } => {
-                add_book(&library, title, path, language).await?;
+                add_book(
+                    &library,
+                    title,
+                    path,
+                    &source_language(language)?,
+                    chapter_headings.as_deref(),
+                    *preserve_line_breaks,
+                    folder.as_deref(),
+                )
+                .await?;
             }
-            Commands::ImportEpub { path, language } => {
-                add_epub(&library, path, language).await?;
+            Commands::ImportEpub {
+                path,
+                language,
+                lenient,
+                merge_chapters_below,
+                split_on_headings,
+                html_only,
+                text_only,
+                folder,
+            } => {
+                let granularity = match (merge_chapters_below, split_on_headings) {
+                    (Some(min_chars), _) => ChapterGranularity::MergeSmall {
+                        min_chars: *min_chars,
+                    },
+                    (None, true) => ChapterGranularity::Headings,
+                    (None, false) => ChapterGranularity::SpineItems,
+                };
+                let storage = match (html_only, text_only) {
+                    (true, _) => ParagraphStorage::HtmlOnly,
+                    (false, true) => ParagraphStorage::TextOnly,
+                    (false, false) => ParagraphStorage::Both,
+                };
+                add_epub(
+                    &library,
+                    path,
+                    &source_language(language)?,
+                    EpubImportOptions {
+                        lenient: *lenient,
+                        granularity,
+                        storage,
+                    },
+                    None,
+                    folder.as_deref(),
+                )
+                .await?;
+            }
+            Commands::ImportDir {
+                title,
+                language,
+                dir,
+            } => {
+                add_dir(&library, title, dir, &source_language(language)?).await?;
+            }
+            Commands::ImportManifest { path } => {
+                import_manifest(&library, path).await?;
+            }
+            Commands::Config { default_language } => {
+                if let Some(language) = default_language {
+                    save_default_source_language(language)?;
+                }
+                match load_default_source_language()? {
+                    Some(language) => println!("Default source language: {language}"),
+                    None => println!("Default source language: not set"),
+                }
             }
             Commands::List {} => {
-                list_books(&library).await?;
+                list_books(&library, cli.json).await?;
+            }
+            Commands::Verify { id } => {
+                verify_books(&library, *id).await?;
+            }
+            Commands::Doctor {} => {
+                doctor(&library).await?;
+            }
+            Commands::Upgrade {} => {
+                let upgraded = library.upgrade_translation_formats().await?;
+                println!("Upgraded {upgraded} translation file(s)");
+            }
+            Commands::Compact {} => {
+                let reclaimed = library.compact_strings().await?;
+                println!("Reclaimed {reclaimed} byte(s) of unreferenced strings");
+            }
+            Commands::TrainDictionary {
+                sample_books,
+                max_size,
+            } => {
+                let report = library
+                    .train_strings_dictionary(*sample_books, *max_size)
+                    .await?;
+                println!(
+                    "Recompressed {} book(s) with dictionary {:016x}: {} -> {} bytes",
+                    report.books, report.dictionary_id, report.bytes_before, report.bytes_after
+                );
+            }
+            Commands::Repair { id, remove } => {
+                library.repair_book(id, *remove).await?;
+                if *remove {
+                    println!("Removed incomplete book {id}");
+                } else {
+                    println!("Repaired book {id}");
+                }
+            }
+            Commands::Snapshot { id, out } => {
+                let book = library.get_book(id).await?;
+                book.lock().await.save_to(out).await?;
+                println!("Saved a copy of book {id} to {}", out.display());
+            }
+            Commands::ExportSentences {
+                id,
+                target_language,
+                output,
+            } => {
+                let (language, variant) = split_language_tag(target_language);
+                let csv = library
+                    .export_sentence_pairs_csv(
+                        id,
+                        &Language::from_str(&language)?,
+                        variant.as_deref(),
+                    )
+                    .await?;
+                match output {
+                    Some(path) => std::fs::write(path, csv)?,
+                    None => print!("{csv}"),
+                }
+            }
+            Commands::ExportTmx {
+                id,
+                target_language,
+                output,
+            } => {
+                let (language, variant) = split_language_tag(target_language);
+                let tmx = library
+                    .export_tmx(id, &Language::from_str(&language)?, variant.as_deref())
+                    .await?;
+                match output {
+                    Some(path) => std::fs::write(path, tmx)?,
+                    None => print!("{tmx}"),
+                }
+            }
+            Commands::ImportTmx {
+                id,
+                target_language,
+                path,
+            } => {
+                let tmx = std::fs::read_to_string(path)?;
+                let (language, variant) = split_language_tag(target_language);
+                let report = library
+                    .import_tmx(
+                        id,
+                        &Language::from_str(&language)?,
+                        variant.as_deref(),
+                        &tmx,
+                    )
+                    .await?;
+                println!(
+                    "Seeded {} paragraph(s) from {} unit(s); {} unit(s) unmatched",
+                    report.paragraphs, report.applied, report.unmatched
+                );
+            }
+            Commands::ExportState { output } => {
+                let json = library.export_user_state().await?;
+                match output {
+                    Some(path) => std::fs::write(path, json)?,
+                    None => println!("{json}"),
+                }
+            }
+            Commands::ImportState { path } => {
+                let json = std::fs::read_to_string(path)?;
+                let imported = library.import_user_state(&json).await?;
+                println!("Restored state of {imported} book(s)");
+            }
+            Commands::Dictionaries {} => {
+                list_dictionaries(&library, cli.json).await?;
+            }
+            Commands::PruneDictionary {
+                source_language,
+                target_language,
+                min_count,
+            } => {
+                let removed = library
+                    .dictionary(
+                        Language::from_str(source_language)?,
+                        Language::from_str(target_language)?,
+                    )
+                    .purge_below(*min_count)
+                    .await?;
+                println!("Removed {removed} rare translation(s)");
+            }
+            Commands::RebuildDictionary {
+                source_language,
+                target_language,
+            } => {
+                let report = library
+                    .rebuild_dictionary(
+                        Language::from_str(source_language)?,
+                        Language::from_str(target_language)?,
+                    )
+                    .await?;
+                println!(
+                    "Rebuilt {} card(s), removed {} stale card(s)",
+                    report.cards, report.removed
+                );
+            }
+            Commands::Relocate { new_path } => {
+                library.move_library(new_path.clone()).await?;
+                println!("Library moved to {}", new_path.display());
             }
             Commands::Summaries {
                 id,
@@ -534,19 +1536,56 @@ async fn do_main() -> anyhow::Result<()> {
             Commands::Translate {
                 id,
                 api_key,
+                fixtures,
                 translation_language,
                 n_parallel,
+                rpm,
+                latency,
+                temperature,
+                thinking_budget,
+                log_requests,
+                retry_failed,
+                source_language,
+                allow_language_mismatch,
             } => {
-                let cache = Arc::new(get_cache().await?);
-                let gemini_prompt_cache = get_gemini_prompt_cache().await?;
+                let n_workers = match n_parallel {
+                    Some(Parallelism::Fixed(n)) => *n,
+                    Some(Parallelism::Auto) => {
+                        let n = suggested_worker_count(*rpm, Duration::from_secs(*latency));
+                        if verbosity.is_verbose() {
+                            println!(
+                                "Using {n} parallel workers ({rpm} requests/min, ~{latency}s per request)"
+                            );
+                        }
+                        n
+                    }
+                    None => 5,
+                };
+                let services = TranslatorServices {
+                    cache: Arc::new(get_cache().await?),
+                    context_provider: Arc::new(NoChapterContext),
+                    gemini_prompt_cache: get_gemini_prompt_cache().await?,
+                };
                 translate_book(
                     library,
-                    cache,
-                    gemini_prompt_cache,
-                    api_key,
-                    *id,
-                    translation_language,
-                    n_parallel.unwrap_or(5),
+                    services,
+                    TranslateOptions {
+                        api_key: api_key.as_deref(),
+                        fixtures: fixtures.as_deref(),
+                        book_id: *id,
+                        target_language: translation_language,
+                        n_workers,
+                        translator_config: TranslatorConfig {
+                            temperature: *temperature,
+                            thinking_budget: *thinking_budget,
+                            request_log: log_requests.clone(),
+                            ..TranslatorConfig::default()
+                        },
+                        retry_failed: *retry_failed,
+                        source_language: source_language.as_deref(),
+                        allow_language_mismatch: *allow_language_mismatch,
+                    },
+                    verbosity,
                 )
                 .await?;
             }
@@ -558,3 +1597,121 @@ async fn do_main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use library::book::translation_import::{ParagraphTranslation, Sentence};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn translate_book_drains_the_queue_and_saves_at_the_end() {
+        let root = std::env::temp_dir().join(format!("flts_cli_translate_{}", Uuid::new_v4()));
+        let fixtures = root.join("fixtures");
+        std::fs::create_dir_all(&fixtures).unwrap();
+        let texts: Vec<String> = (0..25).map(|i| format!("Paragraph {i}.")).collect();
+        let translator = FixtureTranslator::new(&fixtures);
+        for text in &texts {
+            let canned = ParagraphTranslation {
+                timestamp: 0,
+                sentences: vec![Sentence {
+                    full_translation: text.to_uppercase(),
+                    words: vec![],
+                }],
+                total_tokens: None,
+                token_usage: None,
+            };
+            std::fs::write(
+                translator.fixture_path(text),
+                serde_json::to_vec(&canned).unwrap(),
+            )
+            .unwrap();
+        }
+
+        let eng = Language::from_639_3("eng").unwrap();
+        let library = Arc::new(Library::open(root.join("lib")).await.unwrap());
+        let book_id = library
+            .create_book_plain("Queue", &texts.join("\n"), &eng)
+            .await
+            .unwrap()
+            .id;
+        let cache = TranslationsCache::create(&root.join("cache"))
+            .await
+            .unwrap();
+        let services = TranslatorServices {
+            cache: Arc::new(cache),
+            context_provider: Arc::new(NoChapterContext),
+            gemini_prompt_cache: GeminiPromptCache::open(&root.join("gemini"), 1 << 20)
+                .await
+                .unwrap(),
+        };
+        translate_book(
+            library.clone(),
+            services,
+            TranslateOptions {
+                api_key: None,
+                fixtures: Some(&fixtures),
+                book_id,
+                target_language: "rus",
+                // Fewer channel slots than paragraphs, so the feeder has to
+                // wait for the workers.
+                n_workers: 2,
+                translator_config: TranslatorConfig::default(),
+                retry_failed: false,
+                source_language: Some("eng"),
+                allow_language_mismatch: false,
+            },
+            Verbosity::Quiet,
+        )
+        .await
+        .unwrap();
+
+        let book = library.get_book(&book_id).await.unwrap();
+        assert!(!book.lock().await.has_unsaved_changes().await);
+        drop(book);
+        drop(library);
+
+        // Everything translated is on disk, not just in memory.
+        let rus = Language::from_639_3("rus").unwrap();
+        let reopened = Library::open(root.join("lib")).await.unwrap();
+        let book = reopened.get_book(&book_id).await.unwrap();
+        let book = book.lock().await;
+        let translation = book.get_translation(&rus).await.unwrap();
+        let translation = translation.lock().await;
+        assert_eq!(translation.translated_paragraphs_count(), texts.len());
+        let first = translation.paragraph_view(0).unwrap();
+        assert_eq!(first.sentence_view(0).full_translation, "PARAGRAPH 0.");
+        drop(translation);
+        drop(book);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn parses_manifest_entries_relative_to_its_directory() {
+        let entries = parse_manifest(
+            r#"[{"path": "a.epub", "title": "A", "folder": "Fiction"}, {"path": "b.txt"}]"#,
+            Path::new("/books"),
+        )
+        .unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].path, Path::new("/books/a.epub"));
+        assert_eq!(entries[0].title.as_deref(), Some("A"));
+        assert_eq!(entries[0].folder.as_deref(), Some("Fiction"));
+        assert_eq!(entries[1].path, Path::new("/books/b.txt"));
+        assert!(entries[1].language.is_none());
+    }
+
+    #[test]
+    fn rejects_manifest_entries_with_missing_or_invalid_fields() {
+        let error = |json| match parse_manifest(json, Path::new("/books")) {
+            Ok(_) => panic!("manifest {json} should be rejected"),
+            Err(err) => err.to_string(),
+        };
+
+        assert!(error(r#"[{"title": "A"}]"#).contains("missing field `path`"));
+        assert!(error(r#"[{"path": "a.epub", "title": 3}]"#).contains("invalid type"));
+        assert!(error(r#"{"path": "a.epub"}"#).contains("invalid type"));
+    }
+}
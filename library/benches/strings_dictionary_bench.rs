@@ -0,0 +1,98 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use library::book::book::Book;
+use library::book::serialization::Serializable;
+use library::book::strings_dictionary::{DEFAULT_DICTIONARY_SIZE, StringsDictionary};
+use rand::rngs::StdRng;
+use rand::{RngExt, SeedableRng};
+use std::sync::Arc;
+use uuid::Uuid;
+
+const WORDS: [&str; 32] = [
+    "the",
+    "house",
+    "was",
+    "quiet",
+    "when",
+    "she",
+    "opened",
+    "door",
+    "and",
+    "looked",
+    "outside",
+    "again",
+    "morning",
+    "river",
+    "through",
+    "window",
+    "whispered",
+    "never",
+    "before",
+    "across",
+    "garden",
+    "slowly",
+    "remembered",
+    "evening",
+    "letter",
+    "mother",
+    "village",
+    "winter",
+    "forgotten",
+    "answer",
+    "travelled",
+    "station",
+];
+
+/// A short story: one chapter of a handful of paragraphs drawn from a shared
+/// vocabulary, like a library of graded readers in one language.
+fn generate_small_book(rng: &mut StdRng) -> Book {
+    let mut book = Book::create(
+        Uuid::new_v4(),
+        "Short story",
+        &isolang::Language::from_639_3("eng").unwrap(),
+    );
+    let chapter = book.push_chapter(Some("Chapter 1"));
+    for _ in 0..rng.random_range(3..=8) {
+        let words: Vec<_> = (0..rng.random_range(20..=60))
+            .map(|_| WORDS[rng.random_range(0..WORDS.len())])
+            .collect();
+        book.push_paragraph(chapter, &words.join(" "), None);
+    }
+    book
+}
+
+fn serialized_len(book: &Book) -> usize {
+    let mut buffer = Vec::new();
+    book.serialize(&mut buffer).unwrap();
+    buffer.len()
+}
+
+fn bench_strings_dictionary(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut books: Vec<Book> = (0..500).map(|_| generate_small_book(&mut rng)).collect();
+
+    let samples: Vec<Vec<u8>> = books.iter().map(|b| b.strings_blob().to_vec()).collect();
+    let dictionary = Arc::new(StringsDictionary::train(&samples, DEFAULT_DICTIONARY_SIZE).unwrap());
+
+    let plain: usize = books.iter().map(serialized_len).sum();
+    for book in &mut books {
+        book.set_strings_dictionary(Some(dictionary.clone()));
+    }
+    let with_dictionary: usize = books.iter().map(serialized_len).sum();
+    println!(
+        "500 small books: {plain} bytes plain, {with_dictionary} bytes with a {} byte dictionary ({:.1}%)",
+        dictionary.bytes().len(),
+        with_dictionary as f64 * 100.0 / plain as f64
+    );
+
+    c.bench_function("serialize 500 small books (strings dictionary)", |b| {
+        b.iter(|| {
+            for book in &books {
+                let mut buffer = Vec::new();
+                book.serialize(&mut buffer).unwrap();
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_strings_dictionary);
+criterion_main!(benches);
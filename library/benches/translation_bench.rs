@@ -83,6 +83,7 @@ fn generate_translation(paragraphs_count: usize) -> Translation {
             timestamp: 1234567890,
             sentences,
             total_tokens: Some(rng.random_range(50..200)),
+            token_usage: None,
         };
 
         translation.add_paragraph_translation(
@@ -730,6 +730,9 @@ mod tests {
             translations: by_pos,
             examples,
             anki_data: None,
+            usage: Default::default(),
+            last_seen: Default::default(),
+            usage_sources: Default::default(),
         }
     }
 
@@ -1958,6 +1961,9 @@ mod tests {
                 paragraph: 0,
             }],
             anki_data: None,
+            usage: Default::default(),
+            last_seen: Default::default(),
+            usage_sources: Default::default(),
         };
         let bytes = serde_json::to_vec_pretty(&conflict_card).unwrap();
         tokio::fs::write(&conflict_path, bytes).await.unwrap();
@@ -5,6 +5,7 @@ pub mod book_metadata;
 pub mod chapter_summaries;
 pub mod serialization;
 mod soa_helpers;
+pub mod strings_dictionary;
 pub mod translation;
 pub mod translation_import;
 pub mod translation_metadata;
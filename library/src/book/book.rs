@@ -1,14 +1,24 @@
 use log::info;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::book::serialization::{
-    ChecksumedWriter, Magic, Serializable, Version, read_exact_array, read_len_prefixed_vec,
-    read_opt, read_u8, read_u64, read_var_u64, read_vec_slice, validate_hash, write_opt, write_u64,
-    write_var_u64, write_vec_slice,
+    ChecksumedWriter, HTML_ONLY_PARAGRAPHS_FLAG, HashAlgorithm, Magic, STRINGS_DICTIONARY_FLAG,
+    Serializable, Version, read_exact_array, read_len_prefixed_vec, read_opt, read_u8, read_u64,
+    read_var_u64, read_var_usize, read_vec_slice, u64_to_usize, validate_hash, write_opt,
+    write_u64, write_var_u64, write_vec_slice,
 };
+use crate::book::strings_dictionary::StringsDictionary;
 use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::Display;
 use std::io::{self, BufWriter, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
 use std::time::Instant;
+use unicode_segmentation::UnicodeSegmentation;
 
 use super::soa_helpers::*;
 
@@ -20,6 +30,11 @@ pub struct Book {
     paragraph_map: Vec<usize>,
     paragraphs: Vec<Paragraph>,
     strings: Vec<u8>,
+    /// Shared dictionary the strings blob is compressed with; `None` writes
+    /// the plain version 1 format.
+    strings_dictionary: Option<Arc<StringsDictionary>>,
+    /// Checksum written in the file trailer; kept across a load/save cycle.
+    hash_algorithm: HashAlgorithm,
 }
 
 struct Chapter {
@@ -31,9 +46,107 @@ struct Chapter {
 struct Paragraph {
     id: usize,
     original_html: Option<VecSlice<u8>>,
-    original_text: VecSlice<u8>,
+    /// `None` when only the HTML is stored; the text is then derived from it
+    /// with [`html_to_text`].
+    original_text: Option<VecSlice<u8>>,
 }
 
+/// Which of a paragraph's text and HTML an import keeps. HTML paragraphs
+/// otherwise store their content twice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ParagraphStorage {
+    #[default]
+    Both,
+    /// Keep the HTML and derive the text from it. Paragraphs whose HTML
+    /// doesn't strip back to exactly their text keep both.
+    HtmlOnly,
+    /// Drop the HTML; the paragraph renders as plain text.
+    TextOnly,
+}
+
+/// Plain text of a paragraph's sanitized HTML: the tags are dropped, text
+/// between them is kept as is. Importers store text nodes unescaped, so
+/// there are no entities to decode.
+pub fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let tag = &rest[start..];
+        let is_tag = tag[1..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '/');
+        match tag.find('>') {
+            Some(end) if is_tag => rest = &tag[end + 1..],
+            _ => {
+                text.push('<');
+                rest = &tag[1..];
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+/// Structural inconsistencies found by [`Book::validate`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum BookError {
+    /// A chapter's paragraph slice extends past the end of `paragraph_map`.
+    ChapterOutOfRange {
+        chapter: usize,
+        end: usize,
+        paragraph_map_len: usize,
+    },
+    /// Two chapters claim overlapping ranges of `paragraph_map`.
+    ChaptersOverlap { first: usize, second: usize },
+    /// A chapter maps to a paragraph index that doesn't exist.
+    ParagraphOutOfRange { chapter: usize, paragraph: usize },
+    /// The same paragraph id is stored or mapped more than once.
+    DuplicateParagraphId(usize),
+    /// A chapter's title isn't valid UTF-8.
+    InvalidChapterTitle(usize),
+    /// A paragraph's text or HTML isn't valid UTF-8.
+    InvalidParagraphText(usize),
+}
+
+impl Display for BookError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BookError::ChapterOutOfRange {
+                chapter,
+                end,
+                paragraph_map_len,
+            } => write!(
+                f,
+                "Chapter {chapter} ends at {end}, past the paragraph map ({paragraph_map_len})"
+            ),
+            BookError::ChaptersOverlap { first, second } => {
+                write!(
+                    f,
+                    "Chapters {first} and {second} overlap in the paragraph map"
+                )
+            }
+            BookError::ParagraphOutOfRange { chapter, paragraph } => {
+                write!(
+                    f,
+                    "Chapter {chapter} references missing paragraph {paragraph}"
+                )
+            }
+            BookError::DuplicateParagraphId(id) => write!(f, "Duplicate paragraph id {id}"),
+            BookError::InvalidChapterTitle(chapter) => {
+                write!(f, "Chapter {chapter} has a title that isn't valid UTF-8")
+            }
+            BookError::InvalidParagraphText(id) => {
+                write!(f, "Paragraph {id} has text that isn't valid UTF-8")
+            }
+        }
+    }
+}
+
+impl Error for BookError {}
+
 pub struct ChapterView<'a> {
     pub idx: usize,
     book: &'a Book,
@@ -47,6 +160,24 @@ pub struct ParagraphView<'a> {
     pub original_text: Cow<'a, str>,
 }
 
+impl ParagraphView<'_> {
+    /// Character (Unicode scalar) ranges `[start, end)` of the words in
+    /// `original_text`, following UAX #29 word boundaries. Spaces and
+    /// punctuation between words are not included.
+    pub fn word_boundaries(&self) -> Vec<(usize, usize)> {
+        let mut boundaries = Vec::new();
+        let mut offset = 0;
+        for segment in self.original_text.split_word_bounds() {
+            let len = segment.chars().count();
+            if segment.chars().any(char::is_alphanumeric) {
+                boundaries.push((offset, offset + len));
+            }
+            offset += len;
+        }
+        boundaries
+    }
+}
+
 impl Book {
     pub fn create(id: Uuid, title: &str, language: &isolang::Language) -> Self {
         Book {
@@ -57,9 +188,34 @@ impl Book {
             paragraph_map: vec![],
             paragraphs: vec![],
             strings: vec![],
+            strings_dictionary: None,
+            hash_algorithm: HashAlgorithm::default(),
         }
     }
 
+    /// Uncompressed strings blob, e.g. as a dictionary training sample.
+    pub fn strings_blob(&self) -> &[u8] {
+        &self.strings
+    }
+
+    pub fn strings_dictionary(&self) -> Option<&Arc<StringsDictionary>> {
+        self.strings_dictionary.as_ref()
+    }
+
+    /// Compress the strings blob with `dictionary` from the next save on.
+    pub fn set_strings_dictionary(&mut self, dictionary: Option<Arc<StringsDictionary>>) {
+        self.strings_dictionary = dictionary;
+    }
+
+    pub fn hash_algorithm(&self) -> HashAlgorithm {
+        self.hash_algorithm
+    }
+
+    /// Checksum the file with `algorithm` from the next save on.
+    pub fn set_hash_algorithm(&mut self, algorithm: HashAlgorithm) {
+        self.hash_algorithm = algorithm;
+    }
+
     pub fn chapter_count(&self) -> usize {
         self.chapters.len()
     }
@@ -70,9 +226,7 @@ impl Book {
         ChapterView {
             idx: chapter_index,
             book: self,
-            title: chapter
-                .title
-                .map(|t| String::from_utf8_lossy(t.slice(&self.strings))),
+            title: chapter.title.map(|t| t.to_str(&self.strings)),
             paragraph_indices,
         }
     }
@@ -85,10 +239,37 @@ impl Book {
         let paragraph = &self.paragraphs[paragraph_id];
         ParagraphView {
             id: paragraph_id,
-            original_html: paragraph
-                .original_html
-                .map(|h| String::from_utf8_lossy(h.slice(&self.strings))),
-            original_text: String::from_utf8_lossy(paragraph.original_text.slice(&self.strings)),
+            original_html: paragraph.original_html.map(|h| h.to_str(&self.strings)),
+            original_text: self.paragraph_text(paragraph),
+        }
+    }
+
+    fn paragraph_text(&self, paragraph: &Paragraph) -> Cow<'_, str> {
+        match (paragraph.original_text, paragraph.original_html) {
+            (Some(text), _) => text.to_str(&self.strings),
+            (None, Some(html)) => Cow::Owned(html_to_text(&html.to_str(&self.strings))),
+            (None, None) => Cow::Borrowed(""),
+        }
+    }
+
+    /// Pushes a paragraph's strings as `storage` asks.
+    fn push_paragraph_strings(
+        &mut self,
+        original_text: &str,
+        original_html: Option<&str>,
+        storage: ParagraphStorage,
+    ) -> (Option<VecSlice<u8>>, Option<VecSlice<u8>>) {
+        match (storage, original_html) {
+            (ParagraphStorage::TextOnly, _) | (_, None) => {
+                (Some(push_string(&mut self.strings, original_text)), None)
+            }
+            (ParagraphStorage::HtmlOnly, Some(html)) if html_to_text(html) == original_text => {
+                (None, Some(push_string(&mut self.strings, html)))
+            }
+            (_, Some(html)) => (
+                Some(push_string(&mut self.strings, original_text)),
+                Some(push_string(&mut self.strings, html)),
+            ),
         }
     }
 
@@ -107,8 +288,24 @@ impl Book {
         original_text: &str,
         original_html: Option<&str>,
     ) -> usize {
-        let original_text = push_string(&mut self.strings, original_text);
-        let original_html = original_html.map(|s| push_string(&mut self.strings, s));
+        self.push_paragraph_with_storage(
+            chapter_index,
+            original_text,
+            original_html,
+            ParagraphStorage::Both,
+        )
+    }
+
+    /// Like [`Book::push_paragraph`], keeping only what `storage` asks for.
+    pub fn push_paragraph_with_storage(
+        &mut self,
+        chapter_index: usize,
+        original_text: &str,
+        original_html: Option<&str>,
+        storage: ParagraphStorage,
+    ) -> usize {
+        let (original_text, original_html) =
+            self.push_paragraph_strings(original_text, original_html, storage);
         let new_paragraph = Paragraph {
             id: 0,
             original_html,
@@ -128,10 +325,221 @@ impl Book {
         paragraphs_slice.len - 1
     }
 
+    /// Inserts a paragraph at `position` within the chapter, shifting the
+    /// following ones down, and returns its paragraph id.
+    ///
+    /// The new paragraph gets the next free id; existing ids are never
+    /// renumbered. Translations are keyed by paragraph id, so they stay
+    /// attached to their paragraphs and the new one starts untranslated.
+    /// Reading-order positions ([`Book::global_paragraph_index`]) of the
+    /// paragraphs after it do shift by one.
+    ///
+    /// Panics if `position` is greater than the chapter's paragraph count.
+    pub fn insert_paragraph(
+        &mut self,
+        chapter_index: usize,
+        position: usize,
+        original_text: &str,
+        original_html: Option<&str>,
+    ) -> usize {
+        let chapter_len = self.chapters[chapter_index].paragraphs.len;
+        assert!(
+            position <= chapter_len,
+            "insertion position {position} past the end of chapter {chapter_index} ({chapter_len} paragraphs)"
+        );
+
+        let (original_text, original_html) =
+            self.push_paragraph_strings(original_text, original_html, ParagraphStorage::Both);
+        let paragraph_id = self.paragraphs.len();
+        self.paragraphs.push(Paragraph {
+            id: paragraph_id,
+            original_html,
+            original_text,
+        });
+
+        let paragraphs_slice = insert(
+            &mut self.paragraph_map,
+            &self.chapters[chapter_index].paragraphs,
+            position,
+            paragraph_id,
+        )
+        .unwrap();
+        self.chapters[chapter_index].paragraphs = paragraphs_slice;
+        paragraph_id
+    }
+
+    /// Replaces the original text and HTML of paragraph `paragraph_id`,
+    /// keeping its id, position and [storage](ParagraphStorage). The old
+    /// strings stay in the blob, unused.
+    ///
+    /// Returns `true` if the text changed, i.e. the paragraph's translations
+    /// were made against a different original and may be stale. An HTML-only
+    /// change returns `false`.
+    ///
+    /// Panics if there is no paragraph `paragraph_id`.
+    pub fn update_paragraph_text(
+        &mut self,
+        paragraph_id: usize,
+        new_text: &str,
+        new_html: Option<&str>,
+    ) -> bool {
+        let paragraph = self.paragraphs[paragraph_id];
+        let text_changed = self.paragraph_text(&paragraph) != new_text;
+        let html_changed =
+            paragraph.original_html.map(|h| h.slice(&self.strings)) != new_html.map(str::as_bytes);
+        if !text_changed && !html_changed {
+            return false;
+        }
+
+        let storage = match paragraph.original_text {
+            Some(_) => ParagraphStorage::Both,
+            None => ParagraphStorage::HtmlOnly,
+        };
+        let (original_text, original_html) =
+            self.push_paragraph_strings(new_text, new_html, storage);
+        self.paragraphs[paragraph_id] = Paragraph {
+            id: paragraph.id,
+            original_html,
+            original_text,
+        };
+        text_changed
+    }
+
     pub fn paragraphs_count(&self) -> usize {
         self.paragraphs.len()
     }
 
+    /// Structural consistency check beyond the file checksum: every chapter
+    /// slice lies within `paragraph_map`, non-empty chapter slices don't
+    /// overlap, every mapped index is a valid paragraph, paragraph ids are
+    /// unique (both as stored and as mapped from chapters), and every string
+    /// is valid UTF-8.
+    pub fn validate(&self) -> Result<(), BookError> {
+        let mut ranges = Vec::with_capacity(self.chapters.len());
+        for (chapter_idx, chapter) in self.chapters.iter().enumerate() {
+            if chapter.paragraphs.end() > self.paragraph_map.len() {
+                return Err(BookError::ChapterOutOfRange {
+                    chapter: chapter_idx,
+                    end: chapter.paragraphs.end(),
+                    paragraph_map_len: self.paragraph_map.len(),
+                });
+            }
+            if chapter.paragraphs.len > 0 {
+                ranges.push((
+                    chapter.paragraphs.start,
+                    chapter.paragraphs.end(),
+                    chapter_idx,
+                ));
+            }
+        }
+
+        ranges.sort_unstable();
+        for pair in ranges.windows(2) {
+            let (_, first_end, first) = pair[0];
+            let (second_start, _, second) = pair[1];
+            if second_start < first_end {
+                return Err(BookError::ChaptersOverlap {
+                    first: first.min(second),
+                    second: first.max(second),
+                });
+            }
+        }
+
+        let mut mapped = vec![false; self.paragraphs.len()];
+        for (chapter_idx, chapter) in self.chapters.iter().enumerate() {
+            for &paragraph in chapter.paragraphs.slice(&self.paragraph_map) {
+                if paragraph >= self.paragraphs.len() {
+                    return Err(BookError::ParagraphOutOfRange {
+                        chapter: chapter_idx,
+                        paragraph,
+                    });
+                }
+                if mapped[paragraph] {
+                    return Err(BookError::DuplicateParagraphId(paragraph));
+                }
+                mapped[paragraph] = true;
+            }
+        }
+
+        let mut ids = HashSet::with_capacity(self.paragraphs.len());
+        for paragraph in &self.paragraphs {
+            if !ids.insert(paragraph.id) {
+                return Err(BookError::DuplicateParagraphId(paragraph.id));
+            }
+        }
+
+        for (chapter_idx, chapter) in self.chapters.iter().enumerate() {
+            if chapter
+                .title
+                .is_some_and(|t| t.try_str(&self.strings).is_err())
+            {
+                return Err(BookError::InvalidChapterTitle(chapter_idx));
+            }
+        }
+        for paragraph in &self.paragraphs {
+            if paragraph
+                .original_text
+                .is_some_and(|t| t.try_str(&self.strings).is_err())
+                || paragraph
+                    .original_html
+                    .is_some_and(|h| h.try_str(&self.strings).is_err())
+            {
+                return Err(BookError::InvalidParagraphText(paragraph.id));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Groups of ids of chapter paragraphs whose `original_text` is
+    /// identical, each group in ascending id order. Paragraphs without a
+    /// duplicate are omitted.
+    pub fn find_duplicate_paragraphs(&self) -> Vec<Vec<usize>> {
+        let mut groups: HashMap<Cow<'_, str>, Vec<usize>> = HashMap::new();
+        for chapter in &self.chapters {
+            for &paragraph_id in chapter.paragraphs.slice(&self.paragraph_map) {
+                groups
+                    .entry(self.paragraph_text(&self.paragraphs[paragraph_id]))
+                    .or_default()
+                    .push(paragraph_id);
+            }
+        }
+        let mut duplicates: Vec<Vec<usize>> =
+            groups.into_values().filter(|g| g.len() > 1).collect();
+        for group in &mut duplicates {
+            group.sort_unstable();
+        }
+        duplicates.sort_unstable_by_key(|g| g[0]);
+        duplicates
+    }
+
+    /// Takes every paragraph but the first of each duplicate group out of
+    /// its chapter. Paragraph ids don't change, so translations, footnotes
+    /// and reading positions keyed by id stay valid. Returns, for each
+    /// paragraph id, the id of the paragraph it duplicates, or its own id.
+    pub fn dedupe_paragraphs(&mut self) -> Vec<usize> {
+        let mut survivor: Vec<usize> = (0..self.paragraphs.len()).collect();
+        for group in self.find_duplicate_paragraphs() {
+            for &duplicate in &group[1..] {
+                survivor[duplicate] = group[0];
+            }
+        }
+
+        let mut paragraph_map = Vec::with_capacity(self.paragraph_map.len());
+        for chapter in self.chapters.iter_mut() {
+            let start = paragraph_map.len();
+            for &paragraph_id in chapter.paragraphs.slice(&self.paragraph_map) {
+                if survivor[paragraph_id] == paragraph_id {
+                    paragraph_map.push(paragraph_id);
+                }
+            }
+            chapter.paragraphs = VecSlice::new(start, paragraph_map.len() - start);
+        }
+
+        self.paragraph_map = paragraph_map;
+        survivor
+    }
+
     /// Linear scan over chapters to find which one contains the given
     /// flat paragraph id. `None` if the id is out of range or not
     /// assigned to any chapter.
@@ -144,310 +552,428 @@ impl Book {
         }
         None
     }
-}
 
-impl<'a> ChapterView<'a> {
-    pub fn paragraph_count(&self) -> usize {
-        self.paragraph_indices.len()
+    /// Index of the chapter titled `title`, compared case-insensitively.
+    /// With duplicate titles the first chapter wins; untitled chapters never
+    /// match.
+    pub fn chapter_index_by_title(&self, title: &str) -> Option<usize> {
+        let title = title.to_lowercase();
+        self.chapter_views()
+            .position(|chapter| chapter.title.is_some_and(|t| t.to_lowercase() == title))
     }
 
-    pub fn paragraph_view(&self, paragraph_index: usize) -> ParagraphView<'a> {
-        let paragraph_id = self.paragraph_indices[paragraph_index];
-        let paragraph = &self.book.paragraphs[paragraph_id];
-        ParagraphView {
-            id: paragraph.id,
-            original_html: paragraph
-                .original_html
-                .map(|s| String::from_utf8_lossy(s.slice(&self.book.strings))),
-            original_text: String::from_utf8_lossy(
-                paragraph.original_text.slice(&self.book.strings),
-            ),
-        }
+    /// Reading-order position of each chapter's paragraphs: chapter `i`
+    /// covers `ranges[i]` of the concatenation of all chapters.
+    pub fn chapter_paragraph_ranges(&self) -> Vec<Range<usize>> {
+        let mut start = 0;
+        self.chapters
+            .iter()
+            .map(|chapter| {
+                let range = start..start + chapter.paragraphs.len;
+                start = range.end;
+                range
+            })
+            .collect()
     }
 
-    pub fn paragraphs(&self) -> impl Iterator<Item = ParagraphView<'a>> + '_ {
-        (0..self.paragraph_count()).map(|p| self.paragraph_view(p))
+    /// Reading-order position of the `local`-th paragraph of `chapter`,
+    /// counting every paragraph of the preceding chapters. `None` if the
+    /// chapter doesn't exist or has no `local`-th paragraph.
+    pub fn global_paragraph_index(&self, chapter: usize, local: usize) -> Option<usize> {
+        if local >= self.chapters.get(chapter)?.paragraphs.len {
+            return None;
+        }
+        let start: usize = self.chapters[..chapter]
+            .iter()
+            .map(|c| c.paragraphs.len)
+            .sum();
+        Some(start + local)
     }
-}
-
-impl Serializable for Book {
-    fn serialize<TWriter: io::Write>(&self, output_stream: &mut TWriter) -> std::io::Result<()> {
-        // Binary format (little-endian):
-        // magic[4] = BK01
-        // u8 version = 1
-        // Metadata section
-        // u64 metadata hash
-        // u8[16] id
-        // u64 title_len, [u8]*
-        // u64 language_len, [u8]*
-        // u64 chapters_count
-        // u64 paragraphs_count
-        // Data section
-        // u64 strings_len (compressed), [u8]* (strings blob (zstd compressed))
-        // u64 paragraphs_count
-        //   repeat paragraphs_count times:
-        //     u64 original_text.start, u64 original_text.len
-        //     u8 has_html (0/1)
-        //       if 1: u64 original_html.start, u64 original_html.len
-        // u64 chapters_count
-        //   repeat chapters_count times:
-        //     u64 title.start, u64 title.len
-        //     u64 paragraphs.start, u64 paragraphs.len
-        // u64 fnv1 hash of the entire file except the hash itself
 
+    /// Reads a book whose strings blob may be compressed with a shared
+    /// dictionary, looked up in `dictionaries` (see
+    /// [`strings_dictionary`](crate::book::strings_dictionary)).
+    /// Without `dictionaries` such books fail to read.
+    pub fn deserialize_with_dictionaries<TReader: io::Seek + io::Read>(
+        input_stream: &mut TReader,
+        dictionaries: Option<&Path>,
+    ) -> std::io::Result<Self> {
         let total_start = Instant::now();
 
-        let mut hashing_stream_unbuffered = ChecksumedWriter::create(output_stream);
-        let mut hashing_stream = BufWriter::new(hashing_stream_unbuffered);
+        // Validate checksum
+        let t_hash = Instant::now();
+        let hash_valid = validate_hash(input_stream)?;
+        if !hash_valid {
+            log::error!("Failed to read book: Invalid hash");
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid hash"));
+        }
+        let d_hash = t_hash.elapsed();
 
         // Magic + version
         let t_magic = Instant::now();
-        Magic::Book.write(&mut hashing_stream)?; // magic
-        Version::V1.write_version(&mut hashing_stream)?; // version
+        let magic = read_exact_array::<4>(input_stream)?;
+        if &magic != Magic::Book.as_bytes() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid magic"));
+        }
+        let (version, hash_algorithm) = Version::read_version_and_hash(input_stream)?;
         let d_magic = t_magic.elapsed();
 
-        // Build metadata buffer
-        let t_meta_build = Instant::now();
-        let mut metadata_buf = Vec::new();
-        let mut metadata_buf_hasher = ChecksumedWriter::create(&mut metadata_buf);
-        metadata_buf_hasher.write_all(self.id.as_bytes())?;
+        // Metadata (skip hash/len, then read fields)
+        let t_meta = Instant::now();
+        // Skip metadata hash - it's only for when read only metadata
+        _ = read_u64(input_stream)?;
+
+        // Skip metadata size
+        _ = read_var_u64(input_stream)?;
+
+        let id = Uuid::from_bytes(read_exact_array::<16>(input_stream)?);
+
         // Title
-        write_var_u64(&mut metadata_buf_hasher, self.title.len() as u64)?;
-        metadata_buf_hasher.write_all(self.title.as_bytes())?;
+        let title_len = read_var_usize(input_stream)?;
+        let mut title_buf = vec![0u8; title_len];
+        input_stream.read_exact(&mut title_buf)?;
+        let title = String::from_utf8(title_buf)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF-8 in title"))?;
+
         // Language
-        write_var_u64(&mut metadata_buf_hasher, self.language.len() as u64)?;
-        metadata_buf_hasher.write_all(self.language.as_bytes())?;
-        // chapters count
-        let chapters_count = self.chapter_count();
-        write_var_u64(&mut metadata_buf_hasher, chapters_count as u64)?;
-        // paragraphs count
-        write_var_u64(&mut metadata_buf_hasher, self.paragraphs.len() as u64)?;
-        let metadata_hash = metadata_buf_hasher.current_hash();
-        let d_meta_build = t_meta_build.elapsed();
+        let language_len = read_var_usize(input_stream)?;
+        let mut language_buf = vec![0u8; language_len];
+        input_stream.read_exact(&mut language_buf)?;
+        let language = String::from_utf8(language_buf)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF-8 in language"))?;
 
-        // Write metadata
-        let t_meta_write = Instant::now();
-        write_u64(&mut hashing_stream, metadata_hash)?;
-        write_var_u64(&mut hashing_stream, metadata_buf.len() as u64)?;
-        hashing_stream.write_all(&metadata_buf)?;
-        let d_meta_write = t_meta_write.elapsed();
+        // skip chapters count
+        _ = read_var_u64(input_stream)?;
 
-        // Strings blob compress
-        let t_compress = Instant::now();
-        let encoded = zstd::stream::encode_all(self.strings.as_slice(), -7)?;
-        let d_compress = t_compress.elapsed();
+        // skip paragraphs count
+        _ = read_var_u64(input_stream)?;
+        let d_meta = t_meta.elapsed();
 
-        // Strings write
-        let t_write_strings = Instant::now();
-        write_var_u64(&mut hashing_stream, encoded.len() as u64)?;
-        hashing_stream.write_all(&encoded)?;
-        let d_write_strings = t_write_strings.elapsed();
+        // Strings blob
+        let t_strings_read = Instant::now();
+        let strings_dictionary = match version {
+            Version::V1 => None,
+            Version::V2 => {
+                let id = read_u64(input_stream)?;
+                let dictionaries = dictionaries.ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Book strings need strings dictionary {id:016x}"),
+                    )
+                })?;
+                Some(Arc::new(StringsDictionary::load(dictionaries, id)?))
+            }
+        };
+        let encoded_data = read_len_prefixed_vec(input_stream)?;
+        let d_strings_read = t_strings_read.elapsed();
+        let t_strings_decompress = Instant::now();
+        let strings = match &strings_dictionary {
+            Some(dictionary) => dictionary.decompress(&encoded_data)?,
+            None => zstd::stream::decode_all(encoded_data.as_slice())?,
+        };
+        let d_strings_decompress = t_strings_decompress.elapsed();
 
         // Paragraphs
         let t_paragraphs = Instant::now();
-        write_var_u64(&mut hashing_stream, self.paragraphs.len() as u64)?;
-        for p in &self.paragraphs {
-            write_var_u64(&mut hashing_stream, p.id as u64)?;
-            write_vec_slice(&mut hashing_stream, &p.original_text)?;
-            match p.original_html {
-                Some(slice) => {
-                    hashing_stream.write_all(&[1u8])?;
-                    write_vec_slice(&mut hashing_stream, &slice)?;
-                }
-                None => hashing_stream.write_all(&[0u8])?,
-            }
+        let paragraphs_len = read_var_usize(input_stream)?;
+        let mut paragraphs = Vec::with_capacity(paragraphs_len);
+        for _ in 0..paragraphs_len {
+            let id = read_var_usize(input_stream)?;
+            let original_text = read_vec_slice::<u8>(input_stream)?;
+            let has_html = read_u8(input_stream)?;
+            let original_html = if has_html == 1 || has_html == 2 {
+                Some(read_vec_slice::<u8>(input_stream)?)
+            } else {
+                None
+            };
+            let original_text = (has_html != 2).then_some(original_text);
+            let paragraph = Paragraph {
+                id,
+                original_html,
+                original_text,
+            };
+            paragraphs.push(paragraph);
         }
         let d_paragraphs = t_paragraphs.elapsed();
 
         // Paragraphs map
         let t_pmap = Instant::now();
-        write_var_u64(&mut hashing_stream, self.paragraph_map.len() as u64)?;
-        for p in &self.paragraph_map {
-            write_var_u64(&mut hashing_stream, *p as u64)?;
+        let paragraph_map_len = read_var_u64(input_stream)?;
+        let mut paragraph_map = Vec::with_capacity(u64_to_usize(paragraph_map_len)?);
+        for _ in 0..paragraph_map_len {
+            let p = read_var_usize(input_stream)?;
+            paragraph_map.push(p);
         }
         let d_pmap = t_pmap.elapsed();
 
         // Chapters
         let t_chapters = Instant::now();
-        write_var_u64(&mut hashing_stream, self.chapters.len() as u64)?;
-        for c in &self.chapters {
-            write_opt(&mut hashing_stream, &c.title)?;
-            write_vec_slice(&mut hashing_stream, &c.paragraphs)?;
+        let chapters_len = read_var_usize(input_stream)?;
+        let mut chapters = Vec::with_capacity(chapters_len);
+        for _ in 0..chapters_len {
+            let title = read_opt(input_stream)?;
+            let paragraphs_slice = read_vec_slice::<usize>(input_stream)?;
+            chapters.push(Chapter {
+                title,
+                paragraphs: paragraphs_slice,
+            });
         }
         let d_chapters = t_chapters.elapsed();
 
-        // Hash
-        let t_finalize = Instant::now();
-        hashing_stream_unbuffered = hashing_stream.into_inner()?;
-        let hash = hashing_stream_unbuffered.current_hash();
-        write_u64(output_stream, hash)?;
-        output_stream.flush()?;
-        let d_finalize = t_finalize.elapsed();
-
         let total = total_start.elapsed();
 
         info!(
-            "Serialization timings (Book):\n  - magic+version: {:?}\n  - metadata build: {:?}\n  - metadata write: {:?}\n  - strings compress ({} -> {} bytes): {:?}\n  - strings write: {:?}\n  - paragraphs ({}): {:?}\n  - paragraph map ({}): {:?}\n  - chapters ({}): {:?}\n  - finalize hash+flush: {:?}\n  - TOTAL: {:?}",
+            "Deserialization timings (Book):\n  - hash validate: {:?}\n  - magic+version: {:?}\n  - metadata (incl. read): {:?}\n  - strings read: {:?}\n  - strings decompress ({} -> {} bytes): {:?}\n  - paragraphs ({}): {:?}\n  - paragraph map ({}): {:?}\n  - chapters ({}): {:?}\n  - TOTAL: {:?}",
+            d_hash,
             d_magic,
-            d_meta_build,
-            d_meta_write,
-            self.strings.len(),
-            encoded.len(),
-            d_compress,
-            d_write_strings,
-            self.paragraphs.len(),
+            d_meta,
+            d_strings_read,
+            encoded_data.len(),
+            strings.len(),
+            d_strings_decompress,
+            paragraphs_len,
             d_paragraphs,
-            self.paragraph_map.len(),
+            paragraph_map_len,
             d_pmap,
-            self.chapters.len(),
+            chapters_len,
             d_chapters,
-            d_finalize,
             total
         );
 
-        Ok(())
+        let book = Book {
+            id,
+            title,
+            language,
+            chapters,
+            paragraphs,
+            paragraph_map,
+            strings,
+            strings_dictionary,
+            hash_algorithm,
+        };
+        // Only logged: callers such as `flts verify` load malformed books on
+        // purpose and report `validate()` themselves.
+        #[cfg(debug_assertions)]
+        if let Err(err) = book.validate() {
+            log::warn!(
+                "Deserialized book {} is structurally invalid: {err}",
+                book.id
+            );
+        }
+
+        Ok(book)
     }
 
-    fn deserialize<TReader: io::Seek + io::Read>(
-        input_stream: &mut TReader,
-    ) -> std::io::Result<Self>
-    where
-        Self: Sized,
-    {
+    /// Serializes the book with its strings blob compressed with
+    /// `dictionary` instead of the book's own, e.g. `None` for a copy that
+    /// must be readable outside the library.
+    pub fn serialize_with_dictionary<TWriter: io::Write>(
+        &self,
+        output_stream: &mut TWriter,
+        dictionary: Option<&StringsDictionary>,
+    ) -> std::io::Result<()> {
+        // Binary format (little-endian):
+        // magic[4] = BK01
+        // u8 version = 1, or 2 with a strings dictionary
+        // Metadata section
+        // u64 metadata hash
+        // u8[16] id
+        // u64 title_len, [u8]*
+        // u64 language_len, [u8]*
+        // u64 chapters_count
+        // u64 paragraphs_count
+        // Data section
+        // (version 2 only) u64 strings dictionary id
+        // u64 strings_len (compressed), [u8]* (strings blob (zstd compressed))
+        // u64 paragraphs_count
+        //   repeat paragraphs_count times:
+        //     u64 original_text.start, u64 original_text.len
+        //     u8 has_html (0/1, or 2 when only the HTML is stored and
+        //       original_text is an empty placeholder)
+        //       if 1 or 2: u64 original_html.start, u64 original_html.len
+        // u64 chapters_count
+        //   repeat chapters_count times:
+        //     u64 title.start, u64 title.len
+        //     u64 paragraphs.start, u64 paragraphs.len
+        // u64 hash of the entire file except the hash itself (fnv1, or
+        //   truncated sha256 when flagged in the version byte)
+        // The version byte also flags books with HTML-only paragraphs and
+        // books with a strings dictionary, so readers that predate them
+        // refuse the file.
+
         let total_start = Instant::now();
 
-        // Validate checksum
-        let t_hash = Instant::now();
-        let hash_valid = validate_hash(input_stream)?;
-        if !hash_valid {
-            log::error!("Failed to read book: Invalid hash");
-            return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid hash"));
-        }
-        let d_hash = t_hash.elapsed();
+        let mut hashing_stream_unbuffered =
+            ChecksumedWriter::create_with(output_stream, self.hash_algorithm);
+        let mut hashing_stream = BufWriter::new(hashing_stream_unbuffered);
 
         // Magic + version
         let t_magic = Instant::now();
-        let magic = read_exact_array::<4>(input_stream)?;
-        if &magic != Magic::Book.as_bytes() {
-            return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid magic"));
+        Magic::Book.write(&mut hashing_stream)?; // magic
+        let version = match dictionary {
+            Some(_) => Version::V2,
+            None => Version::V1,
+        };
+        let mut flags = 0;
+        if self.paragraphs.iter().any(|p| p.original_text.is_none()) {
+            flags |= HTML_ONLY_PARAGRAPHS_FLAG;
+        }
+        if dictionary.is_some() {
+            flags |= STRINGS_DICTIONARY_FLAG;
         }
-        Version::read_version(input_stream)?; // ensure supported
+        version.write_version_with_flags(&mut hashing_stream, self.hash_algorithm, flags)?; // version
         let d_magic = t_magic.elapsed();
 
-        // Metadata (skip hash/len, then read fields)
-        let t_meta = Instant::now();
-        // Skip metadata hash - it's only for when read only metadata
-        _ = read_u64(input_stream)?;
-
-        // Skip metadata size
-        _ = read_var_u64(input_stream)?;
-
-        let id = Uuid::from_bytes(read_exact_array::<16>(input_stream)?);
-
+        // Build metadata buffer
+        let t_meta_build = Instant::now();
+        let mut metadata_buf = Vec::new();
+        let mut metadata_buf_hasher = ChecksumedWriter::create(&mut metadata_buf);
+        metadata_buf_hasher.write_all(self.id.as_bytes())?;
         // Title
-        let title_len = read_var_u64(input_stream)? as usize;
-        let mut title_buf = vec![0u8; title_len];
-        input_stream.read_exact(&mut title_buf)?;
-        let title = String::from_utf8(title_buf)
-            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF-8 in title"))?;
-
+        write_var_u64(&mut metadata_buf_hasher, self.title.len() as u64)?;
+        metadata_buf_hasher.write_all(self.title.as_bytes())?;
         // Language
-        let language_len = read_var_u64(input_stream)? as usize;
-        let mut language_buf = vec![0u8; language_len];
-        input_stream.read_exact(&mut language_buf)?;
-        let language = String::from_utf8(language_buf)
-            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF-8 in language"))?;
+        write_var_u64(&mut metadata_buf_hasher, self.language.len() as u64)?;
+        metadata_buf_hasher.write_all(self.language.as_bytes())?;
+        // chapters count
+        let chapters_count = self.chapter_count();
+        write_var_u64(&mut metadata_buf_hasher, chapters_count as u64)?;
+        // paragraphs count
+        write_var_u64(&mut metadata_buf_hasher, self.paragraphs.len() as u64)?;
+        let metadata_hash = metadata_buf_hasher.current_hash();
+        let d_meta_build = t_meta_build.elapsed();
 
-        // skip chapters count
-        _ = read_var_u64(input_stream)?;
+        // Write metadata
+        let t_meta_write = Instant::now();
+        write_u64(&mut hashing_stream, metadata_hash)?;
+        write_var_u64(&mut hashing_stream, metadata_buf.len() as u64)?;
+        hashing_stream.write_all(&metadata_buf)?;
+        let d_meta_write = t_meta_write.elapsed();
 
-        // skip paragraphs count
-        _ = read_var_u64(input_stream)?;
-        let d_meta = t_meta.elapsed();
+        // Strings blob compress
+        let t_compress = Instant::now();
+        let encoded = match dictionary {
+            Some(dictionary) => dictionary.compress(&self.strings)?,
+            None => zstd::stream::encode_all(self.strings.as_slice(), -7)?,
+        };
+        let d_compress = t_compress.elapsed();
 
-        // Strings blob
-        let t_strings_read = Instant::now();
-        let encoded_data = read_len_prefixed_vec(input_stream)?;
-        let d_strings_read = t_strings_read.elapsed();
-        let t_strings_decompress = Instant::now();
-        let strings = zstd::stream::decode_all(encoded_data.as_slice())?;
-        let d_strings_decompress = t_strings_decompress.elapsed();
+        // Strings write
+        let t_write_strings = Instant::now();
+        if let Some(dictionary) = dictionary {
+            write_u64(&mut hashing_stream, dictionary.id())?;
+        }
+        write_var_u64(&mut hashing_stream, encoded.len() as u64)?;
+        hashing_stream.write_all(&encoded)?;
+        let d_write_strings = t_write_strings.elapsed();
 
         // Paragraphs
         let t_paragraphs = Instant::now();
-        let paragraphs_len = read_var_u64(input_stream)? as usize;
-        let mut paragraphs = Vec::with_capacity(paragraphs_len);
-        for _ in 0..paragraphs_len {
-            let id = read_var_u64(input_stream)? as usize;
-            let original_text = read_vec_slice::<u8>(input_stream)?;
-            let has_html = read_u8(input_stream)?;
-            let original_html = if has_html == 1 {
-                Some(read_vec_slice::<u8>(input_stream)?)
-            } else {
-                None
-            };
-            let paragraph = Paragraph {
-                id,
-                original_html,
-                original_text,
-            };
-            paragraphs.push(paragraph);
+        write_var_u64(&mut hashing_stream, self.paragraphs.len() as u64)?;
+        for p in &self.paragraphs {
+            write_var_u64(&mut hashing_stream, p.id as u64)?;
+            write_vec_slice(
+                &mut hashing_stream,
+                &p.original_text.unwrap_or(VecSlice::new(0, 0)),
+            )?;
+            match (p.original_text, p.original_html) {
+                (_, None) => hashing_stream.write_all(&[0u8])?,
+                (Some(_), Some(slice)) => {
+                    hashing_stream.write_all(&[1u8])?;
+                    write_vec_slice(&mut hashing_stream, &slice)?;
+                }
+                (None, Some(slice)) => {
+                    hashing_stream.write_all(&[2u8])?;
+                    write_vec_slice(&mut hashing_stream, &slice)?;
+                }
+            }
         }
         let d_paragraphs = t_paragraphs.elapsed();
 
         // Paragraphs map
         let t_pmap = Instant::now();
-        let paragraph_map_len = read_var_u64(input_stream)?;
-        let mut paragraph_map = Vec::with_capacity(paragraph_map_len as usize);
-        for _ in 0..paragraph_map_len {
-            let p = read_var_u64(input_stream)? as usize;
-            paragraph_map.push(p);
+        write_var_u64(&mut hashing_stream, self.paragraph_map.len() as u64)?;
+        for p in &self.paragraph_map {
+            write_var_u64(&mut hashing_stream, *p as u64)?;
         }
         let d_pmap = t_pmap.elapsed();
 
         // Chapters
         let t_chapters = Instant::now();
-        let chapters_len = read_var_u64(input_stream)? as usize;
-        let mut chapters = Vec::with_capacity(chapters_len);
-        for _ in 0..chapters_len {
-            let title = read_opt(input_stream)?;
-            let paragraphs_slice = read_vec_slice::<usize>(input_stream)?;
-            chapters.push(Chapter {
-                title,
-                paragraphs: paragraphs_slice,
-            });
+        write_var_u64(&mut hashing_stream, self.chapters.len() as u64)?;
+        for c in &self.chapters {
+            write_opt(&mut hashing_stream, &c.title)?;
+            write_vec_slice(&mut hashing_stream, &c.paragraphs)?;
         }
         let d_chapters = t_chapters.elapsed();
 
+        // Hash
+        let t_finalize = Instant::now();
+        hashing_stream_unbuffered = hashing_stream.into_inner()?;
+        let hash = hashing_stream_unbuffered.current_hash();
+        write_u64(output_stream, hash)?;
+        output_stream.flush()?;
+        let d_finalize = t_finalize.elapsed();
+
         let total = total_start.elapsed();
 
         info!(
-            "Deserialization timings (Book):\n  - hash validate: {:?}\n  - magic+version: {:?}\n  - metadata (incl. read): {:?}\n  - strings read: {:?}\n  - strings decompress ({} -> {} bytes): {:?}\n  - paragraphs ({}): {:?}\n  - paragraph map ({}): {:?}\n  - chapters ({}): {:?}\n  - TOTAL: {:?}",
-            d_hash,
+            "Serialization timings (Book):\n  - magic+version: {:?}\n  - metadata build: {:?}\n  - metadata write: {:?}\n  - strings compress ({} -> {} bytes): {:?}\n  - strings write: {:?}\n  - paragraphs ({}): {:?}\n  - paragraph map ({}): {:?}\n  - chapters ({}): {:?}\n  - finalize hash+flush: {:?}\n  - TOTAL: {:?}",
             d_magic,
-            d_meta,
-            d_strings_read,
-            encoded_data.len(),
-            strings.len(),
-            d_strings_decompress,
-            paragraphs_len,
+            d_meta_build,
+            d_meta_write,
+            self.strings.len(),
+            encoded.len(),
+            d_compress,
+            d_write_strings,
+            self.paragraphs.len(),
             d_paragraphs,
-            paragraph_map_len,
+            self.paragraph_map.len(),
             d_pmap,
-            chapters_len,
+            self.chapters.len(),
             d_chapters,
+            d_finalize,
             total
         );
 
-        Ok(Book {
-            id,
-            title,
-            language,
-            chapters,
-            paragraphs,
-            paragraph_map,
-            strings,
-        })
+        Ok(())
+    }
+}
+
+impl<'a> ChapterView<'a> {
+    pub fn paragraph_count(&self) -> usize {
+        self.paragraph_indices.len()
+    }
+
+    pub fn paragraph_view(&self, paragraph_index: usize) -> ParagraphView<'a> {
+        let paragraph_id = self.paragraph_indices[paragraph_index];
+        let paragraph = &self.book.paragraphs[paragraph_id];
+        ParagraphView {
+            id: paragraph.id,
+            original_html: paragraph
+                .original_html
+                .map(|s| s.to_str(&self.book.strings)),
+            original_text: self.book.paragraph_text(paragraph),
+        }
+    }
+
+    pub fn paragraphs(&self) -> impl Iterator<Item = ParagraphView<'a>> + '_ {
+        (0..self.paragraph_count()).map(|p| self.paragraph_view(p))
+    }
+}
+
+impl Serializable for Book {
+    fn serialize<TWriter: io::Write>(&self, output_stream: &mut TWriter) -> std::io::Result<()> {
+        self.serialize_with_dictionary(output_stream, self.strings_dictionary.as_deref())
+    }
+
+    fn deserialize<TReader: io::Seek + io::Read>(
+        input_stream: &mut TReader,
+    ) -> std::io::Result<Self>
+    where
+        Self: Sized,
+    {
+        Self::deserialize_with_dictionaries(input_stream, None)
     }
 }
 
@@ -458,6 +984,7 @@ mod book_tests {
     use isolang::Language;
 
     use super::*;
+    use crate::book::book_metadata::BookMetadata;
 
     #[test]
     fn create_book() {
@@ -555,6 +1082,297 @@ mod book_tests {
         assert_eq!(p2.original_html.as_ref().unwrap(), "<i>Another</i> one");
     }
 
+    #[test]
+    fn html_only_paragraphs_derive_their_text() {
+        let mut both = Book::create(
+            Uuid::new_v4(),
+            "My Book",
+            &Language::from_639_3("eng").unwrap(),
+        );
+        let mut html_only = Book::create(
+            Uuid::new_v4(),
+            "My Book",
+            &Language::from_639_3("eng").unwrap(),
+        );
+        for book in [&mut both, &mut html_only] {
+            book.push_chapter(Some("Intro"));
+        }
+        let paragraphs = [
+            ("Hello world", Some("Hello <b>world</b>")),
+            ("Line one\nLine two", Some("Line one<br>\nLine two")),
+            ("a < b", Some("a < b")),
+            ("Plain", None),
+            // The HTML doesn't strip to the text, so both are kept.
+            ("Different", Some("<i>Other</i>")),
+        ];
+        for (text, html) in paragraphs {
+            both.push_paragraph(0, text, html);
+            html_only.push_paragraph_with_storage(0, text, html, ParagraphStorage::HtmlOnly);
+        }
+        assert!(html_only.strings_blob().len() < both.strings_blob().len());
+
+        let mut buffer: Vec<u8> = vec![];
+        html_only.serialize(&mut buffer).unwrap();
+        let book = Book::deserialize(&mut Cursor::new(buffer)).unwrap();
+        assert!(book.validate().is_ok());
+        for (id, (text, html)) in paragraphs.into_iter().enumerate() {
+            let paragraph = book.paragraph_view(id);
+            assert_eq!(paragraph.original_text, text);
+            assert_eq!(paragraph.original_html.as_deref(), html);
+        }
+
+        // An edit keeps the paragraph HTML-only.
+        assert!(!html_only.update_paragraph_text(0, "Hello world", Some("Hello <i>world</i>")));
+        assert!(html_only.update_paragraph_text(0, "Hi world", Some("Hi <i>world</i>")));
+        assert!(html_only.paragraphs[0].original_text.is_none());
+        assert_eq!(html_only.paragraph_view(0).original_text, "Hi world");
+    }
+
+    #[test]
+    fn text_only_storage_drops_html() {
+        let mut book = Book::create(
+            Uuid::new_v4(),
+            "My Book",
+            &Language::from_639_3("eng").unwrap(),
+        );
+        book.push_chapter(None);
+        book.push_paragraph_with_storage(
+            0,
+            "Hello world",
+            Some("Hello <b>world</b>"),
+            ParagraphStorage::TextOnly,
+        );
+        let paragraph = book.paragraph_view(0);
+        assert_eq!(paragraph.original_text, "Hello world");
+        assert!(paragraph.original_html.is_none());
+    }
+
+    fn two_chapter_book() -> Book {
+        let mut book = Book::create(
+            Uuid::new_v4(),
+            "My Book",
+            &Language::from_639_3("eng").unwrap(),
+        );
+        book.push_chapter(Some("Intro"));
+        book.push_paragraph(0, "Hello world", None);
+        book.push_chapter(Some("Second Chapter"));
+        book.push_paragraph(1, "Another one", None);
+        // Appending to the first chapter again relocates its slice.
+        book.push_paragraph(0, "Late addition", None);
+        book.push_chapter(None);
+        book
+    }
+
+    #[test]
+    fn strings_dictionary_round_trip_needs_the_dictionary_file() {
+        let tmp = crate::test_utils::TempDir::new("flts_book_dictionary");
+        let mut book = two_chapter_book();
+        let dictionary = Arc::new(StringsDictionary::from_bytes(
+            b"Hello world Another one Late addition".to_vec(),
+        ));
+        book.set_strings_dictionary(Some(dictionary.clone()));
+        let mut buffer: Vec<u8> = vec![];
+        book.serialize(&mut buffer).unwrap();
+        // Flagged, so readers without dictionary support refuse the file.
+        assert_ne!(buffer[4] & STRINGS_DICTIONARY_FLAG, 0);
+
+        let err = Book::deserialize(&mut Cursor::new(&buffer)).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = Book::deserialize_with_dictionaries(&mut Cursor::new(&buffer), Some(&tmp.path))
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+        dictionary.save(&tmp.path).unwrap();
+        let book2 = Book::deserialize_with_dictionaries(&mut Cursor::new(&buffer), Some(&tmp.path))
+            .unwrap();
+        assert_eq!(book2.strings_blob(), book.strings_blob());
+        assert_eq!(book2.strings_dictionary().unwrap().id(), dictionary.id());
+        let metadata = BookMetadata::read_metadata(&mut Cursor::new(&buffer)).unwrap();
+        assert_eq!(metadata.title, "My Book");
+    }
+
+    #[test]
+    fn sha256_checksum_round_trips_and_detects_corruption() {
+        let mut book = two_chapter_book();
+        book.set_hash_algorithm(HashAlgorithm::Sha256);
+        let mut buffer: Vec<u8> = vec![];
+        book.serialize(&mut buffer).unwrap();
+
+        let book2 = Book::deserialize(&mut Cursor::new(&buffer)).unwrap();
+        assert_eq!(book2.hash_algorithm(), HashAlgorithm::Sha256);
+        assert_eq!(book2.strings_blob(), book.strings_blob());
+        let metadata = BookMetadata::read_metadata(&mut Cursor::new(&buffer)).unwrap();
+        assert_eq!(metadata.title, "My Book");
+
+        let last_body_byte = buffer.len() - 9;
+        buffer[last_body_byte] ^= 0xff;
+        let err = Book::deserialize(&mut Cursor::new(&buffer)).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn update_paragraph_text_reports_text_changes_only() {
+        let mut book = two_chapter_book();
+
+        assert!(!book.update_paragraph_text(0, "Hello world", None));
+        assert!(!book.update_paragraph_text(0, "Hello world", Some("<b>Hello</b> world")));
+        assert_eq!(
+            book.paragraph_view(0).original_html.unwrap(),
+            "<b>Hello</b> world"
+        );
+        assert!(book.update_paragraph_text(0, "Hello, world", None));
+
+        let mut buffer: Vec<u8> = vec![];
+        book.serialize(&mut buffer).unwrap();
+        let book2 = Book::deserialize(&mut Cursor::new(&buffer)).unwrap();
+        let ch0 = book2.chapter_view(0);
+        assert_eq!(ch0.paragraph_view(0).original_text, "Hello, world");
+        assert!(ch0.paragraph_view(0).original_html.is_none());
+        assert_eq!(ch0.paragraph_view(1).original_text, "Late addition");
+    }
+
+    #[test]
+    fn chapter_index_by_title_ignores_case_and_prefers_first() {
+        let mut book = two_chapter_book();
+        book.push_chapter(Some("intro"));
+
+        assert_eq!(book.chapter_index_by_title("second chapter"), Some(1));
+        assert_eq!(book.chapter_index_by_title("INTRO"), Some(0));
+        assert_eq!(book.chapter_index_by_title("Epilogue"), None);
+    }
+
+    #[test]
+    fn insert_paragraph_keeps_existing_ids() {
+        let mut book = two_chapter_book();
+        let id = book.insert_paragraph(0, 1, "Missing", Some("<p>Missing</p>"));
+        assert_eq!(id, 3);
+        assert_eq!(book.validate(), Ok(()));
+
+        let texts = |book: &Book, chapter: usize| -> Vec<(usize, String)> {
+            book.chapter_view(chapter)
+                .paragraphs()
+                .map(|p| (p.id, p.original_text.into_owned()))
+                .collect()
+        };
+        assert_eq!(
+            texts(&book, 0),
+            vec![
+                (0, "Hello world".to_owned()),
+                (3, "Missing".to_owned()),
+                (2, "Late addition".to_owned()),
+            ]
+        );
+        assert_eq!(texts(&book, 1), vec![(1, "Another one".to_owned())]);
+        assert_eq!(
+            book.paragraph_view(3).original_html.unwrap(),
+            "<p>Missing</p>"
+        );
+
+        // Inserting into the empty last chapter and at the end both work.
+        assert_eq!(book.insert_paragraph(2, 0, "Epilogue", None), 4);
+        assert_eq!(book.insert_paragraph(0, 3, "Tail", None), 5);
+        assert_eq!(book.chapter_view(0).paragraph_count(), 4);
+        assert_eq!(book.validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_accepts_well_formed_book() {
+        let book = two_chapter_book();
+        assert_eq!(book.validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_rejects_chapter_past_paragraph_map() {
+        let mut book = two_chapter_book();
+        book.chapters[1].paragraphs = VecSlice::new(book.paragraph_map.len(), 1);
+        assert!(matches!(
+            book.validate(),
+            Err(BookError::ChapterOutOfRange { chapter: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn validate_rejects_overlapping_chapters() {
+        let mut book = two_chapter_book();
+        book.chapters[1].paragraphs = book.chapters[0].paragraphs;
+        assert_eq!(
+            book.validate(),
+            Err(BookError::ChaptersOverlap {
+                first: 0,
+                second: 1
+            })
+        );
+    }
+
+    #[test]
+    fn validate_rejects_missing_paragraph() {
+        let mut book = two_chapter_book();
+        let idx = book.chapters[1].paragraphs.start;
+        book.paragraph_map[idx] = 99;
+        assert_eq!(
+            book.validate(),
+            Err(BookError::ParagraphOutOfRange {
+                chapter: 1,
+                paragraph: 99
+            })
+        );
+    }
+
+    #[test]
+    fn validate_rejects_duplicate_paragraph_ids() {
+        let mut book = two_chapter_book();
+        book.paragraphs[2].id = 0;
+        assert_eq!(book.validate(), Err(BookError::DuplicateParagraphId(0)));
+    }
+
+    #[test]
+    fn validate_rejects_invalid_utf8() {
+        let mut book = two_chapter_book();
+        let start = book.paragraphs[1].original_text.unwrap().start;
+        book.strings[start] = 0xff;
+        assert_eq!(book.validate(), Err(BookError::InvalidParagraphText(1)));
+
+        let mut book = two_chapter_book();
+        let start = book.chapters[0].title.unwrap().start;
+        book.strings[start] = 0xff;
+        assert_eq!(book.validate(), Err(BookError::InvalidChapterTitle(0)));
+    }
+
+    #[test]
+    fn invalid_book_still_deserializes_for_validate_to_report() {
+        let mut book = two_chapter_book();
+        book.paragraphs[2].id = 0;
+        let mut buffer = Vec::new();
+        book.serialize(&mut buffer).unwrap();
+
+        let book = Book::deserialize(&mut Cursor::new(buffer)).unwrap();
+        assert_eq!(book.validate(), Err(BookError::DuplicateParagraphId(0)));
+    }
+
+    #[test]
+    fn dedupe_paragraphs_drops_later_duplicates() {
+        let mut book = two_chapter_book();
+        book.push_paragraph(1, "Hello world", None);
+        assert_eq!(book.find_duplicate_paragraphs(), vec![vec![0, 3]]);
+
+        let survivors = book.dedupe_paragraphs();
+
+        assert_eq!(survivors, vec![0, 1, 2, 0]);
+        assert_eq!(book.paragraphs_count(), 4);
+        assert_eq!(book.chapter_for_paragraph(3), None);
+        assert_eq!(book.paragraph_view(3).original_text, "Hello world");
+        assert!(book.find_duplicate_paragraphs().is_empty());
+        assert_eq!(book.validate(), Ok(()));
+        let second: Vec<_> = book
+            .chapter_view(1)
+            .paragraphs()
+            .map(|p| p.original_text.to_string())
+            .collect();
+        assert_eq!(second, vec!["Another one"]);
+        assert_eq!(book.chapter_view(0).paragraph_count(), 2);
+    }
+
     #[test]
     fn serialize_deserialize_corruption() {
         let mut book = Book::create(
@@ -579,4 +1397,53 @@ mod book_tests {
         let book2 = Book::deserialize(&mut cursor);
         assert!(book2.is_err());
     }
+
+    #[test]
+    fn word_boundaries_skip_spaces_and_punctuation() {
+        let mut book = Book::create(
+            Uuid::new_v4(),
+            "Test",
+            &Language::from_639_3("rus").unwrap(),
+        );
+        book.push_chapter(None);
+        book.push_paragraph(0, "Ёж, don't 42 раза!", None);
+        assert_eq!(
+            book.paragraph_view(0).word_boundaries(),
+            vec![(0, 2), (4, 9), (10, 12), (13, 17)]
+        );
+    }
+
+    #[test]
+    fn paragraph_offsets_span_chapters() {
+        let mut book = Book::create(
+            Uuid::new_v4(),
+            "Test",
+            &Language::from_639_3("eng").unwrap(),
+        );
+        book.push_chapter(Some("One"));
+        book.push_paragraph(0, "a", None);
+        book.push_paragraph(0, "b", None);
+        book.push_chapter(Some("Empty"));
+        book.push_chapter(Some("Three"));
+        book.push_paragraph(2, "c", None);
+        book.push_paragraph(2, "d", None);
+        book.push_paragraph(2, "e", None);
+
+        assert_eq!(book.chapter_paragraph_ranges(), vec![0..2, 2..2, 2..5]);
+        assert_eq!(book.global_paragraph_index(0, 1), Some(1));
+        assert_eq!(book.global_paragraph_index(2, 0), Some(2));
+        assert_eq!(book.global_paragraph_index(2, 2), Some(4));
+        for (chapter, range) in book.chapter_paragraph_ranges().into_iter().enumerate() {
+            for local in 0..range.len() {
+                assert_eq!(
+                    book.global_paragraph_index(chapter, local),
+                    Some(range.start + local)
+                );
+            }
+        }
+
+        assert_eq!(book.global_paragraph_index(0, 2), None);
+        assert_eq!(book.global_paragraph_index(1, 0), None);
+        assert_eq!(book.global_paragraph_index(3, 0), None);
+    }
 }
@@ -6,7 +6,7 @@ use std::{
 use uuid::Uuid;
 
 use crate::book::serialization::{
-    Magic, Version, read_exact_array, read_len_prefixed_vec, read_u64, read_var_u64,
+    Magic, Version, read_exact_array, read_len_prefixed_vec, read_u64, read_var_usize,
 };
 
 pub struct BookMetadata {
@@ -49,22 +49,22 @@ impl BookMetadata {
         let id = Uuid::from_bytes(read_exact_array(&mut cursor)?);
 
         // Title
-        let title_len = read_var_u64(&mut cursor)? as usize;
+        let title_len = read_var_usize(&mut cursor)?;
         let mut title_buf = vec![0u8; title_len];
         cursor.read_exact(&mut title_buf)?;
         let title = String::from_utf8(title_buf)
             .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF-8 in title"))?;
 
         // Language
-        let language_len = read_var_u64(&mut cursor)? as usize;
+        let language_len = read_var_usize(&mut cursor)?;
         let mut language_buf = vec![0u8; language_len];
         cursor.read_exact(&mut language_buf)?;
         let language = String::from_utf8(language_buf)
             .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF-8 in language"))?;
 
-        let chapters_count = read_var_u64(&mut cursor)? as usize;
+        let chapters_count = read_var_usize(&mut cursor)?;
 
-        let paragraphs_count = read_var_u64(&mut cursor)? as usize;
+        let paragraphs_count = read_var_usize(&mut cursor)?;
 
         Ok(BookMetadata {
             id,
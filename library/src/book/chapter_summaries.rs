@@ -18,7 +18,8 @@ use crate::{
     book::{
         serialization::{
             ChecksumedWriter, Magic, Serializable, Version, create_random_string, read_exact_array,
-            read_u8, read_u64, read_var_u64, validate_hash, write_u64, write_u8, write_var_u64,
+            read_u8, read_u64, read_var_u64, read_var_usize, validate_hash, write_u64, write_u8,
+            write_var_u64,
         },
         soa_helpers::VecSlice,
     },
@@ -322,16 +323,16 @@ impl Serializable for ChapterSummaries {
         let book_id_bytes = read_exact_array::<16>(input_stream)?;
         let book_id = Uuid::from_bytes(book_id_bytes);
 
-        let chapter_count = read_var_u64(input_stream)? as usize;
+        let chapter_count = read_var_usize(input_stream)?;
 
         let mut entries = Vec::with_capacity(chapter_count);
         let mut slices = Vec::with_capacity(chapter_count);
         for _ in 0..chapter_count {
             let generated = read_u8(input_stream)? == 1;
-            let model = TranslationModel::from(read_var_u64(input_stream)? as usize);
+            let model = TranslationModel::from(read_var_usize(input_stream)?);
             let timestamp = read_var_u64(input_stream)?;
-            let start = read_var_u64(input_stream)? as usize;
-            let len = read_var_u64(input_stream)? as usize;
+            let start = read_var_usize(input_stream)?;
+            let len = read_var_usize(input_stream)?;
             slices.push(VecSlice::<u8>::new(start, len));
             entries.push(ChapterSummary {
                 generated,
@@ -341,7 +342,7 @@ impl Serializable for ChapterSummaries {
             });
         }
 
-        let strings_len = read_var_u64(input_stream)? as usize;
+        let strings_len = read_var_usize(input_stream)?;
         let mut encoded = vec![0u8; strings_len];
         input_stream.read_exact(&mut encoded)?;
         let strings = zstd::stream::decode_all(encoded.as_slice())?;
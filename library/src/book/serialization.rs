@@ -1,6 +1,7 @@
 use rand::{RngExt, distr::Alphanumeric};
 
 use super::soa_helpers::VecSlice;
+use sha2::Digest;
 use std::{
     hash::Hasher,
     io::{self},
@@ -93,13 +94,29 @@ pub fn read_var_u64(r: &mut dyn io::Read) -> io::Result<u64> {
     }
     Ok(result)
 }
+/// Stored lengths and indices are written as `u64`; on 32-bit targets one
+/// that doesn't fit in `usize` is an error rather than silently truncated.
+pub fn u64_to_usize(v: u64) -> io::Result<usize> {
+    usize::try_from(v).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Stored index {v} exceeds usize::MAX ({}) on this target",
+                usize::MAX
+            ),
+        )
+    })
+}
+pub fn read_var_usize(r: &mut dyn io::Read) -> io::Result<usize> {
+    u64_to_usize(read_var_u64(r)?)
+}
 pub fn read_exact_array<const N: usize>(r: &mut dyn io::Read) -> io::Result<[u8; N]> {
     let mut buf = [0u8; N];
     r.read_exact(&mut buf)?;
     Ok(buf)
 }
 pub fn read_len_prefixed_vec(r: &mut dyn io::Read) -> io::Result<Vec<u8>> {
-    let len = read_var_u64(r)? as usize;
+    let len = read_var_usize(r)?;
     let mut v = vec![0u8; len];
     r.read_exact(&mut v)?;
     Ok(v)
@@ -111,8 +128,8 @@ pub fn read_len_prefixed_string(r: &mut dyn io::Read) -> io::Result<String> {
 pub fn read_opt(r: &mut dyn io::Read) -> io::Result<Option<VecSlice<u8>>> {
     let has = read_u8(r)?;
     if has == 1 {
-        let s = read_var_u64(r)? as usize;
-        let l = read_var_u64(r)? as usize;
+        let s = read_var_usize(r)?;
+        let l = read_var_usize(r)?;
         Ok(Some(VecSlice::new(s, l)))
     } else {
         Ok(None)
@@ -133,8 +150,8 @@ pub fn write_vec_slice<T>(w: &mut dyn io::Write, slice: &VecSlice<T>) -> io::Res
     write_var_u64(w, slice.len as u64)
 }
 pub fn read_vec_slice<T>(r: &mut dyn io::Read) -> io::Result<VecSlice<T>> {
-    let start = read_var_u64(r)? as usize;
-    let len = read_var_u64(r)? as usize;
+    let start = read_var_usize(r)?;
+    let len = read_var_usize(r)?;
     Ok(VecSlice::new(start, len))
 }
 
@@ -182,18 +199,58 @@ pub enum Version {
     V2,
 }
 
+/// Set in the version byte when the trailing hash is `HashAlgorithm::Sha256`.
+/// Readers that predate it see an unknown version and refuse the file.
+const SHA256_HASH_FLAG: u8 = 0x80;
+
+/// Set in the version byte of a book whose paragraphs may store only their
+/// HTML. Like the hash flag, it makes older readers refuse the file rather
+/// than load those paragraphs without text.
+pub const HTML_ONLY_PARAGRAPHS_FLAG: u8 = 0x40;
+
+/// Set in the version byte of a book whose strings blob is compressed with a
+/// shared strings dictionary, so older readers refuse it instead of reading
+/// the dictionary id as the start of the blob.
+pub const STRINGS_DICTIONARY_FLAG: u8 = 0x20;
+
 impl Version {
     pub fn write_version(&self, w: &mut dyn io::Write) -> io::Result<()> {
-        match self {
-            Version::V1 => write_u8(w, 1),
-            Version::V2 => write_u8(w, 2),
-        }
+        self.write_version_with_hash(w, HashAlgorithm::Fnv1)
     }
+
+    pub fn write_version_with_hash(
+        &self,
+        w: &mut dyn io::Write,
+        algorithm: HashAlgorithm,
+    ) -> io::Result<()> {
+        self.write_version_with_flags(w, algorithm, 0)
+    }
+
+    /// Like [`Version::write_version_with_hash`], also setting `flags`
+    /// (e.g. [`HTML_ONLY_PARAGRAPHS_FLAG`]) in the version byte.
+    pub fn write_version_with_flags(
+        &self,
+        w: &mut dyn io::Write,
+        algorithm: HashAlgorithm,
+        flags: u8,
+    ) -> io::Result<()> {
+        let v = match self {
+            Version::V1 => 1,
+            Version::V2 => 2,
+        };
+        write_u8(w, v | algorithm.version_flag() | flags)
+    }
+
     pub fn read_version(r: &mut dyn io::Read) -> io::Result<Self> {
+        Ok(Self::read_version_and_hash(r)?.0)
+    }
+
+    pub fn read_version_and_hash(r: &mut dyn io::Read) -> io::Result<(Self, HashAlgorithm)> {
         let v = read_u8(r)?;
-        match v {
-            1 => Ok(Version::V1),
-            2 => Ok(Version::V2),
+        let algorithm = HashAlgorithm::from_version_byte(v);
+        match v & !(SHA256_HASH_FLAG | HTML_ONLY_PARAGRAPHS_FLAG | STRINGS_DICTIONARY_FLAG) {
+            1 => Ok((Version::V1, algorithm)),
+            2 => Ok((Version::V2, algorithm)),
             _ => Err(io::Error::new(
                 io::ErrorKind::InvalidData,
                 "Unsupported version",
@@ -202,16 +259,85 @@ impl Version {
     }
 }
 
+/// Checksum stored in the trailing 8 bytes of a file.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum HashAlgorithm {
+    /// 64-bit FNV-1. Fast, catches accidental corruption only.
+    #[default]
+    Fnv1,
+    /// SHA-256 truncated to 64 bits. Slower, but far less likely to let a
+    /// damaged file pass as intact. Like FNV-1 it is a checksum, not a
+    /// signature: whoever edits the file can recompute it.
+    Sha256,
+}
+
+impl HashAlgorithm {
+    fn version_flag(self) -> u8 {
+        match self {
+            HashAlgorithm::Fnv1 => 0,
+            HashAlgorithm::Sha256 => SHA256_HASH_FLAG,
+        }
+    }
+
+    fn from_version_byte(v: u8) -> Self {
+        if v & SHA256_HASH_FLAG != 0 {
+            HashAlgorithm::Sha256
+        } else {
+            HashAlgorithm::Fnv1
+        }
+    }
+}
+
+enum ContentHasher {
+    Fnv1(fnv::FnvHasher),
+    Sha256(sha2::Sha256),
+}
+
+impl ContentHasher {
+    fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Fnv1 => ContentHasher::Fnv1(fnv::FnvHasher::default()),
+            HashAlgorithm::Sha256 => ContentHasher::Sha256(sha2::Sha256::new()),
+        }
+    }
+
+    fn write(&mut self, buf: &[u8]) {
+        match self {
+            ContentHasher::Fnv1(hasher) => hasher.write(buf),
+            ContentHasher::Sha256(hasher) => hasher.update(buf),
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        match self {
+            ContentHasher::Fnv1(hasher) => hasher.finish(),
+            ContentHasher::Sha256(hasher) => {
+                let digest = hasher.clone().finalize();
+                let mut truncated = [0u8; 8];
+                truncated.copy_from_slice(&digest[..8]);
+                u64::from_le_bytes(truncated)
+            }
+        }
+    }
+}
+
 pub struct ChecksumedWriter<'a> {
     backing_writer: &'a mut dyn io::Write,
-    hasher: fnv::FnvHasher,
+    hasher: ContentHasher,
 }
 
 impl<'a> ChecksumedWriter<'a> {
     pub fn create(backing_writer: &'a mut dyn io::Write) -> Self {
+        Self::create_with(backing_writer, HashAlgorithm::Fnv1)
+    }
+
+    /// The caller must also pass `algorithm` to
+    /// `Version::write_version_with_hash`, or `validate_hash` will recompute
+    /// the trailer with the wrong hasher.
+    pub fn create_with(backing_writer: &'a mut dyn io::Write, algorithm: HashAlgorithm) -> Self {
         ChecksumedWriter {
             backing_writer,
-            hasher: fnv::FnvHasher::default(),
+            hasher: ContentHasher::new(algorithm),
         }
     }
 
@@ -232,7 +358,7 @@ impl<'a> io::Write for ChecksumedWriter<'a> {
     }
 }
 
-/// Reads the trailing 8-byte FNV content hash without rehashing the body.
+/// Reads the trailing 8-byte content hash without rehashing the body.
 /// Returns the stored hash and the byte offset where it begins (= body length).
 /// Leaves the reader seeked to the start of the stream.
 pub fn read_stored_hash<T: io::Seek + io::Read>(reader: &mut T) -> io::Result<(u64, u64)> {
@@ -247,17 +373,27 @@ pub fn read_stored_hash<T: io::Seek + io::Read>(reader: &mut T) -> io::Result<(u
     Ok((read_hash, end))
 }
 
-/// Opens `path` and returns its trailing 8-byte FNV content hash, without
+/// Opens `path` and returns its trailing 8-byte content hash, without
 /// deserializing the body. Used to detect "same content" file-watcher echoes.
 pub fn read_stored_hash_from_path(path: &std::path::Path) -> io::Result<u64> {
     let mut file = std::fs::File::open(path)?;
     Ok(read_stored_hash(&mut file)?.0)
 }
 
+/// Recomputes the content hash with the algorithm flagged in the version
+/// byte (just after the 4-byte magic) and compares it to the trailer.
 pub fn validate_hash<T: io::Seek + io::Read>(reader: &mut T) -> io::Result<bool> {
     let (read_hash, end) = read_stored_hash(reader)?;
 
-    let mut hasher = fnv::FnvHasher::default();
+    let algorithm = if end > 4 {
+        reader.seek(io::SeekFrom::Start(4))?;
+        let v = read_u8(reader)?;
+        reader.seek(io::SeekFrom::Start(0))?;
+        HashAlgorithm::from_version_byte(v)
+    } else {
+        HashAlgorithm::Fnv1
+    };
+    let mut hasher = ContentHasher::new(algorithm);
 
     // Read in chunks up to the position of the stored hash (end)
     let mut remaining: u64 = end;
@@ -471,3 +607,24 @@ mod serialization_tests {
         assert_eq!(read_hash, hash);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn var_usize_round_trips_largest_index() {
+        let mut buf = Vec::new();
+        write_var_u64(&mut buf, usize::MAX as u64).unwrap();
+        assert_eq!(read_var_usize(&mut buf.as_slice()).unwrap(), usize::MAX);
+    }
+
+    #[cfg(not(target_pointer_width = "64"))]
+    #[test]
+    fn var_usize_rejects_index_past_usize_max() {
+        let mut buf = Vec::new();
+        write_var_u64(&mut buf, usize::MAX as u64 + 1).unwrap();
+        let err = read_var_usize(&mut buf.as_slice()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -1,4 +1,4 @@
-use std::marker::PhantomData;
+use std::{borrow::Cow, marker::PhantomData, str::Utf8Error};
 
 #[derive(Clone, Copy, PartialEq, Debug, Eq, Hash)]
 pub struct VecSlice<T> {
@@ -33,6 +33,20 @@ impl<T> VecSlice<T> {
     }
 }
 
+impl VecSlice<u8> {
+    /// The slice of a strings blob as text. Strings only ever come in as
+    /// `&str`, so invalid UTF-8 means the blob is corrupt; views show
+    /// replacement characters rather than failing. Validation uses
+    /// [`VecSlice::try_str`] to report it instead.
+    pub fn to_str(self, strings: &[u8]) -> Cow<'_, str> {
+        String::from_utf8_lossy(self.slice(strings))
+    }
+
+    pub fn try_str<'a>(&self, strings: &'a [u8]) -> Result<&'a str, Utf8Error> {
+        std::str::from_utf8(self.slice(strings))
+    }
+}
+
 pub fn push_string(strings: &mut Vec<u8>, string: &str) -> VecSlice<u8> {
     let start = strings.len();
     strings.extend(string.bytes());
@@ -60,10 +74,60 @@ pub fn push<T: Clone>(items: &mut Vec<T>, slice: &VecSlice<T>, item: T) -> Optio
     Some(slice)
 }
 
+/// Like [`push`], but puts `item` at `index` within the slice. A slice that
+/// doesn't end at the end of `items` is first relocated there, so items of
+/// other slices never move.
+pub fn insert<T: Clone>(
+    items: &mut Vec<T>,
+    slice: &VecSlice<T>,
+    index: usize,
+    item: T,
+) -> Option<VecSlice<T>> {
+    let mut slice = slice.clone();
+    if slice.end() > items.len() || index > slice.len {
+        return None;
+    }
+
+    if slice.end() < items.len() {
+        let slice_items_copy = slice.slice(items).to_vec();
+        slice.start = items.len();
+        items.extend(slice_items_copy);
+    }
+
+    items.insert(slice.start + index, item);
+    slice.len += 1;
+    Some(slice)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn to_str_is_lossy_on_corrupt_strings() {
+        let strings = b"ok\xffok";
+        let slice = VecSlice::<u8>::new(0, strings.len());
+        assert_eq!(slice.to_str(strings), "ok\u{fffd}ok");
+        assert!(slice.try_str(strings).is_err());
+    }
+
+    #[test]
+    fn insert_relocates_slice_before_shifting() {
+        let mut vec = vec![1, 2, 3];
+        let slice = VecSlice {
+            start: 0,
+            len: 2,
+            phantom: PhantomData,
+        };
+        let slice = insert(&mut vec, &slice, 1, 4).unwrap();
+        assert_eq!(slice.slice(&vec), vec![1, 4, 2]);
+        assert_eq!(vec, vec![1, 2, 3, 1, 4, 2]);
+
+        let slice = insert(&mut vec, &slice, 3, 5).unwrap();
+        assert_eq!(slice.slice(&vec), vec![1, 4, 2, 5]);
+        assert!(insert(&mut vec, &slice, 5, 6).is_none());
+    }
+
     #[test]
     fn push_empty() {
         let mut vec = vec![];
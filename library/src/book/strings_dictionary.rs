@@ -0,0 +1,221 @@
+//! Shared zstd dictionaries for the strings blob of `book.dat`. Small books
+//! compress poorly on their own; a dictionary trained on a sample of the
+//! library lets each one reuse what the others have in common.
+//!
+//! A book written with a dictionary stores only its id (the FNV hash of the
+//! dictionary bytes), so the dictionary itself must be present when the book
+//! is read: it lives in `<library>/`[`DICTIONARIES_DIR`]`/<id>.zdict`, which
+//! syncs along with the books. The dictionary new books are written with is
+//! recorded in [`CURRENT_FILE`] next to it.
+
+use std::{
+    hash::Hasher,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+/// Directory under the library root holding `<id>.zdict` files.
+pub const DICTIONARIES_DIR: &str = "dictionaries";
+
+/// File in [`DICTIONARIES_DIR`] naming the dictionary new books use.
+pub const CURRENT_FILE: &str = "current";
+
+/// [`DICTIONARIES_DIR`] of the library holding `book_dat`
+/// (`<library>/<book id>/book.dat`).
+pub fn dictionaries_dir_for(book_dat: &Path) -> Option<PathBuf> {
+    let library_root = book_dat.parent()?.parent()?;
+    Some(library_root.join(DICTIONARIES_DIR))
+}
+
+/// Size zstd's own tooling defaults to.
+pub const DEFAULT_DICTIONARY_SIZE: usize = 110 * 1024;
+
+/// Same level as the plain strings blob.
+const COMPRESSION_LEVEL: i32 = -7;
+
+pub struct StringsDictionary {
+    id: u64,
+    bytes: Vec<u8>,
+}
+
+impl StringsDictionary {
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        let mut hasher = fnv::FnvHasher::default();
+        hasher.write(&bytes);
+        Self {
+            id: hasher.finish(),
+            bytes,
+        }
+    }
+
+    /// Trains a dictionary of at most `max_size` bytes on `samples`, e.g. the
+    /// strings blobs of a few dozen books. zstd needs a reasonable amount of
+    /// sample data and fails on too little.
+    pub fn train(samples: &[Vec<u8>], max_size: usize) -> io::Result<Self> {
+        Ok(Self::from_bytes(zstd::dict::from_samples(
+            samples, max_size,
+        )?))
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn file_name(id: u64) -> String {
+        format!("{id:016x}.zdict")
+    }
+
+    pub fn compress(&self, data: &[u8]) -> io::Result<Vec<u8>> {
+        let mut encoder =
+            zstd::stream::Encoder::with_dictionary(Vec::new(), COMPRESSION_LEVEL, &self.bytes)?;
+        encoder.write_all(data)?;
+        encoder.finish()
+    }
+
+    pub fn decompress(&self, data: &[u8]) -> io::Result<Vec<u8>> {
+        let mut decoder = zstd::stream::Decoder::with_dictionary(data, &self.bytes)?;
+        let mut out = Vec::new();
+        decoder.read_to_end(&mut out)?;
+        Ok(out)
+    }
+
+    /// Writes the dictionary to `<dir>/<id>.zdict` and returns the path.
+    pub fn save(&self, dir: &Path) -> io::Result<PathBuf> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(Self::file_name(self.id));
+        std::fs::write(&path, &self.bytes)?;
+        Ok(path)
+    }
+
+    /// Reads dictionary `id` from `dir`. Fails if it's missing, since every
+    /// book compressed with it is unreadable without it.
+    pub fn load(dir: &Path, id: u64) -> io::Result<Self> {
+        let path = dir.join(Self::file_name(id));
+        let bytes = std::fs::read(&path).map_err(|err| {
+            io::Error::new(
+                err.kind(),
+                format!("Strings dictionary {path:?} is required to read this book: {err}"),
+            )
+        })?;
+        let dictionary = Self::from_bytes(bytes);
+        if dictionary.id != id {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Strings dictionary {path:?} doesn't match its id"),
+            ));
+        }
+        Ok(dictionary)
+    }
+
+    /// Makes this the dictionary new books in `dir`'s library are written
+    /// with. Call [`StringsDictionary::save`] first.
+    pub fn make_current(&self, dir: &Path) -> io::Result<()> {
+        std::fs::write(dir.join(CURRENT_FILE), format!("{:016x}\n", self.id))
+    }
+
+    /// The dictionary recorded by [`StringsDictionary::make_current`], or
+    /// `None` if no dictionary has been trained yet.
+    pub fn load_current(dir: &Path) -> io::Result<Option<Self>> {
+        let current = match std::fs::read_to_string(dir.join(CURRENT_FILE)) {
+            Ok(current) => current,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let id = u64::from_str_radix(current.trim(), 16).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid current strings dictionary {current:?}: {err}"),
+            )
+        })?;
+        Self::load(dir, id).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Many small, similar blobs, like the strings of short books in one
+    /// language. Deterministic so the test doesn't flake.
+    fn sample_blobs(count: usize) -> Vec<Vec<u8>> {
+        const WORDS: [&str; 24] = [
+            "the",
+            "house",
+            "was",
+            "quiet",
+            "when",
+            "she",
+            "opened",
+            "door",
+            "and",
+            "looked",
+            "outside",
+            "again",
+            "morning",
+            "river",
+            "through",
+            "window",
+            "whispered",
+            "never",
+            "before",
+            "across",
+            "garden",
+            "slowly",
+            "remembered",
+            "evening",
+        ];
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        (0..count)
+            .map(|_| {
+                let mut blob = String::new();
+                for _ in 0..60 {
+                    state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                    blob.push_str(WORDS[(state >> 33) as usize % WORDS.len()]);
+                    blob.push(' ');
+                }
+                blob.into_bytes()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn dictionary_round_trips_and_beats_plain_compression() {
+        let samples = sample_blobs(300);
+        let dictionary = StringsDictionary::train(&samples, 4096).unwrap();
+
+        let blob = &samples[0];
+        let compressed = dictionary.compress(blob).unwrap();
+        assert_eq!(&dictionary.decompress(&compressed).unwrap(), blob);
+
+        let plain = zstd::stream::encode_all(blob.as_slice(), COMPRESSION_LEVEL).unwrap();
+        assert!(compressed.len() < plain.len());
+    }
+
+    #[test]
+    fn saved_dictionaries_load_by_id() {
+        let tmp = crate::test_utils::TempDir::new("flts_strings_dictionary");
+        assert!(
+            StringsDictionary::load_current(&tmp.path)
+                .unwrap()
+                .is_none()
+        );
+
+        let dictionary = StringsDictionary::train(&sample_blobs(300), 4096).unwrap();
+        dictionary.save(&tmp.path).unwrap();
+        dictionary.make_current(&tmp.path).unwrap();
+
+        let loaded = StringsDictionary::load(&tmp.path, dictionary.id()).unwrap();
+        assert_eq!(loaded.bytes(), dictionary.bytes());
+        let current = StringsDictionary::load_current(&tmp.path).unwrap().unwrap();
+        assert_eq!(current.id(), dictionary.id());
+
+        let err = StringsDictionary::load(&tmp.path, dictionary.id() ^ 1)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -5,13 +5,15 @@ use uuid::Uuid;
 use crate::{
     book::{
         serialization::{
-            ChecksumedWriter, Magic, Serializable, Version, read_exact_array,
+            ChecksumedWriter, HashAlgorithm, Magic, Serializable, Version, read_exact_array,
             read_len_prefixed_string, read_len_prefixed_vec, read_opt, read_opt_var_u64, read_u8,
-            read_u64, read_var_u64, read_vec_slice, validate_hash, write_len_prefixed_bytes,
-            write_opt, write_opt_var_u64, write_u64, write_var_u64, write_vec_slice,
+            read_u64, read_var_u64, read_var_usize, read_vec_slice, u64_to_usize, validate_hash,
+            write_len_prefixed_bytes, write_opt, write_opt_var_u64, write_u64, write_var_u64,
+            write_vec_slice,
         },
-        translation_import,
+        translation_import::{self, TokenUsage},
     },
+    card::canonicalize_lemma_display,
     translator::TranslationModel,
 };
 use std::io::{self, Write};
@@ -25,12 +27,34 @@ use std::{
 
 use super::soa_helpers::*;
 
+/// How [`Translation::merge_with_strategy`] resolves a paragraph whose
+/// version history diverged between the two sides being merged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergeStrategy {
+    /// Keep every version from both sides, ordered by timestamp.
+    #[default]
+    UnionHistory,
+    /// Keep the side whose latest version of the paragraph is newer. File
+    /// modification times aren't used: while saving, the on-disk copy is
+    /// always newer than the unsaved in-memory versions.
+    PreferNewerMtime,
+    /// Keep the history of `self` (the in-memory / main file side).
+    PreferLocal,
+    /// Keep the history of `other` (the on-disk / conflict file side).
+    PreferRemote,
+}
+
 pub struct Translation {
     strings_cache: AHashMap<String, VecSlice<u8>>,
 
     pub id: Uuid,
     pub source_language: String,
     pub target_language: String,
+    /// Regional variant of `target_language` (e.g. `BR` for Brazilian
+    /// Portuguese). Stored in a translation-level tagged field, so
+    /// `target_language` stays a bare ISO 639-3 code that older readers
+    /// understand, and in the file name; files without one load as `None`.
+    pub target_variant: Option<String>,
 
     strings: Vec<u8>,
 
@@ -39,6 +63,8 @@ pub struct Translation {
     sentences: Vec<Sentence>,
     words: Vec<Word>,
     word_contextual_translations: Vec<WordContextualTranslation>,
+    /// Checksum written in the file trailer; kept across a load/save cycle.
+    hash_algorithm: HashAlgorithm,
 }
 
 #[derive(Debug)]
@@ -56,10 +82,18 @@ impl Display for FieldTagError {
     }
 }
 
+/// Tags of the per-paragraph-translation tagged fields.
+///
+/// Rule for set- or map-backed fields: write their entries through
+/// [`sorted_keys`] / [`sorted_entries`], never in hash iteration order, so
+/// identical content always serializes to identical bytes (file hashes are
+/// used for dedup and sync).
 enum FieldTag {
     TranslationModel = 1,
     TotalTokens = 2,
     VisibleWords = 3,
+    RevealedAt = 4,
+    TokenUsage = 5,
 }
 
 impl TryFrom<u64> for FieldTag {
@@ -70,18 +104,52 @@ impl TryFrom<u64> for FieldTag {
             1 => Ok(FieldTag::TranslationModel),
             2 => Ok(FieldTag::TotalTokens),
             3 => Ok(FieldTag::VisibleWords),
+            4 => Ok(FieldTag::RevealedAt),
+            5 => Ok(FieldTag::TokenUsage),
             _ => Err(FieldTagError::InvalidValue(value)),
         }
     }
 }
 
+/// Tags of the translation-level tagged fields that follow the paragraphs.
+enum TranslationFieldTag {
+    TargetVariant = 1,
+}
+
+impl TryFrom<u64> for TranslationFieldTag {
+    type Error = FieldTagError;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(TranslationFieldTag::TargetVariant),
+            _ => Err(FieldTagError::InvalidValue(value)),
+        }
+    }
+}
+
+fn sorted_keys(set: &AHashSet<usize>) -> Vec<usize> {
+    let mut keys: Vec<_> = set.iter().copied().collect();
+    keys.sort_unstable();
+    keys
+}
+
+fn sorted_entries<V: Copy>(map: &AHashMap<usize, V>) -> Vec<(usize, V)> {
+    let mut entries: Vec<_> = map.iter().map(|(&k, &v)| (k, v)).collect();
+    entries.sort_unstable_by_key(|&(k, _)| k);
+    entries
+}
+
 struct ParagraphTranslation {
     timestamp: u64,
     previous_version: Option<usize>,
     sentences: VecSlice<Sentence>,
     model: TranslationModel,
     total_tokens: Option<u64>,
+    token_usage: Option<TokenUsage>,
     visible_words: AHashSet<usize>,
+    /// First time each word in `visible_words` was revealed, for words
+    /// revealed through [`Translation::mark_word_visible`].
+    revealed_at: AHashMap<usize, u64>,
 }
 
 pub struct ParagraphTranslationView<'a> {
@@ -91,7 +159,9 @@ pub struct ParagraphTranslationView<'a> {
     sentences: &'a [Sentence],
     pub model: TranslationModel,
     pub total_tokens: Option<u64>,
+    pub token_usage: Option<TokenUsage>,
     visible_words: &'a AHashSet<usize>,
+    revealed_at: &'a AHashMap<usize, u64>,
 }
 
 #[derive(Clone)]
@@ -156,19 +226,61 @@ pub struct WordContextualTranslationView<'a> {
     pub translation: Cow<'a, str>,
 }
 
+/// Whether `variant` can be used as a target language variant: one
+/// BCP-47-style subtag of 1 to 8 ASCII letters or digits, so it never
+/// clashes with the `-` and `_` separators of tags and file names.
+pub fn is_valid_variant(variant: &str) -> bool {
+    (1..=8).contains(&variant.len()) && variant.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Splits a `<lang>[-<variant>]` tag as written by [`Translation::target_language_tag`].
+pub fn split_language_tag(tag: &str) -> (String, Option<String>) {
+    match tag.split_once('-') {
+        Some((language, variant)) => (language.to_owned(), Some(variant.to_owned())),
+        None => (tag.to_owned(), None),
+    }
+}
+
 impl Translation {
     pub fn create(source_language: &str, target_language: &str) -> Self {
+        Self::create_with_variant(source_language, target_language, None)
+    }
+
+    pub fn create_with_variant(
+        source_language: &str,
+        target_language: &str,
+        target_variant: Option<&str>,
+    ) -> Self {
         Translation {
             strings_cache: AHashMap::new(),
             id: Uuid::new_v4(),
             source_language: source_language.to_owned(),
             target_language: target_language.to_owned(),
+            target_variant: target_variant.map(str::to_owned),
             strings: vec![],
             paragraphs: vec![],
             paragraph_translations: vec![],
             sentences: vec![],
             words: vec![],
             word_contextual_translations: vec![],
+            hash_algorithm: HashAlgorithm::default(),
+        }
+    }
+
+    pub fn hash_algorithm(&self) -> HashAlgorithm {
+        self.hash_algorithm
+    }
+
+    /// Checksum the file with `algorithm` from the next save on.
+    pub fn set_hash_algorithm(&mut self, algorithm: HashAlgorithm) {
+        self.hash_algorithm = algorithm;
+    }
+
+    /// `target_language`, suffixed with `-<variant>` when there is one.
+    pub fn target_language_tag(&self) -> Cow<'_, str> {
+        match &self.target_variant {
+            Some(variant) => Cow::Owned(format!("{}-{variant}", self.target_language)),
+            None => Cow::Borrowed(&self.target_language),
         }
     }
 
@@ -185,14 +297,163 @@ impl Translation {
             sentences: p.sentences.slice(&self.sentences),
             model: p.model,
             total_tokens: p.total_tokens,
+            token_usage: p.token_usage,
             visible_words: &p.visible_words,
+            revealed_at: &p.revealed_at,
         })
     }
 
+    /// Paragraph slots, translated or not: one past the highest paragraph
+    /// id the translation knows about.
+    pub fn paragraphs_count(&self) -> usize {
+        self.paragraphs.len()
+    }
+
     pub fn translated_paragraphs_count(&self) -> usize {
         self.paragraphs.iter().filter(|p| p.is_some()).count()
     }
 
+    /// Id of the first paragraph whose latest translation has a string that
+    /// isn't valid UTF-8, which only a corrupt strings blob can produce.
+    pub fn first_invalid_utf8_paragraph(&self) -> Option<usize> {
+        self.paragraphs
+            .iter()
+            .enumerate()
+            .find_map(|(paragraph, idx)| {
+                let sentences = self.paragraph_translations[(*idx)?]
+                    .sentences
+                    .slice(&self.sentences);
+                let valid = sentences.iter().all(|sentence| {
+                    sentence.full_translation.try_str(&self.strings).is_ok()
+                        && sentence
+                            .words
+                            .slice(&self.words)
+                            .iter()
+                            .all(|word| self.word_is_valid_utf8(word))
+                });
+                (!valid).then_some(paragraph)
+            })
+    }
+
+    fn word_is_valid_utf8(&self, word: &Word) -> bool {
+        let grammar = &word.grammar;
+        [
+            word.original,
+            word.note,
+            grammar.original_initial_form,
+            grammar.target_initial_form,
+            grammar.part_of_speech,
+        ]
+        .into_iter()
+        .chain(
+            [
+                grammar.plurality,
+                grammar.person,
+                grammar.tense,
+                grammar.case,
+                grammar.other,
+            ]
+            .into_iter()
+            .flatten(),
+        )
+        .chain(
+            word.contextual_translations
+                .slice(&self.word_contextual_translations)
+                .iter()
+                .map(|t| t.translation),
+        )
+        .all(|s| s.try_str(&self.strings).is_ok())
+    }
+
+    /// Sentences across the latest version of every translated paragraph.
+    pub fn total_sentences(&self) -> usize {
+        self.paragraphs
+            .iter()
+            .flatten()
+            .map(|&idx| self.paragraph_translations[idx].sentences.len)
+            .sum()
+    }
+
+    /// Non-punctuation words across the latest version of every translated
+    /// paragraph.
+    pub fn total_words(&self) -> usize {
+        self.paragraphs
+            .iter()
+            .flatten()
+            .flat_map(|&idx| {
+                self.paragraph_translations[idx]
+                    .sentences
+                    .slice(&self.sentences)
+            })
+            .flat_map(|s| s.words.slice(&self.words))
+            .filter(|w| !w.is_punctuation)
+            .count()
+    }
+
+    /// `(paragraph, sentence, word)` indices of every word in the latest
+    /// version of each paragraph whose dictionary form is `lemma`, so
+    /// inflected forms match too. Forms are compared after the same
+    /// normalization cards use, ignoring case; punctuation is skipped.
+    pub fn search_lemma(&self, lemma: &str) -> Vec<(usize, usize, usize)> {
+        let lemma = canonicalize_lemma_display(lemma).to_lowercase();
+        let mut hits = Vec::new();
+        if lemma.is_empty() {
+            return hits;
+        }
+        for (paragraph, idx) in self.paragraphs.iter().enumerate() {
+            let Some(idx) = idx else {
+                continue;
+            };
+            let sentences = self.paragraph_translations[*idx]
+                .sentences
+                .slice(&self.sentences);
+            for (sentence_idx, sentence) in sentences.iter().enumerate() {
+                for (word_idx, word) in sentence.words.slice(&self.words).iter().enumerate() {
+                    if word.is_punctuation {
+                        continue;
+                    }
+                    let form = String::from_utf8_lossy(
+                        word.grammar.original_initial_form.slice(&self.strings),
+                    );
+                    if canonicalize_lemma_display(&form).to_lowercase() == lemma {
+                        hits.push((paragraph, sentence_idx, word_idx));
+                    }
+                }
+            }
+        }
+        hits
+    }
+
+    /// Translations of up to `max` sentences using `lemma` (matched as in
+    /// [`Translation::search_lemma`]), in reading order, for showing a word
+    /// in context. Repeated translations appear once; sentences whose
+    /// translation is only punctuation are skipped.
+    pub fn examples_for_lemma(&self, lemma: &str, max: usize) -> Vec<String> {
+        let mut examples: Vec<String> = Vec::new();
+        let mut last_sentence = None;
+        for (paragraph, sentence, _) in self.search_lemma(lemma) {
+            if examples.len() >= max {
+                break;
+            }
+            if last_sentence == Some((paragraph, sentence)) {
+                continue;
+            }
+            last_sentence = Some((paragraph, sentence));
+            let Some(view) = self.paragraph_view(paragraph) else {
+                continue;
+            };
+            let example = view
+                .sentence_view(sentence)
+                .full_translation
+                .trim()
+                .to_owned();
+            if example.chars().any(char::is_alphanumeric) && !examples.contains(&example) {
+                examples.push(example);
+            }
+        }
+        examples
+    }
+
     pub fn version_count(&self) -> usize {
         self.paragraph_translations.len()
     }
@@ -207,6 +468,85 @@ impl Translation {
         vs
     }
 
+    /// Rebuilds the strings blob with only the bytes reachable from the
+    /// current paragraphs and their version histories, remapping every slice.
+    /// Entries no longer reachable point at the empty string afterwards.
+    /// Returns the number of bytes reclaimed.
+    pub fn compact_strings(&mut self) -> usize {
+        let mut live_sentences = vec![false; self.sentences.len()];
+        let mut live_words = vec![false; self.words.len()];
+        let mut live_contextual_translations = vec![false; self.word_contextual_translations.len()];
+        let mut visited = vec![false; self.paragraph_translations.len()];
+        for &head in self.paragraphs.iter().flatten() {
+            let mut curr = Some(head);
+            while let Some(idx) = curr {
+                if std::mem::replace(&mut visited[idx], true) {
+                    break;
+                }
+                let pt = &self.paragraph_translations[idx];
+                live_sentences[pt.sentences.start..pt.sentences.end()].fill(true);
+                for sentence in pt.sentences.slice(&self.sentences) {
+                    let words = &sentence.words;
+                    live_words[words.start..words.end()].fill(true);
+                    for word in words.slice(&self.words) {
+                        let cts = &word.contextual_translations;
+                        live_contextual_translations[cts.start..cts.end()].fill(true);
+                    }
+                }
+                curr = pt.previous_version;
+            }
+        }
+
+        let old = std::mem::take(&mut self.strings);
+        let mut strings = Vec::new();
+        let mut moved = AHashMap::new();
+        let mut relocate = |slice: VecSlice<u8>, live: bool| {
+            if !live {
+                return VecSlice::empty();
+            }
+            let bytes = slice.slice(&old);
+            *moved.entry(bytes).or_insert_with(|| {
+                let start = strings.len();
+                strings.extend_from_slice(bytes);
+                VecSlice::new(start, bytes.len())
+            })
+        };
+        for (sentence, &live) in self.sentences.iter_mut().zip(&live_sentences) {
+            sentence.full_translation = relocate(sentence.full_translation, live);
+        }
+        for (word, &live) in self.words.iter_mut().zip(&live_words) {
+            word.original = relocate(word.original, live);
+            word.note = relocate(word.note, live);
+            let g = &mut word.grammar;
+            g.original_initial_form = relocate(g.original_initial_form, live);
+            g.target_initial_form = relocate(g.target_initial_form, live);
+            g.part_of_speech = relocate(g.part_of_speech, live);
+            for field in [
+                &mut g.plurality,
+                &mut g.person,
+                &mut g.tense,
+                &mut g.case,
+                &mut g.other,
+            ] {
+                *field = field.map(|f| relocate(f, live));
+            }
+        }
+        for (ct, &live) in self
+            .word_contextual_translations
+            .iter_mut()
+            .zip(&live_contextual_translations)
+        {
+            ct.translation = relocate(ct.translation, live);
+        }
+
+        self.strings_cache = moved
+            .into_iter()
+            .filter_map(|(bytes, slice)| Some((String::from_utf8(bytes.to_vec()).ok()?, slice)))
+            .collect();
+        self.strings = strings;
+        old.len().saturating_sub(self.strings.len())
+    }
+
     pub fn add_paragraph_translation(
         &mut self,
         paragraph_index: usize,
@@ -228,7 +568,9 @@ impl Translation {
             sentences: VecSlice::empty(),
             model,
             total_tokens: translation.total_tokens,
+            token_usage: translation.token_usage,
             visible_words: AHashSet::new(),
+            revealed_at: AHashMap::new(),
         };
         let new_index = self.paragraph_translations.len();
         self.paragraph_translations.push(new_paragraph);
@@ -242,14 +584,8 @@ impl Translation {
                 let original = self.push_string(&word.original);
                 let note = self.push_string(&word.note.clone().unwrap_or("".to_string()));
                 let grammar = Grammar {
-                    original_initial_form: push_string(
-                        &mut self.strings,
-                        &word.grammar.original_initial_form,
-                    ),
-                    target_initial_form: push_string(
-                        &mut self.strings,
-                        &word.grammar.target_initial_form,
-                    ),
+                    original_initial_form: self.push_string(&word.grammar.original_initial_form),
+                    target_initial_form: self.push_string(&word.grammar.target_initial_form),
                     part_of_speech: self.push_string(&word.grammar.part_of_speech),
                     plurality: word.grammar.plurality.as_ref().map(|s| self.push_string(s)),
                     person: word.grammar.person.as_ref().map(|s| self.push_string(s)),
@@ -309,7 +645,9 @@ impl Translation {
             sentences: VecSlice::empty(),
             model: translation.model,
             total_tokens: translation.total_tokens,
+            token_usage: translation.token_usage,
             visible_words: translation.visible_words().clone(),
+            revealed_at: translation.revealed_at().clone(),
         };
 
         let new_index = self.paragraph_translations.len();
@@ -324,14 +662,8 @@ impl Translation {
                 let original = self.push_string(&word.original);
                 let note = self.push_string(&word.note);
                 let grammar = Grammar {
-                    original_initial_form: push_string(
-                        &mut self.strings,
-                        &word.grammar.original_initial_form,
-                    ),
-                    target_initial_form: push_string(
-                        &mut self.strings,
-                        &word.grammar.target_initial_form,
-                    ),
+                    original_initial_form: self.push_string(&word.grammar.original_initial_form),
+                    target_initial_form: self.push_string(&word.grammar.target_initial_form),
                     part_of_speech: self.push_string(&word.grammar.part_of_speech),
                     plurality: word.grammar.plurality.as_ref().map(|s| self.push_string(s)),
                     person: word.grammar.person.as_ref().map(|s| self.push_string(s)),
@@ -342,10 +674,7 @@ impl Translation {
                 let mut contextual_translations = VecSlice::empty();
                 for contextual_translation in word.contextual_translations() {
                     let contextual_translation = WordContextualTranslation {
-                        translation: push_string(
-                            &mut self.strings,
-                            &contextual_translation.translation,
-                        ),
+                        translation: self.push_string(&contextual_translation.translation),
                     };
                     contextual_translations = push(
                         &mut self.word_contextual_translations,
@@ -391,6 +720,54 @@ impl Translation {
             .insert(word_index);
     }
 
+    /// Marks a word of the paragraph's current version as revealed at
+    /// `timestamp`. Re-revealing keeps the original time.
+    pub fn mark_word_visible(&mut self, paragraph: usize, word_index: usize, timestamp: u64) {
+        self.add_visible_word(paragraph, word_index);
+        self.add_revealed_at(paragraph, word_index, timestamp);
+    }
+
+    /// Reveals (or hides) every word of the paragraph's current version at
+    /// once, recording `timestamp` as the reveal time of newly revealed
+    /// words. Word indexes count all words of the paragraph across sentences;
+    /// punctuation is never added. Older versions are left untouched.
+    pub fn set_all_words_visible(&mut self, paragraph: usize, visible: bool, timestamp: u64) {
+        let Some(Some(idx)) = self.paragraphs.get(paragraph).copied() else {
+            return;
+        };
+        if !visible {
+            let p = &mut self.paragraph_translations[idx];
+            p.visible_words.clear();
+            p.revealed_at.clear();
+            return;
+        }
+
+        let word_indexes: Vec<usize> = self.paragraph_translations[idx]
+            .sentences
+            .slice(&self.sentences)
+            .iter()
+            .flat_map(|s| s.words.slice(&self.words))
+            .enumerate()
+            .filter(|(_, w)| !w.is_punctuation)
+            .map(|(i, _)| i)
+            .collect();
+        for word_index in word_indexes {
+            self.mark_word_visible(paragraph, word_index, timestamp);
+        }
+    }
+
+    /// Records a reveal time, keeping the earliest one seen for the word.
+    fn add_revealed_at(&mut self, paragraph: usize, word_index: usize, timestamp: u64) {
+        let Some(Some(idx)) = self.paragraphs.get(paragraph).copied() else {
+            return;
+        };
+        self.paragraph_translations[idx]
+            .revealed_at
+            .entry(word_index)
+            .and_modify(|ts| *ts = (*ts).min(timestamp))
+            .or_insert(timestamp);
+    }
+
     fn paragraph_content_matches(
         a: &ParagraphTranslationView,
         b: &ParagraphTranslationView,
@@ -407,12 +784,28 @@ impl Translation {
     }
 
     pub fn merge(&self, other: &Self) -> Self {
-        let mut merged_translation = Self::create(&self.source_language, &self.target_language);
+        self.merge_with_strategy(other, MergeStrategy::default())
+    }
+
+    /// Like [`Translation::merge`], but lets the caller decide what happens
+    /// when a paragraph's history has diverged, i.e. both sides carry versions
+    /// the other side has never seen. Histories where one side is simply
+    /// behind the other are always unioned regardless of strategy.
+    pub fn merge_with_strategy(&self, other: &Self, strategy: MergeStrategy) -> Self {
+        let mut merged_translation = Self::create_with_variant(
+            &self.source_language,
+            &self.target_language,
+            self.target_variant.as_deref(),
+        );
         merged_translation.id = self.id;
+        merged_translation.hash_algorithm = self.hash_algorithm;
         for paragraph_idx in 0..self.paragraphs.len().max(other.paragraphs.len()) {
             if let Some(paragarph) = self.paragraph_view(paragraph_idx)
                 && let Some(other_paragraph) = other.paragraph_view(paragraph_idx)
             {
+                let self_head_timestamp = paragarph.timestamp;
+                let other_head_timestamp = other_paragraph.timestamp;
+
                 let mut versions = Vec::new();
                 let mut curr_paragraph = paragarph;
                 loop {
@@ -423,8 +816,11 @@ impl Translation {
                         None => break,
                     }
                 }
+                let self_versions_count = versions.len();
 
                 let mut other_visible_words: AHashSet<usize> = AHashSet::new();
+                let mut other_revealed_at: AHashMap<usize, u64> = AHashMap::new();
+                let mut self_versions_matched = AHashSet::new();
                 curr_paragraph = other_paragraph;
 
                 loop {
@@ -432,16 +828,21 @@ impl Translation {
                     // Check if a self version shares this timestamp AND has identical content.
                     // Same timestamp + same content = true duplicate (merge visible_words only).
                     // Same timestamp + different content = collision (keep both, bump later).
-                    let is_true_dup = versions
-                        .iter()
-                        .find(|(ts, _)| *ts == curr_paragraph.timestamp)
-                        .map(|(_, self_view)| {
-                            Self::paragraph_content_matches(self_view, &curr_paragraph)
-                        })
-                        .unwrap_or(false);
-
-                    if is_true_dup {
+                    let true_dup =
+                        versions[..self_versions_count]
+                            .iter()
+                            .position(|(ts, self_view)| {
+                                *ts == curr_paragraph.timestamp
+                                    && Self::paragraph_content_matches(self_view, &curr_paragraph)
+                            });
+
+                    if let Some(self_idx) = true_dup {
+                        self_versions_matched.insert(self_idx);
                         other_visible_words.extend(curr_paragraph.visible_words().iter().copied());
+                        for (word_idx, ts) in curr_paragraph.revealed_at() {
+                            let entry = other_revealed_at.entry(*word_idx).or_insert(*ts);
+                            *entry = (*entry).min(*ts);
+                        }
                     } else {
                         versions.push((curr_paragraph.timestamp, curr_paragraph));
                     }
@@ -451,6 +852,36 @@ impl Translation {
                     }
                 }
 
+                let diverged = versions.len() > self_versions_count
+                    && self_versions_matched.len() < self_versions_count;
+                let keep_self = match strategy {
+                    MergeStrategy::UnionHistory => None,
+                    _ if !diverged => None,
+                    MergeStrategy::PreferLocal => Some(true),
+                    MergeStrategy::PreferRemote => Some(false),
+                    MergeStrategy::PreferNewerMtime => {
+                        Some(self_head_timestamp >= other_head_timestamp)
+                    }
+                };
+
+                match keep_self {
+                    Some(true) => {
+                        versions.truncate(self_versions_count);
+                    }
+                    Some(false) => {
+                        versions.clear();
+                        let mut curr = other.paragraph_view(paragraph_idx);
+                        while let Some(p) = curr {
+                            let prev = p.get_previous_version();
+                            versions.push((p.timestamp, p));
+                            curr = prev;
+                        }
+                        other_visible_words.clear();
+                        other_revealed_at.clear();
+                    }
+                    None => {}
+                }
+
                 versions.sort_by_key(|(timestamp, _)| *timestamp);
 
                 // Resolve timestamp collisions: bump later entries by +1, cascading.
@@ -469,6 +900,9 @@ impl Translation {
                     for word_idx in &other_visible_words {
                         merged_translation.add_visible_word(paragraph_idx, *word_idx);
                     }
+                    for (word_idx, revealed_at) in &other_revealed_at {
+                        merged_translation.add_revealed_at(paragraph_idx, *word_idx, *revealed_at);
+                    }
                 }
             } else if let Some(paragarph) = self.paragraph_view(paragraph_idx)
                 && other.paragraph_view(paragraph_idx).is_none()
@@ -513,8 +947,67 @@ impl Translation {
         merged_translation
     }
 
+    /// Folds the history of each duplicate paragraph into the paragraph it
+    /// duplicates, after [`Book::dedupe_paragraphs`]: `survivors[id]` is the
+    /// id a paragraph's history ends up under. Paragraph ids don't change;
+    /// duplicates are left without a translation.
+    ///
+    /// [`Book::dedupe_paragraphs`]: crate::book::book::Book::dedupe_paragraphs
+    pub fn merge_duplicate_paragraphs(&self, survivors: &[usize]) -> Self {
+        let mut merged = Self::create_with_variant(
+            &self.source_language,
+            &self.target_language,
+            self.target_variant.as_deref(),
+        );
+        merged.id = self.id;
+        merged.hash_algorithm = self.hash_algorithm;
+        let mut duplicates = Vec::new();
+        for paragraph_idx in 0..self.paragraphs.len() {
+            let Some(paragraph) = self.paragraph_view(paragraph_idx) else {
+                continue;
+            };
+            let survivor = survivors
+                .get(paragraph_idx)
+                .copied()
+                .unwrap_or(paragraph_idx);
+            if survivor == paragraph_idx {
+                merged.copy_history(paragraph_idx, paragraph);
+            } else {
+                duplicates.push((survivor, paragraph));
+            }
+        }
+
+        for (survivor, paragraph) in duplicates {
+            let mut duplicate = Self::create_with_variant(
+                &self.source_language,
+                &self.target_language,
+                self.target_variant.as_deref(),
+            );
+            duplicate.copy_history(survivor, paragraph);
+            merged = merged.merge(&duplicate);
+        }
+        merged
+    }
+
+    /// Appends every version of `paragraph`, oldest first, under `paragraph_idx`.
+    fn copy_history(&mut self, paragraph_idx: usize, paragraph: ParagraphTranslationView) {
+        let mut versions = Vec::new();
+        let mut curr = Some(paragraph);
+        while let Some(p) = curr {
+            curr = p.get_previous_version();
+            versions.push(p);
+        }
+        versions.sort_by_key(|v| v.timestamp);
+        for v in versions {
+            self.add_paragraph_translation_from_view(paragraph_idx, &v, v.timestamp);
+        }
+    }
+
     #[cfg(test)]
-    fn serialize_v1<TWriter: io::Write>(&self, output_stream: &mut TWriter) -> std::io::Result<()> {
+    pub(crate) fn serialize_v1<TWriter: io::Write>(
+        &self,
+        output_stream: &mut TWriter,
+    ) -> std::io::Result<()> {
         // Binary format TR01 v1 (little endian):
         // magic[4] = TR01
         // u8 version = 1
@@ -739,18 +1232,29 @@ impl Translation {
         //       Tag 1 (TranslationModel): v64 model enum variant
         //       Tag 2 (TotalTokens): v64 has_value, if 1 then v64 token_count
         //       Tag 3 (VisibleWords): v64 count, then v64[] word_indexes
+        //       Tag 4 (RevealedAt): v64 count, then (v64 word_index, v64 timestamp)[];
+        //         only written when non-empty
+        //       Set/map-backed fields are written in ascending key order.
         // u64 paragraphs_count, then each: u8 has_translation (if 1 then u64 paragraph_translation_index)
-        // u64 fnv1 hash of the entire file except the hash itself
+        // Translation-level tagged fields, only written when there is one; readers
+        //   that predate them stop after the paragraphs and never see them:
+        //   v64 number_of_fields
+        //   for each field: v64 field_data_length
+        //   for each field: v64 tag, data
+        //     Tag 1 (TargetVariant): v64 len, [u8]* variant
+        // u64 hash of the entire file except the hash itself (fnv1, or
+        //   truncated sha256 when flagged in the version byte)
 
         let total_start = Instant::now();
 
-        let mut hashing_stream_unbuffered = ChecksumedWriter::create(output_stream);
+        let mut hashing_stream_unbuffered =
+            ChecksumedWriter::create_with(output_stream, self.hash_algorithm);
 
         let mut hashing_stream = BufWriter::new(hashing_stream_unbuffered);
         // magic + version
         let t_magic = Instant::now();
         Magic::Translation.write(&mut hashing_stream)?;
-        Version::V2.write_version(&mut hashing_stream)?;
+        Version::V2.write_version_with_hash(&mut hashing_stream, self.hash_algorithm)?;
         let d_magic = t_magic.elapsed();
 
         // Build metadata and compute its hash
@@ -877,22 +1381,60 @@ impl Translation {
                 // Visible words
                 write_var_u64(&mut cursor, FieldTag::VisibleWords as u64)?;
                 write_var_u64(&mut cursor, pt.visible_words.len() as u64)?;
-                // Sort for deterministic serialization
-                let mut sorted_words: Vec<_> = pt.visible_words.iter().map(|&x| x as u64).collect();
-                sorted_words.sort_unstable();
-                for word_idx in sorted_words {
-                    write_var_u64(&mut cursor, word_idx)?;
+                for word_idx in sorted_keys(&pt.visible_words) {
+                    write_var_u64(&mut cursor, word_idx as u64)?;
                 }
                 cursor.into_inner()
             };
 
-            write_var_u64(&mut hashing_stream, 3)?;
-            write_var_u64(&mut hashing_stream, translation_model_field.len() as u64)?;
-            write_var_u64(&mut hashing_stream, tokens_count_field.len() as u64)?;
-            write_var_u64(&mut hashing_stream, visible_words_field.len() as u64)?;
-            hashing_stream.write_all(&translation_model_field)?;
-            hashing_stream.write_all(&tokens_count_field)?;
-            hashing_stream.write_all(&visible_words_field)?;
+            // Skipped when empty so files without reveal times stay readable
+            // by builds that predate the tag.
+            let revealed_at_field = if pt.revealed_at.is_empty() {
+                None
+            } else {
+                let buf = Vec::new();
+                let mut cursor = Cursor::new(buf);
+
+                write_var_u64(&mut cursor, FieldTag::RevealedAt as u64)?;
+                write_var_u64(&mut cursor, pt.revealed_at.len() as u64)?;
+                for (word_idx, timestamp) in sorted_entries(&pt.revealed_at) {
+                    write_var_u64(&mut cursor, word_idx as u64)?;
+                    write_var_u64(&mut cursor, timestamp)?;
+                }
+                Some(cursor.into_inner())
+            };
+
+            // Skipped when unknown, for the same reason.
+            let token_usage_field = match pt.token_usage {
+                None => None,
+                Some(usage) => {
+                    let buf = Vec::new();
+                    let mut cursor = Cursor::new(buf);
+
+                    write_var_u64(&mut cursor, FieldTag::TokenUsage as u64)?;
+                    write_var_u64(&mut cursor, usage.prompt_tokens)?;
+                    write_var_u64(&mut cursor, usage.completion_tokens)?;
+                    Some(cursor.into_inner())
+                }
+            };
+
+            let fields: Vec<&Vec<u8>> = [
+                Some(&translation_model_field),
+                Some(&tokens_count_field),
+                Some(&visible_words_field),
+                revealed_at_field.as_ref(),
+                token_usage_field.as_ref(),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            write_var_u64(&mut hashing_stream, fields.len() as u64)?;
+            for field in &fields {
+                write_var_u64(&mut hashing_stream, field.len() as u64)?;
+            }
+            for field in fields {
+                hashing_stream.write_all(field)?;
+            }
         }
         let d_pt = t_pt.elapsed();
 
@@ -910,6 +1452,29 @@ impl Translation {
         }
         let d_paragraphs = t_paragraphs.elapsed();
 
+        // Translation-level tagged fields
+        let target_variant_field = match &self.target_variant {
+            None => None,
+            Some(variant) => {
+                let buf = Vec::new();
+                let mut cursor = Cursor::new(buf);
+
+                write_var_u64(&mut cursor, TranslationFieldTag::TargetVariant as u64)?;
+                write_len_prefixed_bytes(&mut cursor, variant.as_bytes())?;
+                Some(cursor.into_inner())
+            }
+        };
+        let fields: Vec<Vec<u8>> = target_variant_field.into_iter().collect();
+        if !fields.is_empty() {
+            write_var_u64(&mut hashing_stream, fields.len() as u64)?;
+            for field in &fields {
+                write_var_u64(&mut hashing_stream, field.len() as u64)?;
+            }
+            for field in &fields {
+                hashing_stream.write_all(field)?;
+            }
+        }
+
         // Finalize hash and flush
         let t_finalize = Instant::now();
         hashing_stream_unbuffered = hashing_stream.into_inner()?;
@@ -946,9 +1511,17 @@ impl Translation {
         Ok(())
     }
 
-    fn read_header_to_version<TReader: io::Seek + io::Read>(
+    /// Format version of a serialized translation. The checksum and magic
+    /// are validated first, so a damaged file is reported as an error.
+    pub fn stored_version<TReader: io::Seek + io::Read>(
+        input_stream: &mut TReader,
+    ) -> std::io::Result<Version> {
+        Ok(Self::read_header(input_stream)?.0)
+    }
+
+    fn read_header<TReader: io::Seek + io::Read>(
         input_stream: &mut TReader,
-    ) -> std::io::Result<Version>
+    ) -> std::io::Result<(Version, HashAlgorithm)>
     where
         Self: Sized,
     {
@@ -965,9 +1538,7 @@ impl Translation {
         if &magic != Magic::Translation.as_bytes() {
             return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid magic"));
         }
-        let version = Version::read_version(input_stream)?;
-
-        Ok(version)
+        Version::read_version_and_hash(input_stream)
     }
 
     fn deserialize_v1<TReader: io::Seek + io::Read>(
@@ -1025,7 +1596,7 @@ impl Translation {
 
         // Contextual translations
         let t_ct = Instant::now();
-        let ct_len = read_var_u64(input_stream)? as usize;
+        let ct_len = read_var_usize(input_stream)?;
         let mut word_contextual_translations = Vec::with_capacity(ct_len);
         for _ in 0..ct_len {
             let slice = cache_vec_slice(read_vec_slice::<u8>(input_stream)?);
@@ -1035,7 +1606,7 @@ impl Translation {
 
         // Words
         let t_words = Instant::now();
-        let words_len = read_var_u64(input_stream)? as usize;
+        let words_len = read_var_usize(input_stream)?;
         let mut words = Vec::with_capacity(words_len);
         for _ in 0..words_len {
             let original = cache_vec_slice(read_vec_slice::<u8>(input_stream)?);
@@ -1073,7 +1644,7 @@ impl Translation {
 
         // Sentences
         let t_sentences = Instant::now();
-        let sentences_len = read_var_u64(input_stream)? as usize;
+        let sentences_len = read_var_usize(input_stream)?;
         let mut sentences = Vec::with_capacity(sentences_len);
         for _ in 0..sentences_len {
             let full_translation = cache_vec_slice(read_vec_slice::<u8>(input_stream)?);
@@ -1087,13 +1658,13 @@ impl Translation {
 
         // Paragraph translations
         let t_pt = Instant::now();
-        let pt_len = read_var_u64(input_stream)? as usize;
+        let pt_len = read_var_usize(input_stream)?;
         let mut paragraph_translations = Vec::with_capacity(pt_len);
         for _ in 0..pt_len {
             let timestamp = read_var_u64(input_stream)?;
             let has_prev = read_u8(input_stream)?;
             let previous_version = if has_prev == 1 {
-                Some(read_var_u64(input_stream)? as usize)
+                Some(read_var_usize(input_stream)?)
             } else {
                 None
             };
@@ -1105,7 +1676,9 @@ impl Translation {
                 sentences: sentences_slice,
                 model: TranslationModel::Unknown,
                 total_tokens: None,
+                token_usage: None,
                 visible_words: AHashSet::new(),
+                revealed_at: AHashMap::new(),
             };
             paragraph_translations.push(translation);
         }
@@ -1113,12 +1686,12 @@ impl Translation {
 
         // Paragraphs (Option indices)
         let t_paragraphs = Instant::now();
-        let paragraphs_len = read_var_u64(input_stream)? as usize;
+        let paragraphs_len = read_var_usize(input_stream)?;
         let mut paragraphs = Vec::with_capacity(paragraphs_len);
         for _ in 0..paragraphs_len {
             let has = read_u8(input_stream)?;
             let val = if has == 1 {
-                Some(read_var_u64(input_stream)? as usize)
+                Some(read_var_usize(input_stream)?)
             } else {
                 None
             };
@@ -1153,12 +1726,14 @@ impl Translation {
             id,
             source_language,
             target_language,
+            target_variant: None,
             strings,
             paragraphs,
             paragraph_translations,
             sentences,
             words,
             word_contextual_translations,
+            hash_algorithm: HashAlgorithm::default(),
         })
     }
 
@@ -1217,7 +1792,7 @@ impl Translation {
 
         // Contextual translations
         let t_ct = Instant::now();
-        let ct_len = read_var_u64(input_stream)? as usize;
+        let ct_len = read_var_usize(input_stream)?;
         let mut word_contextual_translations = Vec::with_capacity(ct_len);
         for _ in 0..ct_len {
             let slice = cache_vec_slice(read_vec_slice::<u8>(input_stream)?);
@@ -1227,7 +1802,7 @@ impl Translation {
 
         // Words
         let t_words = Instant::now();
-        let words_len = read_var_u64(input_stream)? as usize;
+        let words_len = read_var_usize(input_stream)?;
         let mut words = Vec::with_capacity(words_len);
         for _ in 0..words_len {
             let original = cache_vec_slice(read_vec_slice::<u8>(input_stream)?);
@@ -1265,7 +1840,7 @@ impl Translation {
 
         // Sentences
         let t_sentences = Instant::now();
-        let sentences_len = read_var_u64(input_stream)? as usize;
+        let sentences_len = read_var_usize(input_stream)?;
         let mut sentences = Vec::with_capacity(sentences_len);
         for _ in 0..sentences_len {
             let full_translation = cache_vec_slice(read_vec_slice::<u8>(input_stream)?);
@@ -1279,13 +1854,13 @@ impl Translation {
 
         // Paragraph translations
         let t_pt = Instant::now();
-        let pt_len = read_var_u64(input_stream)? as usize;
+        let pt_len = read_var_usize(input_stream)?;
         let mut paragraph_translations = Vec::with_capacity(pt_len);
         for _ in 0..pt_len {
             let timestamp = read_var_u64(input_stream)?;
             let has_prev = read_u8(input_stream)?;
             let previous_version = if has_prev == 1 {
-                Some(read_var_u64(input_stream)? as usize)
+                Some(read_var_usize(input_stream)?)
             } else {
                 None
             };
@@ -1297,18 +1872,20 @@ impl Translation {
                 sentences: sentences_slice,
                 model: TranslationModel::Unknown,
                 total_tokens: None,
+                token_usage: None,
                 visible_words: AHashSet::new(),
+                revealed_at: AHashMap::new(),
             };
 
             // Tagged fields
 
             let tagged_fields_count = read_var_u64(input_stream)?;
-            let mut fields_length = Vec::with_capacity(tagged_fields_count as usize);
+            let mut fields_length = Vec::with_capacity(u64_to_usize(tagged_fields_count)?);
             for _ in 0..tagged_fields_count {
                 fields_length.push(read_var_u64(input_stream)?);
             }
             for fl in fields_length {
-                let mut buf = vec![0; fl as usize];
+                let mut buf = vec![0; u64_to_usize(fl)?];
                 input_stream.read_exact(&mut buf)?;
                 let mut cursor = Cursor::new(buf);
 
@@ -1318,7 +1895,7 @@ impl Translation {
 
                 match tag {
                     FieldTag::TranslationModel => {
-                        let model: TranslationModel = (read_var_u64(&mut cursor)? as usize).into();
+                        let model: TranslationModel = read_var_usize(&mut cursor)?.into();
                         translation.model = model;
                     }
                     FieldTag::TotalTokens => {
@@ -1326,13 +1903,28 @@ impl Translation {
                         translation.total_tokens = tokens;
                     }
                     FieldTag::VisibleWords => {
-                        let count = read_var_u64(&mut cursor)? as usize;
+                        let count = read_var_usize(&mut cursor)?;
                         let mut words = AHashSet::with_capacity(count);
                         for _ in 0..count {
-                            words.insert(read_var_u64(&mut cursor)? as usize);
+                            words.insert(read_var_usize(&mut cursor)?);
                         }
                         translation.visible_words = words;
                     }
+                    FieldTag::RevealedAt => {
+                        let count = read_var_usize(&mut cursor)?;
+                        let mut revealed_at = AHashMap::with_capacity(count);
+                        for _ in 0..count {
+                            let word_idx = read_var_usize(&mut cursor)?;
+                            revealed_at.insert(word_idx, read_var_u64(&mut cursor)?);
+                        }
+                        translation.revealed_at = revealed_at;
+                    }
+                    FieldTag::TokenUsage => {
+                        translation.token_usage = Some(TokenUsage {
+                            prompt_tokens: read_var_u64(&mut cursor)?,
+                            completion_tokens: read_var_u64(&mut cursor)?,
+                        });
+                    }
                 }
             }
 
@@ -1342,12 +1934,12 @@ impl Translation {
 
         // Paragraphs (Option indices)
         let t_paragraphs = Instant::now();
-        let paragraphs_len = read_var_u64(input_stream)? as usize;
+        let paragraphs_len = read_var_usize(input_stream)?;
         let mut paragraphs = Vec::with_capacity(paragraphs_len);
         for _ in 0..paragraphs_len {
             let has = read_u8(input_stream)?;
             let val = if has == 1 {
-                Some(read_var_u64(input_stream)? as usize)
+                Some(read_var_usize(input_stream)?)
             } else {
                 None
             };
@@ -1355,6 +1947,35 @@ impl Translation {
         }
         let d_paragraphs = t_paragraphs.elapsed();
 
+        // Translation-level tagged fields, if any, sit between the paragraphs
+        // and the trailing hash.
+        let mut target_variant = None;
+        let position = input_stream.stream_position()?;
+        let body_end = input_stream.seek(io::SeekFrom::End(-8))?;
+        input_stream.seek(io::SeekFrom::Start(position))?;
+        if position < body_end {
+            let fields_count = read_var_u64(input_stream)?;
+            let mut fields_length = Vec::with_capacity(u64_to_usize(fields_count)?);
+            for _ in 0..fields_count {
+                fields_length.push(read_var_u64(input_stream)?);
+            }
+            for fl in fields_length {
+                let mut buf = vec![0; u64_to_usize(fl)?];
+                input_stream.read_exact(&mut buf)?;
+                let mut cursor = Cursor::new(buf);
+
+                let tag: TranslationFieldTag = read_var_u64(&mut cursor)?
+                    .try_into()
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+                match tag {
+                    TranslationFieldTag::TargetVariant => {
+                        target_variant = Some(read_len_prefixed_string(&mut cursor)?);
+                    }
+                }
+            }
+        }
+
         let total = total_start.elapsed();
 
         info!(
@@ -1382,12 +2003,14 @@ impl Translation {
             id,
             source_language,
             target_language,
+            target_variant,
             strings,
             paragraphs,
             paragraph_translations,
             sentences,
             words,
             word_contextual_translations,
+            hash_algorithm: HashAlgorithm::default(),
         })
     }
 }
@@ -1403,11 +2026,13 @@ impl Serializable for Translation {
     where
         Self: Sized,
     {
-        let version = Self::read_header_to_version(input_stream)?;
-        match version {
-            Version::V1 => Self::deserialize_v1(input_stream, version),
-            Version::V2 => Self::deserialize_v2(input_stream, version),
-        }
+        let (version, hash_algorithm) = Self::read_header(input_stream)?;
+        let mut translation = match version {
+            Version::V1 => Self::deserialize_v1(input_stream, version)?,
+            Version::V2 => Self::deserialize_v2(input_stream, version)?,
+        };
+        translation.hash_algorithm = hash_algorithm;
+        Ok(translation)
     }
 }
 
@@ -1423,7 +2048,9 @@ impl<'a> ParagraphTranslationView<'a> {
             sentences: p.sentences.slice(&self.translation.sentences),
             model: p.model,
             total_tokens: p.total_tokens,
+            token_usage: p.token_usage,
             visible_words: &p.visible_words,
+            revealed_at: &p.revealed_at,
         })
     }
 
@@ -1431,6 +2058,10 @@ impl<'a> ParagraphTranslationView<'a> {
         self.visible_words
     }
 
+    pub fn revealed_at(&self) -> &AHashMap<usize, u64> {
+        self.revealed_at
+    }
+
     pub fn sentence_count(&self) -> usize {
         self.sentences.len()
     }
@@ -1439,9 +2070,7 @@ impl<'a> ParagraphTranslationView<'a> {
         let sentence = &self.sentences[sentence];
         SentenceView {
             translation: self.translation,
-            full_translation: String::from_utf8_lossy(
-                sentence.full_translation.slice(&self.translation.strings),
-            ),
+            full_translation: sentence.full_translation.to_str(&self.translation.strings),
             words: sentence.words.slice(&self.translation.words),
         }
     }
@@ -1454,6 +2083,7 @@ impl<'a> ParagraphTranslationView<'a> {
         translation_import::ParagraphTranslation {
             timestamp: self.timestamp,
             total_tokens: self.total_tokens,
+            token_usage: self.token_usage,
             sentences: self
                 .sentences()
                 .map(|s| translation_import::Sentence {
@@ -1502,42 +2132,41 @@ impl<'a> SentenceView<'a> {
         let word = &self.words[word];
         WordView {
             translation: self.translation,
-            original: String::from_utf8_lossy(word.original.slice(&self.translation.strings)),
-            note: String::from_utf8_lossy(word.note.slice(&self.translation.strings)),
+            original: word.original.to_str(&self.translation.strings),
+            note: word.note.to_str(&self.translation.strings),
             grammar: GrammarView {
-                original_initial_form: String::from_utf8_lossy(
-                    word.grammar
-                        .original_initial_form
-                        .slice(&self.translation.strings),
-                ),
-                target_initial_form: String::from_utf8_lossy(
-                    word.grammar
-                        .target_initial_form
-                        .slice(&self.translation.strings),
-                ),
-                part_of_speech: String::from_utf8_lossy(
-                    word.grammar.part_of_speech.slice(&self.translation.strings),
-                ),
+                original_initial_form: word
+                    .grammar
+                    .original_initial_form
+                    .to_str(&self.translation.strings),
+                target_initial_form: word
+                    .grammar
+                    .target_initial_form
+                    .to_str(&self.translation.strings),
+                part_of_speech: word
+                    .grammar
+                    .part_of_speech
+                    .to_str(&self.translation.strings),
                 plurality: word
                     .grammar
                     .plurality
-                    .map(|s| String::from_utf8_lossy(s.slice(&self.translation.strings))),
+                    .map(|s| s.to_str(&self.translation.strings)),
                 person: word
                     .grammar
                     .person
-                    .map(|s| String::from_utf8_lossy(s.slice(&self.translation.strings))),
+                    .map(|s| s.to_str(&self.translation.strings)),
                 tense: word
                     .grammar
                     .tense
-                    .map(|s| String::from_utf8_lossy(s.slice(&self.translation.strings))),
+                    .map(|s| s.to_str(&self.translation.strings)),
                 case: word
                     .grammar
                     .case
-                    .map(|s| String::from_utf8_lossy(s.slice(&self.translation.strings))),
+                    .map(|s| s.to_str(&self.translation.strings)),
                 other: word
                     .grammar
                     .other
-                    .map(|s| String::from_utf8_lossy(s.slice(&self.translation.strings))),
+                    .map(|s| s.to_str(&self.translation.strings)),
             },
             is_punctuation: word.is_punctuation,
             contextual_translations: word
@@ -1559,11 +2188,9 @@ impl<'a> WordView<'a> {
     pub fn contextual_translations_view(&self, index: usize) -> WordContextualTranslationView<'a> {
         let contextual_translation = &self.contextual_translations[index];
         WordContextualTranslationView {
-            translation: String::from_utf8_lossy(
-                contextual_translation
-                    .translation
-                    .slice(&self.translation.strings),
-            ),
+            translation: contextual_translation
+                .translation
+                .to_str(&self.translation.strings),
         }
     }
 
@@ -1572,6 +2199,28 @@ impl<'a> WordView<'a> {
     ) -> impl Iterator<Item = WordContextualTranslationView<'_>> {
         (0..self.contextual_translations_count()).map(|t| self.contextual_translations_view(t))
     }
+
+    /// Shorthand for [`GrammarView::fields`].
+    pub fn all_grammar_fields(&self) -> impl Iterator<Item = (&'static str, Option<Cow<'a, str>>)> {
+        self.grammar.fields().into_iter()
+    }
+}
+
+impl<'a> GrammarView<'a> {
+    /// Every grammar field with its display label, in the order the reader
+    /// shows them. The required fields are always `Some`.
+    pub fn fields(&self) -> Vec<(&'static str, Option<Cow<'a, str>>)> {
+        vec![
+            ("Part of speech", Some(self.part_of_speech.clone())),
+            ("Initial form", Some(self.original_initial_form.clone())),
+            ("Translated form", Some(self.target_initial_form.clone())),
+            ("Plurality", self.plurality.clone()),
+            ("Person", self.person.clone()),
+            ("Tense", self.tense.clone()),
+            ("Case", self.case.clone()),
+            ("Other", self.other.clone()),
+        ]
+    }
 }
 
 #[cfg(test)]
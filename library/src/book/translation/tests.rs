@@ -1,6 +1,7 @@
 use std::io::Cursor;
 
 use super::*;
+use crate::test_utils::{full_word, one_sentence_paragraph};
 
 fn make_word(original: &str) -> translation_import::Word {
     translation_import::Word {
@@ -29,6 +30,7 @@ fn make_paragraph(ts: u64, text: &str) -> translation_import::ParagraphTranslati
             words: vec![make_word(text)],
         }],
         total_tokens: None,
+        token_usage: None,
     }
 }
 
@@ -37,6 +39,7 @@ fn test_translation_add_paragraph_translation() {
     let mut translation = Translation::create("en", "ru");
     let paragraph_translation = translation_import::ParagraphTranslation {
         total_tokens: None,
+        token_usage: None,
         timestamp: 1234567890,
         sentences: vec![translation_import::Sentence {
             full_translation: "Hello, world!".to_string(),
@@ -139,6 +142,7 @@ fn translation_serialize_deserialize_round_trip() {
     let mut translation = Translation::create("en", "ru");
     let paragraph_translation = translation_import::ParagraphTranslation {
         total_tokens: Some(1234),
+        token_usage: None,
         timestamp: 1,
         sentences: vec![translation_import::Sentence {
             full_translation: "Hi".into(),
@@ -169,6 +173,10 @@ fn translation_serialize_deserialize_round_trip() {
     // second version
     let paragraph_translation2 = translation_import::ParagraphTranslation {
         total_tokens: Some(4321),
+        token_usage: Some(translation_import::TokenUsage {
+            prompt_tokens: 4000,
+            completion_tokens: 321,
+        }),
         timestamp: 2,
         sentences: vec![translation_import::Sentence {
             full_translation: "Hi there".into(),
@@ -226,6 +234,13 @@ fn translation_serialize_deserialize_round_trip() {
     assert_eq!(latest.sentence_count(), 1);
     assert_eq!(latest.model, TranslationModel::Gemini25FlashLight);
     assert_eq!(latest.total_tokens, Some(4321));
+    assert_eq!(
+        latest.token_usage,
+        Some(translation_import::TokenUsage {
+            prompt_tokens: 4000,
+            completion_tokens: 321,
+        })
+    );
     let sentence = latest.sentence_view(0);
     assert_eq!(sentence.full_translation, "Hi there");
     assert_eq!(sentence.word_count(), 2);
@@ -236,6 +251,7 @@ fn translation_serialize_deserialize_round_trip() {
     assert_eq!(word1.original, "there");
     // Previous version chain
     let prev = latest.get_previous_version().unwrap();
+    assert_eq!(prev.token_usage, None);
     let prev_sentence = prev.sentence_view(0);
     assert_eq!(prev_sentence.full_translation, "Hi");
 }
@@ -245,6 +261,7 @@ fn translation_serialize_v1_deserialize_round_trip() {
     let mut translation = Translation::create("en", "ru");
     let paragraph_translation = translation_import::ParagraphTranslation {
         total_tokens: None,
+        token_usage: None,
         timestamp: 1,
         sentences: vec![translation_import::Sentence {
             full_translation: "Hi".into(),
@@ -275,6 +292,7 @@ fn translation_serialize_v1_deserialize_round_trip() {
     // second version
     let paragraph_translation2 = translation_import::ParagraphTranslation {
         total_tokens: None,
+        token_usage: None,
         timestamp: 2,
         sentences: vec![translation_import::Sentence {
             full_translation: "Hi there".into(),
@@ -350,6 +368,7 @@ fn translation_serialize_deserialize_corruption() {
     let mut translation = Translation::create("en", "ru");
     let paragraph_translation = translation_import::ParagraphTranslation {
         total_tokens: None,
+        token_usage: None,
         timestamp: 1,
         sentences: vec![translation_import::Sentence {
             full_translation: "Hi".into(),
@@ -380,6 +399,7 @@ fn translation_serialize_deserialize_corruption() {
     // second version
     let paragraph_translation2 = translation_import::ParagraphTranslation {
         total_tokens: None,
+        token_usage: None,
         timestamp: 2,
         sentences: vec![translation_import::Sentence {
             full_translation: "Hi there".into(),
@@ -602,12 +622,497 @@ fn merge_visible_words_union() {
     assert_eq!(visible, vec![1, 2, 3]); // Union of [1, 3] and [2, 3]
 }
 
+#[test]
+fn revealed_at_round_trips_and_merges_to_earliest() {
+    let mut a = Translation::create("en", "ru");
+    a.add_paragraph_translation(
+        0,
+        &make_paragraph(1, "shared"),
+        TranslationModel::Gemini25Flash,
+    );
+    a.mark_word_visible(0, 1, 200);
+    a.mark_word_visible(0, 1, 300); // Re-reveal keeps the first time
+
+    let mut buf: Vec<u8> = vec![];
+    a.serialize(&mut buf).unwrap();
+    let a = Translation::deserialize(&mut Cursor::new(buf)).unwrap();
+    let view = a.paragraph_view(0).unwrap();
+    assert_eq!(view.revealed_at().get(&1), Some(&200));
+    assert!(view.visible_words().contains(&1));
+
+    let mut b = Translation::create("en", "ru");
+    b.add_paragraph_translation(
+        0,
+        &make_paragraph(1, "shared"),
+        TranslationModel::Gemini25Flash,
+    );
+    b.mark_word_visible(0, 1, 100);
+    b.mark_word_visible(0, 2, 400);
+
+    let merged = a.merge(&b);
+    let view = merged.paragraph_view(0).unwrap();
+    assert_eq!(view.revealed_at().get(&1), Some(&100));
+    assert_eq!(view.revealed_at().get(&2), Some(&400));
+}
+
+#[test]
+fn sha256_checksum_round_trips_and_detects_corruption() {
+    let mut translation = Translation::create("en", "ru");
+    translation.add_paragraph_translation(
+        0,
+        &make_paragraph(1, "checked"),
+        TranslationModel::Gemini25Flash,
+    );
+    translation.set_hash_algorithm(HashAlgorithm::Sha256);
+    let mut buffer: Vec<u8> = vec![];
+    translation.serialize(&mut buffer).unwrap();
+
+    let restored = Translation::deserialize(&mut Cursor::new(&buffer)).unwrap();
+    assert_eq!(restored.hash_algorithm(), HashAlgorithm::Sha256);
+    assert_eq!(
+        Translation::stored_version(&mut Cursor::new(&buffer)).unwrap(),
+        Version::V2
+    );
+    assert_eq!(
+        restored.paragraph_view(0).unwrap().timestamp,
+        translation.paragraph_view(0).unwrap().timestamp
+    );
+
+    let last_body_byte = buffer.len() - 9;
+    buffer[last_body_byte] ^= 0xff;
+    assert!(Translation::deserialize(&mut Cursor::new(&buffer)).is_err());
+}
+
+#[test]
+fn target_variant_is_stored_after_the_paragraphs() {
+    let id = Uuid::new_v4();
+    let serialize = |variant: Option<&str>| {
+        let mut translation = Translation::create_with_variant("eng", "por", variant);
+        translation.id = id;
+        translation.add_paragraph_translation(
+            0,
+            &make_paragraph(1, "ônibus"),
+            TranslationModel::Gemini25Flash,
+        );
+        let mut buffer: Vec<u8> = vec![];
+        translation.serialize(&mut buffer).unwrap();
+        buffer
+    };
+    let bare = serialize(None);
+    let variant = serialize(Some("BR"));
+
+    let restored = Translation::deserialize(&mut Cursor::new(&variant)).unwrap();
+    assert_eq!(restored.target_language, "por");
+    assert_eq!(restored.target_variant.as_deref(), Some("BR"));
+    let restored = Translation::deserialize(&mut Cursor::new(&bare)).unwrap();
+    assert_eq!(restored.target_variant, None);
+
+    // Up to the paragraphs both files are the same, so readers that predate
+    // the variant load it as the base language.
+    let body = &bare[..bare.len() - 8];
+    assert!(variant.len() > bare.len());
+    assert_eq!(&variant[..body.len()], body);
+}
+
+#[test]
+fn serialization_is_byte_identical_for_identical_content() {
+    // Same id for both builds: it is part of the serialized bytes.
+    let id = Uuid::new_v4();
+    let build = |reveal_order: &[usize]| {
+        let mut t = Translation::create("en", "ru");
+        t.id = id;
+        t.add_paragraph_translation(
+            0,
+            &make_paragraph(1, "shared"),
+            TranslationModel::Gemini25Flash,
+        );
+        for &word in reveal_order {
+            t.mark_word_visible(0, word, 100 + word as u64);
+        }
+        t
+    };
+    let serialize = |t: &Translation| {
+        let mut buf: Vec<u8> = vec![];
+        t.serialize(&mut buf).unwrap();
+        buf
+    };
+
+    let order: Vec<usize> = (0..64).collect();
+    let reversed: Vec<usize> = order.iter().rev().copied().collect();
+    let a = build(&order);
+    let first = serialize(&a);
+    assert_eq!(first, serialize(&a));
+    assert_eq!(first, serialize(&build(&reversed)));
+
+    // Sets rebuilt by deserialization hash differently but must still
+    // write the same bytes.
+    let restored = Translation::deserialize(&mut Cursor::new(first.clone())).unwrap();
+    assert_eq!(first, serialize(&restored));
+}
+
+#[test]
+fn set_all_words_visible_touches_latest_version_only() {
+    let mut punctuation = make_word(",");
+    punctuation.is_punctuation = true;
+    let paragraph = translation_import::ParagraphTranslation {
+        timestamp: 2,
+        sentences: vec![
+            translation_import::Sentence {
+                full_translation: "a, b".to_string(),
+                words: vec![make_word("a"), punctuation, make_word("b")],
+            },
+            translation_import::Sentence {
+                full_translation: "c".to_string(),
+                words: vec![make_word("c")],
+            },
+        ],
+        total_tokens: None,
+        token_usage: None,
+    };
+
+    let mut translation = Translation::create("en", "ru");
+    translation.add_paragraph_translation(
+        0,
+        &make_paragraph(1, "old"),
+        TranslationModel::Gemini25Flash,
+    );
+    translation.add_paragraph_translation(0, &paragraph, TranslationModel::Gemini25Flash);
+    translation.mark_word_visible(0, 2, 50);
+    translation.set_all_words_visible(0, true, 100);
+    let previous = translation
+        .paragraph_view(0)
+        .unwrap()
+        .get_previous_version();
+    assert!(previous.unwrap().visible_words().is_empty());
+
+    let mut buf: Vec<u8> = vec![];
+    translation.serialize(&mut buf).unwrap();
+    let restored = Translation::deserialize(&mut Cursor::new(buf)).unwrap();
+    let merged = restored.merge(&translation);
+    let view = merged.paragraph_view(0).unwrap();
+    let mut visible: Vec<usize> = view.visible_words().iter().copied().collect();
+    visible.sort();
+    assert_eq!(visible, vec![0, 2, 3]);
+    // Words revealed earlier keep their first reveal time.
+    let mut revealed: Vec<(usize, u64)> =
+        view.revealed_at().iter().map(|(w, ts)| (*w, *ts)).collect();
+    revealed.sort();
+    assert_eq!(revealed, vec![(0, 100), (2, 50), (3, 100)]);
+
+    translation.set_all_words_visible(0, false, 200);
+    let view = translation.paragraph_view(0).unwrap();
+    assert!(view.visible_words().is_empty());
+    assert!(view.revealed_at().is_empty());
+}
+
+#[test]
+fn merge_duplicate_paragraphs_keeps_ids() {
+    let mut translation = Translation::create("en", "ru");
+    translation.add_paragraph_translation(
+        0,
+        &make_paragraph(1, "first"),
+        TranslationModel::Gemini25Flash,
+    );
+    translation.add_paragraph_translation(
+        1,
+        &make_paragraph(2, "dup"),
+        TranslationModel::Gemini25Flash,
+    );
+    translation.add_paragraph_translation(
+        2,
+        &make_paragraph(3, "third"),
+        TranslationModel::Gemini25Flash,
+    );
+
+    // Paragraph 1 duplicated paragraph 0.
+    let merged = translation.merge_duplicate_paragraphs(&[0, 0, 2]);
+    assert_eq!(history_texts(&merged, 0), vec!["dup", "first"]);
+    assert!(merged.paragraph_view(1).is_none());
+    assert_eq!(history_texts(&merged, 2), vec!["third"]);
+}
+
+#[test]
+fn totals_count_latest_versions_without_punctuation() {
+    let mut punctuation = make_word(".");
+    punctuation.is_punctuation = true;
+    let two_sentences = translation_import::ParagraphTranslation {
+        timestamp: 2,
+        sentences: vec![
+            translation_import::Sentence {
+                full_translation: "a b.".to_string(),
+                words: vec![make_word("a"), make_word("b"), punctuation],
+            },
+            translation_import::Sentence {
+                full_translation: "c".to_string(),
+                words: vec![make_word("c")],
+            },
+        ],
+        total_tokens: None,
+        token_usage: None,
+    };
+
+    let mut translation = Translation::create("en", "ru");
+    translation.add_paragraph_translation(
+        0,
+        &make_paragraph(1, "old"),
+        TranslationModel::Gemini25Flash,
+    );
+    translation.add_paragraph_translation(0, &two_sentences, TranslationModel::Gemini25Flash);
+    translation.add_paragraph_translation(
+        2,
+        &make_paragraph(3, "other"),
+        TranslationModel::Gemini25Flash,
+    );
+
+    assert_eq!(translation.total_sentences(), 3);
+    assert_eq!(translation.total_words(), 4);
+}
+
+#[test]
+fn search_lemma_finds_inflected_forms_in_latest_versions() {
+    let inflected = |original: &str, lemma: &str| {
+        let mut word = make_word(original);
+        word.grammar.original_initial_form = lemma.to_string();
+        word
+    };
+    let mut punctuation = make_word("ir");
+    punctuation.is_punctuation = true;
+    let paragraph = translation_import::ParagraphTranslation {
+        timestamp: 2,
+        sentences: vec![
+            translation_import::Sentence {
+                full_translation: "I go.".to_string(),
+                words: vec![inflected("Voy", "ir"), punctuation],
+            },
+            translation_import::Sentence {
+                full_translation: "They went home.".to_string(),
+                words: vec![inflected("Fueron", "Ir"), make_word("casa")],
+            },
+        ],
+        total_tokens: None,
+        token_usage: None,
+    };
+
+    let mut translation = Translation::create("es", "en");
+    translation.add_paragraph_translation(
+        0,
+        &make_paragraph(1, "ir"),
+        TranslationModel::Gemini25Flash,
+    );
+    translation.add_paragraph_translation(3, &paragraph, TranslationModel::Gemini25Flash);
+    translation.add_paragraph_translation(
+        0,
+        &make_paragraph(3, "x"),
+        TranslationModel::Gemini25Flash,
+    );
+
+    assert_eq!(translation.search_lemma(" IR "), vec![(3, 0, 0), (3, 1, 0)]);
+    assert!(translation.search_lemma("").is_empty());
+}
+
+#[test]
+fn examples_for_lemma_dedups_and_caps_sentences() {
+    let inflected = |original: &str, lemma: &str| {
+        let mut word = make_word(original);
+        word.grammar.original_initial_form = lemma.to_string();
+        word
+    };
+    let sentence = |full_translation: &str, words| translation_import::Sentence {
+        full_translation: full_translation.to_string(),
+        words,
+    };
+    let paragraph = |sentences| translation_import::ParagraphTranslation {
+        timestamp: 1,
+        sentences,
+        total_tokens: None,
+        token_usage: None,
+    };
+
+    let mut translation = Translation::create("es", "en");
+    translation.add_paragraph_translation(
+        0,
+        &paragraph(vec![
+            // Both words match; the sentence is still one example.
+            sentence(
+                " I go and go. ",
+                vec![inflected("Voy", "ir"), inflected("voy", "ir")],
+            ),
+            sentence("¡…!", vec![inflected("Ve", "ir")]),
+        ]),
+        TranslationModel::Gemini25Flash,
+    );
+    translation.add_paragraph_translation(
+        1,
+        &paragraph(vec![
+            sentence("I go and go.", vec![inflected("Voy", "ir")]),
+            sentence("They went home.", vec![inflected("Fueron", "ir")]),
+            sentence("We will go.", vec![inflected("Iremos", "ir")]),
+        ]),
+        TranslationModel::Gemini25Flash,
+    );
+
+    assert_eq!(
+        translation.examples_for_lemma("ir", 2),
+        vec!["I go and go.", "They went home."]
+    );
+    assert_eq!(translation.examples_for_lemma("ir", 10).len(), 3);
+    assert!(translation.examples_for_lemma("ir", 0).is_empty());
+}
+
+fn diverged_pair() -> (Translation, Translation) {
+    // local:  1 -> 2 -> 4
+    let mut local = Translation::create("en", "ru");
+    local.add_paragraph_translation(0, &make_paragraph(1, "c1"), TranslationModel::Gemini25Flash);
+    local.add_paragraph_translation(0, &make_paragraph(2, "l2"), TranslationModel::Gemini25Flash);
+    local.add_paragraph_translation(0, &make_paragraph(4, "l4"), TranslationModel::Gemini25Flash);
+
+    // remote: 1 -> 3
+    let mut remote = Translation::create("en", "ru");
+    remote.add_paragraph_translation(0, &make_paragraph(1, "c1"), TranslationModel::Gemini25Flash);
+    remote.add_paragraph_translation(0, &make_paragraph(3, "r3"), TranslationModel::Gemini25Flash);
+
+    (local, remote)
+}
+
+fn history_texts(translation: &Translation, paragraph: usize) -> Vec<String> {
+    let mut texts = Vec::new();
+    let mut curr = translation.paragraph_view(paragraph);
+    while let Some(v) = curr {
+        texts.push(v.sentence_view(0).full_translation.to_string());
+        curr = v.get_previous_version();
+    }
+    texts
+}
+
+#[test]
+fn merge_strategy_union_history_keeps_both_sides() {
+    let (local, remote) = diverged_pair();
+
+    let merged = local.merge_with_strategy(&remote, MergeStrategy::UnionHistory);
+
+    assert_eq!(history_texts(&merged, 0), vec!["l4", "r3", "l2", "c1"]);
+    assert_eq!(
+        history_texts(&merged, 0),
+        history_texts(&local.merge(&remote), 0)
+    );
+}
+
+#[test]
+fn merge_strategy_prefer_local_drops_remote_branch() {
+    let (local, remote) = diverged_pair();
+
+    let merged = local.merge_with_strategy(&remote, MergeStrategy::PreferLocal);
+
+    assert_eq!(history_texts(&merged, 0), vec!["l4", "l2", "c1"]);
+}
+
+#[test]
+fn merge_strategy_prefer_remote_drops_local_branch() {
+    let (local, remote) = diverged_pair();
+
+    let merged = local.merge_with_strategy(&remote, MergeStrategy::PreferRemote);
+
+    assert_eq!(history_texts(&merged, 0), vec!["r3", "c1"]);
+}
+
+#[test]
+fn merge_strategy_prefer_newer_keeps_latest_head() {
+    let (local, remote) = diverged_pair();
+
+    // Local head (4) is newer than remote head (3) from either direction.
+    let merged = local.merge_with_strategy(&remote, MergeStrategy::PreferNewerMtime);
+    assert_eq!(history_texts(&merged, 0), vec!["l4", "l2", "c1"]);
+
+    let merged = remote.merge_with_strategy(&local, MergeStrategy::PreferNewerMtime);
+    assert_eq!(history_texts(&merged, 0), vec!["l4", "l2", "c1"]);
+}
+
+#[test]
+fn merge_strategy_ignored_when_history_not_diverged() {
+    // remote is local plus one newer version: a fast-forward, not a conflict.
+    let mut local = Translation::create("en", "ru");
+    local.add_paragraph_translation(0, &make_paragraph(1, "c1"), TranslationModel::Gemini25Flash);
+    let mut remote = Translation::create("en", "ru");
+    remote.add_paragraph_translation(0, &make_paragraph(1, "c1"), TranslationModel::Gemini25Flash);
+    remote.add_paragraph_translation(0, &make_paragraph(2, "r2"), TranslationModel::Gemini25Flash);
+
+    let merged = local.merge_with_strategy(&remote, MergeStrategy::PreferLocal);
+
+    assert_eq!(history_texts(&merged, 0), vec!["r2", "c1"]);
+}
+
+#[test]
+fn strings_blob_stores_repeated_grammar_fields_once() {
+    let mut translation = Translation::create("spa", "eng");
+    for i in 0..100 {
+        translation.add_paragraph_translation(
+            i,
+            &make_paragraph(i as u64, "casa"),
+            TranslationModel::Gemini25Flash,
+        );
+    }
+
+    // "casa" (original, both initial forms, full translation), "casa-ct"
+    // and "n" are each stored exactly once; the empty note takes no bytes.
+    let distinct = "casa".len() + "casa-ct".len() + "n".len();
+    assert_eq!(translation.strings.len(), distinct);
+
+    // Merging replays every version through the view path, which must
+    // dedup as well.
+    let merged = translation.merge(&Translation::create("spa", "eng"));
+    assert_eq!(merged.strings.len(), distinct);
+}
+
+#[test]
+fn compact_strings_drops_bytes_of_unreachable_versions() {
+    let mut translation = Translation::create("spa", "eng");
+    let model = TranslationModel::Gemini25Flash;
+    translation.add_paragraph_translation(0, &make_paragraph(1, "casa"), model);
+    translation.add_paragraph_translation(0, &make_paragraph(2, "perro"), model);
+    translation.add_paragraph_translation(1, &make_paragraph(3, "gato"), model);
+    assert_eq!(translation.compact_strings(), 0);
+
+    // Cut the history of paragraph 0, as if its first version was dropped.
+    let head = translation.paragraphs[0].unwrap();
+    translation.paragraph_translations[head].previous_version = None;
+    let before = translation.strings.len();
+
+    assert_eq!(
+        translation.compact_strings(),
+        "casa".len() + "casa-ct".len()
+    );
+    assert_eq!(translation.strings.len(), before - "casacasa-ct".len());
+
+    let mut buf = Vec::new();
+    translation.serialize(&mut buf).unwrap();
+    let restored = Translation::deserialize(&mut Cursor::new(buf)).unwrap();
+    for (paragraph, text) in [(0, "perro"), (1, "gato")] {
+        let view = restored.paragraph_view(paragraph).unwrap();
+        assert!(view.get_previous_version().is_none());
+        let sentence = view.sentence_view(0);
+        assert_eq!(sentence.full_translation, text);
+        let word = sentence.word_view(0);
+        assert_eq!(word.original, text);
+        assert_eq!(word.grammar.part_of_speech, "n");
+        assert_eq!(
+            word.contextual_translations_view(0).translation,
+            format!("{text}-ct")
+        );
+    }
+
+    // Strings added afterwards still dedup against the compacted blob.
+    let len = translation.strings.len();
+    translation.add_paragraph_translation(2, &make_paragraph(4, "gato"), model);
+    assert_eq!(translation.strings.len(), len);
+}
+
 #[test]
 fn to_import_empty_paragraph() {
     let mut translation = Translation::create("eng", "rus");
     let input = translation_import::ParagraphTranslation {
         timestamp: 42,
         total_tokens: Some(17),
+        token_usage: None,
         sentences: vec![],
     };
     translation.add_paragraph_translation(0, &input, TranslationModel::Gemini25Flash);
@@ -626,6 +1131,7 @@ fn to_import_word_grammar_and_punctuation() {
     let input = translation_import::ParagraphTranslation {
         timestamp: 100,
         total_tokens: None,
+        token_usage: None,
         sentences: vec![translation_import::Sentence {
             full_translation: "Я могу.".into(),
             words: vec![
@@ -695,6 +1201,64 @@ fn to_import_word_grammar_and_punctuation() {
     assert_eq!(punct.grammar.part_of_speech, "punctuation");
 }
 
+#[test]
+fn grammar_fields_are_labeled_in_fixed_order() {
+    let mut translation = Translation::create("spa", "rus");
+    let mut word = full_word("puedo", "poder", "мочь", "verb", &["могу"], false);
+    word.grammar.tense = Some("present".into());
+    let input = one_sentence_paragraph("Я могу.", vec![word]);
+    translation.add_paragraph_translation(0, &input, TranslationModel::Gemini25Flash);
+
+    let paragraph = translation.paragraph_view(0).unwrap();
+    let word = paragraph.sentence_view(0).word_view(0);
+    let fields: Vec<_> = word
+        .all_grammar_fields()
+        .map(|(label, value)| (label, value.map(|v| v.into_owned())))
+        .collect();
+    let labels: Vec<_> = fields.iter().map(|(label, _)| *label).collect();
+    assert_eq!(
+        labels,
+        vec![
+            "Part of speech",
+            "Initial form",
+            "Translated form",
+            "Plurality",
+            "Person",
+            "Tense",
+            "Case",
+            "Other"
+        ]
+    );
+    assert_eq!(fields[0].1.as_deref(), Some("verb"));
+    assert_eq!(fields[3].1, None);
+    assert_eq!(fields[5].1.as_deref(), Some("present"));
+}
+
+#[test]
+fn first_invalid_utf8_paragraph_finds_corrupt_word() {
+    let mut translation = Translation::create("spa", "rus");
+    let input = one_sentence_paragraph(
+        "Я могу.",
+        vec![full_word(
+            "puedo",
+            "poder",
+            "мочь",
+            "verb",
+            &["могу"],
+            false,
+        )],
+    );
+    translation.add_paragraph_translation(0, &input, TranslationModel::Gemini25Flash);
+    translation.add_paragraph_translation(2, &input, TranslationModel::Gemini25Flash);
+    assert_eq!(translation.first_invalid_utf8_paragraph(), None);
+
+    // Cut "мочь" in the middle of its first two-byte character.
+    let word = translation.words[0].clone();
+    let start = word.grammar.target_initial_form.start;
+    translation.strings[start + 1] = b'x';
+    assert_eq!(translation.first_invalid_utf8_paragraph(), Some(0));
+}
+
 #[test]
 fn to_import_round_trip_via_add_paragraph_translation() {
     // Construct a paragraph that uses only the conventions that survive a
@@ -705,6 +1269,7 @@ fn to_import_round_trip_via_add_paragraph_translation() {
     let input = translation_import::ParagraphTranslation {
         timestamp: 1234567890,
         total_tokens: Some(256),
+        token_usage: None,
         sentences: vec![
             translation_import::Sentence {
                 full_translation: "Я могу есть.".into(),
@@ -9,6 +9,18 @@ pub struct ParagraphTranslation {
     pub sentences: Vec<Sentence>,
     #[serde(skip)]
     pub total_tokens: Option<u64>,
+    /// Input/output split of `total_tokens`, when the backend reports it.
+    #[serde(skip)]
+    pub token_usage: Option<TokenUsage>,
+}
+
+/// Tokens billed for one translation request, split so input and output can
+/// be priced separately.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    /// Includes reasoning tokens for models that report them separately.
+    pub completion_tokens: u64,
 }
 
 impl ParagraphTranslation {
@@ -47,6 +59,52 @@ impl ParagraphTranslation {
             }
         }
     }
+
+    /// Keeps only the first `max` contextual translations of every word. The
+    /// prompt asks for best-fitting variants first, so this drops the least
+    /// relevant ones when a model ignores the requested bound.
+    pub fn truncate_contextual_translations(&mut self, max: usize) {
+        for word in self.sentences.iter_mut().flat_map(|s| s.words.iter_mut()) {
+            word.contextual_translations.truncate(max);
+        }
+    }
+
+    /// Appends `note` to the note of the first non-punctuation word matching
+    /// `anchor_word` (case-insensitive, ignoring surrounding punctuation).
+    /// Returns `false` and leaves the translation untouched if no word matches.
+    pub fn attach_note(&mut self, anchor_word: &str, note: &str) -> bool {
+        let anchor = normalize_word(anchor_word);
+        if anchor.is_empty() {
+            return false;
+        }
+        let word = self
+            .sentences
+            .iter_mut()
+            .flat_map(|s| s.words.iter_mut())
+            .find(|w| !w.is_punctuation && normalize_word(&w.original) == anchor);
+        let Some(word) = word else {
+            return false;
+        };
+        match word.note.as_mut() {
+            Some(existing) if !existing.trim().is_empty() => {
+                existing.push('\n');
+                existing.push_str(note);
+            }
+            _ => word.note = Some(note.to_owned()),
+        }
+        true
+    }
+}
+
+/// `Word.original` may carry HTML entities (see `normalize_html_entities`),
+/// so decode before comparing against plain text.
+fn normalize_word(word: &str) -> String {
+    let decoded = decode(word.as_bytes())
+        .to_string()
+        .unwrap_or_else(|_| word.to_owned());
+    decoded
+        .trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
 }
 
 fn decode_in_place(s: &mut String) {
@@ -143,6 +201,7 @@ mod tests {
                 }],
             }],
             total_tokens: None,
+            token_usage: None,
         };
 
         p.normalize_html_entities();
@@ -174,6 +233,7 @@ mod tests {
                 }],
             }],
             total_tokens: None,
+            token_usage: None,
         };
 
         let json = serde_json::to_string(&p).unwrap();
@@ -301,6 +361,86 @@ mod tests {
         assert_eq!(words[1].grammar, Grammar::default());
     }
 
+    fn word(original: &str, note: Option<&str>, is_punctuation: bool) -> Word {
+        Word {
+            original: original.to_owned(),
+            contextual_translations: vec![],
+            note: note.map(str::to_owned),
+            is_punctuation,
+            grammar: grammar(original, original, "noun"),
+        }
+    }
+
+    #[test]
+    fn attach_note_matches_word_ignoring_case_and_punctuation() {
+        let mut p = ParagraphTranslation {
+            timestamp: 0,
+            sentences: vec![Sentence {
+                full_translation: "".to_owned(),
+                words: vec![
+                    word("The", None, false),
+                    word("Manor", Some("estate"), false),
+                    word(",", None, true),
+                ],
+            }],
+            total_tokens: None,
+            token_usage: None,
+        };
+
+        assert!(p.attach_note("manor,", "A country house."));
+        assert_eq!(
+            p.sentences[0].words[1].note.as_deref(),
+            Some("estate\nA country house.")
+        );
+
+        assert!(p.attach_note("the", "Article."));
+        assert_eq!(p.sentences[0].words[0].note.as_deref(), Some("Article."));
+    }
+
+    #[test]
+    fn truncate_contextual_translations_keeps_leading_variants() {
+        let mut many = word("casa", None, false);
+        many.contextual_translations = ["house", "home", "household", "dwelling"]
+            .map(str::to_owned)
+            .to_vec();
+        let mut few = word("mi", None, false);
+        few.contextual_translations = vec!["my".to_owned()];
+        let mut p = ParagraphTranslation {
+            timestamp: 0,
+            sentences: vec![Sentence {
+                full_translation: "".to_owned(),
+                words: vec![few, many],
+            }],
+            total_tokens: None,
+            token_usage: None,
+        };
+
+        p.truncate_contextual_translations(3);
+        assert_eq!(p.sentences[0].words[0].contextual_translations, ["my"]);
+        assert_eq!(
+            p.sentences[0].words[1].contextual_translations,
+            ["house", "home", "household"]
+        );
+    }
+
+    #[test]
+    fn attach_note_without_match_is_a_no_op() {
+        let mut p = ParagraphTranslation {
+            timestamp: 0,
+            sentences: vec![Sentence {
+                full_translation: "".to_owned(),
+                words: vec![word("hola", None, false)],
+            }],
+            total_tokens: None,
+            token_usage: None,
+        };
+        let before = p.clone();
+
+        assert!(!p.attach_note("adios", "Goodbye."));
+        assert!(!p.attach_note("...", "Nothing."));
+        assert_eq!(p, before);
+    }
+
     #[test]
     fn normalize_html_entities_is_idempotent_on_clean_text() {
         let mut p = ParagraphTranslation {
@@ -316,6 +456,7 @@ mod tests {
                 }],
             }],
             total_tokens: None,
+            token_usage: None,
         };
         let before = p.clone();
         p.normalize_html_entities();
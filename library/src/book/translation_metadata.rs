@@ -7,7 +7,7 @@ use uuid::Uuid;
 
 use crate::book::serialization::{
     Magic, Version, read_exact_array, read_len_prefixed_string, read_len_prefixed_vec, read_u64,
-    read_var_u64,
+    read_var_usize,
 };
 
 #[derive(Debug)]
@@ -52,7 +52,7 @@ impl TranslationMetadata {
         let source_language = read_len_prefixed_string(&mut cursor)?;
         let target_language = read_len_prefixed_string(&mut cursor)?;
 
-        let translated_paragraphs_count = read_var_u64(&mut cursor)? as usize;
+        let translated_paragraphs_count = read_var_usize(&mut cursor)?;
 
         Ok(TranslationMetadata {
             id,
@@ -80,6 +80,7 @@ mod translation_metadata_test {
         let mut translation = Translation::create("en", "ru");
         let paragraph_translation = translation_import::ParagraphTranslation {
             total_tokens: None,
+            token_usage: None,
             timestamp: 1,
             sentences: vec![translation_import::Sentence {
                 full_translation: "Hi".into(),
@@ -111,6 +112,7 @@ mod translation_metadata_test {
         // another paragraph
         let paragraph_translation2 = translation_import::ParagraphTranslation {
             total_tokens: None,
+            token_usage: None,
             timestamp: 2,
             sentences: vec![translation_import::Sentence {
                 full_translation: "Hi there".into(),
@@ -167,11 +169,23 @@ mod translation_metadata_test {
         assert_eq!(metadata.translated_paragraphs_count, 2);
     }
 
+    #[test]
+    fn test_metadata_keeps_bare_target_language_for_variants() {
+        let translation = Translation::create_with_variant("eng", "por", Some("BR"));
+
+        let mut buf: Vec<u8> = vec![];
+        translation.serialize(&mut buf).unwrap();
+
+        let metadata = TranslationMetadata::read_metadata(&mut Cursor::new(buf)).unwrap();
+        assert_eq!(metadata.target_language, "por");
+    }
+
     #[test]
     fn test_metadata_corruption() {
         let mut translation = Translation::create("en", "ru");
         let paragraph_translation = translation_import::ParagraphTranslation {
             total_tokens: None,
+            token_usage: None,
             timestamp: 1,
             sentences: vec![translation_import::Sentence {
                 full_translation: "Hi".into(),
@@ -202,6 +216,7 @@ mod translation_metadata_test {
         // another paragraph
         let paragraph_translation2 = translation_import::ParagraphTranslation {
             total_tokens: None,
+            token_usage: None,
             timestamp: 2,
             sentences: vec![translation_import::Sentence {
                 full_translation: "Hi there".into(),
@@ -2,7 +2,7 @@ use std::path::Path;
 
 use isolang::Language;
 
-use crate::book::translation_import::ParagraphTranslation;
+use crate::{book::translation_import::ParagraphTranslation, translator::TranslationModel};
 
 pub mod disk;
 pub mod weak_lru;
@@ -32,11 +32,20 @@ impl TranslationsCache {
         Ok(Self { cache })
     }
 
-    fn make_key(source_language: &Language, target_language: &Language, paragraph: &str) -> String {
+    /// Entries are keyed by the language pair and the model as well as the
+    /// text, so switching either never serves another model's or pair's
+    /// translation. The model goes in as its stable numeric id.
+    fn make_key(
+        source_language: &Language,
+        target_language: &Language,
+        model: TranslationModel,
+        paragraph: &str,
+    ) -> String {
         format!(
-            "{}\n{}\n{}",
+            "{}\n{}\n{}\n{}",
             source_language.to_639_3(),
             target_language.to_639_3(),
+            model as usize,
             paragraph
         )
     }
@@ -45,11 +54,12 @@ impl TranslationsCache {
         &self,
         source_language: &Language,
         target_language: &Language,
+        model: TranslationModel,
         paragraph: &str,
         data: &ParagraphTranslation,
     ) {
         self.cache.insert(
-            Self::make_key(source_language, target_language, paragraph),
+            Self::make_key(source_language, target_language, model, paragraph),
             data.clone(),
         );
     }
@@ -62,10 +72,56 @@ impl TranslationsCache {
         &self,
         source_language: &Language,
         target_language: &Language,
+        model: TranslationModel,
         paragraph: &str,
     ) -> anyhow::Result<Option<ParagraphTranslation>> {
         self.cache
-            .get(&Self::make_key(source_language, target_language, paragraph))
+            .get(&Self::make_key(
+                source_language,
+                target_language,
+                model,
+                paragraph,
+            ))
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TempDir, one_sentence_paragraph};
+
+    #[tokio::test]
+    async fn entries_are_keyed_by_model_and_language_pair() {
+        let tmp = TempDir::new("flts_translations_cache");
+        let spa = Language::from_639_3("spa").unwrap();
+        let eng = Language::from_639_3("eng").unwrap();
+        let rus = Language::from_639_3("rus").unwrap();
+
+        let cache = TranslationsCache::create(&tmp.path).await.unwrap();
+        let flash = one_sentence_paragraph("Hello.", vec![]);
+        let pro = one_sentence_paragraph("Hi.", vec![]);
+        cache.set(&spa, &eng, TranslationModel::Gemini25Flash, "Hola.", &flash);
+        cache.set(&spa, &eng, TranslationModel::Gemini25Pro, "Hola.", &pro);
+        cache.close().await;
+
+        let cache = TranslationsCache::create(&tmp.path).await.unwrap();
+        let get = async |target: &Language, model: TranslationModel| {
+            cache
+                .get(&spa, target, model, "Hola.")
+                .await
+                .unwrap()
+                .map(|t| t.sentences[0].full_translation.clone())
+        };
+        assert_eq!(
+            get(&eng, TranslationModel::Gemini25Flash).await.as_deref(),
+            Some("Hello.")
+        );
+        assert_eq!(
+            get(&eng, TranslationModel::Gemini25Pro).await.as_deref(),
+            Some("Hi.")
+        );
+        assert_eq!(get(&eng, TranslationModel::OpenAIGpt5Mini).await, None);
+        assert_eq!(get(&rus, TranslationModel::Gemini25Flash).await, None);
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use htmlentity::entity::{ICodedDataTrait, decode};
 use isolang::Language;
@@ -17,12 +17,29 @@ pub struct Card {
     pub translations: BTreeMap<String, Vec<String>>,
     pub examples: Vec<Example>,
     pub anki_data: Option<AnkiData>,
+    /// How many times each target form has been produced for this lemma,
+    /// across all books and parts of speech. Absent on cards written before
+    /// usage was tracked; those rank by insertion order only.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub usage: BTreeMap<String, u32>,
+    /// `(book, paragraph)` translations counted in `usage`, per target form,
+    /// so applying the same paragraph again (a backfill, a retranslation)
+    /// doesn't count it twice.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub usage_sources: BTreeMap<String, BTreeSet<(Uuid, usize)>>,
+    /// Unix seconds of the latest translation that produced each target
+    /// form. Added in version 3; absent on older cards.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub last_seen: BTreeMap<String, u64>,
 }
 
 fn default_version() -> u32 {
     2
 }
 
+/// Version written by this build: 3 added `last_seen`.
+pub const CARD_VERSION: u32 = 3;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Example {
     pub source: String,
@@ -306,6 +323,15 @@ pub struct CardUpdate {
     pub part_of_speech: String,
     pub translations: Vec<String>,
     pub example: Option<Example>,
+    /// Unix seconds of the paragraph translation the update comes from.
+    pub seen_at: u64,
+}
+
+impl CardUpdate {
+    /// The `(book, paragraph)` the update was extracted from, if known.
+    fn source(&self) -> Option<(Uuid, usize)> {
+        self.example.as_ref().map(|e| (e.book_id, e.paragraph))
+    }
 }
 
 impl Card {
@@ -324,12 +350,28 @@ impl Card {
             .map(|e| vec![e.clone()])
             .unwrap_or_default();
         Card {
-            version: 2,
+            version: CARD_VERSION,
             id: update.key.id(),
             lemma: update.key.lemma.clone(),
             translations,
             examples,
             anki_data: None,
+            usage: update.translations.iter().map(|t| (t.clone(), 1)).collect(),
+            usage_sources: update
+                .source()
+                .map(|source| {
+                    update
+                        .translations
+                        .iter()
+                        .map(|t| (t.clone(), BTreeSet::from([source])))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            last_seen: update
+                .translations
+                .iter()
+                .map(|t| (t.clone(), update.seen_at))
+                .collect(),
         }
     }
 
@@ -342,7 +384,21 @@ impl Card {
             if !bucket.contains(t) {
                 bucket.push(t.clone());
             }
+            let new_source = match update.source() {
+                Some(source) => self
+                    .usage_sources
+                    .entry(t.clone())
+                    .or_default()
+                    .insert(source),
+                None => true,
+            };
+            if new_source {
+                *self.usage.entry(t.clone()).or_default() += 1;
+            }
+            let seen = self.last_seen.entry(t.clone()).or_default();
+            *seen = (*seen).max(update.seen_at);
         }
+        self.version = self.version.max(CARD_VERSION);
         if let Some(example) = &update.example
             && !self.examples.iter().any(|e| {
                 e.book_id == example.book_id
@@ -355,6 +411,21 @@ impl Card {
         }
     }
 
+    /// Raises this card's usage counts and last-seen times to `previous`'s
+    /// where those are higher, for the forms this card still has.
+    pub fn keep_counters(&mut self, previous: &Card) {
+        for (form, count) in &mut self.usage {
+            if let Some(&previous) = previous.usage.get(form) {
+                *count = (*count).max(previous);
+            }
+        }
+        for (form, seen) in &mut self.last_seen {
+            if let Some(&previous) = previous.last_seen.get(form) {
+                *seen = (*seen).max(previous);
+            }
+        }
+    }
+
     /// Flatten the per-PoS translation buckets into a single ordered, deduped
     /// list. Iteration order is the BTreeMap's PoS-key order; within each
     /// bucket, insertion order is preserved.
@@ -374,7 +445,8 @@ impl Card {
     /// Caller has already verified that `self.id == other.id` (i.e. both files
     /// address the same `(src, tgt, slug)` key). `self.anki_data` is kept;
     /// `other.anki_data` is discarded — it's a local cache, not authoritative
-    /// across instances.
+    /// across instances. Usage counts take the per-form maximum: both sides
+    /// usually share most of their history, so summing would double-count.
     pub fn merge(&mut self, other: Card) {
         for (pos, translations) in other.translations {
             let bucket = self.translations.entry(pos).or_default();
@@ -385,6 +457,22 @@ impl Card {
             }
         }
 
+        for (form, count) in other.usage {
+            let entry = self.usage.entry(form).or_default();
+            *entry = (*entry).max(count);
+        }
+        for (form, sources) in other.usage_sources {
+            let entry = self.usage_sources.entry(form.clone()).or_default();
+            entry.extend(sources);
+            let count = self.usage.entry(form).or_default();
+            *count = (*count).max(entry.len() as u32);
+        }
+        for (form, seen) in other.last_seen {
+            let entry = self.last_seen.entry(form).or_default();
+            *entry = (*entry).max(seen);
+        }
+        self.version = self.version.max(other.version);
+
         let mut combined: Vec<Example> = std::mem::take(&mut self.examples);
         for e in other.examples {
             if !combined.iter().any(|existing| {
@@ -410,6 +498,27 @@ impl Card {
 
         self.examples = combined;
     }
+
+    /// Drop target forms produced fewer than `min_count` times, along with
+    /// their usage counts and any part-of-speech bucket left empty. Forms with
+    /// no recorded usage (legacy cards) are kept, since their count is
+    /// unknown rather than low. Returns the number of forms removed.
+    pub fn purge_below(&mut self, min_count: u32) -> usize {
+        let rare = |form: &String| self.usage.get(form).is_some_and(|&n| n < min_count);
+        let mut removed = 0;
+        for bucket in self.translations.values_mut() {
+            let before = bucket.len();
+            bucket.retain(|form| !rare(form));
+            removed += before - bucket.len();
+        }
+        self.translations.retain(|_, bucket| !bucket.is_empty());
+        self.usage.retain(|_, count| *count >= min_count);
+        self.usage_sources
+            .retain(|form, _| self.translations.values().any(|b| b.contains(form)));
+        self.last_seen
+            .retain(|form, _| self.translations.values().any(|b| b.contains(form)));
+        removed
+    }
 }
 
 pub fn extract_card_updates(
@@ -480,6 +589,7 @@ pub fn extract_card_updates(
                     part_of_speech,
                     translations: vec![target_dictionary.to_owned()],
                     example: Some(example.clone()),
+                    seen_at: paragraph.timestamp,
                 });
             }
         }
@@ -776,6 +886,7 @@ mod tests {
             part_of_speech: "verb".into(),
             translations: translations.into_iter().map(String::from).collect(),
             example,
+            seen_at: 0,
         }
     }
 
@@ -790,10 +901,29 @@ mod tests {
     }
 
     #[test]
-    fn new_card_from_update_has_version_2_anki_data_null() {
+    fn usage_counts_each_paragraph_once() {
+        let book_id = Uuid::new_v4();
+        let mut card =
+            Card::new_from_update(&verb_update(vec!["мочь"], Some(example_at(book_id, 0, 1))));
+        // A backfill or retranslation applies the same paragraph again.
+        card.apply_update(&verb_update(vec!["мочь"], Some(example_at(book_id, 0, 1))));
+        assert_eq!(card.usage, [("мочь".to_owned(), 1)].into());
+
+        card.apply_update(&verb_update(vec!["мочь"], Some(example_at(book_id, 0, 2))));
+        assert_eq!(card.usage, [("мочь".to_owned(), 2)].into());
+
+        let mut other =
+            Card::new_from_update(&verb_update(vec!["мочь"], Some(example_at(book_id, 0, 2))));
+        other.apply_update(&verb_update(vec!["мочь"], Some(example_at(book_id, 0, 3))));
+        card.merge(other);
+        assert_eq!(card.usage, [("мочь".to_owned(), 3)].into());
+    }
+
+    #[test]
+    fn new_card_from_update_has_current_version_anki_data_null() {
         let update = verb_update(vec!["мочь"], Some(example_at(Uuid::nil(), 1, 2)));
         let card = Card::new_from_update(&update);
-        assert_eq!(card.version, 2);
+        assert_eq!(card.version, CARD_VERSION);
         assert_eq!(card.id, "flts_spa_rus_poder");
         assert_eq!(card.lemma, "poder");
         assert_eq!(card.translations_flat(), vec!["мочь"]);
@@ -809,6 +939,22 @@ mod tests {
         assert_eq!(card.translations_flat(), vec!["мочь", "уметь"]);
     }
 
+    #[test]
+    fn apply_update_keeps_latest_seen_timestamp() {
+        let mut card = Card::new_from_update(&CardUpdate {
+            seen_at: 20,
+            ..verb_update(vec!["мочь"], None)
+        });
+        card.apply_update(&CardUpdate {
+            seen_at: 10,
+            ..verb_update(vec!["мочь", "уметь"], None)
+        });
+        assert_eq!(
+            card.last_seen,
+            [("мочь".to_owned(), 20), ("уметь".to_owned(), 10)].into()
+        );
+    }
+
     #[test]
     fn apply_update_dedups_translation() {
         let mut card = Card::new_from_update(&verb_update(vec!["мочь"], None));
@@ -826,6 +972,7 @@ mod tests {
             part_of_speech: "verb_auxiliary".into(),
             translations: vec!["мочь".into()],
             example: None,
+            seen_at: 0,
         });
         assert_eq!(card.translations.len(), 2);
         assert_eq!(card.translations.get("verb").unwrap().as_slice(), ["мочь"]);
@@ -1098,6 +1245,7 @@ mod tests {
         let p = translation_import::ParagraphTranslation {
             timestamp: 0,
             total_tokens: None,
+            token_usage: None,
             sentences: vec![
                 translation_import::Sentence {
                     full_translation: "Я могу.".into(),
@@ -1227,6 +1375,9 @@ mod tests {
             translations: map,
             examples,
             anki_data,
+            usage: Default::default(),
+            last_seen: Default::default(),
+            usage_sources: Default::default(),
         }
     }
 
@@ -1246,6 +1397,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn purge_below_drops_rare_forms_and_empty_buckets() {
+        let mut card = make_card_with(vec!["мочь", "уметь"], vec![], None);
+        card.translations
+            .insert("noun".into(), vec!["власть".into()]);
+        card.usage = [("мочь".to_owned(), 3), ("власть".to_owned(), 1)].into();
+
+        assert_eq!(card.purge_below(2), 1);
+        // "уметь" has no recorded usage, so it survives.
+        assert_eq!(card.translations_flat(), vec!["мочь", "уметь"]);
+        assert!(!card.translations.contains_key("noun"));
+        assert_eq!(card.usage, [("мочь".to_owned(), 3)].into());
+
+        // A purged card round-trips and still merges.
+        let json = serde_json::to_string(&card).unwrap();
+        let mut reloaded: Card = serde_json::from_str(&json).unwrap();
+        assert_eq!(reloaded, card);
+        reloaded.merge(make_card_with(vec!["уметь", "знать"], vec![], None));
+        assert_eq!(reloaded.translations_flat(), vec!["мочь", "уметь", "знать"]);
+    }
+
     #[test]
     fn merge_unions_translations() {
         let mut base = make_card_with(vec!["мочь"], vec![], None);
@@ -1254,6 +1426,21 @@ mod tests {
         assert_eq!(base.translations_flat(), vec!["мочь", "уметь"]);
     }
 
+    #[test]
+    fn merge_keeps_latest_seen_timestamp_and_version() {
+        let mut base = make_card_with(vec!["мочь", "уметь"], vec![], None);
+        base.last_seen = [("мочь".to_owned(), 5), ("уметь".to_owned(), 30)].into();
+        let mut other = make_card_with(vec!["мочь"], vec![], None);
+        other.version = CARD_VERSION;
+        other.last_seen = [("мочь".to_owned(), 10)].into();
+        base.merge(other);
+        assert_eq!(
+            base.last_seen,
+            [("мочь".to_owned(), 10), ("уметь".to_owned(), 30)].into()
+        );
+        assert_eq!(base.version, CARD_VERSION);
+    }
+
     #[test]
     fn merge_dedups_translations() {
         let mut base = make_card_with(vec!["мочь", "уметь"], vec![], None);
@@ -1375,6 +1562,9 @@ mod tests {
                 paragraph: 12,
             }],
             anki_data: None,
+            usage: Default::default(),
+            last_seen: Default::default(),
+            usage_sources: Default::default(),
         };
         let json = serde_json::to_string(&card).unwrap();
         let back: Card = serde_json::from_str(&json).unwrap();
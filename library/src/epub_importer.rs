@@ -1,6 +1,11 @@
-use std::path::Path;
-
-use epub::doc::EpubDoc;
+use std::{
+    collections::HashMap,
+    io::{Read, Seek},
+    path::{Component, Path, PathBuf},
+};
+
+use epub::doc::{EpubDoc, NavPoint};
+use log::warn;
 use scraper::{ElementRef, Html, Node, Selector};
 use serde::{Deserialize, Serialize};
 
@@ -22,44 +27,80 @@ pub struct EpubChapter {
 pub struct EpubParagraph {
     pub text: String,
     pub html: String,
+    /// Footnotes referenced from inside this paragraph, in document order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub footnotes: Vec<EpubFootnote>,
+    /// `1`-`6` when the paragraph is an `<h1>`-`<h6>` heading.
+    #[serde(
+        rename = "headingLevel",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub heading_level: Option<u8>,
+}
+
+/// How the content of an EPUB is grouped into chapters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChapterGranularity {
+    /// One chapter per spine item, or per TOC entry within a spine item.
+    #[default]
+    SpineItems,
+    /// A chapter with less than `min_chars` of text absorbs the chapters
+    /// after it until it reaches the threshold, for books that split one
+    /// logical chapter across many tiny files.
+    MergeSmall { min_chars: usize },
+    /// A new chapter starts at every `<h1>`/`<h2>`, titled by the heading,
+    /// regardless of spine boundaries. Books without such headings are left
+    /// as they are.
+    Headings,
+}
+
+/// A footnote definition resolved from a note reference inside a paragraph.
+/// `anchor_word` is the word immediately preceding the reference marker; the
+/// footnote text is later attached to that word's translation note.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct EpubFootnote {
+    #[serde(rename = "anchorWord")]
+    pub anchor_word: String,
+    pub text: String,
 }
 
 impl EpubBook {
     pub fn load(path: &Path) -> anyhow::Result<EpubBook> {
-        let mut epub = EpubDoc::new(path)?;
-
-        let mut chapters = Vec::new();
+        Self::load_with(path, false).map(|(book, _)| book)
+    }
 
-        // Clone spine to avoid borrow issues
-        let spine_items = epub.spine.clone();
-        let toc_items = epub.toc.clone();
+    /// Like [`EpubBook::load`], but with `lenient` set a spine item that
+    /// can't be read, parsed or located through its TOC anchors is skipped
+    /// and counted instead of failing the whole import or becoming an empty
+    /// chapter. Returns the book together with the number of skipped items.
+    pub fn load_with(path: &Path, lenient: bool) -> anyhow::Result<(EpubBook, usize)> {
+        let mut epub = EpubDoc::new(path)?;
 
-        // Process spine contents
-        for spine_item in &spine_items {
-            // Get TOC elements that match this spine item
-            // Compute c_href_doc once per spine item, not for every TOC element
-            let resource = epub.resources.get(&spine_item.idref).unwrap();
-            let c_href_doc = resource.path.to_str().unwrap().replace("OEBPS/", "");
+        // EPUB 3 books may ship only a navigation document and no NCX.
+        let toc_items = if epub.toc.is_empty() {
+            nav_document_toc(&mut epub)
+        } else {
+            epub.toc.clone()
+        };
 
-            let toc_elements: Vec<_> = toc_items
-                .iter()
-                .filter(|t| {
-                    let t_href_doc = t
-                        .content
-                        .to_string_lossy()
-                        .split('#')
-                        .next()
-                        .unwrap_or("")
-                        .replace("OEBPS/", "");
-                    t_href_doc == c_href_doc
-                })
-                .collect();
-
-            // Get chapter content
-            if let Some((content, _)) = epub.get_resource_str(&spine_item.idref) {
-                chapters.extend(parse_chapter(&content, &toc_elements)?);
-            }
-        }
+        let documents: Vec<_> = epub
+            .spine
+            .clone()
+            .into_iter()
+            .map(|spine_item| SpineDocument {
+                href_doc: epub
+                    .resources
+                    .get(&spine_item.idref)
+                    .and_then(|r| r.path.to_str())
+                    .map(|p| p.replace("OEBPS/", "")),
+                content: epub
+                    .get_resource_str(&spine_item.idref)
+                    .map(|(content, _)| content),
+                idref: spine_item.idref,
+            })
+            .collect();
+        let (chapters, skipped) = parse_spine(&documents, &toc_items, lenient)?;
 
         // Build title
         let mut title_parts = Vec::new();
@@ -74,23 +115,300 @@ impl EpubBook {
             title_parts.push(title.value.clone());
         }
 
-        Ok(EpubBook {
-            title: title_parts.join(" - "),
-            chapters,
-        })
+        Ok((
+            EpubBook {
+                title: title_parts.join(" - "),
+                chapters,
+            },
+            skipped,
+        ))
+    }
+}
+
+/// A spine item as read from the archive.
+struct SpineDocument {
+    idref: String,
+    /// Path of the document without the `OEBPS/` prefix; `None` when the
+    /// spine item doesn't resolve to a resource.
+    href_doc: Option<String>,
+    /// `None` when the resource can't be read as text.
+    content: Option<String>,
+}
+
+/// Parses the spine documents into chapters. Without `lenient`, an
+/// unreadable document is left out and a missing TOC anchor yields an empty
+/// chapter; with it, both are skipped and counted, as is any document that
+/// can't be resolved or parsed.
+fn parse_spine(
+    documents: &[SpineDocument],
+    toc_items: &[NavPoint],
+    lenient: bool,
+) -> anyhow::Result<(Vec<EpubChapter>, usize)> {
+    let mut flat_toc = Vec::new();
+    flatten_toc(toc_items, 0, &mut flat_toc);
+
+    // Footnotes often live in a separate notes document, so collect every
+    // definition up front before resolving references chapter by chapter.
+    let mut footnotes = HashMap::new();
+    for content in documents.iter().filter_map(|d| d.content.as_deref()) {
+        collect_footnotes(&Html::parse_document(content), &mut footnotes);
+    }
+
+    let mut chapters = Vec::new();
+    let mut skipped = 0;
+    for document in documents {
+        let parsed = parse_spine_document(document, toc_items, &flat_toc, &footnotes, lenient);
+        match parsed {
+            Ok(parsed) => chapters.extend(parsed),
+            Err(err) if lenient => {
+                warn!("Skipping malformed chapter {:?}: {err}", document.idref);
+                skipped += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Ok((chapters, skipped))
+}
+
+fn parse_spine_document(
+    document: &SpineDocument,
+    toc_items: &[NavPoint],
+    flat_toc: &[(usize, &NavPoint)],
+    footnotes: &HashMap<String, String>,
+    lenient: bool,
+) -> anyhow::Result<Vec<EpubChapter>> {
+    let Some(href_doc) = &document.href_doc else {
+        anyhow::bail!("Spine item {:?} has no resource", document.idref);
+    };
+    let Some(content) = &document.content else {
+        if lenient {
+            anyhow::bail!("Spine item {:?} can't be read as text", document.idref);
+        }
+        return Ok(Vec::new());
+    };
+
+    let toc_elements = spine_item_toc(toc_items, href_doc);
+    let mut parsed = parse_chapter(content, &toc_elements, footnotes, lenient)?;
+    // A spine item listed only as a section stays whole, but takes its
+    // title from the TOC.
+    if toc_elements.is_empty()
+        && let Some(title) = nested_toc_title(flat_toc, href_doc)
+    {
+        parsed.iter_mut().for_each(|c| c.title = title.clone());
+    }
+    Ok(parsed)
+}
+
+/// Reads the table of contents from the EPUB 3 navigation document, with
+/// entry paths resolved against the document's directory like the NCX ones.
+fn nav_document_toc<R: Read + Seek>(epub: &mut EpubDoc<R>) -> Vec<NavPoint> {
+    let Some(nav_id) = epub.get_nav_id() else {
+        return Vec::new();
+    };
+    let Some(nav_dir) = epub
+        .resources
+        .get(&nav_id)
+        .and_then(|r| r.path.parent())
+        .map(Path::to_path_buf)
+    else {
+        return Vec::new();
+    };
+    let Some((content, _)) = epub.get_resource_str(&nav_id) else {
+        return Vec::new();
+    };
+    parse_nav_document(&content, &nav_dir)
+}
+
+/// Parses the `<nav epub:type="toc">` list of a navigation document. Entries
+/// without a link (e.g. part headings) are replaced by their children.
+fn parse_nav_document(nav_html: &str, nav_dir: &Path) -> Vec<NavPoint> {
+    let document = Html::parse_document(nav_html);
+    let nav_selector = Selector::parse("nav").unwrap();
+    let Some(nav) = document
+        .select(&nav_selector)
+        .find(|nav| has_semantic_type(*nav, &["toc", "doc-toc"]))
+        .or_else(|| document.select(&nav_selector).next())
+    else {
+        return Vec::new();
+    };
+    child_elements(nav, "ol")
+        .next()
+        .map(|list| nav_list_points(list, nav_dir))
+        .unwrap_or_default()
+}
+
+fn nav_list_points(list: ElementRef, nav_dir: &Path) -> Vec<NavPoint> {
+    let mut points = Vec::new();
+    for item in child_elements(list, "li") {
+        let children = child_elements(item, "ol")
+            .next()
+            .map(|list| nav_list_points(list, nav_dir))
+            .unwrap_or_default();
+        let link = child_elements(item, "a").next().and_then(|a| {
+            let label = collapse_whitespace(&a.text().collect::<String>());
+            Some((label, a.value().attr("href")?))
+        });
+        match link {
+            Some((label, href)) => points.push(NavPoint {
+                label,
+                content: resolve_href(nav_dir, href),
+                children,
+                play_order: None,
+            }),
+            None => points.extend(children),
+        }
+    }
+    points
+}
+
+fn child_elements<'a>(
+    element: ElementRef<'a>,
+    name: &'static str,
+) -> impl Iterator<Item = ElementRef<'a>> {
+    element
+        .children()
+        .filter_map(ElementRef::wrap)
+        .filter(move |child| child.value().name().eq_ignore_ascii_case(name))
+}
+
+/// Joins a relative `href` onto `base_dir`, resolving `.` and `..`.
+fn resolve_href(base_dir: &Path, href: &str) -> PathBuf {
+    let mut path = PathBuf::new();
+    for component in base_dir.join(href).components() {
+        match component {
+            Component::ParentDir => {
+                path.pop();
+            }
+            Component::CurDir => {}
+            other => path.push(other),
+        }
+    }
+    path
+}
+
+/// Lists TOC entries depth-first, in reading order, with their nesting depth.
+fn flatten_toc<'a>(toc: &'a [NavPoint], depth: usize, out: &mut Vec<(usize, &'a NavPoint)>) {
+    for point in toc {
+        out.push((depth, point));
+        flatten_toc(&point.children, depth + 1, out);
+    }
+}
+
+/// The top-level TOC entries pointing into the spine document at `href_doc`.
+/// These are where the document is split into chapters.
+fn spine_item_toc<'a>(toc: &'a [NavPoint], href_doc: &str) -> Vec<&'a NavPoint> {
+    toc.iter().filter(|t| toc_href_doc(t) == href_doc).collect()
+}
+
+/// Title for a spine item that no top-level TOC entry points into: the
+/// label of its shallowest nested entry, if it's listed only as a section.
+fn nested_toc_title(flat_toc: &[(usize, &NavPoint)], href_doc: &str) -> Option<String> {
+    flat_toc
+        .iter()
+        .filter(|(_, t)| toc_href_doc(t) == href_doc)
+        .min_by_key(|(depth, _)| *depth)
+        .map(|(_, t)| t.label.clone())
+}
+
+fn toc_href_doc(point: &NavPoint) -> String {
+    point
+        .content
+        .to_string_lossy()
+        .split('#')
+        .next()
+        .unwrap_or("")
+        .replace("OEBPS/", "")
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+impl EpubBook {
+    /// Regroups the loaded chapters per `granularity`.
+    pub fn regroup_chapters(&mut self, granularity: ChapterGranularity) {
+        let chapters = std::mem::take(&mut self.chapters);
+        self.chapters = match granularity {
+            ChapterGranularity::SpineItems => chapters,
+            ChapterGranularity::MergeSmall { min_chars } => {
+                merge_small_chapters(chapters, min_chars)
+            }
+            ChapterGranularity::Headings => split_on_headings(chapters),
+        };
+    }
+}
+
+fn chapter_text_len(chapter: &EpubChapter) -> usize {
+    chapter
+        .paragraphs
+        .iter()
+        .map(|p| p.text.chars().count())
+        .sum()
+}
+
+fn merge_small_chapters(chapters: Vec<EpubChapter>, min_chars: usize) -> Vec<EpubChapter> {
+    let mut merged: Vec<EpubChapter> = Vec::new();
+    for chapter in chapters {
+        match merged.last_mut() {
+            Some(last) if chapter_text_len(last) < min_chars => {
+                if last.title.trim().is_empty() {
+                    last.title = chapter.title;
+                }
+                last.paragraphs.extend(chapter.paragraphs);
+            }
+            _ => merged.push(chapter),
+        }
     }
+    merged
 }
 
+fn split_on_headings(chapters: Vec<EpubChapter>) -> Vec<EpubChapter> {
+    let is_chapter_heading = |p: &EpubParagraph| matches!(p.heading_level, Some(1 | 2));
+    if !chapters
+        .iter()
+        .any(|c| c.paragraphs.iter().any(is_chapter_heading))
+    {
+        return chapters;
+    }
+
+    let mut split: Vec<EpubChapter> = Vec::new();
+    for chapter in chapters {
+        for paragraph in chapter.paragraphs {
+            // The heading stays in the text, as it does in the other modes.
+            if is_chapter_heading(&paragraph) {
+                split.push(EpubChapter {
+                    title: paragraph.text.clone(),
+                    paragraphs: vec![paragraph],
+                });
+                continue;
+            }
+            match split.last_mut() {
+                Some(current) => current.paragraphs.push(paragraph),
+                // Front matter before the first heading keeps its own chapter.
+                None => split.push(EpubChapter {
+                    title: chapter.title.clone(),
+                    paragraphs: vec![paragraph],
+                }),
+            }
+        }
+    }
+    split
+}
+
+/// With `lenient` set, a missing start anchor is an error so the caller can
+/// skip the document; otherwise that chapter is just left empty.
 fn parse_chapter(
     chapter_html: &str,
-    toc: &[&epub::doc::NavPoint],
+    toc: &[&NavPoint],
+    footnotes: &HashMap<String, String>,
+    lenient: bool,
 ) -> anyhow::Result<Vec<EpubChapter>> {
     let document = Html::parse_document(chapter_html);
 
     if toc.is_empty() {
         return Ok(vec![EpubChapter {
             title: extract_title(&document),
-            paragraphs: text_between_anchors(&document, "", None)?,
+            paragraphs: text_between_anchors(&document, "", None, footnotes, lenient)?,
         }]);
     }
 
@@ -106,7 +424,13 @@ fn parse_chapter(
         let start_anchor = split_anchor(&t_curr.content.to_string_lossy());
         let end_anchor = t_next.map(|t| split_anchor(&t.content.to_string_lossy()));
 
-        let paragraphs = text_between_anchors(&document, &start_anchor, end_anchor.as_deref())?;
+        let paragraphs = text_between_anchors(
+            &document,
+            &start_anchor,
+            end_anchor.as_deref(),
+            footnotes,
+            lenient,
+        )?;
 
         chapters.push(EpubChapter {
             title: t_curr.label.clone(),
@@ -117,11 +441,108 @@ fn parse_chapter(
     Ok(chapters)
 }
 
+/// EPUB 3 marks notes with `epub:type`, EPUB 2-era and DPUB-ARIA content with
+/// `role`. Either attribute may carry several space-separated values.
+fn has_semantic_type(element: ElementRef, types: &[&str]) -> bool {
+    ["epub:type", "role"].iter().any(|attr| {
+        element
+            .value()
+            .attr(attr)
+            .is_some_and(|value| value.split_whitespace().any(|v| types.contains(&v)))
+    })
+}
+
+fn is_footnote_definition(element: ElementRef) -> bool {
+    has_semantic_type(
+        element,
+        &[
+            "footnote",
+            "endnote",
+            "rearnote",
+            "note",
+            "doc-footnote",
+            "doc-endnote",
+        ],
+    )
+}
+
+fn is_footnote_reference(element: ElementRef) -> bool {
+    element.value().name().eq_ignore_ascii_case("a")
+        && has_semantic_type(element, &["noteref", "doc-noteref"])
+}
+
+/// Collects footnote definitions keyed by element id.
+fn collect_footnotes(document: &Html, out: &mut HashMap<String, String>) {
+    let selector = Selector::parse("[id]").unwrap();
+    for element in document.select(&selector) {
+        if !is_footnote_definition(element) {
+            continue;
+        }
+        let Some(id) = element.value().id() else {
+            continue;
+        };
+        let text = collapse_whitespace(&element.text().collect::<String>());
+        if !text.is_empty() {
+            out.insert(id.to_owned(), text);
+        }
+    }
+}
+
+/// Resolves the note references inside a paragraph element. References whose
+/// target wasn't collected, or that have no preceding word, are skipped.
+fn paragraph_footnotes(
+    element: ElementRef,
+    footnotes: &HashMap<String, String>,
+) -> Vec<EpubFootnote> {
+    let mut result = Vec::new();
+    let mut preceding_text = String::new();
+    for node in element.descendants() {
+        if let Some(child) = ElementRef::wrap(node)
+            && is_footnote_reference(child)
+        {
+            let target = child.value().attr("href").map(split_anchor);
+            let anchor_word = preceding_text
+                .split_whitespace()
+                .next_back()
+                .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()))
+                .unwrap_or("");
+            if let Some(text) = target.and_then(|t| footnotes.get(&t))
+                && !anchor_word.is_empty()
+            {
+                result.push(EpubFootnote {
+                    anchor_word: anchor_word.to_owned(),
+                    text: text.clone(),
+                });
+            }
+        } else if let Node::Text(text) = node.value()
+            && !node
+                .ancestors()
+                .filter_map(ElementRef::wrap)
+                .any(is_footnote_reference)
+        {
+            preceding_text.push_str(text);
+        }
+    }
+    result
+}
+
 fn split_anchor(href: &str) -> String {
     href.split('#').nth(1).unwrap_or("").to_string()
 }
 
+/// Title of a spine item without a TOC entry: its first heading, or else its
+/// `<title>`, which is often just the file name.
 fn extract_title(document: &Html) -> String {
+    let heading_selector =
+        Selector::parse("body h1, body h2, body h3, body h4, body h5, body h6").unwrap();
+    if let Some(heading) = document
+        .select(&heading_selector)
+        .map(|h| collapse_whitespace(&h.text().collect::<String>()))
+        .find(|h| !h.is_empty())
+    {
+        return heading;
+    }
+
     let title_selector = Selector::parse("title").unwrap();
     if let Some(title_element) = document.select(&title_selector).next() {
         title_element.text().collect::<String>()
@@ -134,6 +555,8 @@ fn text_between_anchors(
     document: &Html,
     anchor1: &str,
     anchor2: Option<&str>,
+    footnotes: &HashMap<String, String>,
+    lenient: bool,
 ) -> anyhow::Result<Vec<EpubParagraph>> {
     let start_element = if anchor1.is_empty() {
         find_body_element(document)
@@ -143,10 +566,11 @@ fn text_between_anchors(
 
     let end_element = anchor2.and_then(|a| find_element_by_id(document, a));
 
-    if let Some(start) = start_element {
-        Ok(text_between(start, end_element))
-    } else {
-        Ok(Vec::new())
+    match start_element {
+        Some(start) => Ok(text_between(start, end_element, footnotes)),
+        None if !lenient => Ok(Vec::new()),
+        None if anchor1.is_empty() => anyhow::bail!("Document has no body"),
+        None => anyhow::bail!("TOC anchor #{anchor1} is missing from the document"),
     }
 }
 
@@ -227,7 +651,11 @@ fn is_inline_element(tag_name: &str) -> bool {
     )
 }
 
-fn text_between(start: ElementRef, end: Option<ElementRef>) -> Vec<EpubParagraph> {
+fn text_between(
+    start: ElementRef,
+    end: Option<ElementRef>,
+    footnotes: &HashMap<String, String>,
+) -> Vec<EpubParagraph> {
     let mut paragraphs = Vec::new();
     let mut current = Some(start);
 
@@ -242,10 +670,15 @@ fn text_between(start: ElementRef, end: Option<ElementRef>) -> Vec<EpubParagraph
         // Check if this is a paragraph-like element
         let has_text = elem.text().any(|t| !t.trim().is_empty());
         if has_text && (elem.children().count() == 0 || all_children_are_inline(elem)) {
-            let text = elem.text().collect::<String>().trim().to_string();
+            let text = paragraph_text(elem).trim().to_string();
             if !text.is_empty() {
                 let html = get_sanitized_html(elem, false).trim().to_string();
-                paragraphs.push(EpubParagraph { text, html });
+                paragraphs.push(EpubParagraph {
+                    text,
+                    html,
+                    footnotes: paragraph_footnotes(elem, footnotes),
+                    heading_level: heading_level(elem.value().name()),
+                });
             }
         }
 
@@ -278,6 +711,31 @@ fn text_between(start: ElementRef, end: Option<ElementRef>) -> Vec<EpubParagraph
     paragraphs
 }
 
+/// Text of a paragraph element without its note reference markers; the
+/// footnotes themselves are kept separately.
+fn paragraph_text(element: ElementRef) -> String {
+    element
+        .descendants()
+        .filter(|node| {
+            !node
+                .ancestors()
+                .filter_map(ElementRef::wrap)
+                .any(is_footnote_reference)
+        })
+        .filter_map(|node| match node.value() {
+            Node::Text(text) => Some(&**text),
+            _ => None,
+        })
+        .collect()
+}
+
+fn heading_level(tag_name: &str) -> Option<u8> {
+    match tag_name.as_bytes() {
+        [b'h' | b'H', level @ b'1'..=b'6'] => Some(level - b'0'),
+        _ => None,
+    }
+}
+
 fn find_next_sibling(element: ElementRef) -> Option<ElementRef> {
     let mut next = element.next_sibling();
     while let Some(node) = next {
@@ -309,7 +767,9 @@ fn get_sanitized_html(element: ElementRef, keep_bounding_tag: bool) -> String {
     for child in element.children() {
         match child.value() {
             Node::Element(_) => {
-                if let Some(child_elem) = ElementRef::wrap(child) {
+                if let Some(child_elem) = ElementRef::wrap(child)
+                    && !is_footnote_reference(child_elem)
+                {
                     html.push_str(&get_sanitized_html(child_elem, true));
                 }
             }
@@ -326,3 +786,222 @@ fn get_sanitized_html(element: ElementRef, keep_bounding_tag: bool) -> String {
 
     html
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHAPTER: &str = r##"<html xmlns:epub="http://www.idpf.org/2007/ops"><body>
+        <p>The old manor<a epub:type="noteref" href="#fn1">1</a> stood empty.</p>
+        <p>A missing note<a epub:type="noteref" href="notes.xhtml#gone">2</a> here.</p>
+        <aside epub:type="footnote" id="fn1"><p>A large   country house.</p></aside>
+    </body></html>"##;
+
+    #[test]
+    fn collects_footnote_definitions_by_id() {
+        let mut footnotes = HashMap::new();
+        collect_footnotes(&Html::parse_document(CHAPTER), &mut footnotes);
+
+        assert_eq!(footnotes.len(), 1);
+        assert_eq!(footnotes["fn1"], "A large country house.");
+    }
+
+    #[test]
+    fn attaches_footnotes_to_preceding_word_and_skips_missing_targets() {
+        let mut footnotes = HashMap::new();
+        collect_footnotes(&Html::parse_document(CHAPTER), &mut footnotes);
+
+        let chapters = parse_chapter(CHAPTER, &[], &footnotes, false).unwrap();
+        let paragraphs = &chapters[0].paragraphs;
+
+        assert_eq!(
+            paragraphs[0].footnotes,
+            vec![EpubFootnote {
+                anchor_word: "manor".to_owned(),
+                text: "A large country house.".to_owned(),
+            }]
+        );
+        assert!(paragraphs[1].footnotes.is_empty());
+    }
+
+    #[test]
+    fn strips_note_reference_markers_from_paragraphs() {
+        let chapters = parse_chapter(CHAPTER, &[], &HashMap::new(), false).unwrap();
+        let paragraphs = &chapters[0].paragraphs;
+
+        assert_eq!(paragraphs[0].text, "The old manor stood empty.");
+        assert_eq!(paragraphs[0].html, "The old manor stood empty.");
+        assert_eq!(paragraphs[1].text, "A missing note here.");
+        assert_eq!(paragraphs[1].html, "A missing note here.");
+    }
+
+    const NAV: &str = r##"<html xmlns:epub="http://www.idpf.org/2007/ops"><body>
+        <nav epub:type="landmarks"><ol><li><a href="text/cover.xhtml">Cover</a></li></ol></nav>
+        <nav epub:type="toc"><ol>
+            <li><a href="text/one.xhtml">Chapter   One</a>
+                <ol>
+                    <li><a href="text/one.xhtml#s1">Scene 1</a></li>
+                    <li><a href="./text/one.xhtml#s2">Scene 2</a></li>
+                </ol>
+            </li>
+            <li><span>Part Two</span>
+                <ol><li><a href="../OEBPS/text/two.xhtml#start">Chapter Two</a>
+                    <ol><li><a href="text/three.xhtml">Interlude</a></li></ol>
+                </li></ol>
+            </li>
+        </ol></nav>
+    </body></html>"##;
+
+    #[test]
+    fn nav_document_titles_chapters_by_spine_reference() {
+        let toc = parse_nav_document(NAV, Path::new("OEBPS"));
+        let mut flat_toc = Vec::new();
+        flatten_toc(&toc, 0, &mut flat_toc);
+        let labels = |href_doc| -> Vec<String> {
+            spine_item_toc(&toc, href_doc)
+                .iter()
+                .map(|t| t.label.clone())
+                .collect()
+        };
+
+        assert_eq!(labels("text/one.xhtml"), vec!["Chapter One"]);
+        assert_eq!(labels("text/two.xhtml"), vec!["Chapter Two"]);
+        // Sections don't split chapters, they only title unlisted spine items.
+        assert!(labels("text/three.xhtml").is_empty());
+        assert_eq!(
+            nested_toc_title(&flat_toc, "text/three.xhtml").as_deref(),
+            Some("Interlude")
+        );
+        assert_eq!(
+            nested_toc_title(&flat_toc, "text/one.xhtml").as_deref(),
+            Some("Chapter One")
+        );
+        assert!(labels("text/cover.xhtml").is_empty());
+
+        let two = r#"<html><head><title>two.xhtml</title></head><body>
+            <p>Before.</p><p id="start">Opening line.</p></body></html>"#;
+        let chapters = parse_chapter(
+            two,
+            &spine_item_toc(&toc, "text/two.xhtml"),
+            &HashMap::new(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(chapters.len(), 1);
+        assert_eq!(chapters[0].title, "Chapter Two");
+        assert_eq!(chapters[0].paragraphs[0].text, "Opening line.");
+
+        let untitled = r#"<html><head><title>part0004.xhtml</title></head><body>
+            <p>Epigraph.</p><h2>  The   Return </h2><p>Text.</p></body></html>"#;
+        let chapters = parse_chapter(untitled, &[], &HashMap::new(), false).unwrap();
+        assert_eq!(chapters[0].title, "The Return");
+    }
+
+    #[test]
+    fn lenient_import_skips_malformed_chapters() {
+        let document = |idref: &str, href_doc: Option<&str>, content: Option<&str>| SpineDocument {
+            idref: idref.to_owned(),
+            href_doc: href_doc.map(str::to_owned),
+            content: content.map(str::to_owned),
+        };
+        let toc = parse_nav_document(
+            r##"<nav epub:type="toc"><ol>
+                <li><a href="one.xhtml">One</a></li>
+                <li><a href="two.xhtml#missing">Two</a></li>
+            </ol></nav>"##,
+            Path::new(""),
+        );
+        let documents = [
+            document("one", Some("one.xhtml"), Some("<body><p>First.</p></body>")),
+            document(
+                "two",
+                Some("two.xhtml"),
+                Some("<body><p>Second.</p></body>"),
+            ),
+            document("binary", Some("three.xhtml"), None),
+            document("dangling", None, None),
+        ];
+
+        let (chapters, skipped) = parse_spine(&documents, &toc, true).unwrap();
+        assert_eq!(skipped, 3);
+        assert_eq!(chapters.len(), 1);
+        assert_eq!(chapters[0].title, "One");
+        assert_eq!(chapters[0].paragraphs[0].text, "First.");
+
+        // A strict import keeps the chapter with the dangling anchor, empty,
+        // and leaves out the unreadable document, as it always has.
+        let (chapters, skipped) = parse_spine(&documents[..3], &toc, false).unwrap();
+        assert_eq!(skipped, 0);
+        let titles: Vec<_> = chapters.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(titles, vec!["One", "Two"]);
+        assert!(chapters[1].paragraphs.is_empty());
+        let err = parse_spine(&documents[3..], &toc, false).unwrap_err();
+        assert!(err.to_string().contains("has no resource"), "{err}");
+    }
+
+    fn chapter(title: &str, html: &str) -> EpubChapter {
+        parse_chapter(html, &[], &HashMap::new(), false)
+            .unwrap()
+            .into_iter()
+            .map(|c| EpubChapter {
+                title: title.to_owned(),
+                ..c
+            })
+            .next()
+            .unwrap()
+    }
+
+    fn shape(book: &EpubBook) -> Vec<(&str, usize)> {
+        book.chapters
+            .iter()
+            .map(|c| (c.title.as_str(), c.paragraphs.len()))
+            .collect()
+    }
+
+    #[test]
+    fn merges_consecutive_tiny_spine_items() {
+        let mut book = EpubBook {
+            title: "Tiny".to_owned(),
+            chapters: vec![
+                chapter("", "<body><p>One.</p></body>"),
+                chapter("Part 1", "<body><p>Two.</p></body>"),
+                chapter("", "<body><p>Three.</p><p>Four.</p></body>"),
+                chapter("Part 2", "<body><p>A much longer paragraph.</p></body>"),
+                chapter("", "<body><p>Five.</p></body>"),
+            ],
+        };
+        let unchanged = shape(&book).len();
+        book.regroup_chapters(ChapterGranularity::SpineItems);
+        assert_eq!(shape(&book).len(), unchanged);
+
+        book.regroup_chapters(ChapterGranularity::MergeSmall { min_chars: 12 });
+        assert_eq!(shape(&book), vec![("Part 1", 4), ("Part 2", 1), ("", 1)]);
+    }
+
+    #[test]
+    fn splits_on_chapter_headings_across_spine_items() {
+        let mut book = EpubBook {
+            title: "Headed".to_owned(),
+            chapters: vec![
+                chapter("Cover", "<body><p>Front.</p><h1>One</h1><p>a</p></body>"),
+                chapter(
+                    "",
+                    "<body><p>b</p><h3>Scene</h3><h2>Two</h2><p>c</p></body>",
+                ),
+            ],
+        };
+        let text = |book: &EpubBook| -> Vec<String> {
+            book.chapters
+                .iter()
+                .flat_map(|c| c.paragraphs.iter().map(|p| p.text.clone()))
+                .collect()
+        };
+        let spine_text = text(&book);
+
+        book.regroup_chapters(ChapterGranularity::Headings);
+        assert_eq!(shape(&book), vec![("Cover", 1), ("One", 4), ("Two", 2)]);
+        assert_eq!(book.chapters[1].paragraphs[0].heading_level, Some(1));
+        assert_eq!(book.chapters[1].paragraphs[3].heading_level, Some(3));
+        assert_eq!(text(&book), spine_text);
+    }
+}
@@ -1,35 +1,61 @@
 use std::{
+    borrow::Cow,
+    collections::BTreeMap,
     error::Error,
     fmt::Display,
     path::{Path, PathBuf},
     str::FromStr,
-    sync::Arc,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::SystemTime,
 };
 
+use anyhow::Context;
 use isolang::Language;
 use itertools::Itertools;
-use log::{info, trace};
+use log::{info, trace, warn};
+use regex_lite::Regex;
+use serde::Serialize;
 use tokio::io::AsyncReadExt;
 use uuid::Uuid;
 
 use crate::{
     book::{
-        book_metadata::BookMetadata, translation_import, translation_metadata::TranslationMetadata,
+        book::ParagraphStorage,
+        book_metadata::BookMetadata,
+        serialization::HashAlgorithm,
+        strings_dictionary::{DICTIONARIES_DIR, StringsDictionary},
+        translation::MergeStrategy,
+        translation_import,
+        translation_metadata::TranslationMetadata,
     },
     cache::WeakLruCache,
     card::{Card, extract_card_updates},
     epub_importer::EpubBook,
     library::{
         file_watcher::LibraryFileChange,
-        library_book::{LibraryBook, load_book_user_state},
+        known_words::KnownWordsStore,
+        library_book::{
+            BookUserState, LibraryBook, load_book_user_state, translation_file_variant,
+        },
         library_card::LibraryCardStore,
     },
     tla_trace::mutex::TracedMutex,
 };
 
 pub mod file_watcher;
+pub mod known_words;
 pub mod library_book;
 pub mod library_card;
+pub mod library_dictionary;
+pub mod load_report;
+
+/// Matches common chapter heading lines: "Chapter 1", "CHAPTER XII: The End",
+/// "Part 2", "Capítulo 3", "Глава 4". `regex-lite` folds case for ASCII only, hence the spelled-out Cyrillic
+/// variants.
+pub const DEFAULT_CHAPTER_HEADING_PATTERN: &str = r"^(?:(?i:chapter|part|book|chapitre|kapitel|cap[ií]tulo)|CAPÍTULO|[Гг]лава|ГЛАВА|[Чч]асть|ЧАСТЬ)\s+(?:[0-9]+|(?i:[ivxlcdm]+))\b";
 
 /// Default number of books to pin in the warm LRU. Books accessed beyond this
 /// count are still reachable via the weak index while any holder keeps them
@@ -39,6 +65,14 @@ pub const DEFAULT_BOOKS_CACHE_CAPACITY: usize = 8;
 #[derive(Debug)]
 pub enum LibraryError {
     DuplicateTitle(String),
+    RelocateIntoSelf(PathBuf),
+    RelocateTargetNotEmpty(PathBuf),
+    /// Two translations of different language pairs (`src_tgt[-variant]`)
+    /// were about to be merged.
+    TranslationLanguageMismatch {
+        expected: String,
+        found: String,
+    },
 }
 
 impl Display for LibraryError {
@@ -47,50 +81,130 @@ impl Display for LibraryError {
             LibraryError::DuplicateTitle(title) => {
                 write!(f, "Failed to create book: duplicate title ({title})")
             }
+            LibraryError::RelocateIntoSelf(path) => {
+                write!(f, "Cannot move library into itself ({})", path.display())
+            }
+            LibraryError::RelocateTargetNotEmpty(path) => {
+                write!(f, "Cannot move library: {} is not empty", path.display())
+            }
+            LibraryError::TranslationLanguageMismatch { expected, found } => {
+                write!(
+                    f,
+                    "Cannot merge a {found} translation into a {expected} translation"
+                )
+            }
         }
     }
 }
 
 impl Error for LibraryError {}
 
+/// Outcome of [`Library::train_strings_dictionary`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StringsDictionaryReport {
+    pub dictionary_id: u64,
+    /// Books recompressed with the new dictionary.
+    pub books: usize,
+    /// Total size of their `book.dat` files before and after.
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+#[derive(Serialize)]
 pub struct LibraryTranslationMetadata {
     pub id: Uuid,
+    #[serde(rename = "sourceLanguage")]
     pub source_langugage: String,
+    #[serde(rename = "targetLanguage")]
     pub target_language: String,
+    #[serde(rename = "targetVariant")]
+    pub target_variant: Option<String>,
+    #[serde(rename = "translatedParagraphsCount")]
     pub translated_paragraphs_count: usize,
+    #[serde(rename = "mainPath")]
     pub main_path: PathBuf,
+    #[serde(rename = "conflictingPaths")]
     pub conflicting_paths: Vec<PathBuf>,
+    /// Modification time of the main `.dat` file.
+    #[serde(rename = "translationLastModified")]
+    pub last_modified: Option<SystemTime>,
 }
 
+/// What a plain-text import produced, so callers can report it without
+/// loading and locking the new book again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CreatedBook {
+    pub id: Uuid,
+    /// ISO 639-3 code of the declared source language.
+    pub language: String,
+    #[serde(rename = "chaptersCount")]
+    pub chapters_count: usize,
+    #[serde(rename = "paragraphsCount")]
+    pub paragraphs_count: usize,
+}
+
+#[derive(Serialize)]
 pub struct LibraryBookMetadata {
     pub id: Uuid,
     pub title: String,
+    #[serde(rename = "mainPath")]
     pub main_path: PathBuf,
+    #[serde(rename = "conflictingPaths")]
     pub conflicting_paths: Vec<PathBuf>,
+    #[serde(rename = "chaptersCount")]
     pub chapters_count: usize,
+    #[serde(rename = "paragraphsCount")]
     pub paragraphs_count: usize,
+    #[serde(rename = "translationsMetadata")]
     pub translations_metadata: Vec<LibraryTranslationMetadata>,
+    #[serde(rename = "folderPath")]
     pub folder_path: Vec<String>,
+    /// See [`BookUserState::translated_titles`].
+    #[serde(rename = "translatedTitles")]
+    pub translated_titles: BTreeMap<String, String>,
     /// `chapter_summaries.dat` for this book, if present. `None` for
     /// legacy books that predate the sidecar; the summary generation queue
     /// creates one on first enqueue.
+    #[serde(rename = "chapterSummariesMainPath")]
     pub chapter_summaries_main_path: Option<PathBuf>,
     /// Sibling `chapter_summaries~*.dat` files left behind by an
     /// interrupted save. Merged into the main file at load time.
+    #[serde(rename = "chapterSummariesConflictingPaths")]
     pub chapter_summaries_conflicting_paths: Vec<PathBuf>,
+    /// The directory has no `book.dat` (e.g. a sync that delivered the
+    /// translations first). Title and counts are placeholders; see
+    /// [`Library::repair_book`].
+    #[serde(rename = "incomplete")]
+    pub incomplete: bool,
 }
 
 impl LibraryBookMetadata {
     pub async fn load(path: &Path) -> anyhow::Result<Self> {
         let book_dat = path.join("book.dat");
 
-        let book_metadata = {
+        let book_metadata = if tokio::fs::try_exists(&book_dat).await? {
             let mut file = tokio::fs::File::open(&book_dat).await?;
             let mut buffer = vec![0u8; 65536];
             let n = file.read(&mut buffer).await?;
             buffer.truncate(n);
             let mut cursor = std::io::Cursor::new(buffer);
-            BookMetadata::read_metadata(&mut cursor)?
+            Some(BookMetadata::read_metadata(&mut cursor)?)
+        } else {
+            None
+        };
+        // Without book.dat only a directory named after a book id counts as
+        // a (partially synced) book; anything else isn't ours.
+        let id = match &book_metadata {
+            Some(metadata) => metadata.id,
+            None => {
+                let id = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .and_then(|n| Uuid::parse_str(n).ok())
+                    .ok_or_else(|| anyhow::anyhow!("No book.dat in {path:?}"))?;
+                warn!("Book directory {path:?} has no book.dat, listing it as incomplete");
+                id
+            }
         };
 
         let conflicting_paths = {
@@ -122,7 +236,7 @@ impl LibraryBookMetadata {
 
                 match metadata {
                     Ok(metadata) => {
-                        if metadata.id == book_metadata.id {
+                        if metadata.id == id {
                             result.push(path);
                         } else {
                             println!(
@@ -159,42 +273,48 @@ impl LibraryBookMetadata {
                     let mut cursor = std::io::Cursor::new(buffer);
                     TranslationMetadata::read_metadata(&mut cursor)?
                 };
-                all_translations.push((path, metadata));
+                let last_modified = entry.metadata().await?.modified().ok();
+                all_translations.push((path, metadata, last_modified));
             }
         }
 
         let grouped_translations = all_translations
             .into_iter()
-            .chunk_by(|(_, translation)| translation.id);
+            .chunk_by(|(_, translation, _)| translation.id);
         let grouped_translations = grouped_translations
             .into_iter()
-            .map(|(id, chunk)| (id, chunk.sorted_by_key(|(p, _)| p.as_os_str().len())));
+            .map(|(id, chunk)| (id, chunk.sorted_by_key(|(p, _, _)| p.as_os_str().len())));
 
         let mut translations_metadata = Vec::new();
 
         for (_, mut translations) in grouped_translations {
-            let (main_path, main_translation) = translations.next().unwrap(); // There is always at least one translation in chunk
+            let (main_path, main_translation, last_modified) = translations.next().unwrap(); // There is always at least one translation in chunk
 
-            let conflicting_iterations = translations.map(|(p, _)| p).collect();
+            let conflicting_iterations = translations.map(|(p, _, _)| p).collect();
 
             translations_metadata.push(LibraryTranslationMetadata {
                 id: main_translation.id,
                 source_langugage: main_translation.source_language,
                 target_language: main_translation.target_language,
+                target_variant: main_path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .and_then(translation_file_variant),
                 translated_paragraphs_count: main_translation.translated_paragraphs_count,
                 main_path,
                 conflicting_paths: conflicting_iterations,
+                last_modified,
             })
         }
 
-        let folder_path = match load_book_user_state(path).await {
-            Ok(state) => state.folder_path,
+        let user_state = match load_book_user_state(path).await {
+            Ok(state) => state,
             Err(err) => {
                 println!(
                     "Failed to load state for {:?}, continuing with empty folder path: {}",
                     path, err
                 );
-                Vec::new()
+                BookUserState::default()
             }
         };
 
@@ -220,17 +340,28 @@ impl LibraryBookMetadata {
         }
 
         info!("Loaded metadata for {path:?}");
+        let incomplete = book_metadata.is_none();
+        let (title, chapters_count, paragraphs_count) = match book_metadata {
+            Some(metadata) => (
+                metadata.title,
+                metadata.chapters_count,
+                metadata.paragraphs_count,
+            ),
+            None => (id.to_string(), 0, 0),
+        };
         Ok(LibraryBookMetadata {
-            id: book_metadata.id,
-            title: book_metadata.title,
+            id,
+            title,
             main_path: book_dat,
             conflicting_paths,
-            chapters_count: book_metadata.chapters_count,
-            paragraphs_count: book_metadata.paragraphs_count,
+            chapters_count,
+            paragraphs_count,
             translations_metadata,
-            folder_path,
+            folder_path: user_state.folder_path,
+            translated_titles: user_state.translated_titles,
             chapter_summaries_main_path,
             chapter_summaries_conflicting_paths,
+            incomplete,
         })
     }
 }
@@ -239,6 +370,10 @@ pub struct Library {
     library_root: PathBuf,
     pub(crate) books_cache: WeakLruCache<Uuid, TracedMutex<LibraryBook>>,
     card_store: Arc<LibraryCardStore>,
+    known_words: Arc<KnownWordsStore>,
+    auto_merge_conflicts: AtomicBool,
+    merge_strategy: Mutex<MergeStrategy>,
+    hash_algorithm: Mutex<HashAlgorithm>,
 }
 
 impl Library {
@@ -250,23 +385,74 @@ impl Library {
         library_root: PathBuf,
         cache_capacity: usize,
     ) -> anyhow::Result<Self> {
-        if !tokio::fs::try_exists(&library_root).await? {
-            tokio::fs::create_dir_all(&library_root).await?;
+        // Only the root itself: a missing parent usually means a mistyped
+        // path, which is better reported than silently created.
+        match tokio::fs::create_dir(&library_root).await {
+            Err(err) if err.kind() != std::io::ErrorKind::AlreadyExists => {
+                return Err(err).with_context(|| {
+                    format!("Failed to create library root {}", library_root.display())
+                });
+            }
+            _ => {}
         }
 
         let card_store = Arc::new(LibraryCardStore::new(&library_root));
+        let known_words = Arc::new(KnownWordsStore::new(&library_root));
 
         Ok(Library {
             library_root,
             books_cache: WeakLruCache::new(cache_capacity),
             card_store,
+            known_words,
+            auto_merge_conflicts: AtomicBool::new(true),
+            merge_strategy: Mutex::new(MergeStrategy::default()),
+            hash_algorithm: Mutex::new(HashAlgorithm::default()),
         })
     }
 
+    /// Whether books merge their conflict files (sync conflicts, interrupted
+    /// saves) into the main files when loaded. On by default; when off,
+    /// books load only their main files and keep the conflicts on disk until
+    /// [`Library::merge_conflicts`]. Applies to books loaded afterwards.
+    pub fn set_auto_merge_conflicts(&self, enabled: bool) {
+        self.auto_merge_conflicts.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn auto_merge_conflicts(&self) -> bool {
+        self.auto_merge_conflicts.load(Ordering::Relaxed)
+    }
+
+    /// How books reconcile diverged translation histories, both when merging
+    /// conflict files at load time and when a save finds newer content on
+    /// disk. Applies to books loaded afterwards; a loaded book can still be
+    /// changed with [`LibraryBook::set_merge_strategy`].
+    pub fn set_merge_strategy(&self, strategy: MergeStrategy) {
+        *self.merge_strategy.lock().unwrap() = strategy;
+    }
+
+    pub fn merge_strategy(&self) -> MergeStrategy {
+        *self.merge_strategy.lock().unwrap()
+    }
+
+    /// Checksum book and translation files are written with. Applies to
+    /// books created or loaded afterwards; existing files switch over the
+    /// next time they are saved.
+    pub fn set_hash_algorithm(&self, algorithm: HashAlgorithm) {
+        *self.hash_algorithm.lock().unwrap() = algorithm;
+    }
+
+    pub fn hash_algorithm(&self) -> HashAlgorithm {
+        *self.hash_algorithm.lock().unwrap()
+    }
+
     pub fn card_store(&self) -> &Arc<LibraryCardStore> {
         &self.card_store
     }
 
+    pub fn known_words(&self) -> &Arc<KnownWordsStore> {
+        &self.known_words
+    }
+
     pub async fn apply_paragraph_to_cards(
         &self,
         book_id: Uuid,
@@ -352,7 +538,7 @@ impl Library {
 
         while let Some(entry) = library_root_content.next_entry().await? {
             let path = entry.path();
-            if !path.is_dir() {
+            if !path.is_dir() || path.file_name().is_some_and(|n| n == DICTIONARIES_DIR) {
                 continue;
             }
 
@@ -368,6 +554,52 @@ impl Library {
         Ok(books)
     }
 
+    /// Number of book directories (named after a book id, or holding a
+    /// `book.dat`), without reading any of them. Unlike
+    /// [`Library::list_books`] this also counts books that fail to load.
+    pub async fn books_count(&self) -> anyhow::Result<usize> {
+        let mut library_root_content = tokio::fs::read_dir(&self.library_root).await?;
+
+        let mut count = 0;
+        while let Some(entry) = library_root_content.next_entry().await? {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let named_after_book = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| Uuid::parse_str(n).is_ok());
+            if named_after_book || tokio::fs::try_exists(path.join("book.dat")).await? {
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Whether the library has a directory for book `id`, loaded or not.
+    /// Incomplete books (see [`LibraryBookMetadata::incomplete`]) count.
+    pub async fn book_exists(&self, id: &Uuid) -> anyhow::Result<bool> {
+        if self.books_cache.get(id).await.is_some() {
+            return Ok(true);
+        }
+        Ok(tokio::fs::metadata(self.library_root.join(id.to_string()))
+            .await
+            .is_ok_and(|m| m.is_dir()))
+    }
+
+    /// Books whose `folder_path` starts with `prefix`, including those in
+    /// nested subfolders. An empty prefix therefore matches every book.
+    pub async fn books_in_folder(
+        &self,
+        prefix: &[String],
+    ) -> anyhow::Result<Vec<LibraryBookMetadata>> {
+        let mut books = self.list_books().await?;
+        books.retain(|b| b.folder_path.starts_with(prefix));
+        Ok(books)
+    }
+
     pub async fn get_book(&self, uuid: &Uuid) -> anyhow::Result<Arc<TracedMutex<LibraryBook>>> {
         if let Some(book) = self.books_cache.get(uuid).await {
             return Ok(book);
@@ -375,26 +607,171 @@ impl Library {
 
         let path = self.library_root.join(uuid.to_string());
         let metadata = LibraryBookMetadata::load(&path).await?;
-        let book = Arc::new(TracedMutex::new(
-            LibraryBook::load_from_metadata(metadata).await?,
-        ));
+        let mut book = LibraryBook::load_from_metadata_with_strategy(
+            metadata,
+            self.auto_merge_conflicts(),
+            self.merge_strategy(),
+        )
+        .await?;
+        book.set_hash_algorithm(self.hash_algorithm());
+        let book = Arc::new(TracedMutex::new(book));
 
         Ok(self.books_cache.insert(*uuid, book).await)
     }
 
+    /// Directory holding `uuid`'s files, or `None` if there is no such book
+    /// on disk.
+    pub fn book_directory(&self, uuid: &Uuid) -> Option<PathBuf> {
+        let path = self.library_root.join(uuid.to_string());
+        path.is_dir().then_some(path)
+    }
+
+    /// Like [`Library::get_book`], but a cached book whose files changed on
+    /// disk since it was loaded (e.g. a sync that landed before the file
+    /// watcher reported it) is reloaded first.
+    pub async fn get_book_fresh(
+        &self,
+        uuid: &Uuid,
+    ) -> anyhow::Result<Arc<TracedMutex<LibraryBook>>> {
+        let book = self.get_book(uuid).await?;
+        book.lock().await.refresh_from_disk().await?;
+        Ok(book)
+    }
+
     pub async fn create_book_plain(
         &self,
         title: &str,
         text: &str,
         language: &Language,
+    ) -> anyhow::Result<CreatedBook> {
+        self.create_book_plain_inner(title, text, language, None, false)
+            .await
+    }
+
+    /// Like [`Library::create_book_plain`], but every line matching
+    /// `heading_pattern` starts a new chapter titled with that line. Lines
+    /// before the first heading go into an untitled intro chapter. See
+    /// [`DEFAULT_CHAPTER_HEADING_PATTERN`] for a sensible default.
+    pub async fn create_book_plain_with_headings(
+        &self,
+        title: &str,
+        text: &str,
+        language: &Language,
+        heading_pattern: &str,
+    ) -> anyhow::Result<CreatedBook> {
+        self.create_book_plain_with_options(title, text, language, Some(heading_pattern), false)
+            .await
+    }
+
+    /// Plain-text import with every option spelled out. With
+    /// `preserve_line_breaks`, paragraphs are separated by blank lines
+    /// instead of single line breaks, so a stanza or code sample stays one
+    /// paragraph: its `original_text` keeps the lines joined with `\n` and
+    /// its `original_html` joins them with `<br>`. Headings then only match
+    /// single-line paragraphs.
+    pub async fn create_book_plain_with_options(
+        &self,
+        title: &str,
+        text: &str,
+        language: &Language,
+        heading_pattern: Option<&str>,
+        preserve_line_breaks: bool,
+    ) -> anyhow::Result<CreatedBook> {
+        let heading = heading_pattern.map(Regex::new).transpose()?;
+        self.create_book_plain_inner(
+            title,
+            text,
+            language,
+            heading.as_ref(),
+            preserve_line_breaks,
+        )
+        .await
+    }
+
+    async fn create_book_plain_inner(
+        &self,
+        title: &str,
+        text: &str,
+        language: &Language,
+        heading: Option<&Regex>,
+        preserve_line_breaks: bool,
+    ) -> anyhow::Result<CreatedBook> {
+        let book = self.create_book(title, language).await?;
+        let mut book = book.lock().await;
+        let mut chapter_index = None;
+
+        let paragraphs: Vec<(String, Option<String>)> = if preserve_line_breaks {
+            split_stanzas(text)
+                .into_iter()
+                .map(|lines| {
+                    let html = (lines.len() > 1).then(|| {
+                        lines
+                            .iter()
+                            .map(|l| escape_html(l))
+                            .collect::<Vec<_>>()
+                            .join("<br>")
+                    });
+                    (lines.join("\n"), html)
+                })
+                .collect()
+        } else {
+            split_paragraphs(text)
+                .map(|p| (p.to_owned(), None))
+                .collect()
+        };
+
+        for (paragraph, html) in &paragraphs {
+            if html.is_none() && heading.is_some_and(|h| h.is_match(paragraph)) {
+                chapter_index = Some(book.book.push_chapter(Some(paragraph)));
+                continue;
+            }
+            let index = match chapter_index {
+                Some(index) => index,
+                None => *chapter_index.insert(book.book.push_chapter(None)),
+            };
+            book.book.push_paragraph(index, paragraph, html.as_deref());
+        }
+        if chapter_index.is_none() {
+            book.book.push_chapter(None);
+        }
+
+        book.save().await?;
+
+        Ok(CreatedBook {
+            id: book.book.id,
+            language: book.book.language.clone(),
+            chapters_count: book.book.chapter_count(),
+            paragraphs_count: book.book.paragraphs_count(),
+        })
+    }
+
+    /// Creates a book with one chapter per `(fallback_title, text)` pair, in
+    /// order. A chapter whose first line matches
+    /// [`DEFAULT_CHAPTER_HEADING_PATTERN`] is titled with that line; otherwise
+    /// it takes `fallback_title` (e.g. the source file's name).
+    pub async fn create_book_from_chapters(
+        &self,
+        title: &str,
+        chapters: &[(&str, &str)],
+        language: &Language,
     ) -> anyhow::Result<Uuid> {
+        let heading = Regex::new(DEFAULT_CHAPTER_HEADING_PATTERN)?;
         let book = self.create_book(title, language).await?;
         let mut book = book.lock().await;
-        let chapter_index = book.book.push_chapter(None);
-        let paragraphs = split_paragraphs(text);
 
-        for paragraph in paragraphs {
-            book.book.push_paragraph(chapter_index, paragraph, None);
+        for (fallback_title, text) in chapters {
+            let mut paragraphs = split_paragraphs(text).peekable();
+            let chapter_title = match paragraphs.peek() {
+                Some(first) if heading.is_match(first) => paragraphs.next(),
+                _ => Some(*fallback_title),
+            };
+            let index = book.book.push_chapter(chapter_title);
+            for paragraph in paragraphs {
+                book.book.push_paragraph(index, paragraph, None);
+            }
+        }
+        if chapters.is_empty() {
+            book.book.push_chapter(None);
         }
 
         book.save().await?;
@@ -402,24 +779,57 @@ impl Library {
         Ok(book.book.id)
     }
 
+    /// Imports `epub`, keeping each paragraph's text and HTML as `storage`
+    /// asks.
     pub async fn create_book_epub(
         &self,
         epub: &EpubBook,
         language: &Language,
+        storage: ParagraphStorage,
+    ) -> anyhow::Result<Uuid> {
+        self.create_book_epub_with_progress(epub, language, storage, |_, _| {})
+            .await
+    }
+
+    /// Like [`Library::create_book_epub`], calling `on_chapter(done, total)`
+    /// after each chapter is added.
+    pub async fn create_book_epub_with_progress(
+        &self,
+        epub: &EpubBook,
+        language: &Language,
+        storage: ParagraphStorage,
+        mut on_chapter: impl FnMut(usize, usize) + Send,
     ) -> anyhow::Result<Uuid> {
         let book = self.create_book(&epub.title, language).await?;
         let mut book = book.lock().await;
 
-        for ch in &epub.chapters {
+        let mut footnotes = BTreeMap::new();
+        for (done, ch) in epub.chapters.iter().enumerate() {
             let ch_idx = book.book.push_chapter(Some(&ch.title));
             for p in &ch.paragraphs {
-                book.book.push_paragraph(ch_idx, &p.text, Some(&p.html));
+                book.book
+                    .push_paragraph_with_storage(ch_idx, &p.text, Some(&p.html), storage);
+                if !p.footnotes.is_empty() {
+                    footnotes.insert(book.book.paragraphs_count() - 1, p.footnotes.clone());
+                }
             }
+            on_chapter(done + 1, epub.chapters.len());
         }
 
-        book.save().await?;
+        // Nothing touches disk until the book is fully built, so a failure
+        // here is the only way to leave a half-written directory behind.
+        let saved = match book.save().await {
+            Ok(()) => book.save_footnotes(&footnotes).await,
+            Err(err) => Err(err),
+        };
+        let book_id = book.book.id;
+        drop(book);
+        if let Err(err) = saved {
+            self.discard_partial_book(&book_id).await;
+            return Err(err);
+        }
 
-        Ok(book.book.id)
+        Ok(book_id)
     }
 
     pub async fn backfill_cards_from_translations(&self) -> anyhow::Result<()> {
@@ -458,7 +868,23 @@ impl Library {
 
                 let collected: Vec<(usize, translation_import::ParagraphTranslation)> = {
                     let mut book = book_arc.lock().await;
-                    let translation_arc = book.get_or_create_translation(&target_language).await;
+                    let translation_arc = match book
+                        .get_or_create_translation_variant(
+                            &target_language,
+                            translation_meta.target_variant.as_deref(),
+                        )
+                        .await
+                    {
+                        Ok(arc) => arc,
+                        Err(err) => {
+                            log::warn!(
+                                "Backfill: skipping translation {} of book {}: {err}",
+                                translation_meta.id,
+                                book_meta.id
+                            );
+                            continue;
+                        }
+                    };
                     let translation = translation_arc.lock().await;
                     let mut out = Vec::new();
                     for chapter in book.book.chapter_views() {
@@ -494,6 +920,108 @@ impl Library {
         Ok(())
     }
 
+    /// Rewrites every v1 translation file in the library in the current
+    /// format, so fields only v2 can store have somewhere to go. Content is
+    /// preserved. Returns the number of files upgraded.
+    pub async fn upgrade_translation_formats(&self) -> anyhow::Result<usize> {
+        let mut upgraded = 0;
+        for book_meta in self.list_books().await? {
+            let book = match self.get_book(&book_meta.id).await {
+                Ok(book) => book,
+                Err(err) => {
+                    log::warn!("Upgrade: failed to load book {}: {err}", book_meta.id);
+                    continue;
+                }
+            };
+            let count = book.lock().await.upgrade_translation_formats().await?;
+            if count > 0 {
+                info!(
+                    "Upgraded {count} translation file(s) of book {}",
+                    book_meta.id
+                );
+            }
+            upgraded += count;
+        }
+        Ok(upgraded)
+    }
+
+    /// Garbage-collects the strings blobs of every translation in the
+    /// library, see [`Translation::compact_strings`]. Returns the number of
+    /// bytes reclaimed.
+    ///
+    /// [`Translation::compact_strings`]: crate::book::translation::Translation::compact_strings
+    pub async fn compact_strings(&self) -> anyhow::Result<usize> {
+        let mut reclaimed = 0;
+        for book_meta in self.list_books().await? {
+            let book = match self.get_book(&book_meta.id).await {
+                Ok(book) => book,
+                Err(err) => {
+                    log::warn!("Compact: failed to load book {}: {err}", book_meta.id);
+                    continue;
+                }
+            };
+            let bytes = book.lock().await.compact_strings().await?;
+            if bytes > 0 {
+                info!("Reclaimed {bytes} string byte(s) in book {}", book_meta.id);
+            }
+            reclaimed += bytes;
+        }
+        Ok(reclaimed)
+    }
+
+    /// Trains a shared zstd dictionary on the strings of up to
+    /// `sample_books` books, stores it under [`DICTIONARIES_DIR`], makes it
+    /// the one new books are written with and recompresses every book with
+    /// it. Other devices can only read those books once the dictionary file
+    /// has synced to them too; until then loading them fails.
+    pub async fn train_strings_dictionary(
+        &self,
+        sample_books: usize,
+        max_size: usize,
+    ) -> anyhow::Result<StringsDictionaryReport> {
+        let mut books = Vec::new();
+        for book_meta in self.list_books().await? {
+            match self.get_book(&book_meta.id).await {
+                Ok(book) => books.push(book),
+                Err(err) => log::warn!("Dictionary: failed to load book {}: {err}", book_meta.id),
+            }
+        }
+
+        let mut samples = Vec::new();
+        for book in books.iter().take(sample_books) {
+            samples.push(book.lock().await.book.strings_blob().to_vec());
+        }
+        let dictionaries = self.library_root.join(DICTIONARIES_DIR);
+        let dictionary = tokio::task::spawn_blocking(move || -> std::io::Result<_> {
+            let dictionary = StringsDictionary::train(&samples, max_size)?;
+            dictionary.save(&dictionaries)?;
+            dictionary.make_current(&dictionaries)?;
+            Ok(Arc::new(dictionary))
+        })
+        .await??;
+
+        let mut report = StringsDictionaryReport {
+            dictionary_id: dictionary.id(),
+            books: 0,
+            bytes_before: 0,
+            bytes_after: 0,
+        };
+        for book in books {
+            let mut book = book.lock().await;
+            let book_dat = book.path().join("book.dat");
+            report.bytes_before += tokio::fs::metadata(&book_dat).await?.len();
+            book.book.set_strings_dictionary(Some(dictionary.clone()));
+            book.save().await?;
+            report.bytes_after += tokio::fs::metadata(&book_dat).await?.len();
+            report.books += 1;
+        }
+        info!(
+            "Recompressed {} book(s) with strings dictionary {:016x}: {} -> {} bytes",
+            report.books, report.dictionary_id, report.bytes_before, report.bytes_after
+        );
+        Ok(report)
+    }
+
     pub async fn save_all(&self) {
         let books = self.books_cache.live_values().await;
         for book_arc in books {
@@ -506,6 +1034,46 @@ impl Library {
         }
     }
 
+    /// Moves everything under the library root (book directories with their
+    /// `state.json` and sync-conflict siblings, card decks) into `new_root`
+    /// and opens a fresh `Library` there. Loaded books are saved first. The
+    /// old handle must be dropped afterwards: its cached books still point at
+    /// the old location.
+    pub async fn move_library(&self, new_root: PathBuf) -> anyhow::Result<Library> {
+        self.save_all().await;
+
+        // Validate before creating anything so a rejected target leaves no
+        // stray directory behind.
+        let old_root = tokio::fs::canonicalize(&self.library_root).await?;
+        let canonical_new_root = canonicalize_uncreated(new_root.clone()).await?;
+        if canonical_new_root == old_root {
+            return Library::open(new_root).await;
+        }
+        if canonical_new_root.starts_with(&old_root) {
+            Err(LibraryError::RelocateIntoSelf(new_root.clone()))?
+        }
+        if tokio::fs::try_exists(&new_root).await?
+            && tokio::fs::read_dir(&new_root)
+                .await?
+                .next_entry()
+                .await?
+                .is_some()
+        {
+            Err(LibraryError::RelocateTargetNotEmpty(new_root.clone()))?
+        }
+
+        tokio::fs::create_dir_all(&new_root).await?;
+
+        let mut entries = tokio::fs::read_dir(&self.library_root).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let target = new_root.join(entry.file_name());
+            info!("Moving {:?} to {:?}", entry.path(), target);
+            move_path(entry.path(), target).await?;
+        }
+
+        Library::open(new_root).await
+    }
+
     pub async fn handle_file_change_event(
         &self,
         event: &LibraryFileChange,
@@ -563,6 +1131,97 @@ fn split_paragraphs(text: &str) -> impl Iterator<Item = &str> {
     text.lines().map(str::trim).filter(|p| !p.is_empty())
 }
 
+/// Groups of trimmed, non-empty lines separated by one or more blank lines.
+fn split_stanzas(text: &str) -> Vec<Vec<&str>> {
+    let mut stanzas = vec![];
+    let mut current = vec![];
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() {
+            if !current.is_empty() {
+                stanzas.push(std::mem::take(&mut current));
+            }
+        } else {
+            current.push(line);
+        }
+    }
+    if !current.is_empty() {
+        stanzas.push(current);
+    }
+    stanzas
+}
+
+fn escape_html(text: &str) -> Cow<'_, str> {
+    if !text.contains(['&', '<', '>', '"']) {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;"),
+    )
+}
+
+/// Canonical form of `path` even if it does not exist yet: the nearest
+/// existing ancestor is canonicalized and the missing components appended.
+async fn canonicalize_uncreated(path: PathBuf) -> std::io::Result<PathBuf> {
+    let mut existing = path.as_path();
+    let mut missing = Vec::new();
+    loop {
+        match tokio::fs::canonicalize(existing).await {
+            Ok(canonical) => {
+                return Ok(missing
+                    .iter()
+                    .rev()
+                    .fold(canonical, |acc, part| acc.join(part)));
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                let (Some(parent), Some(name)) = (existing.parent(), existing.file_name()) else {
+                    return Err(err);
+                };
+                missing.push(name.to_os_string());
+                existing = if parent.as_os_str().is_empty() {
+                    Path::new(".")
+                } else {
+                    parent
+                };
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// `rename` when source and target share a filesystem, otherwise a recursive
+/// copy followed by removal of the source.
+async fn move_path(from: PathBuf, to: PathBuf) -> anyhow::Result<()> {
+    if tokio::fs::rename(&from, &to).await.is_ok() {
+        return Ok(());
+    }
+    tokio::task::spawn_blocking(move || -> anyhow::Result<()> {
+        copy_recursive(&from, &to)?;
+        if from.is_dir() {
+            std::fs::remove_dir_all(&from)?;
+        } else {
+            std::fs::remove_file(&from)?;
+        }
+        Ok(())
+    })
+    .await?
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> std::io::Result<()> {
+    if !from.is_dir() {
+        std::fs::copy(from, to)?;
+        return Ok(());
+    }
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod library_tests {
     use super::*;
@@ -602,6 +1261,7 @@ mod library_tests {
         translation_import::ParagraphTranslation {
             timestamp: 0,
             total_tokens: None,
+            token_usage: None,
             sentences: vec![translation_import::Sentence {
                 full_translation: full_translation.into(),
                 words,
@@ -638,6 +1298,17 @@ mod library_tests {
         assert!(library_path.is_dir());
     }
 
+    #[tokio::test]
+    async fn library_open_fails_when_parent_is_missing() {
+        let temp_dir = TempDir::new("flts_test");
+        let library_path = temp_dir.path.join("missing").join("test");
+
+        let err = Library::open(library_path.clone()).await.err().unwrap();
+
+        assert!(err.to_string().contains("Failed to create library root"));
+        assert!(!library_path.exists());
+    }
+
     #[tokio::test]
     async fn list_books_empty_library() {
         let temp_dir = TempDir::new("flts_test");
@@ -702,6 +1373,160 @@ mod library_tests {
         );
     }
 
+    #[tokio::test]
+    async fn load_report_records_each_book_directory() {
+        use load_report::BookLoadStatus;
+
+        let temp_dir = TempDir::new("flts_test");
+        let library_path = temp_dir.path.join("lib");
+        let library = Library::open(library_path.clone()).await.unwrap();
+
+        let book = library
+            .create_book("Healthy", &Language::from_639_3("eng").unwrap())
+            .await
+            .unwrap();
+        let healthy_id = {
+            let mut book = book.lock().await;
+            book.save().await.unwrap();
+            book.book.id
+        };
+        let corrupt_id = Uuid::new_v4();
+        std::fs::create_dir(library_path.join(corrupt_id.to_string())).unwrap();
+        std::fs::write(
+            library_path.join(corrupt_id.to_string()).join("book.dat"),
+            b"garbage",
+        )
+        .unwrap();
+        let incomplete_id = Uuid::new_v4();
+        std::fs::create_dir(library_path.join(incomplete_id.to_string())).unwrap();
+        std::fs::create_dir(library_path.join("not-a-book")).unwrap();
+
+        let reports = library.load_report().await.unwrap();
+        assert_eq!(reports.len(), 3, "{reports:?}");
+        let status = |id: Uuid| {
+            reports
+                .iter()
+                .find(|r| r.id == Some(id))
+                .map(|r| r.status.clone())
+                .unwrap()
+        };
+
+        assert_eq!(status(healthy_id), BookLoadStatus::Loaded);
+        assert_eq!(status(incomplete_id), BookLoadStatus::Incomplete);
+        assert!(matches!(status(corrupt_id), BookLoadStatus::Failed { .. }));
+    }
+
+    #[tokio::test]
+    async fn books_count_and_book_exists_skip_reading_books() {
+        let temp_dir = TempDir::new("flts_test");
+        let library_path = temp_dir.path.join("lib");
+        let library = Library::open(library_path.clone()).await.unwrap();
+        assert_eq!(library.books_count().await.unwrap(), 0);
+
+        let book = library
+            .create_book("Saved", &Language::from_639_3("eng").unwrap())
+            .await
+            .unwrap();
+        let saved_id = {
+            let mut book = book.lock().await;
+            book.save().await.unwrap();
+            book.book.id
+        };
+        let incomplete_id = Uuid::new_v4();
+        std::fs::create_dir(library_path.join(incomplete_id.to_string())).unwrap();
+        std::fs::create_dir(library_path.join("not-a-book")).unwrap();
+
+        assert_eq!(library.books_count().await.unwrap(), 2);
+        assert!(library.book_exists(&saved_id).await.unwrap());
+        assert!(library.book_exists(&incomplete_id).await.unwrap());
+        assert!(!library.book_exists(&Uuid::new_v4()).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn translated_title_is_kept_per_target_language() {
+        let temp_dir = TempDir::new("flts_test");
+        let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+        let rus = Language::from_639_3("rus").unwrap();
+
+        let book = library
+            .create_book("War and Peace", &Language::from_639_3("eng").unwrap())
+            .await
+            .unwrap();
+        {
+            let mut book = book.lock().await;
+            book.save().await.unwrap();
+            book.update_folder_path(vec!["Classics".into()])
+                .await
+                .unwrap();
+            book.update_translated_title(&rus, "Война и мир".into())
+                .await
+                .unwrap();
+            assert_eq!(
+                book.translated_title(&rus).await.unwrap().as_deref(),
+                Some("Война и мир")
+            );
+            assert_eq!(
+                book.translated_title(&Language::from_639_3("deu").unwrap())
+                    .await
+                    .unwrap(),
+                None
+            );
+        }
+
+        let books = library.list_books().await.unwrap();
+        assert_eq!(books[0].folder_path, vec!["Classics".to_string()]);
+        assert_eq!(
+            books[0].translated_titles.get("rus").map(String::as_str),
+            Some("Война и мир")
+        );
+    }
+
+    #[tokio::test]
+    async fn books_in_folder_matches_folder_prefix() {
+        let temp_dir = TempDir::new("flts_test");
+        let library_path = temp_dir.path.join("lib");
+        let library = Library::open(library_path.clone()).await.unwrap();
+        let eng = Language::from_639_3("eng").unwrap();
+
+        for (title, folder) in [
+            ("Root", vec![]),
+            ("Shelf", vec!["Shelf".to_string()]),
+            ("Nested", vec!["Shelf".to_string(), "Modern".to_string()]),
+            ("Other", vec!["Shelfish".to_string()]),
+        ] {
+            let book = library.create_book(title, &eng).await.unwrap();
+            let mut book = book.lock().await;
+            book.save().await.unwrap();
+            book.update_folder_path(folder).await.unwrap();
+        }
+
+        let titles = |books: Vec<LibraryBookMetadata>| {
+            let mut titles: Vec<_> = books.into_iter().map(|b| b.title).collect();
+            titles.sort();
+            titles
+        };
+
+        assert_eq!(
+            titles(
+                library
+                    .books_in_folder(&["Shelf".to_string()])
+                    .await
+                    .unwrap()
+            ),
+            vec!["Nested", "Shelf"]
+        );
+        assert_eq!(
+            titles(
+                library
+                    .books_in_folder(&["Shelf".to_string(), "Modern".to_string()])
+                    .await
+                    .unwrap()
+            ),
+            vec!["Nested"]
+        );
+        assert_eq!(library.books_in_folder(&[]).await.unwrap().len(), 4);
+    }
+
     #[test]
     fn split_paragraphs_js_equivalence_basic() {
         let input = "Hello\n\n  world  \r\n\nNext line\n";
@@ -709,6 +1534,123 @@ mod library_tests {
         assert_eq!(result, vec!["Hello", "world", "Next line"]);
     }
 
+    #[test]
+    fn default_chapter_heading_pattern() {
+        let heading = Regex::new(DEFAULT_CHAPTER_HEADING_PATTERN).unwrap();
+        for line in [
+            "Chapter 1: The Beginning",
+            "CHAPTER XII",
+            "Part 2",
+            "Capítulo 3",
+            "Глава 4. Встреча",
+        ] {
+            assert!(heading.is_match(line), "{line:?} should be a heading");
+        }
+        for line in ["The chapter ended.", "Chapterhouse 1", "Chapter and verse"] {
+            assert!(!heading.is_match(line), "{line:?} should not be a heading");
+        }
+    }
+
+    #[tokio::test]
+    async fn create_book_plain_with_headings_splits_chapters() {
+        let tmp = TempDir::new("flts_plain_headings");
+        let library = Library::open(tmp.path.clone()).await.unwrap();
+        let text = "A foreword.\nChapter 1: The Beginning\nFirst.\nSecond.\nChapter 2\nThird.";
+        let created = library
+            .create_book_plain_with_headings(
+                "Headings",
+                text,
+                &Language::from_639_3("eng").unwrap(),
+                DEFAULT_CHAPTER_HEADING_PATTERN,
+            )
+            .await
+            .unwrap();
+        assert_eq!(created.language, "eng");
+        assert_eq!((created.chapters_count, created.paragraphs_count), (3, 4));
+
+        let book = library.get_book(&created.id).await.unwrap();
+        let book = book.lock().await;
+        let chapters: Vec<_> = book
+            .book
+            .chapter_views()
+            .map(|ch| (ch.title.as_deref().map(str::to_owned), ch.paragraph_count()))
+            .collect();
+        assert_eq!(
+            chapters,
+            vec![
+                (None, 1),
+                (Some("Chapter 1: The Beginning".to_owned()), 2),
+                (Some("Chapter 2".to_owned()), 1),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn create_book_plain_preserves_stanza_line_breaks() {
+        let tmp = TempDir::new("flts_plain_line_breaks");
+        let library = Library::open(tmp.path.clone()).await.unwrap();
+        let text = "Chapter 1\n\nRoses are red,\nviolets & blue.\n\nThe end.";
+        let id = library
+            .create_book_plain_with_options(
+                "Verses",
+                text,
+                &Language::from_639_3("eng").unwrap(),
+                Some(DEFAULT_CHAPTER_HEADING_PATTERN),
+                true,
+            )
+            .await
+            .unwrap()
+            .id;
+
+        let book = library.get_book(&id).await.unwrap();
+        let book = book.lock().await;
+        let chapter = book.book.chapter_view(0);
+        assert_eq!(chapter.title.as_deref(), Some("Chapter 1"));
+        assert_eq!(chapter.paragraph_count(), 2);
+
+        let stanza = chapter.paragraph_view(0);
+        assert_eq!(stanza.original_text, "Roses are red,\nviolets & blue.");
+        assert_eq!(
+            stanza.original_html.as_deref(),
+            Some("Roses are red,<br>violets &amp; blue.")
+        );
+        let last = chapter.paragraph_view(1);
+        assert_eq!(last.original_text, "The end.");
+        assert!(last.original_html.is_none());
+    }
+
+    #[tokio::test]
+    async fn create_book_from_chapters_titles_each_chapter() {
+        let tmp = TempDir::new("flts_book_from_chapters");
+        let library = Library::open(tmp.path.clone()).await.unwrap();
+        let id = library
+            .create_book_from_chapters(
+                "Chapters",
+                &[
+                    ("01", "Chapter 1: Arrival\nFirst.\nSecond."),
+                    ("02", "Third."),
+                ],
+                &Language::from_639_3("eng").unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let book = library.get_book(&id).await.unwrap();
+        let book = book.lock().await;
+        let chapters: Vec<_> = book
+            .book
+            .chapter_views()
+            .map(|ch| (ch.title.as_deref().map(str::to_owned), ch.paragraph_count()))
+            .collect();
+        assert_eq!(
+            chapters,
+            vec![
+                (Some("Chapter 1: Arrival".to_owned()), 2),
+                (Some("02".to_owned()), 1),
+            ]
+        );
+    }
+
     #[test]
     fn split_paragraphs_whitespace_only() {
         let input = "  \n\n\t\n\r\n";
@@ -716,6 +1658,130 @@ mod library_tests {
         assert!(result.is_empty());
     }
 
+    #[tokio::test]
+    async fn strings_dictionary_shrinks_many_small_books() {
+        const WORDS: [&str; 16] = [
+            "the", "house", "was", "quiet", "when", "she", "opened", "door", "and", "looked",
+            "outside", "morning", "river", "window", "garden", "evening",
+        ];
+        let temp_dir = TempDir::new("flts_test");
+        let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+        let eng = Language::from_639_3("eng").unwrap();
+        let mut state = 7u64;
+        for i in 0..60 {
+            let mut text = String::new();
+            for _ in 0..50 {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                text.push_str(WORDS[(state >> 33) as usize % WORDS.len()]);
+                text.push(' ');
+            }
+            library
+                .create_book_plain(&format!("Book {i}"), &text, &eng)
+                .await
+                .unwrap();
+        }
+
+        let report = library.train_strings_dictionary(60, 2048).await.unwrap();
+
+        assert_eq!(report.books, 60);
+        assert!(
+            report.bytes_after < report.bytes_before,
+            "{} -> {} bytes",
+            report.bytes_before,
+            report.bytes_after
+        );
+        let reopened = Library::open(temp_dir.path.join("lib")).await.unwrap();
+        let books = reopened.list_books().await.unwrap();
+        assert_eq!(books.len(), 60);
+        let book = reopened.get_book(&books[0].id).await.unwrap();
+        let book = book.lock().await;
+        assert_eq!(
+            book.book.strings_dictionary().map(|d| d.id()),
+            Some(report.dictionary_id)
+        );
+        assert!(!book.book.strings_blob().is_empty());
+        drop(book);
+
+        let new_book = reopened.create_book("New", &eng).await.unwrap();
+        let new_book = new_book.lock().await;
+        assert_eq!(
+            new_book.book.strings_dictionary().map(|d| d.id()),
+            Some(report.dictionary_id)
+        );
+        drop(new_book);
+
+        // Without the dictionary file the books can't be read.
+        std::fs::remove_file(
+            temp_dir
+                .path
+                .join("lib")
+                .join(DICTIONARIES_DIR)
+                .join(StringsDictionary::file_name(report.dictionary_id)),
+        )
+        .unwrap();
+        let reopened = Library::open(temp_dir.path.join("lib")).await.unwrap();
+        assert!(reopened.get_book(&books[1].id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn html_only_epub_import_is_smaller() {
+        use crate::epub_importer::{EpubChapter, EpubParagraph};
+
+        let temp_dir = TempDir::new("flts_html_only_import");
+        let library = Library::open(temp_dir.path.clone()).await.unwrap();
+        let eng = Language::from_639_3("eng").unwrap();
+        // Distinct titles, as the library rejects a second book with the same
+        // title.
+        let epub = |title: &str| EpubBook {
+            title: title.to_owned(),
+            chapters: (0..5)
+                .map(|c| EpubChapter {
+                    title: format!("Chapter {c}"),
+                    paragraphs: (0..40)
+                        .map(|p| EpubParagraph {
+                            text: format!("Paragraph {p} of chapter {c}, with some emphasis."),
+                            html: format!(
+                                "Paragraph {p} of chapter {c}, with <i>some</i> <b>emphasis</b>."
+                            ),
+                            footnotes: vec![],
+                            heading_level: None,
+                        })
+                        .collect(),
+                })
+                .collect(),
+        };
+
+        let mut sizes = Vec::new();
+        for (storage, title) in [
+            (ParagraphStorage::Both, "Styled"),
+            (ParagraphStorage::HtmlOnly, "Styled (HTML only)"),
+        ] {
+            let id = library
+                .create_book_epub(&epub(title), &eng, storage)
+                .await
+                .unwrap();
+            let book = library.get_book(&id).await.unwrap();
+            let book = book.lock().await;
+            let paragraph = book.book.chapter_view(3).paragraph_view(7);
+            assert_eq!(
+                paragraph.original_text,
+                "Paragraph 7 of chapter 3, with some emphasis."
+            );
+            let path = book.path().join("book.dat");
+            sizes.push((
+                book.book.strings_blob().len(),
+                tokio::fs::metadata(&path).await.unwrap().len(),
+            ));
+        }
+        let [(blob_both, file_both), (blob_html, file_html)] = sizes[..] else {
+            unreachable!();
+        };
+        assert!(
+            blob_html < blob_both && file_html < file_both,
+            "strings {blob_both} -> {blob_html} bytes, book.dat {file_both} -> {file_html} bytes"
+        );
+    }
+
     async fn make_saved_book(library: &Library, title: &str) -> Uuid {
         let book = library
             .create_book(title, &Language::from_639_3("eng").unwrap())
@@ -888,6 +1954,68 @@ mod library_tests {
         assert_eq!(card.examples.len(), 1);
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn two_books_fill_one_deck_in_parallel() {
+        let tmp = TempDir::new("flts_card_parallel_books");
+        let library = Arc::new(Library::open(tmp.path.join("lib")).await.unwrap());
+        let tgt = Language::from_639_3("rus").unwrap();
+        let lemmas: Vec<String> = (0..32).map(|i| format!("palabra{i}")).collect();
+
+        let mut book_ids = Vec::new();
+        for title in ["Uno", "Dos"] {
+            let book = library
+                .create_book(title, &Language::from_639_3("spa").unwrap())
+                .await
+                .unwrap();
+            let mut b = book.lock().await;
+            b.book.push_chapter(Some("Intro"));
+            for lemma in &lemmas {
+                b.book.push_paragraph(0, lemma, None);
+            }
+            b.save().await.unwrap();
+            book_ids.push(b.book.id);
+        }
+
+        // Both books write every card of the deck at the same time; the
+        // per-card locks must keep each card's read-modify-write whole.
+        let tasks: Vec<_> = book_ids
+            .iter()
+            .map(|&book_id| {
+                let library = library.clone();
+                let lemmas = lemmas.clone();
+                tokio::spawn(async move {
+                    for (paragraph_id, lemma) in lemmas.iter().enumerate() {
+                        let paragraph = paragraph_with(
+                            "слово",
+                            vec![full_word(lemma, lemma, "слово", "noun", &["слово"], false)],
+                        );
+                        library
+                            .apply_paragraph_to_cards(book_id, paragraph_id, &paragraph, tgt)
+                            .await
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        for lemma in &lemmas {
+            let card = library
+                .card_store()
+                .load("spa", "rus", lemma)
+                .await
+                .unwrap()
+                .expect("card present");
+            let mut sources: Vec<_> = card.examples.iter().map(|e| e.book_id).collect();
+            sources.sort();
+            let mut expected = book_ids.clone();
+            expected.sort();
+            assert_eq!(sources, expected, "card {lemma} lost an update");
+        }
+    }
+
     #[tokio::test]
     async fn integration_new_paragraph_appends_example() {
         let tmp = TempDir::new("flts_card_append");
@@ -979,6 +2107,9 @@ mod library_tests {
                 paragraph: 0,
             }],
             anki_data: None,
+            usage: Default::default(),
+            last_seen: Default::default(),
+            usage_sources: Default::default(),
         };
         tokio::fs::write(
             deck.join("poder.json"),
@@ -1003,6 +2134,9 @@ mod library_tests {
                 paragraph: 5,
             }],
             anki_data: None,
+            usage: Default::default(),
+            last_seen: Default::default(),
+            usage_sources: Default::default(),
         };
         let conflict_path = deck.join("poder.sync-conflict-20260520-test.json");
         tokio::fs::write(
@@ -1310,6 +2444,136 @@ mod library_tests {
         assert_eq!(names, vec!["poder.json".to_string()]);
     }
 
+    #[tokio::test]
+    async fn rebuild_dictionary_restores_cards_and_keeps_anki_state() {
+        use crate::card::{AnkiData, AnkiState, card_id};
+
+        let tmp = TempDir::new("flts_rebuild_dictionary");
+        let library_path = tmp.path.join("lib");
+        let (library, book_id) =
+            library_with_one_paragraph_book(library_path.clone(), "No puedo más.").await;
+        let spa = Language::from_639_3("spa").unwrap();
+        let rus = Language::from_639_3("rus").unwrap();
+        let paragraph = paragraph_with(
+            "Я больше не могу.",
+            vec![full_word(
+                "puedo",
+                "poder",
+                "мочь",
+                "verb",
+                &["могу"],
+                false,
+            )],
+        );
+        seed_translation(&library, book_id, 0, &paragraph, rus).await;
+        library.backfill_cards_from_translations().await.unwrap();
+
+        // Drift: a stale translation with Anki state on the real card, and a
+        // card no translation produces.
+        let store = library.card_store();
+        let mut card = store.load("spa", "rus", "poder").await.unwrap().unwrap();
+        card.translations
+            .get_mut("verb")
+            .unwrap()
+            .push("уметь".into());
+        card.anki_data = Some(AnkiData {
+            state: AnkiState::Active,
+            interval_days: Some(3.0),
+            ease_factor: None,
+            fsrs_difficulty: None,
+            fsrs_stability: None,
+        });
+        store.save(&card, "spa", "rus").await.unwrap();
+        let mut stale = card.clone();
+        stale.id = card_id("spa", "rus", "querer");
+        stale.lemma = "querer".into();
+        stale.anki_data = None;
+        store.save(&stale, "spa", "rus").await.unwrap();
+
+        let report = library.rebuild_dictionary(spa, rus).await.unwrap();
+        assert_eq!(report.cards, 1);
+        assert_eq!(report.removed, 1);
+
+        let card = store.load("spa", "rus", "poder").await.unwrap().unwrap();
+        assert_eq!(card.translations_flat(), vec!["мочь"]);
+        assert_eq!(card.examples.len(), 1);
+        assert_eq!(card.anki_data.map(|a| a.interval_days), Some(Some(3.0)));
+        assert_eq!(
+            store.list_cards_in_pair("spa", "rus").await.unwrap(),
+            vec!["poder".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn rebuild_dictionary_reads_variants_and_keeps_counters() {
+        use crate::translator::TranslationModel;
+
+        let tmp = TempDir::new("flts_rebuild_dictionary_variants");
+        let library_path = tmp.path.join("lib");
+        let (library, book_id) =
+            library_with_one_paragraph_book(library_path.clone(), "No puedo más.").await;
+        let spa = Language::from_639_3("spa").unwrap();
+        let rus = Language::from_639_3("rus").unwrap();
+        let paragraph = paragraph_with(
+            "Я больше не могу.",
+            vec![full_word(
+                "puedo",
+                "poder",
+                "мочь",
+                "verb",
+                &["могу"],
+                false,
+            )],
+        );
+        {
+            let book = library.get_book(&book_id).await.unwrap();
+            let mut book = book.lock().await;
+            let translation = book
+                .get_or_create_translation_variant(&rus, Some("ua"))
+                .await
+                .unwrap();
+            translation.lock().await.add_paragraph_translation(
+                0,
+                &paragraph,
+                TranslationModel::Gemini25Flash,
+            );
+            book.save().await.unwrap();
+        }
+        library.backfill_cards_from_translations().await.unwrap();
+
+        let store = library.card_store();
+        let mut card = store.load("spa", "rus", "poder").await.unwrap().unwrap();
+        card.usage.insert("мочь".into(), 5);
+        card.last_seen.insert("мочь".into(), u64::MAX);
+        store.save(&card, "spa", "rus").await.unwrap();
+
+        // A book whose body is corrupt lists fine but fails to load.
+        let broken = library.create_book("Broken", &spa).await.unwrap();
+        let (broken_id, broken_dat) = {
+            let mut broken = broken.lock().await;
+            broken.book.push_chapter(None);
+            broken.book.push_paragraph(0, "Quiero más.", None);
+            broken.save().await.unwrap();
+            (broken.book.id, broken.path().join("book.dat"))
+        };
+        drop(broken);
+        library.books_cache.remove(&broken_id).await;
+        let mut bytes = std::fs::read(&broken_dat).unwrap();
+        let last_body_byte = bytes.len() - 9;
+        bytes[last_body_byte] ^= 0xff;
+        std::fs::write(&broken_dat, bytes).unwrap();
+        assert!(library.get_book(&broken_id).await.is_err());
+
+        let report = library.rebuild_dictionary(spa, rus).await.unwrap();
+        assert_eq!(report.cards, 1);
+        assert_eq!(report.removed, 0);
+
+        let card = store.load("spa", "rus", "poder").await.unwrap().unwrap();
+        assert_eq!(card.translations_flat(), vec!["мочь"]);
+        assert_eq!(card.usage.get("мочь"), Some(&5));
+        assert_eq!(card.last_seen.get("мочь"), Some(&u64::MAX));
+    }
+
     #[tokio::test]
     async fn backfill_walks_multiple_books_in_list_order() {
         let tmp = TempDir::new("flts_backfill_multi");
@@ -1397,6 +2661,7 @@ mod library_tests {
         let p_eng = translation_import::ParagraphTranslation {
             timestamp: 0,
             total_tokens: None,
+            token_usage: None,
             sentences: vec![translation_import::Sentence {
                 full_translation: "I can.".into(),
                 words: vec![full_word("puedo", "poder", "can", "verb", &["can"], false)],
@@ -1445,4 +2710,88 @@ mod library_tests {
         assert_eq!(rus_card.translations_flat(), vec!["мочь"]);
         assert_eq!(eng_card.translations_flat(), vec!["can"]);
     }
+
+    #[tokio::test]
+    async fn move_library_carries_sidecars_and_conflicts() {
+        let tmp = TempDir::new("flts_move_library");
+        let old_root = tmp.path.join("old");
+        let new_root = tmp.path.join("new");
+        let library = Library::open(old_root.clone()).await.unwrap();
+        let book_id = library
+            .create_book_plain("Moved", "Hola.", &Language::from_639_3("spa").unwrap())
+            .await
+            .unwrap()
+            .id;
+        let book_dir = old_root.join(book_id.to_string());
+        std::fs::write(book_dir.join("state.json"), "{}").unwrap();
+        std::fs::copy(
+            book_dir.join("book.dat"),
+            book_dir.join("book.sync-conflict-20260101-ABC.dat"),
+        )
+        .unwrap();
+
+        let moved = library.move_library(new_root.clone()).await.unwrap();
+        drop(library);
+
+        let new_book_dir = new_root.join(book_id.to_string());
+        assert!(new_book_dir.join("state.json").exists());
+        assert!(
+            new_book_dir
+                .join("book.sync-conflict-20260101-ABC.dat")
+                .exists()
+        );
+        assert_eq!(std::fs::read_dir(&old_root).unwrap().count(), 0);
+
+        let books = moved.list_books().await.unwrap();
+        assert_eq!(books.len(), 1);
+        assert_eq!(books[0].title, "Moved");
+    }
+
+    #[tokio::test]
+    async fn move_library_refuses_non_empty_target() {
+        let tmp = TempDir::new("flts_move_library_non_empty");
+        let new_root = tmp.path.join("new");
+        std::fs::create_dir_all(&new_root).unwrap();
+        std::fs::write(new_root.join("unrelated.txt"), "x").unwrap();
+        let library = Library::open(tmp.path.join("old")).await.unwrap();
+
+        let Err(err) = library.move_library(new_root).await else {
+            panic!("moving into a non-empty directory should fail");
+        };
+        assert!(matches!(
+            err.downcast_ref::<LibraryError>(),
+            Some(LibraryError::RelocateTargetNotEmpty(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn move_library_into_itself_leaves_no_stray_directory() {
+        let tmp = TempDir::new("flts_move_library_into_self");
+        let old_root = tmp.path.join("old");
+        let library = Library::open(old_root.clone()).await.unwrap();
+        let new_root = old_root.join("nested").join("new");
+
+        let Err(err) = library.move_library(new_root).await else {
+            panic!("moving into the library itself should fail");
+        };
+        assert!(matches!(
+            err.downcast_ref::<LibraryError>(),
+            Some(LibraryError::RelocateIntoSelf(_))
+        ));
+        assert!(!old_root.join("nested").exists());
+    }
+
+    #[tokio::test]
+    async fn book_directory_only_for_books_on_disk() {
+        let tmp = TempDir::new("flts_book_directory");
+        let library_path = tmp.path.join("lib");
+        let (library, book_id) =
+            library_with_one_paragraph_book(library_path.clone(), "Hola").await;
+
+        assert_eq!(
+            library.book_directory(&book_id),
+            Some(library_path.join(book_id.to_string()))
+        );
+        assert_eq!(library.book_directory(&Uuid::new_v4()), None);
+    }
 }
@@ -0,0 +1,166 @@
+//! Lemmas the reader already knows, per source language, so the reader can
+//! leave them without an in-text translation. Stored for the whole library in
+//! `<lib>/known_words.json` as `{ "<src>": ["<lemma slug>", ...] }`.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    path::{Path, PathBuf},
+};
+
+use isolang::Language;
+use tokio::{io::AsyncWriteExt, sync::Mutex};
+
+use crate::{
+    book::serialization::create_random_string,
+    card::{canonicalize_lemma, lemma_slug},
+};
+
+const KNOWN_WORDS_FILE: &str = "known_words.json";
+
+type KnownWords = BTreeMap<String, BTreeSet<String>>;
+
+pub struct KnownWordsStore {
+    root: PathBuf,
+    /// Serializes read-modify-write cycles within this process. The file is
+    /// re-read every time, since a sync peer may have changed it.
+    lock: Mutex<()>,
+}
+
+/// Slug a lemma is stored and looked up under, so `Casa` and `casa` are the
+/// same known word.
+pub fn known_word_slug(lemma: &str, source: Language) -> String {
+    lemma_slug(&canonicalize_lemma(lemma, source))
+}
+
+impl KnownWordsStore {
+    pub fn new(library_root: &Path) -> Self {
+        Self {
+            root: library_root.to_owned(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Slugs of the known lemmas of `source`.
+    pub async fn get(&self, source: &Language) -> anyhow::Result<HashSet<String>> {
+        let _guard = self.lock.lock().await;
+        let words = self.load().await?;
+        Ok(words
+            .get(source.to_639_3())
+            .map(|set| set.iter().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    /// Marks `lemma` as known. Returns `false` if it already was.
+    pub async fn add(&self, source: &Language, lemma: &str) -> anyhow::Result<bool> {
+        let slug = known_word_slug(lemma, *source);
+        if slug.is_empty() {
+            anyhow::bail!("Lemma {lemma:?} has no usable form");
+        }
+        let _guard = self.lock.lock().await;
+        let mut words = self.load().await?;
+        if !words
+            .entry(source.to_639_3().to_owned())
+            .or_default()
+            .insert(slug)
+        {
+            return Ok(false);
+        }
+        self.persist(&words).await?;
+        Ok(true)
+    }
+
+    /// Forgets `lemma`. Returns `false` if it wasn't known.
+    pub async fn remove(&self, source: &Language, lemma: &str) -> anyhow::Result<bool> {
+        let slug = known_word_slug(lemma, *source);
+        let _guard = self.lock.lock().await;
+        let mut words = self.load().await?;
+        let Some(set) = words.get_mut(source.to_639_3()) else {
+            return Ok(false);
+        };
+        if !set.remove(&slug) {
+            return Ok(false);
+        }
+        if set.is_empty() {
+            words.remove(source.to_639_3());
+        }
+        self.persist(&words).await?;
+        Ok(true)
+    }
+
+    /// Reads the file as it is now, so writes start from the peers' latest
+    /// changes instead of a copy cached at startup.
+    async fn load(&self) -> anyhow::Result<KnownWords> {
+        let path = self.root.join(KNOWN_WORDS_FILE);
+        if !tokio::fs::try_exists(&path).await? {
+            return Ok(KnownWords::new());
+        }
+        Ok(serde_json::from_slice(&tokio::fs::read(&path).await?)?)
+    }
+
+    async fn persist(&self, words: &KnownWords) -> anyhow::Result<()> {
+        let path = self.root.join(KNOWN_WORDS_FILE);
+        let temp_path = self
+            .root
+            .join(format!("{KNOWN_WORDS_FILE}~{}", create_random_string(8)));
+
+        {
+            let mut file = tokio::fs::File::create(&temp_path).await?;
+            file.write_all(&serde_json::to_vec_pretty(words)?).await?;
+        }
+
+        if tokio::fs::try_exists(&path).await? {
+            tokio::fs::remove_file(&path).await?;
+        }
+        tokio::fs::rename(&temp_path, &path).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TempDir;
+
+    #[tokio::test]
+    async fn known_words_persist_per_source_language() {
+        let tmp = TempDir::new("flts_known_words");
+        let spa = Language::from_639_3("spa").unwrap();
+        let deu = Language::from_639_3("deu").unwrap();
+
+        let store = KnownWordsStore::new(&tmp.path);
+        assert!(store.add(&spa, "Casa").await.unwrap());
+        assert!(!store.add(&spa, "casa").await.unwrap());
+        assert!(store.add(&spa, "perro").await.unwrap());
+        assert!(store.add(&deu, "Haus").await.unwrap());
+        assert!(store.remove(&deu, "haus").await.unwrap());
+        assert!(!store.remove(&deu, "haus").await.unwrap());
+
+        let reopened = KnownWordsStore::new(&tmp.path);
+        assert_eq!(
+            reopened.get(&spa).await.unwrap(),
+            HashSet::from(["casa".to_owned(), "perro".to_owned()])
+        );
+        assert!(reopened.get(&deu).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn known_words_keep_changes_made_by_another_store() {
+        let tmp = TempDir::new("flts_known_words_peer");
+        let spa = Language::from_639_3("spa").unwrap();
+
+        let store = KnownWordsStore::new(&tmp.path);
+        assert!(store.add(&spa, "casa").await.unwrap());
+        assert!(store.get(&spa).await.unwrap().contains("casa"));
+
+        // A sync peer writes the same file behind this store's back.
+        let peer = KnownWordsStore::new(&tmp.path);
+        assert!(peer.add(&spa, "perro").await.unwrap());
+        assert!(peer.remove(&spa, "casa").await.unwrap());
+
+        assert!(store.add(&spa, "gato").await.unwrap());
+        assert_eq!(
+            KnownWordsStore::new(&tmp.path).get(&spa).await.unwrap(),
+            HashSet::from(["perro".to_owned(), "gato".to_owned()])
+        );
+    }
+}
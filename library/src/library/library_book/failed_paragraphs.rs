@@ -0,0 +1,59 @@
+//! `failed_paragraphs.json` sidecar: paragraphs a bulk translation run gave
+//! up on, per target language tag, so a later run can retry just those.
+//! Unlike `translation_progress.json` it outlives the run that wrote it.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::book::serialization::create_random_string;
+
+use super::LibraryBook;
+
+const FAILED_PARAGRAPHS_FILE: &str = "failed_paragraphs.json";
+
+impl LibraryBook {
+    /// Paragraphs the last bulk translation into `target_tag` failed on.
+    pub async fn failed_paragraphs(&self, target_tag: &str) -> anyhow::Result<BTreeSet<usize>> {
+        let mut failed = self.read_failed_paragraphs().await?;
+        Ok(failed.remove(target_tag).unwrap_or_default())
+    }
+
+    /// Replaces the failed paragraphs recorded for `target_tag`. An empty
+    /// set drops the entry, and the file once no entry is left.
+    pub async fn set_failed_paragraphs(
+        &self,
+        target_tag: &str,
+        paragraphs: &BTreeSet<usize>,
+    ) -> anyhow::Result<()> {
+        let mut failed = self.read_failed_paragraphs().await?;
+        if paragraphs.is_empty() {
+            if failed.remove(target_tag).is_none() {
+                return Ok(());
+            }
+        } else {
+            failed.insert(target_tag.to_owned(), paragraphs.clone());
+        }
+
+        let path = self.path.join(FAILED_PARAGRAPHS_FILE);
+        if failed.is_empty() {
+            if tokio::fs::try_exists(&path).await? {
+                tokio::fs::remove_file(&path).await?;
+            }
+            return Ok(());
+        }
+        let temp_path = self.path.join(format!(
+            "{FAILED_PARAGRAPHS_FILE}~{}",
+            create_random_string(8)
+        ));
+        tokio::fs::write(&temp_path, serde_json::to_vec_pretty(&failed)?).await?;
+        tokio::fs::rename(&temp_path, &path).await?;
+        Ok(())
+    }
+
+    async fn read_failed_paragraphs(&self) -> anyhow::Result<BTreeMap<String, BTreeSet<usize>>> {
+        let path = self.path.join(FAILED_PARAGRAPHS_FILE);
+        if !tokio::fs::try_exists(&path).await? {
+            return Ok(BTreeMap::new());
+        }
+        Ok(serde_json::from_slice(&tokio::fs::read(&path).await?)?)
+    }
+}
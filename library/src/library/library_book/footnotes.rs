@@ -0,0 +1,73 @@
+//! `footnotes.json` sidecar: EPUB footnotes captured at import, keyed by
+//! paragraph id. It's written once when the book is created and never edited
+//! afterwards, so unlike `state.json` it needs no conflict resolution.
+
+use std::collections::BTreeMap;
+
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    book::{serialization::create_random_string, translation_import},
+    epub_importer::EpubFootnote,
+};
+
+use super::LibraryBook;
+
+pub(super) const FOOTNOTES_FILE: &str = "footnotes.json";
+
+impl LibraryBook {
+    pub(crate) async fn save_footnotes(
+        &self,
+        footnotes: &BTreeMap<usize, Vec<EpubFootnote>>,
+    ) -> anyhow::Result<()> {
+        if footnotes.is_empty() {
+            return Ok(());
+        }
+        if !tokio::fs::try_exists(&self.path).await? {
+            tokio::fs::create_dir_all(&self.path).await?;
+        }
+
+        let footnotes_path = self.path.join(FOOTNOTES_FILE);
+        let temp_path = self
+            .path
+            .join(format!("{FOOTNOTES_FILE}~{}", create_random_string(8)));
+        {
+            let mut file = tokio::fs::File::create(&temp_path).await?;
+            file.write_all(&serde_json::to_vec(footnotes)?).await?;
+        }
+        if tokio::fs::try_exists(&footnotes_path).await? {
+            tokio::fs::remove_file(&footnotes_path).await?;
+        }
+        tokio::fs::rename(&temp_path, &footnotes_path).await?;
+
+        Ok(())
+    }
+
+    pub async fn paragraph_footnotes(
+        &self,
+        paragraph_id: usize,
+    ) -> anyhow::Result<Vec<EpubFootnote>> {
+        let footnotes_path = self.path.join(FOOTNOTES_FILE);
+        if !tokio::fs::try_exists(&footnotes_path).await? {
+            return Ok(Vec::new());
+        }
+        let content = tokio::fs::read(&footnotes_path).await?;
+        let mut footnotes: BTreeMap<usize, Vec<EpubFootnote>> = serde_json::from_slice(&content)?;
+        Ok(footnotes.remove(&paragraph_id).unwrap_or_default())
+    }
+
+    /// Attaches the paragraph's import-time footnotes to the notes of the
+    /// words they were anchored to. Footnotes whose word the translation
+    /// doesn't contain are dropped. Returns how many were attached.
+    pub async fn apply_footnotes(
+        &self,
+        paragraph_id: usize,
+        translation: &mut translation_import::ParagraphTranslation,
+    ) -> anyhow::Result<usize> {
+        let footnotes = self.paragraph_footnotes(paragraph_id).await?;
+        Ok(footnotes
+            .iter()
+            .filter(|f| translation.attach_note(&f.anchor_word, &f.text))
+            .count())
+    }
+}
@@ -1,14 +1,13 @@
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, HashSet},
     path::{Path, PathBuf},
     str::FromStr,
     sync::Arc,
     time::SystemTime,
 };
 
-use log::info;
-#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
-use log::warn;
+use anyhow::Context;
+use log::{info, warn};
 
 use crate::tla_trace::mutex::{TracedLock, TracedMutex};
 use isolang::Language;
@@ -19,21 +18,40 @@ use uuid::Uuid;
 use crate::{
     book::{
         book::Book,
-        serialization::{Serializable, create_random_string, read_stored_hash_from_path},
-        translation::{ParagraphTranslationView, Translation},
+        chapter_summaries::{ChapterSummaries, chapter_summaries_path},
+        serialization::{
+            HashAlgorithm, Serializable, Version, create_random_string, read_stored_hash_from_path,
+        },
+        strings_dictionary::{DICTIONARIES_DIR, StringsDictionary, dictionaries_dir_for},
+        translation::{
+            MergeStrategy, ParagraphTranslationView, Translation, is_valid_variant,
+            split_language_tag,
+        },
         translation_import,
     },
-    library::{Library, LibraryBookMetadata, LibraryError, LibraryTranslationMetadata},
+    library::{
+        Library, LibraryBookMetadata, LibraryError, LibraryTranslationMetadata,
+        load_report::conflict_count,
+    },
     tla_trace,
     translator::TranslationModel,
 };
 
+mod failed_paragraphs;
+mod footnotes;
 mod reading_state;
+mod sentence_pairs;
+mod study_set;
 #[cfg(test)]
 mod tests;
+mod tmx;
+mod translation_progress;
 
 pub use reading_state::load_book_user_state;
 use reading_state::{load_user_state_from_dir, persist_user_state};
+pub use study_set::{StudiedGrammar, StudiedWord, StudySet};
+pub use tmx::TmxImportReport;
+pub use translation_progress::{LanguageProgress, TranslationProgress};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct BookReadingState {
@@ -57,8 +75,20 @@ pub struct BookUserState {
     pub reading_state: Option<BookReadingState>,
     #[serde(default, rename = "folderPath")]
     pub folder_path: Vec<String>,
+    /// The book's title translated into each target language, keyed by
+    /// ISO 639-3 code.
+    #[serde(default, rename = "translatedTitles")]
+    pub translated_titles: BTreeMap<String, String>,
+    /// Words the reader looked up, oldest first, as `(paragraph, sentence,
+    /// word, unix seconds)`. See [`LibraryBook::record_word_lookup`].
+    #[serde(default, rename = "lookupHistory")]
+    pub lookup_history: Vec<(usize, usize, usize, u64)>,
 }
 
+/// Lookups [`LibraryBook::record_word_lookup`] keeps per book unless the
+/// caller sets its own limit.
+pub const DEFAULT_LOOKUP_HISTORY_LIMIT: usize = 500;
+
 pub struct LibraryBook {
     path: PathBuf,
     last_modified: Option<SystemTime>,
@@ -68,6 +98,19 @@ pub struct LibraryBook {
     pub book: Book,
     translations: Vec<Arc<TracedMutex<LibraryTranslation>>>,
     user_state: BookUserState,
+    /// How diverged translation histories are reconciled when a save finds
+    /// newer content on disk or conflict files next to the main one.
+    merge_strategy: MergeStrategy,
+    /// Checksum the book and its translations are written with. Loaded books
+    /// keep the one their `book.dat` was written with.
+    hash_algorithm: HashAlgorithm,
+    /// Unset when the book was loaded with
+    /// [`LibraryBook::load_from_metadata_unmerged`]; translations showing up
+    /// later are then loaded without merging their conflicts too.
+    auto_merge_conflicts: bool,
+    /// Conflicting copies of the book and translation files that were left
+    /// on disk instead of being merged at load time.
+    unmerged_conflicts: Vec<PathBuf>,
 }
 
 pub struct LibraryTranslation {
@@ -92,6 +135,15 @@ fn trailing_hash(buffer: &[u8]) -> Option<u64> {
     Some(u64::from_le_bytes(buffer[len - 8..len].try_into().ok()?))
 }
 
+/// Target variant named by a translation file, as in the
+/// `translation_<src>_<tgt>[-<variant>].dat` names [`LibraryTranslation`]
+/// saves under. Conflict copies only add suffixes after the first `.`.
+pub(crate) fn translation_file_variant(file_name: &str) -> Option<String> {
+    let stem = file_name.strip_prefix("translation_")?.split('.').next()?;
+    let (_, tag) = stem.split_once('_')?;
+    split_language_tag(tag).1
+}
+
 impl TracedLock for LibraryBook {
     fn lock_name(&self) -> String {
         format!("book:{}", self.book.id)
@@ -103,7 +155,7 @@ impl TracedLock for LibraryTranslation {
         format!(
             "trans:{}_{}",
             self.source_language.to_639_3(),
-            self.target_language.to_639_3()
+            self.translation.target_language_tag()
         )
     }
 }
@@ -112,6 +164,31 @@ impl LibraryBook {
     pub fn path(&self) -> &Path {
         &self.path
     }
+
+    pub fn merge_strategy(&self) -> MergeStrategy {
+        self.merge_strategy
+    }
+
+    pub fn set_merge_strategy(&mut self, strategy: MergeStrategy) {
+        self.merge_strategy = strategy;
+    }
+
+    pub fn hash_algorithm(&self) -> HashAlgorithm {
+        self.hash_algorithm
+    }
+
+    /// Checksum `book.dat` with `algorithm` from the next save on, and each
+    /// translation file from its next change on.
+    pub fn set_hash_algorithm(&mut self, algorithm: HashAlgorithm) {
+        self.hash_algorithm = algorithm;
+        self.book.set_hash_algorithm(algorithm);
+    }
+
+    /// Conflict files waiting for [`Library::merge_conflicts`]. Always empty
+    /// when conflicts are merged at load time.
+    pub fn conflicting_paths(&self) -> &[PathBuf] {
+        &self.unmerged_conflicts
+    }
 }
 
 impl LibraryTranslation {
@@ -119,14 +196,44 @@ impl LibraryTranslation {
         self.changed
     }
 
-    fn merge(&mut self, other: LibraryTranslation) {
+    /// Modification time of the translation file as of the last load or save;
+    /// `None` until it has been written to disk.
+    pub fn last_modified(&self) -> Option<SystemTime> {
+        self.last_modified
+    }
+
+    /// `src_tgt[-variant]` pair this translation is for, as in its file name.
+    fn language_pair(&self) -> String {
+        format!(
+            "{}_{}",
+            self.translation.source_language,
+            self.translation.target_language_tag()
+        )
+    }
+
+    /// Name of the translation's file in the book directory.
+    fn file_name(&self) -> String {
+        format!("translation_{}.dat", self.language_pair())
+    }
+
+    /// Merges `other` into this translation. Fails without touching either
+    /// side if `other` is for a different language pair, e.g. a corrupted or
+    /// misnamed conflict file.
+    fn merge(&mut self, other: LibraryTranslation, strategy: MergeStrategy) -> anyhow::Result<()> {
+        if other.language_pair() != self.language_pair() {
+            Err(LibraryError::TranslationLanguageMismatch {
+                expected: self.language_pair(),
+                found: other.language_pair(),
+            })?
+        }
         let other_t = other.translation;
 
-        let merged_translation = self.translation.merge(&other_t);
+        let merged_translation = self.translation.merge_with_strategy(&other_t, strategy);
 
         self.translation = merged_translation;
         self.last_modified = self.last_modified.max(other.last_modified);
         self.changed = true;
+        Ok(())
     }
 
     async fn load(path: &Path) -> anyhow::Result<Self> {
@@ -151,27 +258,26 @@ impl LibraryTranslation {
         })
     }
 
-    async fn load_from_metadata(metadata: LibraryTranslationMetadata) -> anyhow::Result<Self> {
+    async fn load_from_metadata(
+        metadata: LibraryTranslationMetadata,
+        strategy: MergeStrategy,
+    ) -> anyhow::Result<Self> {
         let translation_path = metadata.main_path.clone();
         if !metadata.conflicting_paths.is_empty() {
-            let mut translation = {
-                let content = tokio::fs::read(&metadata.main_path).await?;
-                let mut cursor = std::io::Cursor::new(content);
-                Translation::deserialize(&mut cursor)?
-            };
+            let mut translation = Self::load(&metadata.main_path).await?;
 
             for conflict in metadata.conflicting_paths {
-                {
-                    let content = tokio::fs::read(&conflict).await?;
-                    let mut cursor = std::io::Cursor::new(content);
-                    let conflict_translation = Translation::deserialize(&mut cursor)?;
-                    translation = translation.merge(&conflict_translation);
+                let conflict_translation = Self::load(&conflict).await?;
+                if let Err(err) = translation.merge(conflict_translation, strategy) {
+                    // Left in place for the user to inspect.
+                    warn!("Skipping conflicting translation {conflict:?}: {err}");
+                    continue;
                 }
                 tokio::fs::remove_file(&conflict).await?;
             }
 
             let mut buf = Vec::new();
-            translation.serialize(&mut buf)?;
+            translation.translation.serialize(&mut buf)?;
             tokio::fs::write(&metadata.main_path, buf).await?;
         }
 
@@ -202,13 +308,73 @@ impl LibraryTranslation {
         self.changed = true;
     }
 
+    /// Adds a new version of `paragraph` that differs from the latest one
+    /// only in sentence `sentence_index`, recording `model` as the one that
+    /// produced it. Word visibility starts over, as for any new version.
+    pub fn replace_sentence(
+        &mut self,
+        paragraph: usize,
+        sentence_index: usize,
+        new: translation_import::Sentence,
+        model: TranslationModel,
+    ) -> anyhow::Result<()> {
+        let mut translation = self
+            .translation
+            .paragraph_view(paragraph)
+            .ok_or_else(|| anyhow::anyhow!("Paragraph {paragraph} is not translated"))?
+            .to_import();
+        let sentence = translation
+            .sentences
+            .get_mut(sentence_index)
+            .ok_or_else(|| {
+                anyhow::anyhow!("Paragraph {paragraph} has no sentence {sentence_index}")
+            })?;
+        *sentence = new;
+        translation.timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)?
+            .as_secs();
+        translation.total_tokens = None;
+        translation.token_usage = None;
+
+        self.add_paragraph_translation(paragraph, &translation, model);
+        Ok(())
+    }
+
+    pub fn mark_word_visible(&mut self, paragraph: usize, word_index: usize, timestamp: u64) {
+        self.translation
+            .mark_word_visible(paragraph, word_index, timestamp);
+        self.changed = true;
+    }
+
+    pub fn set_all_words_visible(&mut self, paragraph: usize, visible: bool, timestamp: u64) {
+        self.translation
+            .set_all_words_visible(paragraph, visible, timestamp);
+        self.changed = true;
+    }
+
     pub fn translated_paragraphs_count(&self) -> usize {
         self.translation.translated_paragraphs_count()
     }
 
+    pub fn total_sentences(&self) -> usize {
+        self.translation.total_sentences()
+    }
+
+    pub fn total_words(&self) -> usize {
+        self.translation.total_words()
+    }
+
     pub fn paragraph_view(&'_ self, paragraph: usize) -> Option<ParagraphTranslationView<'_>> {
         self.translation.paragraph_view(paragraph)
     }
+
+    pub fn search_lemma(&self, lemma: &str) -> Vec<(usize, usize, usize)> {
+        self.translation.search_lemma(lemma)
+    }
+
+    pub fn examples_for_lemma(&self, lemma: &str, max: usize) -> Vec<String> {
+        self.translation.examples_for_lemma(lemma, max)
+    }
 }
 
 impl LibraryBook {
@@ -221,6 +387,21 @@ impl LibraryBook {
         false
     }
 
+    /// Whether saving would write anything: a translation has unsaved changes
+    /// or the book differs from what was last read from or written to disk.
+    /// `book` is edited in place by callers, so it's compared by content hash
+    /// rather than tracked with a flag.
+    pub async fn is_dirty(&self) -> bool {
+        if self.has_unsaved_changes().await {
+            return true;
+        }
+        let mut buffer = Vec::new();
+        if self.book.serialize(&mut buffer).is_err() {
+            return true;
+        }
+        self.last_saved_hash.is_none() || trailing_hash(&buffer) != self.last_saved_hash
+    }
+
     async fn reload_user_state(&mut self) -> anyhow::Result<()> {
         self.user_state = load_user_state_from_dir(&self.path).await?;
         Ok(())
@@ -287,20 +468,139 @@ impl LibraryBook {
         Ok(())
     }
 
+    /// Drops duplicate paragraphs from their chapters (see
+    /// [`Book::dedupe_paragraphs`]), folding the duplicates' translations into
+    /// the surviving paragraph if `merge_translations` is set. Paragraph ids
+    /// stay the same. Saves the book and returns how many paragraphs were
+    /// removed.
+    pub async fn dedupe_paragraphs(&mut self, merge_translations: bool) -> anyhow::Result<usize> {
+        let survivors = self.book.dedupe_paragraphs();
+        let removed = survivors
+            .iter()
+            .enumerate()
+            .filter(|&(paragraph_id, &survivor)| survivor != paragraph_id)
+            .count();
+        if removed == 0 {
+            return Ok(0);
+        }
+
+        if merge_translations {
+            for translation in &self.translations {
+                let mut translation = translation.lock().await;
+                translation.translation = translation
+                    .translation
+                    .merge_duplicate_paragraphs(&survivors);
+                translation.changed = true;
+            }
+        }
+        self.save().await?;
+        Ok(removed)
+    }
+
+    /// Fixes the original text of `paragraph_id` (see
+    /// [`Book::update_paragraph_text`]) and saves the book. Returns `true` if
+    /// the text changed and some translation has a version of the paragraph,
+    /// which was then made against the old text.
+    pub async fn update_paragraph_original(
+        &mut self,
+        paragraph_id: usize,
+        text: &str,
+        html: Option<&str>,
+    ) -> anyhow::Result<bool> {
+        if paragraph_id >= self.book.paragraphs_count() {
+            anyhow::bail!("Book {} has no paragraph {paragraph_id}", self.book.id);
+        }
+        let text_changed = self.book.update_paragraph_text(paragraph_id, text, html);
+        let mut translated = false;
+        for translation in &self.translations {
+            let translation = translation.lock().await;
+            if translation.paragraph_view(paragraph_id).is_some() {
+                translated = true;
+                break;
+            }
+        }
+        self.save().await?;
+        Ok(text_changed && translated)
+    }
+
     pub async fn folder_path(&mut self) -> anyhow::Result<Vec<String>> {
         self.reload_user_state().await?;
         Ok(self.user_state.folder_path.clone())
     }
 
+    pub async fn translated_title(
+        &mut self,
+        target_language: &Language,
+    ) -> anyhow::Result<Option<String>> {
+        self.reload_user_state().await?;
+        Ok(self
+            .user_state
+            .translated_titles
+            .get(target_language.to_639_3())
+            .cloned())
+    }
+
+    pub async fn update_translated_title(
+        &mut self,
+        target_language: &Language,
+        title: String,
+    ) -> anyhow::Result<()> {
+        self.reload_user_state().await?;
+        self.user_state
+            .translated_titles
+            .insert(target_language.to_639_3().to_owned(), title);
+        persist_user_state(&self.path, &self.user_state).await
+    }
+
+    /// Appends a lookup of `word` in `sentence` of `paragraph` to the book's
+    /// lookup history, dropping the oldest entries beyond `limit`.
+    pub async fn record_word_lookup(
+        &mut self,
+        paragraph: usize,
+        sentence: usize,
+        word: usize,
+        limit: usize,
+    ) -> anyhow::Result<()> {
+        if paragraph >= self.book.paragraphs_count() {
+            anyhow::bail!("Book {} has no paragraph {paragraph}", self.book.id);
+        }
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)?
+            .as_secs();
+        self.reload_user_state().await?;
+        let history = &mut self.user_state.lookup_history;
+        history.push((paragraph, sentence, word, timestamp));
+        if history.len() > limit {
+            history.drain(..history.len() - limit);
+        }
+        persist_user_state(&self.path, &self.user_state).await
+    }
+
+    pub async fn lookup_history(&mut self) -> anyhow::Result<Vec<(usize, usize, usize, u64)>> {
+        self.reload_user_state().await?;
+        Ok(self.user_state.lookup_history.clone())
+    }
+
     pub async fn get_translation(
         &self,
         target_language: &Language,
+    ) -> Option<Arc<TracedMutex<LibraryTranslation>>> {
+        self.get_translation_variant(target_language, None).await
+    }
+
+    /// Like [`LibraryBook::get_translation`], for a regional variant of
+    /// `target_language`. `None` selects the base language, never a variant.
+    pub async fn get_translation_variant(
+        &self,
+        target_language: &Language,
+        target_variant: Option<&str>,
     ) -> Option<Arc<TracedMutex<LibraryTranslation>>> {
         let source_language = &self.book.language;
         for t in self.translations.iter() {
             let guard = t.lock().await;
             if &guard.translation.source_language == source_language
                 && guard.translation.target_language == target_language.to_639_3()
+                && guard.translation.target_variant.as_deref() == target_variant
             {
                 drop(guard);
                 return Some(t.clone());
@@ -312,6 +612,33 @@ impl LibraryBook {
     pub async fn get_or_create_translation(
         &mut self,
         target_language: &Language,
+    ) -> Arc<TracedMutex<LibraryTranslation>> {
+        self.find_or_create_translation(target_language, None).await
+    }
+
+    /// Like [`LibraryBook::get_or_create_translation`], but keeps a separate
+    /// translation per regional variant (e.g. `BR` and `PT` for Portuguese),
+    /// saved as `translation_<src>_<tgt>-<variant>.dat`. Fails if the variant
+    /// isn't a single alphanumeric subtag (see [`is_valid_variant`]).
+    pub async fn get_or_create_translation_variant(
+        &mut self,
+        target_language: &Language,
+        target_variant: Option<&str>,
+    ) -> anyhow::Result<Arc<TracedMutex<LibraryTranslation>>> {
+        if let Some(variant) = target_variant
+            && !is_valid_variant(variant)
+        {
+            anyhow::bail!("Invalid target language variant {variant:?}");
+        }
+        Ok(self
+            .find_or_create_translation(target_language, target_variant)
+            .await)
+    }
+
+    async fn find_or_create_translation(
+        &mut self,
+        target_language: &Language,
+        target_variant: Option<&str>,
     ) -> Arc<TracedMutex<LibraryTranslation>> {
         let source_language = &self.book.language;
 
@@ -328,6 +655,7 @@ impl LibraryBook {
                 let tgt_match = {
                     let guard = t.lock().await;
                     guard.translation.target_language == target_language.to_639_3()
+                        && guard.translation.target_variant.as_deref() == target_variant
                 };
 
                 if tgt_match {
@@ -339,7 +667,11 @@ impl LibraryBook {
         // Not found: create and push
         self.translations
             .push(Arc::new(TracedMutex::new(LibraryTranslation {
-                translation: Translation::create(source_language, target_language.to_639_3()),
+                translation: Translation::create_with_variant(
+                    source_language,
+                    target_language.to_639_3(),
+                    target_variant,
+                ),
                 source_language: Language::from_639_3(source_language).unwrap(),
                 target_language: *target_language,
                 last_modified: None,
@@ -352,6 +684,39 @@ impl LibraryBook {
     }
 
     pub async fn load_from_metadata(metadata: LibraryBookMetadata) -> anyhow::Result<Self> {
+        Self::load_from_metadata_with_strategy(metadata, true, MergeStrategy::default()).await
+    }
+
+    /// Like [`LibraryBook::load_from_metadata`], but reads only the main
+    /// book and translation files. Conflicting copies stay on disk and are
+    /// listed by [`LibraryBook::conflicting_paths`].
+    pub async fn load_from_metadata_unmerged(
+        metadata: LibraryBookMetadata,
+    ) -> anyhow::Result<Self> {
+        Self::load_from_metadata_with_strategy(metadata, false, MergeStrategy::default()).await
+    }
+
+    /// Loads the book, merging conflicting copies unless `merge_conflicts`
+    /// is unset, and reconciles translation histories with `strategy` from
+    /// the start instead of only on later saves.
+    pub async fn load_from_metadata_with_strategy(
+        mut metadata: LibraryBookMetadata,
+        merge_conflicts: bool,
+        strategy: MergeStrategy,
+    ) -> anyhow::Result<Self> {
+        if !merge_conflicts {
+            let mut unmerged = std::mem::take(&mut metadata.conflicting_paths);
+            for tm in &mut metadata.translations_metadata {
+                unmerged.append(&mut tm.conflicting_paths);
+            }
+            unmerged.append(&mut metadata.chapter_summaries_conflicting_paths);
+            let mut book = Self::load(&metadata.main_path).await?;
+            book.merge_strategy = strategy;
+            book.auto_merge_conflicts = false;
+            book.unmerged_conflicts = unmerged;
+            return book.load_translations_and_state(metadata).await;
+        }
+
         let mut candidates: Vec<(&PathBuf, Option<SystemTime>)> = Vec::new();
         candidates.push((
             &metadata.main_path,
@@ -389,25 +754,42 @@ impl LibraryBook {
             }
         }
 
-        for p in metadata.conflicting_paths {
+        for p in &metadata.conflicting_paths {
             if p.exists() {
                 // It's possible we've just moved the newest conflict into main, so ignore missing
                 let _ = tokio::fs::remove_file(p).await;
             }
         }
 
+        if let Some(main) = &metadata.chapter_summaries_main_path
+            && !metadata.chapter_summaries_conflicting_paths.is_empty()
+        {
+            ChapterSummaries::load_from_metadata(
+                main,
+                &metadata.chapter_summaries_conflicting_paths,
+            )
+            .await?;
+        }
+
         let mut book = Self::load(&metadata.main_path).await?;
+        book.merge_strategy = strategy;
+        book.load_translations_and_state(metadata).await
+    }
 
+    async fn load_translations_and_state(
+        mut self,
+        metadata: LibraryBookMetadata,
+    ) -> anyhow::Result<Self> {
         for tm in metadata.translations_metadata {
             let translation = Arc::new(TracedMutex::new(
-                LibraryTranslation::load_from_metadata(tm).await?,
+                LibraryTranslation::load_from_metadata(tm, self.merge_strategy).await?,
             ));
-            book.translations.push(translation);
+            self.translations.push(translation);
         }
 
-        book.reload_user_state().await?;
+        self.reload_user_state().await?;
         tla_trace::emit_book_event(
-            &book.path,
+            &self.path,
             "LoadBookFromMetadata",
             None,
             "idle",
@@ -416,7 +798,7 @@ impl LibraryBook {
         )
         .await?;
 
-        Ok(book)
+        Ok(self)
     }
 
     async fn load(path: &Path) -> anyhow::Result<Self> {
@@ -426,15 +808,20 @@ impl LibraryBook {
         file.read_to_end(&mut buffer).await?;
         let last_saved_hash = trailing_hash(&buffer);
         let mut cursor = std::io::Cursor::new(buffer);
-        let book = Book::deserialize(&mut cursor)?;
+        let dictionaries = dictionaries_dir_for(path);
+        let book = Book::deserialize_with_dictionaries(&mut cursor, dictionaries.as_deref())?;
 
         Ok(Self {
             path: path.parent().unwrap().to_path_buf(),
             last_modified,
             last_saved_hash,
+            hash_algorithm: book.hash_algorithm(),
             book,
             translations: vec![],
             user_state: BookUserState::default(),
+            merge_strategy: MergeStrategy::default(),
+            auto_merge_conflicts: true,
+            unmerged_conflicts: Vec::new(),
         })
     }
 
@@ -458,6 +845,7 @@ impl LibraryBook {
             return Ok(false);
         }
 
+        // `save` finds the newer book.dat and loads it into memory.
         self.save().await?;
         Ok(true)
     }
@@ -484,10 +872,7 @@ impl LibraryBook {
             // Content-gate: drop echoes of our own writes (same content,
             // bumped mtime). Only a genuinely different on-disk hash warrants
             // a reload+merge+save.
-            let file_name = format!(
-                "translation_{}_{}.dat",
-                t.translation.source_language, t.translation.target_language
-            );
+            let file_name = t.file_name();
             let translation_path = self.path.join(file_name);
             if let Some(saved_hash) = t.last_saved_hash
                 && let Ok(disk_hash) = read_stored_hash_from_path(&translation_path)
@@ -508,26 +893,95 @@ impl LibraryBook {
         })
     }
 
+    /// Reloads the book and every loaded translation whose file on disk is
+    /// newer than the in-memory copy, without waiting for a file-watcher
+    /// event. Returns whether anything was reloaded.
+    pub async fn refresh_from_disk(&mut self) -> anyhow::Result<bool> {
+        let mut reloaded = false;
+        if let Ok(metadata) = tokio::fs::metadata(self.path.join("book.dat")).await
+            && let Ok(modified) = metadata.modified()
+        {
+            reloaded |= self.reload_book(modified).await?;
+        }
+
+        let mut stale = Vec::new();
+        for translation in &self.translations {
+            let t = translation.lock().await;
+            let file_name = t.file_name();
+            if let Ok(metadata) = tokio::fs::metadata(self.path.join(file_name)).await
+                && let Ok(modified) = metadata.modified()
+                && t.last_modified.is_none_or(|lm| lm < modified)
+            {
+                stale.push((modified, t.source_language, t.target_language));
+            }
+        }
+        for (modified, from, to) in stale {
+            reloaded |= self.reload_translations(modified, from, to).await?;
+        }
+        Ok(reloaded)
+    }
+
+    /// Marks every translation still stored in the v1 format as changed and
+    /// saves, so it's rewritten as v2 through the usual temp-file + rename.
+    /// Returns how many translation files were upgraded.
+    pub async fn upgrade_translation_formats(&mut self) -> anyhow::Result<usize> {
+        let mut upgraded = 0;
+        for translation in &self.translations {
+            let mut t = translation.lock().await;
+            let file_name = t.file_name();
+            let file = match std::fs::File::open(self.path.join(file_name)) {
+                Ok(file) => file,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err.into()),
+            };
+            let version = Translation::stored_version(&mut std::io::BufReader::new(file))?;
+            if version == Version::V1 {
+                t.changed = true;
+                upgraded += 1;
+            }
+        }
+
+        if upgraded > 0 {
+            self.save().await?;
+        }
+        Ok(upgraded)
+    }
+
+    /// Drops unreferenced bytes from the strings blob of every translation
+    /// and saves the ones that shrank. Returns the number of bytes reclaimed.
+    pub async fn compact_strings(&mut self) -> anyhow::Result<usize> {
+        let mut reclaimed = 0;
+        for translation in &self.translations {
+            let mut t = translation.lock().await;
+            let bytes = t.translation.compact_strings();
+            if bytes > 0 {
+                t.changed = true;
+                reclaimed += bytes;
+            }
+        }
+
+        if reclaimed > 0 {
+            self.save().await?;
+        }
+        Ok(reclaimed)
+    }
+
     pub async fn save(&mut self) -> anyhow::Result<()> {
         if !tokio::fs::try_exists(&self.path).await? {
             tokio::fs::create_dir_all(&self.path).await?;
         }
 
         let book = self;
+        let merge_strategy = book.merge_strategy;
+        let hash_algorithm = book.hash_algorithm;
 
         let mut merged_translations = Vec::new();
 
         for translation_arc in book.translations.drain(0..) {
             let mut translation = translation_arc.lock().await;
-            let source_language = translation.translation.source_language.clone();
-            let target_language = translation.translation.target_language.clone();
-            let translation_file_name =
-                format!("translation_{}_{}.dat", source_language, target_language);
+            let translation_file_name = translation.file_name();
             let translation_path = book.path.join(&translation_file_name);
-            let translation_path_temp = book.path.join(format!(
-                "{translation_file_name}~{}",
-                create_random_string(8)
-            ));
+            let translation_path_temp = temp_path(&translation_path);
 
             loop {
                 let translation_path_modified_pre_save =
@@ -550,12 +1004,16 @@ impl LibraryBook {
                         if saved_translation_last_modified > last_modified {
                             let saved_translation =
                                 LibraryTranslation::load(&translation_path).await?;
-                            translation.merge(saved_translation);
+                            translation
+                                .merge(saved_translation, merge_strategy)
+                                .with_context(|| format!("{translation_path:?}"))?;
                         }
                     }
                 } else if tokio::fs::try_exists(&translation_path).await? {
                     let saved_translation = LibraryTranslation::load(&translation_path).await?;
-                    translation.merge(saved_translation);
+                    translation
+                        .merge(saved_translation, merge_strategy)
+                        .with_context(|| format!("{translation_path:?}"))?;
                 }
 
                 tla_trace::emit_translation_event(
@@ -572,6 +1030,7 @@ impl LibraryBook {
                 if translation.changed {
                     let mut translation_file =
                         tokio::fs::File::create(&translation_path_temp).await?;
+                    translation.translation.set_hash_algorithm(hash_algorithm);
                     let mut buffer = Vec::new();
                     translation.translation.serialize(&mut buffer)?;
                     translation_file.write_all(&buffer).await?;
@@ -620,9 +1079,7 @@ impl LibraryBook {
         }
 
         let book_path = book.path.join("book.dat");
-        let book_path_temp = book
-            .path
-            .join(format!("book.dat~{}", create_random_string(8)));
+        let book_path_temp = temp_path(&book_path);
         loop {
             let book_path_modified_pre_save = if tokio::fs::try_exists(&book_path).await? {
                 tokio::fs::metadata(&book_path).await?.modified().ok()
@@ -648,6 +1105,7 @@ impl LibraryBook {
                 book.last_modified = saved_book.last_modified;
                 book.last_saved_hash = saved_book.last_saved_hash;
             }
+            book.book.set_hash_algorithm(hash_algorithm);
 
             let mut buffer = Vec::new();
             book.book.serialize(&mut buffer)?;
@@ -704,10 +1162,15 @@ impl LibraryBook {
             loaded_translations.insert(t.lock().await.translation.id);
         }
 
-        for translation_metadata in all_book_translations.translations_metadata {
+        for mut translation_metadata in all_book_translations.translations_metadata {
             if !loaded_translations.contains(&translation_metadata.id) {
+                if !book.auto_merge_conflicts {
+                    book.unmerged_conflicts
+                        .append(&mut translation_metadata.conflicting_paths);
+                }
                 merged_translations.push(Arc::new(TracedMutex::new(
-                    LibraryTranslation::load_from_metadata(translation_metadata).await?,
+                    LibraryTranslation::load_from_metadata(translation_metadata, merge_strategy)
+                        .await?,
                 )));
             }
         }
@@ -716,6 +1179,66 @@ impl LibraryBook {
 
         Ok(())
     }
+
+    /// Writes the in-memory book, its translations, user state, chapter
+    /// summaries and footnotes into `dir` as a standalone copy. Unlike
+    /// [`LibraryBook::save`] nothing is merged with what's on disk, and the
+    /// book's own files are left alone. The copy doesn't depend on the
+    /// library's strings dictionary, so it can be read anywhere.
+    pub async fn save_to(&self, dir: &Path) -> anyhow::Result<()> {
+        if tokio::fs::try_exists(dir).await?
+            && tokio::fs::canonicalize(dir).await? == tokio::fs::canonicalize(&self.path).await?
+        {
+            anyhow::bail!("Cannot save a copy of the book over itself: {dir:?}");
+        }
+        tokio::fs::create_dir_all(dir).await?;
+
+        let mut buffer = Vec::new();
+        self.book.serialize_with_dictionary(&mut buffer, None)?;
+        write_file_atomically(&dir.join("book.dat"), &buffer).await?;
+
+        for translation in &self.translations {
+            let translation = translation.lock().await;
+            let mut buffer = Vec::new();
+            translation.translation.serialize(&mut buffer)?;
+            write_file_atomically(&dir.join(translation.file_name()), &buffer).await?;
+        }
+
+        let user_state = load_user_state_from_dir(&self.path).await?;
+        if user_state != BookUserState::default() {
+            persist_user_state(dir, &user_state).await?;
+        }
+
+        for sidecar in [
+            chapter_summaries_path(&self.path),
+            self.path.join(footnotes::FOOTNOTES_FILE),
+        ] {
+            let contents = match tokio::fs::read(&sidecar).await {
+                Ok(contents) => contents,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => return Err(err.into()),
+            };
+            let file_name = sidecar.file_name().expect("sidecar paths have a file name");
+            write_file_atomically(&dir.join(file_name), &contents).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Sibling of `path` to write its new contents to before renaming them over
+/// it, so readers never see a half-written file.
+fn temp_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_owned();
+    file_name.push(format!("~{}", create_random_string(8)));
+    path.with_file_name(file_name)
+}
+
+async fn write_file_atomically(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    let temp_path = temp_path(path);
+    tokio::fs::write(&temp_path, contents).await?;
+    tokio::fs::rename(&temp_path, path).await?;
+    Ok(())
 }
 
 async fn remove_dir_recursive(path: &Path) -> anyhow::Result<()> {
@@ -767,13 +1290,24 @@ impl Library {
         let guid = Uuid::new_v4();
         let book_root = self.library_root.join(guid.to_string());
 
+        let hash_algorithm = self.hash_algorithm();
+        let mut book = Book::create(guid, title, language);
+        book.set_hash_algorithm(hash_algorithm);
+        let dictionary =
+            StringsDictionary::load_current(&self.library_root.join(DICTIONARIES_DIR))?;
+        book.set_strings_dictionary(dictionary.map(Arc::new));
+
         let book = Arc::new(TracedMutex::new(LibraryBook {
             path: book_root,
             last_modified: None,
             last_saved_hash: None,
-            book: Book::create(guid, title, language),
+            book,
             translations: vec![],
             user_state: BookUserState::default(),
+            merge_strategy: MergeStrategy::default(),
+            hash_algorithm,
+            auto_merge_conflicts: true,
+            unmerged_conflicts: Vec::new(),
         }));
 
         let book = self.books_cache.insert(guid, book).await;
@@ -802,4 +1336,117 @@ impl Library {
         info!("Book at {:?} removed completely", book_path);
         Ok(())
     }
+
+    /// Makes an [incomplete](LibraryBookMetadata::incomplete) book loadable
+    /// again. With `remove` set the orphaned directory is deleted like any
+    /// other book. Otherwise the newest surviving conflict copy
+    /// (`book~*.dat`) is promoted to `book.dat`. Without one this fails and
+    /// leaves the directory alone: a placeholder `book.dat` would be newer
+    /// than the real one and win over it once sync delivers it.
+    pub async fn repair_book(&self, uuid: &Uuid, remove: bool) -> anyhow::Result<()> {
+        let book_path = self.library_root.join(uuid.to_string());
+        let metadata = LibraryBookMetadata::load(&book_path).await?;
+        if !metadata.incomplete {
+            anyhow::bail!("Book {uuid} has a book.dat, nothing to repair");
+        }
+        if remove {
+            return self.delete_book(uuid).await;
+        }
+
+        let mut newest = None;
+        for conflict in &metadata.conflicting_paths {
+            let modified = tokio::fs::metadata(conflict).await?.modified()?;
+            if newest.as_ref().is_none_or(|(m, _)| modified > *m) {
+                newest = Some((modified, conflict));
+            }
+        }
+        let Some((_, conflict)) = newest else {
+            anyhow::bail!(
+                "Book {uuid} has no book.dat and no conflict copy to rebuild it from; \
+                 wait for sync to deliver it or remove the book"
+            );
+        };
+        let book_dat = book_path.join("book.dat");
+        tokio::fs::rename(conflict, &book_dat).await?;
+        info!("Promoted {conflict:?} to {book_dat:?}");
+        Ok(())
+    }
+
+    /// Merges the conflict files `uuid` was loaded without (see
+    /// [`Library::set_auto_merge_conflicts`]) into the main book and
+    /// translation files, as loading does otherwise. Returns how many
+    /// conflict files there were.
+    pub async fn merge_conflicts(&self, uuid: &Uuid) -> anyhow::Result<usize> {
+        let book = self.get_book(uuid).await?;
+        let mut book = book.lock().await;
+        // The reload below starts from the files, so flush pending edits.
+        book.save().await?;
+
+        let metadata = LibraryBookMetadata::load(&book.path).await?;
+        let conflicts = conflict_count(&metadata);
+        if conflicts == 0 {
+            book.unmerged_conflicts.clear();
+            return Ok(0);
+        }
+
+        let mut merged =
+            LibraryBook::load_from_metadata_with_strategy(metadata, true, book.merge_strategy)
+                .await?;
+        merged.auto_merge_conflicts = book.auto_merge_conflicts;
+        merged.set_hash_algorithm(book.hash_algorithm);
+        *book = merged;
+        info!("Merged {conflicts} conflict file(s) of book {uuid}");
+        Ok(conflicts)
+    }
+
+    /// Every book's user state (reading position, folder path) as a pretty
+    /// JSON object keyed by book id. Books without any state are left out.
+    pub async fn export_user_state(&self) -> anyhow::Result<String> {
+        let mut states = BTreeMap::new();
+        for book in self.list_books().await? {
+            let state =
+                load_user_state_from_dir(&self.library_root.join(book.id.to_string())).await?;
+            if state != BookUserState::default() {
+                states.insert(book.id, state);
+            }
+        }
+        Ok(serde_json::to_string_pretty(&states)?)
+    }
+
+    /// Writes back the states from [`Library::export_user_state`],
+    /// replacing whatever each book had. Ids without a book in this library
+    /// are skipped. Returns the number of books updated.
+    pub async fn import_user_state(&self, json: &str) -> anyhow::Result<usize> {
+        let states: BTreeMap<Uuid, BookUserState> = serde_json::from_str(json)?;
+        let mut imported = 0;
+        for (uuid, state) in states {
+            let book_path = self.library_root.join(uuid.to_string());
+            if !tokio::fs::try_exists(&book_path).await? {
+                warn!("Skipping user state of unknown book {uuid}");
+                continue;
+            }
+            match self.get_book(&uuid).await {
+                Ok(book) => {
+                    let mut book = book.lock().await;
+                    book.user_state = state;
+                    persist_user_state(&book.path, &book.user_state).await?;
+                }
+                // Incomplete books can't be loaded but still own a state.json.
+                Err(_) => persist_user_state(&book_path, &state).await?,
+            }
+            imported += 1;
+        }
+        Ok(imported)
+    }
+
+    /// Drops a book whose initial save failed. Unlike [`Library::delete_book`]
+    /// this never goes through the trash: the directory holds nothing the
+    /// user could want back.
+    pub(crate) async fn discard_partial_book(&self, uuid: &Uuid) {
+        self.books_cache.remove(uuid).await;
+        let book_path = self.library_root.join(uuid.to_string());
+        if let Err(err) = remove_dir_recursive(&book_path).await {
+            warn!("Failed to clean up partial book at {book_path:?}: {err}");
+        }
+    }
 }
@@ -89,7 +89,11 @@ pub(super) async fn load_user_state_from_dir(path: &Path) -> anyhow::Result<Book
         }
 
         let value: serde_json::Value = serde_json::from_str(&contents)?;
-        if value.get("readingState").is_some() || value.get("folderPath").is_some() {
+        if value.get("readingState").is_some()
+            || value.get("folderPath").is_some()
+            || value.get("translatedTitles").is_some()
+            || value.get("lookupHistory").is_some()
+        {
             return Ok(serde_json::from_value(value)?);
         }
 
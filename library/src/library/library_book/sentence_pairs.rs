@@ -0,0 +1,203 @@
+//! Sentence-aligned CSV export for proofreading: one row per translated
+//! sentence with the original sentence next to the model's full translation.
+//! The same pairs feed the TMX export.
+
+use isolang::Language;
+use uuid::Uuid;
+
+use crate::{book::translation::ParagraphTranslationView, library::Library};
+
+const HEADER: &str = "chapter,paragraph,sentence,original,translation\n";
+
+/// A translated sentence next to the original sentence it came from.
+pub(super) struct SentencePair {
+    pub chapter: usize,
+    pub paragraph: usize,
+    pub sentence: usize,
+    /// `None` when the sentence's words can't be found in order in the
+    /// paragraph text.
+    pub original: Option<String>,
+    pub translation: String,
+}
+
+impl Library {
+    /// CSV of `(chapter, paragraph id, sentence index, original sentence,
+    /// full_translation)` over the latest version of every translated
+    /// paragraph of the `target_language` translation, or of its
+    /// `target_variant`. The original sentence is left blank when it can't
+    /// be recovered.
+    pub async fn export_sentence_pairs_csv(
+        &self,
+        book_id: &Uuid,
+        target_language: &Language,
+        target_variant: Option<&str>,
+    ) -> anyhow::Result<String> {
+        let mut csv = HEADER.to_owned();
+        for pair in self
+            .sentence_pairs(book_id, target_language, target_variant)
+            .await?
+        {
+            let row = [
+                pair.chapter.to_string(),
+                pair.paragraph.to_string(),
+                pair.sentence.to_string(),
+                pair.original.unwrap_or_default(),
+                pair.translation,
+            ];
+            let row: Vec<_> = row.iter().map(|f| csv_field(f)).collect();
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        Ok(csv)
+    }
+
+    /// Every sentence of the latest version of each translated paragraph, in
+    /// reading order. The original sentence is recovered by locating the
+    /// sentence's words in the paragraph text.
+    pub(super) async fn sentence_pairs(
+        &self,
+        book_id: &Uuid,
+        target_language: &Language,
+        target_variant: Option<&str>,
+    ) -> anyhow::Result<Vec<SentencePair>> {
+        let book = self.get_book(book_id).await?;
+        let book = book.lock().await;
+        let translation = book
+            .get_translation_variant(target_language, target_variant)
+            .await
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Book {book_id} has no {} translation",
+                    language_tag(target_language, target_variant)
+                )
+            })?;
+        let translation = translation.lock().await;
+
+        let mut pairs = Vec::new();
+        for chapter in book.book.chapter_views() {
+            for paragraph in chapter.paragraphs() {
+                let Some(view) = translation.paragraph_view(paragraph.id) else {
+                    continue;
+                };
+                let originals = sentence_originals(&paragraph.original_text, &view);
+                for (idx, (sentence, original)) in view.sentences().zip(originals).enumerate() {
+                    pairs.push(SentencePair {
+                        chapter: chapter.idx,
+                        paragraph: paragraph.id,
+                        sentence: idx,
+                        original,
+                        translation: sentence.full_translation.into_owned(),
+                    });
+                }
+            }
+        }
+        Ok(pairs)
+    }
+
+    /// Original text of sentence `sentence` in the latest translation of
+    /// `paragraph_id`, e.g. to translate it again on its own.
+    pub async fn sentence_original(
+        &self,
+        book_id: &Uuid,
+        target_language: &Language,
+        paragraph_id: usize,
+        sentence: usize,
+    ) -> anyhow::Result<String> {
+        let book = self.get_book(book_id).await?;
+        let book = book.lock().await;
+        if paragraph_id >= book.book.paragraphs_count() {
+            anyhow::bail!("Book {book_id} has no paragraph {paragraph_id}");
+        }
+        let original = book
+            .book
+            .paragraph_view(paragraph_id)
+            .original_text
+            .into_owned();
+        let translation = book.get_translation(target_language).await.ok_or_else(|| {
+            anyhow::anyhow!(
+                "Book {book_id} has no {} translation",
+                target_language.to_639_3()
+            )
+        })?;
+        let translation = translation.lock().await;
+        let view = translation
+            .paragraph_view(paragraph_id)
+            .ok_or_else(|| anyhow::anyhow!("Paragraph {paragraph_id} is not translated"))?;
+        sentence_originals(&original, &view)
+            .into_iter()
+            .nth(sentence)
+            .flatten()
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Sentence {sentence} of paragraph {paragraph_id} not found in the original text"
+                )
+            })
+    }
+}
+
+/// The slice of `original` covered by each sentence: from its first word up
+/// to where the next sentence starts, so trailing punctuation stays with it.
+/// `None` for sentences whose words aren't found in order.
+fn sentence_originals(original: &str, paragraph: &ParagraphTranslationView) -> Vec<Option<String>> {
+    let mut cursor = 0;
+    let spans: Vec<Option<(usize, usize)>> = paragraph
+        .sentences()
+        .map(|sentence| {
+            let mut span: Option<(usize, usize)> = None;
+            for word in sentence.words().filter(|w| !w.is_punctuation) {
+                let pos = original[cursor..].find(&*word.original)?;
+                let start = cursor + pos;
+                cursor = start + word.original.len();
+                span = Some((span.map_or(start, |(s, _)| s), cursor));
+            }
+            span
+        })
+        .collect();
+
+    (0..spans.len())
+        .map(|i| {
+            let (start, last_word_end) = spans[i]?;
+            let end = match spans.get(i + 1) {
+                Some(Some((next_start, _))) => *next_start,
+                // Without the next sentence's start, keep the punctuation
+                // right after the last word.
+                Some(None) => original[last_word_end..]
+                    .find(|c: char| c.is_whitespace() || c.is_alphanumeric())
+                    .map_or(original.len(), |len| last_word_end + len),
+                None => original.len(),
+            };
+            Some(original[start..end].trim().to_owned())
+        })
+        .collect()
+}
+
+/// `<lang>[-<variant>]`, as in [`Translation::target_language_tag`].
+///
+/// [`Translation::target_language_tag`]: crate::book::translation::Translation::target_language_tag
+pub(super) fn language_tag(target_language: &Language, target_variant: Option<&str>) -> String {
+    match target_variant {
+        Some(variant) => format!("{}-{variant}", target_language.to_639_3()),
+        None => target_language.to_639_3().to_owned(),
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::csv_field;
+
+    #[test]
+    fn csv_field_quotes_only_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+}
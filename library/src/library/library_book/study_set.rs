@@ -0,0 +1,120 @@
+//! Export of just the words the reader revealed, with enough context to
+//! study or share them without the rest of the translation.
+
+use serde::{Deserialize, Serialize};
+
+use crate::book::translation::{GrammarView, ParagraphTranslationView};
+
+use super::LibraryTranslation;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StudySet {
+    #[serde(rename = "sourceLanguage")]
+    pub source_language: String,
+    /// Target language tag, including the variant if there is one.
+    #[serde(rename = "targetLanguage")]
+    pub target_language: String,
+    pub words: Vec<StudiedWord>,
+}
+
+/// A revealed word of the latest version of a paragraph.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StudiedWord {
+    #[serde(rename = "paragraphId")]
+    pub paragraph_id: usize,
+    /// Index among all words of the paragraph, across sentences.
+    #[serde(rename = "wordIndex")]
+    pub word_index: usize,
+    pub original: String,
+    pub grammar: StudiedGrammar,
+    /// The word's first contextual translation, the one the reader shows.
+    pub translation: Option<String>,
+    /// Full translation of the sentence the word is in.
+    pub sentence: String,
+    #[serde(rename = "revealedAt", skip_serializing_if = "Option::is_none")]
+    pub revealed_at: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StudiedGrammar {
+    #[serde(rename = "originalInitialForm")]
+    pub original_initial_form: String,
+    #[serde(rename = "targetInitialForm")]
+    pub target_initial_form: String,
+    #[serde(rename = "partOfSpeech")]
+    pub part_of_speech: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plurality: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub person: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tense: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub case: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub other: Option<String>,
+}
+
+impl From<&GrammarView<'_>> for StudiedGrammar {
+    fn from(grammar: &GrammarView<'_>) -> Self {
+        Self {
+            original_initial_form: grammar.original_initial_form.clone().into_owned(),
+            target_initial_form: grammar.target_initial_form.clone().into_owned(),
+            part_of_speech: grammar.part_of_speech.clone().into_owned(),
+            plurality: grammar.plurality.clone().map(|s| s.into_owned()),
+            person: grammar.person.clone().map(|s| s.into_owned()),
+            tense: grammar.tense.clone().map(|s| s.into_owned()),
+            case: grammar.case.clone().map(|s| s.into_owned()),
+            other: grammar.other.clone().map(|s| s.into_owned()),
+        }
+    }
+}
+
+impl LibraryTranslation {
+    /// Every visible word of the latest version of each paragraph, in
+    /// reading order. Older versions and hidden words are left out.
+    pub fn export_studied(&self) -> StudySet {
+        let mut words = Vec::new();
+        for paragraph_id in 0..self.translation.paragraphs_count() {
+            if let Some(paragraph) = self.translation.paragraph_view(paragraph_id) {
+                studied_words(paragraph_id, &paragraph, &mut words);
+            }
+        }
+        StudySet {
+            source_language: self.translation.source_language.clone(),
+            target_language: self.translation.target_language_tag().into_owned(),
+            words,
+        }
+    }
+}
+
+fn studied_words(
+    paragraph_id: usize,
+    paragraph: &ParagraphTranslationView<'_>,
+    words: &mut Vec<StudiedWord>,
+) {
+    let visible = paragraph.visible_words();
+    if visible.is_empty() {
+        return;
+    }
+    let mut word_index = 0;
+    for sentence in paragraph.sentences() {
+        for word in sentence.words() {
+            if visible.contains(&word_index) && !word.is_punctuation {
+                words.push(StudiedWord {
+                    paragraph_id,
+                    word_index,
+                    original: word.original.clone().into_owned(),
+                    grammar: (&word.grammar).into(),
+                    translation: word
+                        .contextual_translations()
+                        .next()
+                        .map(|t| t.translation.into_owned()),
+                    sentence: sentence.full_translation.trim().to_owned(),
+                    revealed_at: paragraph.revealed_at().get(&word_index).copied(),
+                });
+            }
+            word_index += 1;
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeSet,
     io::Write,
     str::FromStr,
     sync::Arc,
@@ -9,7 +10,13 @@ use crate::tla_trace::mutex::TracedMutex;
 use isolang::Language;
 
 use crate::{
-    book::{book::Book, serialization::Serializable, translation::Translation, translation_import},
+    book::{
+        book::Book,
+        chapter_summaries::{ChapterSummaries, ChapterSummary, chapter_summaries_path},
+        serialization::{HashAlgorithm, Serializable, Version},
+        translation::{MergeStrategy, Translation},
+        translation_import,
+    },
     library::{Library, LibraryTranslationMetadata, library_book::BookReadingState},
     test_utils::TempDir,
     translator::TranslationModel,
@@ -153,6 +160,7 @@ async fn save_after_load_book_and_translation_changed() {
         let mut tr = Translation::create(source_language.to_639_3(), target_language.to_639_3());
         let initial_pt = translation_import::ParagraphTranslation {
             total_tokens: None,
+            token_usage: None,
             timestamp: 1,
             sentences: vec![translation_import::Sentence {
                 full_translation: "Hola".into(),
@@ -197,6 +205,7 @@ async fn save_after_load_book_and_translation_changed() {
         book.book.title = "Second Edition".into();
         let new_pt = translation_import::ParagraphTranslation {
             total_tokens: None,
+            token_usage: None,
             timestamp: 2,
             sentences: vec![translation_import::Sentence {
                 full_translation: "Hola mundo".into(),
@@ -269,6 +278,7 @@ async fn save_merges_translation_with_concurrent_on_disk_change() {
     let mut tr = Translation::create(source_language.to_639_3(), target_language.to_639_3());
     let pt1 = translation_import::ParagraphTranslation {
         total_tokens: None,
+        token_usage: None,
         timestamp: 1,
         sentences: vec![translation_import::Sentence {
             full_translation: "v1".into(),
@@ -318,6 +328,7 @@ async fn save_merges_translation_with_concurrent_on_disk_change() {
     // In-memory change ts=2
     let mem_pt = translation_import::ParagraphTranslation {
         total_tokens: None,
+        token_usage: None,
         timestamp: 2,
         sentences: vec![translation_import::Sentence {
             full_translation: "mem".into(),
@@ -354,6 +365,7 @@ async fn save_merges_translation_with_concurrent_on_disk_change() {
         };
         let disk_pt = translation_import::ParagraphTranslation {
             total_tokens: None,
+            token_usage: None,
             timestamp: 3,
             sentences: vec![translation_import::Sentence {
                 full_translation: "disk".into(),
@@ -456,6 +468,90 @@ async fn folder_path_roundtrip() {
     );
 }
 
+#[tokio::test]
+async fn lookup_history_keeps_the_latest_lookups() {
+    let temp_dir = TempDir::new("flts_test_book");
+    let library_path = temp_dir.path.join("lib");
+    let library = Library::open(library_path.clone()).await.unwrap();
+
+    let book = library
+        .create_book("Looked up", &Language::from_639_3("eng").unwrap())
+        .await
+        .unwrap();
+    let book_id = {
+        let mut book = book.lock().await;
+        let chapter = book.book.push_chapter(None);
+        book.book.push_paragraph(chapter, "One two.", None);
+        book.book.push_paragraph(chapter, "Three four.", None);
+        book.save().await.unwrap();
+        book.record_word_lookup(0, 0, 0, 2).await.unwrap();
+        book.record_word_lookup(1, 0, 1, 2).await.unwrap();
+        book.record_word_lookup(0, 0, 1, 2).await.unwrap();
+        assert!(book.record_word_lookup(2, 0, 0, 2).await.is_err());
+        book.book.id
+    };
+
+    let book = library.get_book(&book_id).await.unwrap();
+    let mut book = book.lock().await;
+    let history: Vec<_> = book
+        .lookup_history()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|(paragraph, sentence, word, _)| (paragraph, sentence, word))
+        .collect();
+    assert_eq!(history, vec![(1, 0, 1), (0, 0, 1)]);
+}
+
+#[tokio::test]
+async fn dedupe_paragraphs_keeps_paragraph_ids() {
+    let temp_dir = TempDir::new("flts_test_book");
+    let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    let book = library
+        .create_book("Duplicated", &Language::from_639_3("eng").unwrap())
+        .await
+        .unwrap();
+    let mut book = book.lock().await;
+    let chapter = book.book.push_chapter(None);
+    book.book.push_paragraph(chapter, "Same text.", None);
+    book.book.push_paragraph(chapter, "Other text.", None);
+    book.book.push_paragraph(chapter, "Same text.", None);
+    let translation = book
+        .get_or_create_translation(&Language::from_639_3("rus").unwrap())
+        .await;
+    {
+        let mut translation = translation.lock().await;
+        translation.add_paragraph_translation(
+            1,
+            &simple_paragraph("other", 1),
+            TranslationModel::Gemini25Flash,
+        );
+        translation.add_paragraph_translation(
+            2,
+            &simple_paragraph("same", 2),
+            TranslationModel::Gemini25Flash,
+        );
+    }
+
+    assert_eq!(book.dedupe_paragraphs(true).await.unwrap(), 1);
+
+    let ids: Vec<_> = book
+        .book
+        .chapter_view(chapter)
+        .paragraphs()
+        .map(|p| p.id)
+        .collect();
+    assert_eq!(ids, vec![0, 1]);
+    let translation = translation.lock().await;
+    let text = |id| {
+        translation
+            .paragraph_view(id)
+            .map(|p| p.sentence_view(0).full_translation.to_string())
+    };
+    assert_eq!(text(0).as_deref(), Some("same"));
+    assert_eq!(text(1).as_deref(), Some("other"));
+}
+
 #[tokio::test]
 async fn reading_state_prefers_latest_conflict() {
     let temp_dir = TempDir::new("flts_test_book");
@@ -540,6 +636,7 @@ async fn load_from_metadata_no_conflicts() {
     let mut t_main = Translation::create(source_language.to_639_3(), target_language.to_639_3());
     let pt2 = translation_import::ParagraphTranslation {
         total_tokens: None,
+        token_usage: None,
         timestamp: 2,
         sentences: vec![translation_import::Sentence {
             full_translation: "m2".into(),
@@ -572,13 +669,15 @@ async fn load_from_metadata_no_conflicts() {
         id: t_main.id,
         source_langugage: "en".into(),
         target_language: "ru".into(),
+        target_variant: None,
         translated_paragraphs_count: 1,
         main_path: main_path.clone(),
         conflicting_paths: vec![],
+        last_modified: None,
     };
 
     // Act
-    let loaded = super::LibraryTranslation::load_from_metadata(meta)
+    let loaded = super::LibraryTranslation::load_from_metadata(meta, MergeStrategy::default())
         .await
         .unwrap();
 
@@ -608,12 +707,17 @@ async fn load_from_metadata_merges_conflicts_and_persists() {
         source_language.to_639_3(),
         target_language.to_639_3()
     ));
-    let conflict2 = dir.join("translation_en_ru.conflict2.dat");
+    let conflict2 = dir.join(format!(
+        "translation_{}_{}.conflict2.dat",
+        source_language.to_639_3(),
+        target_language.to_639_3()
+    ));
 
     // main: ts=2
     let mut t_main = Translation::create(source_language.to_639_3(), target_language.to_639_3());
     let pt2 = translation_import::ParagraphTranslation {
         total_tokens: None,
+        token_usage: None,
         timestamp: 2,
         sentences: vec![translation_import::Sentence {
             full_translation: "m2".into(),
@@ -646,6 +750,7 @@ async fn load_from_metadata_merges_conflicts_and_persists() {
     let mut t_c1 = Translation::create(source_language.to_639_3(), target_language.to_639_3());
     let pt1 = translation_import::ParagraphTranslation {
         total_tokens: None,
+        token_usage: None,
         timestamp: 1,
         sentences: vec![translation_import::Sentence {
             full_translation: "c1".into(),
@@ -675,9 +780,10 @@ async fn load_from_metadata_merges_conflicts_and_persists() {
     }
 
     // conflict2: ts=3
-    let mut t_c2 = Translation::create("en", "ru");
+    let mut t_c2 = Translation::create(source_language.to_639_3(), target_language.to_639_3());
     let pt3 = translation_import::ParagraphTranslation {
         total_tokens: None,
+        token_usage: None,
         timestamp: 3,
         sentences: vec![translation_import::Sentence {
             full_translation: "c3".into(),
@@ -708,15 +814,17 @@ async fn load_from_metadata_merges_conflicts_and_persists() {
 
     let meta = LibraryTranslationMetadata {
         id: t_main.id,
-        source_langugage: "en".into(),
-        target_language: "ru".into(),
+        source_langugage: source_language.to_639_3().into(),
+        target_language: target_language.to_639_3().into(),
+        target_variant: None,
         translated_paragraphs_count: 1,
         main_path: main_path.clone(),
         conflicting_paths: vec![conflict1.clone(), conflict2.clone()],
+        last_modified: None,
     };
 
     // Act
-    let loaded = super::LibraryTranslation::load_from_metadata(meta)
+    let loaded = super::LibraryTranslation::load_from_metadata(meta, MergeStrategy::default())
         .await
         .unwrap();
 
@@ -883,6 +991,80 @@ async fn library_book_load_from_metadata_keeps_main_if_newest_and_cleans() {
     assert!(!conflict_path.exists());
 }
 
+#[tokio::test]
+async fn conflicts_wait_for_merge_conflicts_when_auto_merge_is_off() {
+    use std::{thread::sleep, time::Duration};
+
+    let temp_dir = TempDir::new("flts_test_book");
+    let library_path = temp_dir.path.join("lib");
+    let book_id = {
+        let library = Library::open(library_path.clone()).await.unwrap();
+        let book = library
+            .create_book("Main", &Language::from_639_3("eng").unwrap())
+            .await
+            .unwrap();
+        let mut book = book.lock().await;
+        book.save().await.unwrap();
+        book.book.id
+    };
+
+    let book_dir = library_path.join(book_id.to_string());
+    let book_file = book_dir.join("book.dat");
+    let conflict_path = book_dir.join("book.syncconflict-newer.dat");
+    sleep(Duration::from_millis(5));
+    let mut conflict_book = Book::deserialize(&mut std::io::Cursor::new(
+        std::fs::read(&book_file).unwrap(),
+    ))
+    .unwrap();
+    conflict_book.title = "From Conflict".into();
+    let mut buffer = Vec::new();
+    conflict_book.serialize(&mut buffer).unwrap();
+    std::fs::write(&conflict_path, buffer).unwrap();
+
+    let summaries_path = chapter_summaries_path(&book_dir);
+    ChapterSummaries::empty_for(book_id, 1)
+        .save(&summaries_path)
+        .await
+        .unwrap();
+    let mut conflict_summaries = ChapterSummaries::empty_for(book_id, 1);
+    conflict_summaries.entries[0] = ChapterSummary {
+        generated: true,
+        model: TranslationModel::Gemini25Flash,
+        timestamp: 20,
+        text: "From conflict".into(),
+    };
+    let summaries_conflict_path = book_dir.join("chapter_summaries~abcd1234.dat");
+    let mut buffer = Vec::new();
+    conflict_summaries.serialize(&mut buffer).unwrap();
+    std::fs::write(&summaries_conflict_path, buffer).unwrap();
+
+    let library = Library::open(library_path.clone()).await.unwrap();
+    library.set_auto_merge_conflicts(false);
+    {
+        let book = library.get_book(&book_id).await.unwrap();
+        let book = book.lock().await;
+        assert_eq!(book.book.title, "Main");
+        let mut conflicts = book.conflicting_paths().to_vec();
+        conflicts.sort();
+        assert_eq!(
+            conflicts,
+            [conflict_path.clone(), summaries_conflict_path.clone()]
+        );
+    }
+    assert!(conflict_path.exists());
+    assert!(summaries_conflict_path.exists());
+
+    assert_eq!(library.merge_conflicts(&book_id).await.unwrap(), 2);
+    assert!(!conflict_path.exists());
+    assert!(!summaries_conflict_path.exists());
+    let summaries = ChapterSummaries::load(&summaries_path).await.unwrap();
+    assert_eq!(summaries.entries[0].text, "From conflict");
+    let book = library.get_book(&book_id).await.unwrap();
+    let book = book.lock().await;
+    assert_eq!(book.book.title, "From Conflict");
+    assert!(book.conflicting_paths().is_empty());
+}
+
 #[tokio::test]
 async fn delete_book_removes_directory() {
     let temp_dir = TempDir::new("flts_test_book");
@@ -915,6 +1097,7 @@ async fn delete_book_removes_directory() {
 fn simple_paragraph(text: &str, timestamp: u64) -> translation_import::ParagraphTranslation {
     translation_import::ParagraphTranslation {
         total_tokens: None,
+        token_usage: None,
         timestamp,
         sentences: vec![translation_import::Sentence {
             full_translation: text.into(),
@@ -991,6 +1174,31 @@ async fn book_with_saved_translation(
     (book, tr_path)
 }
 
+#[tokio::test]
+async fn library_hash_algorithm_applies_to_book_and_translation_files() {
+    let temp_dir = TempDir::new("flts_test_book");
+    let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    library.set_hash_algorithm(HashAlgorithm::Sha256);
+    let (book, tr_path) = book_with_saved_translation(&library, "Checksums").await;
+    let book_path = book.lock().await.path.join("book.dat");
+
+    let saved_book = Book::deserialize(&mut std::fs::File::open(&book_path).unwrap()).unwrap();
+    assert_eq!(saved_book.hash_algorithm(), HashAlgorithm::Sha256);
+    let saved_translation =
+        Translation::deserialize(&mut std::fs::File::open(&tr_path).unwrap()).unwrap();
+    assert_eq!(saved_translation.hash_algorithm(), HashAlgorithm::Sha256);
+
+    // A library with the default setting switches the book back on save.
+    let other = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    let book = other.get_book(&saved_book.id).await.unwrap();
+    let mut book = book.lock().await;
+    assert_eq!(book.hash_algorithm(), HashAlgorithm::Fnv1);
+    book.book.push_chapter(Some("New"));
+    book.save().await.unwrap();
+    let saved_book = Book::deserialize(&mut std::fs::File::open(&book_path).unwrap()).unwrap();
+    assert_eq!(saved_book.hash_algorithm(), HashAlgorithm::Fnv1);
+}
+
 #[tokio::test]
 async fn serialize_is_deterministic() {
     // The echo gate assumes re-serializing identical state yields identical
@@ -1032,6 +1240,22 @@ async fn save_clears_changed_flag() {
     );
 }
 
+#[tokio::test]
+async fn is_dirty_tracks_book_edits_until_saved() {
+    let temp_dir = TempDir::new("flts_test_book");
+    let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    let (book, _tr_path) = book_with_saved_translation(&library, "Dirty").await;
+
+    let mut book = book.lock().await;
+    assert!(!book.is_dirty().await);
+
+    book.book.push_chapter(Some("Added later"));
+    assert!(book.is_dirty().await);
+
+    book.save().await.unwrap();
+    assert!(!book.is_dirty().await);
+}
+
 #[tokio::test]
 async fn reload_translations_skips_same_content_echo() {
     let temp_dir = TempDir::new("flts_test_book");
@@ -1129,3 +1353,955 @@ async fn reload_book_saves_on_external_change() {
     let saved = book_a.lock().await.reload_book(future).await.unwrap();
     assert!(saved, "a genuine external book change must trigger a save");
 }
+
+#[tokio::test]
+async fn reload_book_refreshes_in_memory_book_on_external_change() {
+    let temp_dir = TempDir::new("flts_test_book");
+    let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    let (book, _tr_path) = book_with_saved_translation(&library, "Synced").await;
+    let book_file = book.lock().await.path.join("book.dat");
+
+    // Another device adds a chapter and renames the book.
+    {
+        let f = std::fs::File::open(&book_file).unwrap();
+        let mut reader = std::io::BufReader::new(f);
+        let mut on_disk = Book::deserialize(&mut reader).unwrap();
+        on_disk.title = "Synced (edited)".to_owned();
+        on_disk.push_chapter(Some("From another device"));
+        let wf = std::fs::File::create(&book_file).unwrap();
+        let mut writer = std::io::BufWriter::new(wf);
+        on_disk.serialize(&mut writer).unwrap();
+    }
+    let future = bump_mtime_future(&book_file);
+
+    let mut book = book.lock().await;
+    assert!(book.reload_book(future).await.unwrap());
+    assert_eq!(book.book.title, "Synced (edited)");
+    assert_eq!(book.book.chapter_count(), 1);
+}
+
+#[tokio::test]
+async fn get_book_fresh_reloads_externally_modified_book() {
+    let temp_dir = TempDir::new("flts_test_book");
+    let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    let (book, _tr_path) = book_with_saved_translation(&library, "Cached").await;
+    let (book_id, book_file) = {
+        let book = book.lock().await;
+        (book.book.id, book.path.join("book.dat"))
+    };
+
+    // A sync replaces book.dat; no file-watcher event is delivered.
+    {
+        let f = std::fs::File::open(&book_file).unwrap();
+        let mut on_disk = Book::deserialize(&mut std::io::BufReader::new(f)).unwrap();
+        on_disk.title = "Cached (synced)".to_owned();
+        let wf = std::fs::File::create(&book_file).unwrap();
+        on_disk.serialize(&mut std::io::BufWriter::new(wf)).unwrap();
+    }
+    bump_mtime_future(&book_file);
+
+    let cached = library.get_book(&book_id).await.unwrap();
+    assert_eq!(cached.lock().await.book.title, "Cached");
+
+    let fresh = library.get_book_fresh(&book_id).await.unwrap();
+    assert_eq!(fresh.lock().await.book.title, "Cached (synced)");
+    // Nothing changed since, so the next fresh read is a no-op.
+    assert!(!fresh.lock().await.refresh_from_disk().await.unwrap());
+}
+
+#[tokio::test]
+async fn missing_book_dat_is_listed_incomplete_and_repaired() {
+    let temp_dir = TempDir::new("flts_test_book");
+    let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    let (book, _tr_path) = book_with_saved_translation(&library, "Half Synced").await;
+    let (book_id, book_file) = {
+        let book = book.lock().await;
+        (book.book.id, book.path.join("book.dat"))
+    };
+    drop(book);
+    library.books_cache.remove(&book_id).await;
+    let book_bytes = std::fs::read(&book_file).unwrap();
+    std::fs::remove_file(&book_file).unwrap();
+
+    let books = library.list_books().await.unwrap();
+    assert_eq!(books.len(), 1);
+    assert!(books[0].incomplete);
+    assert_eq!(books[0].id, book_id);
+    assert_eq!(books[0].translations_metadata.len(), 1);
+
+    // Nothing to restore from: no placeholder is written.
+    assert!(library.repair_book(&book_id, false).await.is_err());
+    assert!(!book_file.exists());
+
+    let conflict = book_file.with_file_name("book~recovered.dat");
+    std::fs::write(&conflict, &book_bytes).unwrap();
+    library.repair_book(&book_id, false).await.unwrap();
+
+    assert!(!conflict.exists());
+    let books = library.list_books().await.unwrap();
+    assert!(!books[0].incomplete);
+    let book = library.get_book(&book_id).await.unwrap();
+    let book = book.lock().await;
+    assert_eq!(book.book.title, "Half Synced");
+    drop(book);
+
+    assert!(library.repair_book(&book_id, false).await.is_err());
+}
+
+#[tokio::test]
+async fn target_variants_are_stored_side_by_side() {
+    let temp_dir = TempDir::new("flts_test_variants");
+    let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    let por = Language::from_639_3("por").unwrap();
+    let book = library
+        .create_book("Variants", &Language::from_639_3("eng").unwrap())
+        .await
+        .unwrap();
+    let book_id = {
+        let mut book = book.lock().await;
+        for (variant, text) in [
+            (None, "ônibus"),
+            (Some("BR"), "ônibus"),
+            (Some("PT"), "autocarro"),
+        ] {
+            book.get_or_create_translation_variant(&por, variant)
+                .await
+                .unwrap()
+                .lock()
+                .await
+                .add_paragraph_translation(
+                    0,
+                    &simple_paragraph(text, 1),
+                    TranslationModel::Gemini25Flash,
+                );
+        }
+        assert!(
+            book.get_or_create_translation_variant(&por, Some("pt_PT"))
+                .await
+                .is_err()
+        );
+        book.save().await.unwrap();
+        assert!(book.path.join("translation_eng_por-BR.dat").exists());
+        assert!(book.path.join("translation_eng_por-PT.dat").exists());
+        assert!(book.path.join("translation_eng_por.dat").exists());
+        book.book.id
+    };
+    drop(book);
+    library.books_cache.remove(&book_id).await;
+
+    let mut variants: Vec<_> = library.list_books().await.unwrap()[0]
+        .translations_metadata
+        .iter()
+        .map(|t| (t.target_language.clone(), t.target_variant.clone()))
+        .collect();
+    variants.sort();
+    assert_eq!(
+        variants,
+        vec![
+            ("por".to_owned(), None),
+            ("por".to_owned(), Some("BR".to_owned())),
+            ("por".to_owned(), Some("PT".to_owned())),
+        ]
+    );
+
+    let book = library.get_book(&book_id).await.unwrap();
+    let book = book.lock().await;
+    let pt = book
+        .get_translation_variant(&por, Some("PT"))
+        .await
+        .unwrap();
+    {
+        let pt = pt.lock().await;
+        let paragraph = pt.paragraph_view(0).unwrap();
+        assert_eq!(
+            paragraph.sentences().next().unwrap().full_translation,
+            "autocarro"
+        );
+    }
+    let base = book.get_translation(&por).await.unwrap();
+    assert_eq!(base.lock().await.translation.target_variant, None);
+}
+
+#[tokio::test]
+async fn user_state_export_round_trips_into_another_library() {
+    let temp_dir = TempDir::new("flts_test_user_state");
+    let other_dir = TempDir::new("flts_test_user_state_other");
+    let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    let eng = Language::from_639_3("eng").unwrap();
+    let read = library.create_book("Read", &eng).await.unwrap();
+    let untouched = library.create_book("Untouched", &eng).await.unwrap();
+    let read_id = {
+        let mut book = read.lock().await;
+        book.save().await.unwrap();
+        // The other library gets the book before it has any state.
+        book.save_to(&other_dir.path.join("lib").join(book.book.id.to_string()))
+            .await
+            .unwrap();
+        book.update_reading_state(BookReadingState {
+            chapter_id: 2,
+            paragraph_id: 17,
+            page_offset: 1,
+        })
+        .await
+        .unwrap();
+        book.update_folder_path(vec!["Fiction".into()])
+            .await
+            .unwrap();
+        book.book.id
+    };
+    untouched.lock().await.save().await.unwrap();
+
+    let exported = library.export_user_state().await.unwrap();
+    let states: serde_json::Value = serde_json::from_str(&exported).unwrap();
+    assert_eq!(states.as_object().unwrap().len(), 1);
+
+    let other = Library::open(other_dir.path.join("lib")).await.unwrap();
+    let copy = other.get_book(&read_id).await.unwrap();
+    assert_eq!(copy.lock().await.reading_state().await.unwrap(), None);
+    assert_eq!(other.import_user_state(&exported).await.unwrap(), 1);
+
+    let mut book = copy.lock().await;
+    assert_eq!(
+        book.folder_path().await.unwrap(),
+        vec!["Fiction".to_owned()]
+    );
+    assert_eq!(
+        book.reading_state().await.unwrap(),
+        Some(BookReadingState {
+            chapter_id: 2,
+            paragraph_id: 17,
+            page_offset: 1,
+        })
+    );
+    drop(book);
+
+    let unknown = exported.replace(&read_id.to_string(), &uuid::Uuid::new_v4().to_string());
+    assert_eq!(other.import_user_state(&unknown).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn sentence_pairs_csv_aligns_original_sentences() {
+    let temp_dir = TempDir::new("flts_test_sentence_pairs");
+    let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    let eng = Language::from_639_3("eng").unwrap();
+    let book_id = library
+        .create_book_plain(
+            "Pairs",
+            "Hola, mundo. Adiós.\nSin traducir.",
+            &Language::from_639_3("spa").unwrap(),
+        )
+        .await
+        .unwrap()
+        .id;
+
+    let word = |text: &str, is_punctuation: bool| translation_import::Word {
+        is_punctuation,
+        ..simple_paragraph(text, 1).sentences[0].words[0].clone()
+    };
+    let pt = translation_import::ParagraphTranslation {
+        total_tokens: None,
+        token_usage: None,
+        timestamp: 1,
+        sentences: vec![
+            translation_import::Sentence {
+                full_translation: "Hello, world.".into(),
+                words: vec![
+                    word("Hola", false),
+                    word(",", true),
+                    word("mundo", false),
+                    word(".", true),
+                ],
+            },
+            translation_import::Sentence {
+                full_translation: "Bye, \"friend\".".into(),
+                words: vec![word("Adios", false)],
+            },
+        ],
+    };
+    {
+        let book = library.get_book(&book_id).await.unwrap();
+        let mut book = book.lock().await;
+        book.get_or_create_translation(&eng)
+            .await
+            .lock()
+            .await
+            .add_paragraph_translation(0, &pt, TranslationModel::Gemini25Flash);
+    }
+
+    let csv = library
+        .export_sentence_pairs_csv(&book_id, &eng, None)
+        .await
+        .unwrap();
+    // The second sentence's word doesn't match the text ("Adios" vs
+    // "Adiós"), so its original is left blank; the untranslated paragraph
+    // has no rows.
+    assert_eq!(
+        csv,
+        "chapter,paragraph,sentence,original,translation\n\
+         0,0,0,\"Hola, mundo.\",\"Hello, world.\"\n\
+         0,0,1,,\"Bye, \"\"friend\"\".\"\n"
+    );
+}
+
+#[tokio::test]
+async fn sentence_pairs_csv_exports_a_variant_translation() {
+    let temp_dir = TempDir::new("flts_test_sentence_pairs_variant");
+    let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    let por = Language::from_639_3("por").unwrap();
+    let book_id = library
+        .create_book_plain("Pairs", "Hello", &Language::from_639_3("eng").unwrap())
+        .await
+        .unwrap()
+        .id;
+    let pt = translation_import::ParagraphTranslation {
+        sentences: vec![translation_import::Sentence {
+            full_translation: "Olá".into(),
+            ..simple_paragraph("Hello", 1).sentences[0].clone()
+        }],
+        ..simple_paragraph("Hello", 1)
+    };
+    {
+        let book = library.get_book(&book_id).await.unwrap();
+        let mut book = book.lock().await;
+        book.get_or_create_translation_variant(&por, Some("BR"))
+            .await
+            .unwrap()
+            .lock()
+            .await
+            .add_paragraph_translation(0, &pt, TranslationModel::Gemini25Flash);
+    }
+
+    let csv = library
+        .export_sentence_pairs_csv(&book_id, &por, Some("BR"))
+        .await
+        .unwrap();
+    assert_eq!(
+        csv,
+        "chapter,paragraph,sentence,original,translation\n0,0,0,Hello,Olá\n"
+    );
+    // The variant is not the base language's translation.
+    let err = library
+        .export_sentence_pairs_csv(&book_id, &por, None)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("Book {book_id} has no por translation")
+    );
+}
+
+#[tokio::test]
+async fn translation_progress_resumes_interrupted_paragraphs_first() {
+    let temp_dir = TempDir::new("flts_test_translation_progress");
+    let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    let book = library
+        .create_book("Progress", &Language::from_639_3("eng").unwrap())
+        .await
+        .unwrap();
+    let mut book = book.lock().await;
+    book.save().await.unwrap();
+    let progress_path = book.path.join("translation_progress.json");
+
+    // First run: paragraph 2 finishes, 3 is interrupted mid-flight.
+    {
+        let progress = book.translation_progress("rus").await.unwrap();
+        assert_eq!(progress.plan(vec![1, 2, 3, 4]).await.unwrap(), [1, 2, 3, 4]);
+        progress.mark_in_flight(2).await.unwrap();
+        progress.mark_done(2).await.unwrap();
+        progress.mark_in_flight(3).await.unwrap();
+        // Per-paragraph updates wait for the next write interval.
+        let on_disk = std::fs::read_to_string(&progress_path).unwrap();
+        assert!(!on_disk.contains("done"));
+        let reread = book.translation_progress("rus").await.unwrap();
+        assert!(reread.snapshot().await.in_flight.is_empty());
+        progress.flush().await.unwrap();
+    }
+    assert!(progress_path.exists());
+
+    // Second run: 2 is translated now; 3 is retried before the queued ones.
+    let progress = book.translation_progress("rus").await.unwrap();
+    assert_eq!(progress.snapshot().await.in_flight, BTreeSet::from([3]));
+    assert_eq!(progress.plan(vec![1, 3, 4]).await.unwrap(), [3, 1, 4]);
+    for p in [3, 1] {
+        progress.mark_in_flight(p).await.unwrap();
+        progress.mark_done(p).await.unwrap();
+    }
+    progress.mark_in_flight(4).await.unwrap();
+    progress.mark_failed(4).await.unwrap();
+    assert!(!progress.finish().await.unwrap());
+    assert_eq!(progress.snapshot().await.queued, BTreeSet::from([4]));
+
+    progress.mark_in_flight(4).await.unwrap();
+    progress.mark_done(4).await.unwrap();
+    assert!(progress.finish().await.unwrap());
+    assert!(!progress_path.exists());
+}
+
+#[tokio::test]
+async fn failed_paragraphs_are_kept_per_target_until_cleared() {
+    let temp_dir = TempDir::new("flts_test_failed_paragraphs");
+    let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    let book = library
+        .create_book("Dead letters", &Language::from_639_3("eng").unwrap())
+        .await
+        .unwrap();
+    let mut book = book.lock().await;
+    book.save().await.unwrap();
+    let failed_path = book.path.join("failed_paragraphs.json");
+
+    book.set_failed_paragraphs("rus", &BTreeSet::from([3, 7]))
+        .await
+        .unwrap();
+    book.set_failed_paragraphs("deu", &BTreeSet::from([1]))
+        .await
+        .unwrap();
+    assert_eq!(
+        book.failed_paragraphs("rus").await.unwrap(),
+        BTreeSet::from([3, 7])
+    );
+
+    book.set_failed_paragraphs("rus", &BTreeSet::new())
+        .await
+        .unwrap();
+    assert!(book.failed_paragraphs("rus").await.unwrap().is_empty());
+    assert_eq!(
+        book.failed_paragraphs("deu").await.unwrap(),
+        BTreeSet::from([1])
+    );
+    book.set_failed_paragraphs("deu", &BTreeSet::new())
+        .await
+        .unwrap();
+    assert!(!failed_path.exists());
+}
+
+#[tokio::test]
+async fn footnotes_sidecar_attaches_notes_to_anchor_words() {
+    let temp_dir = TempDir::new("flts_test_footnotes");
+    let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    let book = library
+        .create_book("Footnotes", &Language::from_639_3("eng").unwrap())
+        .await
+        .unwrap();
+    let book = book.lock().await;
+
+    let footnotes = std::collections::BTreeMap::from([(
+        3,
+        vec![crate::epub_importer::EpubFootnote {
+            anchor_word: "Hello".into(),
+            text: "A greeting.".into(),
+        }],
+    )]);
+    book.save_footnotes(&footnotes).await.unwrap();
+
+    assert!(book.paragraph_footnotes(0).await.unwrap().is_empty());
+
+    let mut paragraph = simple_paragraph("hello", 1);
+    let attached = book.apply_footnotes(3, &mut paragraph).await.unwrap();
+    assert_eq!(attached, 1);
+    assert_eq!(
+        paragraph.sentences[0].words[0].note.as_deref(),
+        Some("A greeting.")
+    );
+}
+
+#[tokio::test]
+async fn upgrade_translation_formats_rewrites_v1_files_as_v2() {
+    let temp_dir = TempDir::new("flts_test_upgrade");
+    let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    let (book, tr_path) = book_with_saved_translation(&library, "Old Format").await;
+    let book_id = book.lock().await.book.id;
+    drop(book);
+    library.books_cache.remove(&book_id).await;
+
+    let stored_version = |path: &std::path::Path| {
+        let f = std::fs::File::open(path).unwrap();
+        Translation::stored_version(&mut std::io::BufReader::new(f)).unwrap()
+    };
+    let full_translation = |tr: &Translation| {
+        let view = tr.paragraph_view(0).unwrap();
+        let sentence = view.sentences().next().unwrap();
+        sentence.full_translation.into_owned()
+    };
+
+    // Rewrite the saved translation as a v1 file.
+    let original = {
+        let f = std::fs::File::open(&tr_path).unwrap();
+        Translation::deserialize(&mut std::io::BufReader::new(f)).unwrap()
+    };
+    {
+        let wf = std::fs::File::create(&tr_path).unwrap();
+        original
+            .serialize_v1(&mut std::io::BufWriter::new(wf))
+            .unwrap();
+    }
+    assert_eq!(stored_version(&tr_path), Version::V1);
+
+    assert_eq!(library.upgrade_translation_formats().await.unwrap(), 1);
+    assert_eq!(stored_version(&tr_path), Version::V2);
+    let upgraded = {
+        let f = std::fs::File::open(&tr_path).unwrap();
+        Translation::deserialize(&mut std::io::BufReader::new(f)).unwrap()
+    };
+    assert_eq!(full_translation(&upgraded), full_translation(&original));
+    assert_eq!(upgraded.version_count(), original.version_count());
+
+    // Nothing is left to upgrade on the second run.
+    assert_eq!(library.upgrade_translation_formats().await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn tmx_export_pairs_recovered_sentences() {
+    let temp_dir = TempDir::new("flts_test_tmx");
+    let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    let eng = Language::from_639_3("eng").unwrap();
+    let book_id = library
+        .create_book_plain(
+            "Memory",
+            "Hola & mundo. Adiós.",
+            &Language::from_639_3("spa").unwrap(),
+        )
+        .await
+        .unwrap()
+        .id;
+
+    let word = |text: &str| simple_paragraph(text, 1).sentences[0].words[0].clone();
+    let pt = translation_import::ParagraphTranslation {
+        total_tokens: None,
+        token_usage: None,
+        timestamp: 1,
+        sentences: vec![
+            translation_import::Sentence {
+                full_translation: "Hello <and> world.".into(),
+                words: vec![word("Hola"), word("mundo")],
+            },
+            translation_import::Sentence {
+                full_translation: "Bye.".into(),
+                words: vec![word("Adios")],
+            },
+        ],
+    };
+    {
+        let book = library.get_book(&book_id).await.unwrap();
+        let mut book = book.lock().await;
+        book.get_or_create_translation(&eng)
+            .await
+            .lock()
+            .await
+            .add_paragraph_translation(0, &pt, TranslationModel::Gemini25Flash);
+    }
+
+    let tmx = library.export_tmx(&book_id, &eng, None).await.unwrap();
+    assert!(tmx.contains(r#"srclang="es""#));
+    // Only the sentence whose original was found becomes a unit.
+    assert_eq!(tmx.matches("<tu ").count(), 1);
+    assert!(tmx.contains(
+        "    <tu tuid=\"0-0\">\n      \
+         <tuv xml:lang=\"es\"><seg>Hola &amp; mundo.</seg></tuv>\n      \
+         <tuv xml:lang=\"en\"><seg>Hello &lt;and&gt; world.</seg></tuv>\n    \
+         </tu>\n"
+    ));
+}
+
+#[tokio::test]
+async fn tmx_export_tags_a_variant_translation() {
+    let temp_dir = TempDir::new("flts_test_tmx_variant");
+    let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    let por = Language::from_639_3("por").unwrap();
+    let book_id = library
+        .create_book_plain("Memory", "Hello", &Language::from_639_3("eng").unwrap())
+        .await
+        .unwrap()
+        .id;
+    {
+        let book = library.get_book(&book_id).await.unwrap();
+        let mut book = book.lock().await;
+        book.get_or_create_translation_variant(&por, Some("BR"))
+            .await
+            .unwrap()
+            .lock()
+            .await
+            .add_paragraph_translation(
+                0,
+                &simple_paragraph("Hello", 1),
+                TranslationModel::Gemini25Flash,
+            );
+    }
+
+    let tmx = library
+        .export_tmx(&book_id, &por, Some("BR"))
+        .await
+        .unwrap();
+    assert!(tmx.contains(r#"<tuv xml:lang="pt-BR"><seg>Hello</seg></tuv>"#));
+    assert!(library.export_tmx(&book_id, &por, None).await.is_err());
+}
+
+#[tokio::test]
+async fn tmx_import_seeds_fully_covered_paragraphs() {
+    let temp_dir = TempDir::new("flts_test_tmx_import");
+    let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    let eng = Language::from_639_3("eng").unwrap();
+    let book_id = library
+        .create_book_plain(
+            "Memory",
+            "Hola,  mundo. ¿Qué tal?\nAdiós.\nSin pareja.",
+            &Language::from_639_3("spa").unwrap(),
+        )
+        .await
+        .unwrap()
+        .id;
+
+    let tmx = r#"<?xml version="1.0" encoding="UTF-8"?>
+<tmx version="1.4">
+  <header srclang="es" segtype="sentence"/>
+  <body>
+    <tu><tuv xml:lang="es-ES"><seg>Hola, mundo.</seg></tuv><tuv xml:lang="en"><seg>Hello, world.</seg></tuv></tu>
+    <tu><tuv xml:lang="es"><seg>¿Qué <bpt i="1"/>tal?</seg></tuv><tuv xml:lang="en"><seg>How &amp; you?</seg></tuv></tu>
+    <tu><tuv lang="spa"><seg>Adiós.</seg></tuv><tuv lang="eng"><seg>Bye.</seg></tuv></tu>
+    <tu><tuv xml:lang="es"><seg>Sin</seg></tuv><tuv xml:lang="en"><seg>Without</seg></tuv></tu>
+    <tu><tuv xml:lang="es"><seg>Sin pareja.</seg></tuv></tu>
+  </body>
+</tmx>"#;
+
+    let report = library.import_tmx(&book_id, &eng, None, tmx).await.unwrap();
+    // "Sin" only covers part of its paragraph and the last unit has no
+    // English segment.
+    assert_eq!(
+        report,
+        super::TmxImportReport {
+            applied: 3,
+            unmatched: 2,
+            paragraphs: 2,
+        }
+    );
+
+    {
+        let book = library.get_book(&book_id).await.unwrap();
+        let book = book.lock().await;
+        let translation = book.get_translation(&eng).await.unwrap();
+        let translation = translation.lock().await;
+        let first = translation.paragraph_view(0).unwrap();
+        let sentences: Vec<_> = first
+            .sentences()
+            .map(|s| s.full_translation.into_owned())
+            .collect();
+        assert_eq!(sentences, vec!["Hello, world.", "How & you?"]);
+        assert!(translation.paragraph_view(1).is_some());
+        assert!(translation.paragraph_view(2).is_none());
+    }
+
+    // Translated paragraphs are left alone on a second import.
+    let report = library.import_tmx(&book_id, &eng, None, tmx).await.unwrap();
+    assert_eq!(report.paragraphs, 0);
+    assert_eq!(report.unmatched, 5);
+}
+
+#[tokio::test]
+async fn tmx_import_seeds_a_variant_translation() {
+    let temp_dir = TempDir::new("flts_test_tmx_import_variant");
+    let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    let por = Language::from_639_3("por").unwrap();
+    let book_id = library
+        .create_book_plain("Memory", "Bus.", &Language::from_639_3("eng").unwrap())
+        .await
+        .unwrap()
+        .id;
+    let tmx = r#"<tmx version="1.4"><body>
+    <tu><tuv xml:lang="en"><seg>Bus.</seg></tuv><tuv xml:lang="pt-BR"><seg>Ônibus.</seg></tuv></tu>
+</body></tmx>"#;
+
+    let report = library
+        .import_tmx(&book_id, &por, Some("BR"), tmx)
+        .await
+        .unwrap();
+    assert_eq!(report.paragraphs, 1);
+
+    let book = library.get_book(&book_id).await.unwrap();
+    let book = book.lock().await;
+    assert!(book.get_translation(&por).await.is_none());
+    let translation = book
+        .get_translation_variant(&por, Some("BR"))
+        .await
+        .unwrap();
+    let translation = translation.lock().await;
+    let sentences: Vec<_> = translation
+        .paragraph_view(0)
+        .unwrap()
+        .sentences()
+        .map(|s| s.full_translation.into_owned())
+        .collect();
+    assert_eq!(sentences, vec!["Ônibus."]);
+}
+
+#[tokio::test]
+async fn replace_sentence_adds_a_version_with_one_sentence_swapped() {
+    let temp_dir = TempDir::new("flts_test_replace_sentence");
+    let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    let eng = Language::from_639_3("eng").unwrap();
+    let book_id = library
+        .create_book_plain(
+            "Replace",
+            "Hola mundo. Adiós amigo.",
+            &Language::from_639_3("spa").unwrap(),
+        )
+        .await
+        .unwrap()
+        .id;
+
+    let sentence = |full_translation: &str, words: &[&str]| translation_import::Sentence {
+        full_translation: full_translation.into(),
+        words: words
+            .iter()
+            .map(|w| simple_paragraph(w, 1).sentences[0].words[0].clone())
+            .collect(),
+    };
+    let pt = translation_import::ParagraphTranslation {
+        total_tokens: Some(10),
+        token_usage: None,
+        timestamp: 1,
+        sentences: vec![
+            sentence("Hello world.", &["Hola", "mundo"]),
+            sentence("Wrong.", &["Adiós", "amigo"]),
+        ],
+    };
+    {
+        let book = library.get_book(&book_id).await.unwrap();
+        let mut book = book.lock().await;
+        book.get_or_create_translation(&eng)
+            .await
+            .lock()
+            .await
+            .add_paragraph_translation(0, &pt, TranslationModel::Gemini25Flash);
+    }
+
+    let original = library
+        .sentence_original(&book_id, &eng, 0, 1)
+        .await
+        .unwrap();
+    assert_eq!(original, "Adiós amigo.");
+
+    let book = library.get_book(&book_id).await.unwrap();
+    let book = book.lock().await;
+    let translation = book.get_translation(&eng).await.unwrap();
+    let mut translation = translation.lock().await;
+    translation
+        .replace_sentence(
+            0,
+            1,
+            sentence("Goodbye friend.", &["Adiós", "amigo"]),
+            TranslationModel::Gemini25Pro,
+        )
+        .unwrap();
+    assert!(
+        translation
+            .replace_sentence(0, 2, sentence("Extra.", &[]), TranslationModel::Gemini25Pro)
+            .is_err()
+    );
+
+    let latest = translation.paragraph_view(0).unwrap();
+    assert_eq!(latest.model, TranslationModel::Gemini25Pro);
+    let sentences: Vec<_> = latest
+        .sentences()
+        .map(|s| s.full_translation.into_owned())
+        .collect();
+    assert_eq!(sentences, vec!["Hello world.", "Goodbye friend."]);
+    let previous = latest.get_previous_version().unwrap();
+    assert_eq!(previous.model, TranslationModel::Gemini25Flash);
+    assert_eq!(previous.sentence_view(1).full_translation, "Wrong.");
+    assert_eq!(translation.translation.version_count(), 2);
+}
+
+#[test]
+fn merge_rejects_translation_of_another_language_pair() {
+    let library_translation = |target: &str| {
+        let source_language = Language::from_639_3("eng").unwrap();
+        let target_language = Language::from_639_3(target).unwrap();
+        let mut tr = Translation::create(source_language.to_639_3(), target_language.to_639_3());
+        tr.add_paragraph_translation(
+            0,
+            &simple_paragraph(target, 1),
+            TranslationModel::Gemini25Flash,
+        );
+        super::LibraryTranslation {
+            translation: tr,
+            source_language,
+            target_language,
+            last_modified: None,
+            last_saved_hash: None,
+            changed: false,
+        }
+    };
+
+    let mut rus = library_translation("rus");
+    let err = rus
+        .merge(
+            library_translation("deu"),
+            crate::book::translation::MergeStrategy::PreferLocal,
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("eng_deu"), "{err}");
+    assert!(!rus.is_changed());
+    assert_eq!(rus.translation.version_count(), 1);
+
+    rus.merge(
+        library_translation("rus"),
+        crate::book::translation::MergeStrategy::PreferLocal,
+    )
+    .unwrap();
+    assert!(rus.is_changed());
+}
+
+#[test]
+fn merge_prefer_newer_compares_paragraphs_not_file_times() {
+    let library_translation = |text: &str, timestamp: u64, last_modified: SystemTime| {
+        let mut tr = Translation::create("eng", "rus");
+        tr.add_paragraph_translation(
+            0,
+            &simple_paragraph("common", 1),
+            TranslationModel::Gemini25Flash,
+        );
+        tr.add_paragraph_translation(
+            0,
+            &simple_paragraph(text, timestamp),
+            TranslationModel::Gemini25Flash,
+        );
+        super::LibraryTranslation {
+            translation: tr,
+            source_language: Language::from_639_3("eng").unwrap(),
+            target_language: Language::from_639_3("rus").unwrap(),
+            last_modified: Some(last_modified),
+            last_saved_hash: None,
+            changed: false,
+        }
+    };
+
+    // An unsaved local version against the file that was just written to
+    // disk: the file is newer, its paragraph isn't.
+    let mut local = library_translation("local", 3, SystemTime::UNIX_EPOCH);
+    let on_disk = library_translation("remote", 2, SystemTime::now());
+    local
+        .merge(on_disk, MergeStrategy::PreferNewerMtime)
+        .unwrap();
+
+    let latest = local.paragraph_view(0).unwrap();
+    assert_eq!(latest.sentence_view(0).full_translation, "local");
+    assert_eq!(local.translation.version_count(), 2);
+}
+
+#[tokio::test]
+async fn save_to_writes_standalone_copy() {
+    let temp_dir = TempDir::new("flts_test_book");
+    let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    let (book, tr_path) = book_with_saved_translation(&library, "Snapshot").await;
+    let book = book.lock().await;
+    let tr_modified = std::fs::metadata(&tr_path).unwrap().modified().unwrap();
+
+    let out = temp_dir.path.join("snapshot");
+    book.save_to(&out).await.unwrap();
+
+    assert_eq!(
+        std::fs::read(out.join("book.dat")).unwrap(),
+        std::fs::read(book.path.join("book.dat")).unwrap()
+    );
+    let tr_file_name = tr_path.file_name().unwrap();
+    assert_eq!(
+        std::fs::read(out.join(tr_file_name)).unwrap(),
+        std::fs::read(&tr_path).unwrap()
+    );
+    assert_eq!(
+        std::fs::metadata(&tr_path).unwrap().modified().unwrap(),
+        tr_modified
+    );
+
+    assert!(book.save_to(&book.path).await.is_err());
+}
+
+#[tokio::test]
+async fn save_to_copies_sidecars_without_the_strings_dictionary() {
+    use crate::book::strings_dictionary::StringsDictionary;
+
+    let temp_dir = TempDir::new("flts_test_book");
+    let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    let (book, _tr_path) = book_with_saved_translation(&library, "Sidecars").await;
+    let mut book = book.lock().await;
+    book.book.push_chapter(None);
+    book.book.push_paragraph(0, "Hello world", None);
+    book.book
+        .set_strings_dictionary(Some(Arc::new(StringsDictionary::from_bytes(
+            b"Hello world".to_vec(),
+        ))));
+    std::fs::write(book.path.join("footnotes.json"), b"{}").unwrap();
+    std::fs::write(book.path.join("chapter_summaries.dat"), b"summaries").unwrap();
+
+    let out = temp_dir.path.join("snapshot");
+    book.save_to(&out).await.unwrap();
+
+    let copy = Book::deserialize(&mut std::fs::File::open(out.join("book.dat")).unwrap()).unwrap();
+    assert!(copy.strings_dictionary().is_none());
+    assert_eq!(copy.paragraph_view(0).original_text, "Hello world");
+    assert_eq!(std::fs::read(out.join("footnotes.json")).unwrap(), b"{}");
+    assert_eq!(
+        std::fs::read(out.join("chapter_summaries.dat")).unwrap(),
+        b"summaries"
+    );
+}
+
+#[test]
+fn export_studied_keeps_only_visible_words() {
+    let source_language = Language::from_str("en").unwrap();
+    let target_language = Language::from_str("ru").unwrap();
+    let mut tr = Translation::create(source_language.to_639_3(), target_language.to_639_3());
+    tr.add_paragraph_translation(
+        0,
+        &simple_paragraph("hidden", 1),
+        TranslationModel::Gemini25Flash,
+    );
+    tr.add_paragraph_translation(
+        2,
+        &simple_paragraph("shown", 2),
+        TranslationModel::Gemini25Flash,
+    );
+    tr.mark_word_visible(2, 0, 300);
+    let translation = super::LibraryTranslation {
+        translation: tr,
+        source_language,
+        target_language,
+        last_modified: None,
+        last_saved_hash: None,
+        changed: false,
+    };
+
+    let study_set = translation.export_studied();
+    assert_eq!(study_set.source_language, "eng");
+    assert_eq!(study_set.target_language, "rus");
+    assert_eq!(study_set.words.len(), 1);
+    let word = &study_set.words[0];
+    assert_eq!((word.paragraph_id, word.word_index), (2, 0));
+    assert_eq!(word.original, "shown");
+    assert_eq!(word.grammar.part_of_speech, "n");
+    assert_eq!(word.translation.as_deref(), Some("shown"));
+    assert_eq!(word.sentence, "shown");
+    assert_eq!(word.revealed_at, Some(300));
+}
+
+#[tokio::test]
+async fn library_merge_strategy_applies_to_loaded_books() {
+    let temp_dir = TempDir::new("flts_test_book");
+    let library = Library::open(temp_dir.path.join("lib")).await.unwrap();
+    let book_id = {
+        let book = library
+            .create_book("Strategy", &Language::from_639_3("eng").unwrap())
+            .await
+            .unwrap();
+        let mut book = book.lock().await;
+        book.save().await.unwrap();
+        book.book.id
+    };
+    library.books_cache.remove(&book_id).await;
+
+    library.set_merge_strategy(MergeStrategy::PreferRemote);
+    let book = library.get_book(&book_id).await.unwrap();
+    assert_eq!(
+        book.lock().await.merge_strategy(),
+        MergeStrategy::PreferRemote
+    );
+}
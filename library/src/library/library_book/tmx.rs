@@ -0,0 +1,289 @@
+//! TMX 1.4 translation memory for CAT tools. Export writes one `<tu>` per
+//! translated sentence whose original sentence could be recovered; import
+//! seeds untranslated paragraphs from units whose source text spells them
+//! out exactly.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use htmlentity::entity::{ICodedDataTrait, decode};
+use isolang::Language;
+use regex_lite::Regex;
+use uuid::Uuid;
+
+use crate::{
+    book::translation_import::ParagraphTranslation,
+    library::{Library, escape_html},
+    translator::{TranslationModel, google_translate::draft_sentence},
+};
+
+/// Outcome of [`Library::import_tmx`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TmxImportReport {
+    /// Units used to seed at least one paragraph.
+    pub applied: usize,
+    /// Units that matched no untranslated paragraph, duplicated an earlier
+    /// unit's source, or lacked a segment in either language.
+    pub unmatched: usize,
+    /// Paragraphs that received a seeded translation.
+    pub paragraphs: usize,
+}
+
+struct TmxUnit {
+    source: String,
+    target: String,
+}
+
+impl Library {
+    /// TMX document pairing each original sentence with its
+    /// `full_translation`, over the latest version of every translated
+    /// paragraph of the `target_language` translation, or of its
+    /// `target_variant`. Sentences without a recoverable original are
+    /// skipped.
+    pub async fn export_tmx(
+        &self,
+        book_id: &Uuid,
+        target_language: &Language,
+        target_variant: Option<&str>,
+    ) -> anyhow::Result<String> {
+        let source_language = {
+            let book = self.get_book(book_id).await?;
+            let book = book.lock().await;
+            Language::from_639_3(&book.book.language).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Book {book_id} has unknown language {:?}",
+                    book.book.language
+                )
+            })?
+        };
+        let src_lang = tmx_lang(&source_language);
+        let tgt_lang = match target_variant {
+            Some(variant) => format!("{}-{variant}", tmx_lang(target_language)),
+            None => tmx_lang(target_language).to_owned(),
+        };
+
+        let mut tmx = String::new();
+        writeln!(tmx, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(tmx, r#"<tmx version="1.4">"#)?;
+        writeln!(
+            tmx,
+            r#"  <header creationtool="flts" creationtoolversion="{}" segtype="sentence" o-tmf="flts" adminlang="en" srclang="{src_lang}" datatype="plaintext"/>"#,
+            env!("CARGO_PKG_VERSION")
+        )?;
+        writeln!(tmx, "  <body>")?;
+        for pair in self
+            .sentence_pairs(book_id, target_language, target_variant)
+            .await?
+        {
+            let Some(original) = pair.original else {
+                continue;
+            };
+            writeln!(
+                tmx,
+                r#"    <tu tuid="{}-{}">"#,
+                pair.paragraph, pair.sentence
+            )?;
+            for (lang, seg) in [
+                (src_lang, &original),
+                (tgt_lang.as_str(), &pair.translation),
+            ] {
+                writeln!(
+                    tmx,
+                    r#"      <tuv xml:lang="{lang}"><seg>{}</seg></tuv>"#,
+                    escape_html(seg)
+                )?;
+            }
+            writeln!(tmx, "    </tu>")?;
+        }
+        writeln!(tmx, "  </body>")?;
+        writeln!(tmx, "</tmx>")?;
+        Ok(tmx)
+    }
+}
+
+impl Library {
+    /// Seeds translations into `target_language`, or into its
+    /// `target_variant`, from a TMX document. Matching is conservative: an untranslated paragraph is seeded only
+    /// when the source segments of one or more units, in order, make up its
+    /// whole text (whitespace-normalized). Each matched unit becomes a
+    /// sentence carrying its target segment as `full_translation` and
+    /// placeholder words. Already translated paragraphs are never touched.
+    pub async fn import_tmx(
+        &self,
+        book_id: &Uuid,
+        target_language: &Language,
+        target_variant: Option<&str>,
+        tmx: &str,
+    ) -> anyhow::Result<TmxImportReport> {
+        let book = self.get_book(book_id).await?;
+        let mut book = book.lock().await;
+        let source_language = Language::from_639_3(&book.book.language).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Book {book_id} has unknown language {:?}",
+                book.book.language
+            )
+        })?;
+        let (units, skipped) = parse_tmx_units(tmx, &source_language, target_language)?;
+
+        // The first unit wins when several share a source.
+        let mut seen = HashSet::new();
+        let mut by_first_word: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (idx, unit) in units.iter().enumerate() {
+            if !seen.insert(unit.source.as_str()) {
+                continue;
+            }
+            let first_word = unit.source.split(' ').next().unwrap_or_default();
+            by_first_word.entry(first_word).or_default().push(idx);
+        }
+
+        let existing = book
+            .get_translation_variant(target_language, target_variant)
+            .await;
+        let existing = match &existing {
+            Some(translation) => Some(translation.lock().await),
+            None => None,
+        };
+        let mut seeds = Vec::new();
+        for chapter in book.book.chapter_views() {
+            for paragraph in chapter.paragraphs() {
+                if existing
+                    .as_ref()
+                    .is_some_and(|t| t.paragraph_view(paragraph.id).is_some())
+                {
+                    continue;
+                }
+                let text = normalize_segment(&paragraph.original_text);
+                if let Some(cover) = cover_paragraph(&text, &units, &by_first_word) {
+                    seeds.push((paragraph.id, cover));
+                }
+            }
+        }
+        drop(existing);
+
+        let mut used = vec![false; units.len()];
+        if !seeds.is_empty() {
+            let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+            let translation = book
+                .get_or_create_translation_variant(target_language, target_variant)
+                .await?;
+            let mut translation = translation.lock().await;
+            for (paragraph_id, cover) in &seeds {
+                let seeded = ParagraphTranslation {
+                    timestamp,
+                    sentences: cover
+                        .iter()
+                        .map(|&i| draft_sentence(&units[i].source, units[i].target.clone()))
+                        .collect(),
+                    total_tokens: None,
+                    token_usage: None,
+                };
+                translation.add_paragraph_translation(
+                    *paragraph_id,
+                    &seeded,
+                    TranslationModel::Unknown,
+                );
+                cover.iter().for_each(|&i| used[i] = true);
+            }
+            drop(translation);
+            book.save().await?;
+        }
+
+        let applied = used.iter().filter(|&&u| u).count();
+        Ok(TmxImportReport {
+            applied,
+            unmatched: units.len() - applied + skipped,
+            paragraphs: seeds.len(),
+        })
+    }
+}
+
+/// Units with a segment in both languages, plus the number of units
+/// skipped for lacking one. Inline markup inside segments is dropped and
+/// entities decoded.
+fn parse_tmx_units(
+    tmx: &str,
+    source_language: &Language,
+    target_language: &Language,
+) -> anyhow::Result<(Vec<TmxUnit>, usize)> {
+    let tu = Regex::new(r"(?s)<tu\b[^>]*>(.*?)</tu>")?;
+    let tuv = Regex::new(r"(?s)<tuv\b([^>]*)>(.*?)</tuv>")?;
+    let lang = Regex::new(r#"(?:xml:)?lang\s*=\s*"([^"]*)""#)?;
+    let seg = Regex::new(r"(?s)<seg\b[^>]*>(.*?)</seg>")?;
+    let inline_tag = Regex::new(r"<[^>]*>")?;
+
+    let mut units = Vec::new();
+    let mut skipped = 0;
+    for unit in tu.captures_iter(tmx) {
+        let (mut source, mut target) = (None, None);
+        for variant in tuv.captures_iter(&unit[1]) {
+            let Some(language) = lang.captures(&variant[1]).and_then(|l| tag_language(&l[1]))
+            else {
+                continue;
+            };
+            let Some(text) = seg.captures(&variant[2]) else {
+                continue;
+            };
+            let text = inline_tag.replace_all(&text[1], "");
+            let text = decode(text.as_bytes()).to_string().unwrap_or_default();
+            let text = normalize_segment(&text);
+            if language == *source_language && source.is_none() {
+                source = Some(text);
+            } else if language == *target_language && target.is_none() {
+                target = Some(text);
+            }
+        }
+        match (source, target) {
+            (Some(source), Some(target)) if !source.is_empty() && !target.is_empty() => {
+                units.push(TmxUnit { source, target })
+            }
+            _ => skipped += 1,
+        }
+    }
+    Ok((units, skipped))
+}
+
+/// Language of a TMX `xml:lang` tag such as `en`, `en-US` or `spa`.
+fn tag_language(tag: &str) -> Option<Language> {
+    let primary = tag.split(['-', '_']).next()?.to_ascii_lowercase();
+    Language::from_639_1(&primary).or_else(|| Language::from_639_3(&primary))
+}
+
+fn normalize_segment(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Units whose sources, separated by single spaces, spell out `text`
+/// exactly. The longest source that fits wins at each step; `None` unless
+/// the whole text is covered.
+fn cover_paragraph(
+    text: &str,
+    units: &[TmxUnit],
+    by_first_word: &HashMap<&str, Vec<usize>>,
+) -> Option<Vec<usize>> {
+    let mut rest = text;
+    let mut cover = Vec::new();
+    while !rest.is_empty() {
+        let first_word = rest.split(' ').next()?;
+        let &idx = by_first_word
+            .get(first_word)?
+            .iter()
+            .filter(|&&i| {
+                let source = units[i].source.as_str();
+                rest.starts_with(source)
+                    && matches!(rest.as_bytes().get(source.len()), None | Some(b' '))
+            })
+            .max_by_key(|&&i| units[i].source.len())?;
+        cover.push(idx);
+        rest = rest[units[idx].source.len()..].trim_start();
+    }
+    (!cover.is_empty()).then_some(cover)
+}
+
+/// TMX expects RFC 3066 tags, so the two-letter code is used where one
+/// exists.
+fn tmx_lang(language: &Language) -> &'static str {
+    language.to_639_1().unwrap_or_else(|| language.to_639_3())
+}
@@ -0,0 +1,188 @@
+//! `translation_progress.json` sidecar: which paragraphs a bulk translation
+//! run has queued and has in flight, per target language tag. A run that
+//! crashes leaves the file behind, so the next run can retry the interrupted
+//! paragraphs first. Removed once a run completes.
+//!
+//! The book folder is synced, so per-paragraph updates are only written every
+//! [`WRITE_INTERVAL`]. A crash loses at most that much progress, which only
+//! affects the order of the next run: the translation itself is what decides
+//! which paragraphs still need translating.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::book::serialization::create_random_string;
+
+use super::LibraryBook;
+
+const PROGRESS_FILE: &str = "translation_progress.json";
+const WRITE_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct LanguageProgress {
+    pub queued: BTreeSet<usize>,
+    #[serde(rename = "inFlight")]
+    pub in_flight: BTreeSet<usize>,
+}
+
+/// Handle to one target language's entry in the progress file.
+pub struct TranslationProgress {
+    path: PathBuf,
+    target: String,
+    state: Mutex<ProgressState>,
+}
+
+struct ProgressState {
+    languages: BTreeMap<String, LanguageProgress>,
+    /// Whether `languages` has updates that aren't on disk yet.
+    dirty: bool,
+    last_write: Instant,
+}
+
+impl LibraryBook {
+    /// Progress of the bulk translation into `target_tag` (a language code,
+    /// optionally with a `-VARIANT` suffix). Empty if no run was interrupted.
+    pub async fn translation_progress(
+        &self,
+        target_tag: &str,
+    ) -> anyhow::Result<TranslationProgress> {
+        let path = self.path.join(PROGRESS_FILE);
+        let languages = if tokio::fs::try_exists(&path).await? {
+            serde_json::from_slice(&tokio::fs::read(&path).await?)?
+        } else {
+            BTreeMap::new()
+        };
+        Ok(TranslationProgress {
+            path,
+            target: target_tag.to_owned(),
+            state: Mutex::new(ProgressState {
+                languages,
+                dirty: false,
+                last_write: Instant::now(),
+            }),
+        })
+    }
+}
+
+impl TranslationProgress {
+    pub async fn snapshot(&self) -> LanguageProgress {
+        let state = self.state.lock().await;
+        state
+            .languages
+            .get(&self.target)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Orders `untranslated` for a new run: paragraphs interrupted while in
+    /// flight come first, then the ones still queued by the previous run,
+    /// then the rest in the given order. All of them are recorded as queued.
+    ///
+    /// `untranslated` is the source of truth: a recorded paragraph that has
+    /// been translated since is dropped from the plan.
+    pub async fn plan(&self, untranslated: Vec<usize>) -> anyhow::Result<Vec<usize>> {
+        let mut state = self.state.lock().await;
+        let progress = state.languages.entry(self.target.clone()).or_default();
+        let rank = |p: &usize| {
+            if progress.in_flight.contains(p) {
+                0
+            } else if progress.queued.contains(p) {
+                1
+            } else {
+                2
+            }
+        };
+        let mut plan = untranslated;
+        plan.sort_by_key(rank);
+
+        *progress = LanguageProgress {
+            queued: plan.iter().copied().collect(),
+            ..Default::default()
+        };
+        self.write(&mut state).await?;
+        Ok(plan)
+    }
+
+    pub async fn mark_in_flight(&self, paragraph: usize) -> anyhow::Result<()> {
+        self.update(|p| {
+            p.queued.remove(&paragraph);
+            p.in_flight.insert(paragraph);
+        })
+        .await
+    }
+
+    pub async fn mark_done(&self, paragraph: usize) -> anyhow::Result<()> {
+        self.update(|p| {
+            p.in_flight.remove(&paragraph);
+        })
+        .await
+    }
+
+    /// Puts a paragraph that failed for good back in the queue, so the next
+    /// run retries it.
+    pub async fn mark_failed(&self, paragraph: usize) -> anyhow::Result<()> {
+        self.update(|p| {
+            p.in_flight.remove(&paragraph);
+            p.queued.insert(paragraph);
+        })
+        .await
+    }
+
+    /// Drops this language's entry if nothing is left queued or in flight,
+    /// deleting the file once no language has an entry, and writes out any
+    /// pending updates. Returns whether the run was complete.
+    pub async fn finish(&self) -> anyhow::Result<bool> {
+        let mut state = self.state.lock().await;
+        let complete = state
+            .languages
+            .get(&self.target)
+            .is_none_or(|p| p.queued.is_empty() && p.in_flight.is_empty());
+        if complete {
+            state.languages.remove(&self.target);
+        }
+        self.write(&mut state).await?;
+        Ok(complete)
+    }
+
+    /// Writes out updates still waiting for the next interval.
+    pub async fn flush(&self) -> anyhow::Result<()> {
+        let mut state = self.state.lock().await;
+        if state.dirty {
+            self.write(&mut state).await?;
+        }
+        Ok(())
+    }
+
+    async fn update(&self, f: impl FnOnce(&mut LanguageProgress)) -> anyhow::Result<()> {
+        let mut state = self.state.lock().await;
+        f(state.languages.entry(self.target.clone()).or_default());
+        state.dirty = true;
+        if state.last_write.elapsed() >= WRITE_INTERVAL {
+            self.write(&mut state).await?;
+        }
+        Ok(())
+    }
+
+    async fn write(&self, state: &mut ProgressState) -> anyhow::Result<()> {
+        if state.languages.is_empty() {
+            if tokio::fs::try_exists(&self.path).await? {
+                tokio::fs::remove_file(&self.path).await?;
+            }
+        } else {
+            let temp_path = self
+                .path
+                .with_file_name(format!("{PROGRESS_FILE}~{}", create_random_string(8)));
+            tokio::fs::write(&temp_path, serde_json::to_vec_pretty(&state.languages)?).await?;
+            tokio::fs::rename(&temp_path, &self.path).await?;
+        }
+        state.dirty = false;
+        state.last_write = Instant::now();
+        Ok(())
+    }
+}
@@ -360,6 +360,34 @@ impl LibraryCardStore {
         Ok(out)
     }
 
+    /// Number of Syncthing `.sync-conflict-*.json` siblings in the pair's
+    /// deck dir still waiting to be merged by [`load`]. Missing deck dir
+    /// counts as zero.
+    ///
+    /// [`load`]: LibraryCardStore::load
+    pub async fn count_conflicts_in_pair(
+        &self,
+        source_language: &str,
+        target_language: &str,
+    ) -> anyhow::Result<usize> {
+        let deck = self.deck_dir(source_language, target_language);
+        let mut read_dir = match tokio::fs::read_dir(&deck).await {
+            Ok(rd) => rd,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut count = 0;
+        while let Some(entry) = read_dir.next_entry().await? {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name.contains(".sync-conflict-") && name.ends_with(".json") {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
     /// Persist a card to disk and wake any sync task listening on
     /// `change_notify`. Use this from user-driven write paths (translation
     /// completion, backfill, on-disk edits).
@@ -441,6 +469,9 @@ mod tests {
             translations,
             examples: vec![],
             anki_data: None,
+            usage: Default::default(),
+            last_seen: Default::default(),
+            usage_sources: Default::default(),
         }
     }
 
@@ -585,6 +616,9 @@ mod tests {
             translations: by_pos,
             examples,
             anki_data: None,
+            usage: Default::default(),
+            last_seen: Default::default(),
+            usage_sources: Default::default(),
         }
     }
 
@@ -1041,6 +1075,9 @@ mod tests {
                 fsrs_difficulty: None,
                 fsrs_stability,
             }),
+            usage: Default::default(),
+            last_seen: Default::default(),
+            usage_sources: Default::default(),
         }
     }
 
@@ -1056,6 +1093,9 @@ mod tests {
             translations,
             examples: vec![],
             anki_data: None,
+            usage: Default::default(),
+            last_seen: Default::default(),
+            usage_sources: Default::default(),
         }
     }
 
@@ -0,0 +1,515 @@
+//! Dictionary-level lookups over a language pair's card deck. Cards already
+//! aggregate every `(lemma, target form)` pair the translator has produced
+//! across the whole library, so the dictionary is a read-only view on them
+//! rather than a separate store.
+
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, btree_map::Entry},
+    str::FromStr,
+    sync::Arc,
+};
+
+use isolang::Language;
+use log::warn;
+use serde::Serialize;
+
+use crate::{
+    card::{Card, canonicalize_lemma, card_id, extract_card_updates, lemma_slug},
+    library::{Library, library_card::LibraryCardStore},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DictionaryEntry {
+    #[serde(rename = "targetForm")]
+    pub target_form: String,
+    #[serde(rename = "partOfSpeech")]
+    pub part_of_speech: String,
+    #[serde(rename = "usageCount")]
+    pub usage_count: u32,
+    /// Unix seconds; `None` for forms recorded before cards tracked it.
+    #[serde(rename = "lastSeen")]
+    pub last_seen: Option<u64>,
+}
+
+/// A `(lemma, target form)` pair as listed by [`Dictionary::recent_entries`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RecentDictionaryEntry {
+    pub lemma: String,
+    #[serde(rename = "targetForm")]
+    pub target_form: String,
+    #[serde(rename = "partOfSpeech")]
+    pub part_of_speech: String,
+    #[serde(rename = "lastSeen")]
+    pub last_seen: u64,
+}
+
+/// One language pair's deck as found on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DictionaryInfo {
+    pub source_language: Language,
+    pub target_language: Language,
+    /// Number of cards (lemmas) in the deck.
+    pub entries: usize,
+    /// Unmerged `.sync-conflict-*` card files; they are reconciled the next
+    /// time the affected card is loaded.
+    pub conflicts: usize,
+}
+
+/// What [`Library::rebuild_dictionary`] wrote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DictionaryRebuildReport {
+    /// Cards written from the library's translations.
+    pub cards: usize,
+    /// Cards deleted because no translation produces them any more.
+    pub removed: usize,
+}
+
+pub struct Dictionary {
+    cards: Arc<LibraryCardStore>,
+    source_language: Language,
+    target_language: Language,
+}
+
+impl Dictionary {
+    pub fn new(
+        cards: Arc<LibraryCardStore>,
+        source_language: Language,
+        target_language: Language,
+    ) -> Self {
+        Self {
+            cards,
+            source_language,
+            target_language,
+        }
+    }
+
+    /// Target forms known for `source_form` (a dictionary/lemma form), most
+    /// used first. Unknown words yield an empty list.
+    pub async fn lookup(&self, source_form: &str) -> anyhow::Result<Vec<DictionaryEntry>> {
+        let slug = lemma_slug(&canonicalize_lemma(source_form, self.source_language));
+        if slug.is_empty() {
+            return Ok(Vec::new());
+        }
+        let card = self
+            .cards
+            .load_canonical(
+                self.source_language.to_639_3(),
+                self.target_language.to_639_3(),
+                &slug,
+            )
+            .await?;
+        Ok(card.map(|card| ranked_entries(&card)).unwrap_or_default())
+    }
+
+    /// The `n` pairs most recently produced by a translation, newest first.
+    /// Forms without a timestamp (from cards older than version 3) are left
+    /// out.
+    pub async fn recent_entries(&self, n: usize) -> anyhow::Result<Vec<RecentDictionaryEntry>> {
+        let source_language = self.source_language.to_639_3();
+        let target_language = self.target_language.to_639_3();
+        let mut entries = Vec::new();
+        for slug in self
+            .cards
+            .list_cards_in_pair(source_language, target_language)
+            .await?
+        {
+            let Some(card) = self
+                .cards
+                .load_canonical(source_language, target_language, &slug)
+                .await?
+            else {
+                continue;
+            };
+            for entry in ranked_entries(&card) {
+                if let Some(last_seen) = entry.last_seen {
+                    entries.push(RecentDictionaryEntry {
+                        lemma: card.lemma.clone(),
+                        target_form: entry.target_form,
+                        part_of_speech: entry.part_of_speech,
+                        last_seen,
+                    });
+                }
+            }
+        }
+        entries.sort_by_key(|e| Reverse(e.last_seen));
+        entries.truncate(n);
+        Ok(entries)
+    }
+
+    /// Drops target forms seen fewer than `min_count` times from every card
+    /// in this language pair (see [`Card::purge_below`]). Each card is
+    /// rewritten under its lock, so conflict siblings are reconciled first
+    /// and later merges see an ordinary card. Returns the number of forms
+    /// removed.
+    pub async fn purge_below(&self, min_count: u32) -> anyhow::Result<usize> {
+        let source_language = self.source_language.to_639_3();
+        let target_language = self.target_language.to_639_3();
+        let mut removed = 0;
+        for slug in self
+            .cards
+            .list_cards_in_pair(source_language, target_language)
+            .await?
+        {
+            let lock = self
+                .cards
+                .lock_for(&card_id(source_language, target_language, &slug))
+                .await;
+            let _guard = lock.lock().await;
+            let Some(mut card) = self
+                .cards
+                .load(source_language, target_language, &slug)
+                .await?
+            else {
+                continue;
+            };
+            let purged = card.purge_below(min_count);
+            if purged > 0 {
+                self.cards
+                    .save(&card, source_language, target_language)
+                    .await?;
+                removed += purged;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+/// Flattens a card's per-PoS buckets. The sort is stable, so forms with equal
+/// usage (including legacy cards without counts) keep their insertion order.
+fn ranked_entries(card: &Card) -> Vec<DictionaryEntry> {
+    let mut entries: Vec<DictionaryEntry> = card
+        .translations
+        .iter()
+        .flat_map(|(part_of_speech, forms)| {
+            forms.iter().map(|form| DictionaryEntry {
+                target_form: form.clone(),
+                part_of_speech: part_of_speech.clone(),
+                usage_count: card.usage.get(form).copied().unwrap_or(0),
+                last_seen: card.last_seen.get(form).copied(),
+            })
+        })
+        .collect();
+    entries.sort_by_key(|e| Reverse(e.usage_count));
+    entries
+}
+
+impl Library {
+    pub fn dictionary(&self, source_language: Language, target_language: Language) -> Dictionary {
+        Dictionary::new(self.card_store.clone(), source_language, target_language)
+    }
+
+    /// Recomputes the deck of a language pair from every book's translations
+    /// into `target_language`, regional variants included, for when card
+    /// files were lost or went out of sync with the translations. Each card's
+    /// translations and examples are rebuilt from scratch; usage counts and
+    /// last-seen times never drop below what the card had, and its Anki state
+    /// is kept. Cards no translation produces are deleted unless they have
+    /// Anki state. Books that fail to load are skipped with a warning.
+    pub async fn rebuild_dictionary(
+        &self,
+        source_language: Language,
+        target_language: Language,
+    ) -> anyhow::Result<DictionaryRebuildReport> {
+        let source = source_language.to_639_3();
+        let target = target_language.to_639_3();
+
+        let mut cards: BTreeMap<String, Card> = BTreeMap::new();
+        for book_meta in self.list_books().await? {
+            if book_meta.incomplete {
+                continue;
+            }
+            let book = match self.get_book(&book_meta.id).await {
+                Ok(book) => book,
+                Err(err) => {
+                    warn!(
+                        "Dictionary rebuild: failed to load book {}: {err}",
+                        book_meta.id
+                    );
+                    continue;
+                }
+            };
+            let mut book = book.lock().await;
+            if book.book.language != source {
+                continue;
+            }
+            for translation_meta in &book_meta.translations_metadata {
+                if Language::from_str(&translation_meta.target_language).ok()
+                    != Some(target_language)
+                {
+                    continue;
+                }
+                let translation = match book
+                    .get_or_create_translation_variant(
+                        &target_language,
+                        translation_meta.target_variant.as_deref(),
+                    )
+                    .await
+                {
+                    Ok(translation) => translation,
+                    Err(err) => {
+                        warn!(
+                            "Dictionary rebuild: skipping translation {} of book {}: {err}",
+                            translation_meta.id, book_meta.id
+                        );
+                        continue;
+                    }
+                };
+                let translation = translation.lock().await;
+                for chapter in book.book.chapter_views() {
+                    for paragraph in chapter.paragraphs() {
+                        let Some(view) = translation.paragraph_view(paragraph.id) else {
+                            continue;
+                        };
+                        for update in extract_card_updates(
+                            &view.to_import(),
+                            source_language,
+                            target_language,
+                            book_meta.id,
+                            chapter.idx,
+                            paragraph.id,
+                        ) {
+                            match cards.entry(update.key.slug.clone()) {
+                                Entry::Occupied(mut card) => card.get_mut().apply_update(&update),
+                                Entry::Vacant(card) => {
+                                    card.insert(Card::new_from_update(&update));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut removed = 0;
+        for slug in self.card_store.list_cards_in_pair(source, target).await? {
+            let lock = self
+                .card_store
+                .lock_for(&card_id(source, target, &slug))
+                .await;
+            let _guard = lock.lock().await;
+            let Some(existing) = self.card_store.load(source, target, &slug).await? else {
+                continue;
+            };
+            match cards.get_mut(&slug) {
+                Some(card) => {
+                    card.keep_counters(&existing);
+                    card.anki_data = existing.anki_data;
+                }
+                None if existing.anki_data.is_none() => {
+                    tokio::fs::remove_file(self.card_store.card_path(source, target, &slug))
+                        .await?;
+                    self.card_store
+                        .invalidate_familiarity(source, target, &slug);
+                    removed += 1;
+                }
+                None => {}
+            }
+        }
+
+        for (slug, card) in &cards {
+            let lock = self
+                .card_store
+                .lock_for(&card_id(source, target, slug))
+                .await;
+            let _guard = lock.lock().await;
+            self.card_store.save(card, source, target).await?;
+        }
+
+        Ok(DictionaryRebuildReport {
+            cards: cards.len(),
+            removed,
+        })
+    }
+
+    /// Every language pair with a deck on disk, sorted by language codes.
+    /// Deck directories whose names aren't two ISO 639-3 codes are skipped.
+    pub async fn list_dictionaries(&self) -> anyhow::Result<Vec<DictionaryInfo>> {
+        let mut dictionaries = Vec::new();
+        for (source, target) in self.card_store.list_pairs().await? {
+            let (Some(source_language), Some(target_language)) =
+                (Language::from_639_3(&source), Language::from_639_3(&target))
+            else {
+                log::warn!("Skipping deck with unknown language pair {source}-{target}");
+                continue;
+            };
+            dictionaries.push(DictionaryInfo {
+                source_language,
+                target_language,
+                entries: self
+                    .card_store
+                    .list_cards_in_pair(&source, &target)
+                    .await?
+                    .len(),
+                conflicts: self
+                    .card_store
+                    .count_conflicts_in_pair(&source, &target)
+                    .await?,
+            });
+        }
+        Ok(dictionaries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        card::{CardKey, CardUpdate},
+        test_utils::TempDir,
+    };
+
+    fn update(translations: &[&str]) -> CardUpdate {
+        CardUpdate {
+            key: CardKey {
+                source_language: "spa".into(),
+                target_language: "eng".into(),
+                lemma: "poder".into(),
+                slug: "poder".into(),
+            },
+            part_of_speech: "verb".into(),
+            translations: translations.iter().map(|t| t.to_string()).collect(),
+            example: None,
+            seen_at: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn lookup_ranks_by_usage() {
+        let tmp = TempDir::new("flts_dictionary_lookup");
+        let library = Library::open(tmp.path.clone()).await.unwrap();
+        let mut card = Card::new_from_update(&update(&["can"]));
+        card.apply_update(&update(&["be able to"]));
+        card.apply_update(&update(&["be able to"]));
+        library
+            .card_store()
+            .save(&card, "spa", "eng")
+            .await
+            .unwrap();
+
+        let dictionary = library.dictionary(
+            Language::from_639_3("spa").unwrap(),
+            Language::from_639_3("eng").unwrap(),
+        );
+        let entries = dictionary.lookup("Poder").await.unwrap();
+        assert_eq!(
+            entries
+                .iter()
+                .map(|e| (e.target_form.as_str(), e.usage_count))
+                .collect::<Vec<_>>(),
+            vec![("be able to", 2), ("can", 1)]
+        );
+        assert!(dictionary.lookup("querer").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn recent_entries_lists_newest_first() {
+        let tmp = TempDir::new("flts_dictionary_recent");
+        let library = Library::open(tmp.path.clone()).await.unwrap();
+        let mut card = Card::new_from_update(&CardUpdate {
+            seen_at: 10,
+            ..update(&["can"])
+        });
+        card.apply_update(&CardUpdate {
+            seen_at: 30,
+            ..update(&["be able to"])
+        });
+        card.translations
+            .get_mut("verb")
+            .unwrap()
+            .push("may".to_owned());
+        library
+            .card_store()
+            .save(&card, "spa", "eng")
+            .await
+            .unwrap();
+        let other = Card::new_from_update(&CardUpdate {
+            key: CardKey {
+                source_language: "spa".into(),
+                target_language: "eng".into(),
+                lemma: "querer".into(),
+                slug: "querer".into(),
+            },
+            seen_at: 20,
+            ..update(&["want"])
+        });
+        library
+            .card_store()
+            .save(&other, "spa", "eng")
+            .await
+            .unwrap();
+
+        let dictionary = library.dictionary(
+            Language::from_639_3("spa").unwrap(),
+            Language::from_639_3("eng").unwrap(),
+        );
+        let recent = dictionary.recent_entries(2).await.unwrap();
+        assert_eq!(
+            recent
+                .iter()
+                .map(|e| (e.lemma.as_str(), e.target_form.as_str(), e.last_seen))
+                .collect::<Vec<_>>(),
+            vec![("poder", "be able to", 30), ("querer", "want", 20)]
+        );
+        // "may" has no timestamp and never shows up.
+        assert_eq!(dictionary.recent_entries(10).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn purge_below_removes_one_off_forms() {
+        let tmp = TempDir::new("flts_dictionary_purge");
+        let library = Library::open(tmp.path.clone()).await.unwrap();
+        let mut card = Card::new_from_update(&update(&["can"]));
+        card.apply_update(&update(&["be able to"]));
+        card.apply_update(&update(&["be able to"]));
+        library
+            .card_store()
+            .save(&card, "spa", "eng")
+            .await
+            .unwrap();
+
+        let dictionary = library.dictionary(
+            Language::from_639_3("spa").unwrap(),
+            Language::from_639_3("eng").unwrap(),
+        );
+        assert_eq!(dictionary.purge_below(2).await.unwrap(), 1);
+        let entries = dictionary.lookup("poder").await.unwrap();
+        assert_eq!(
+            entries
+                .iter()
+                .map(|e| (e.target_form.as_str(), e.usage_count))
+                .collect::<Vec<_>>(),
+            vec![("be able to", 2)]
+        );
+        assert_eq!(dictionary.purge_below(2).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn list_dictionaries_counts_entries_and_conflicts() {
+        let tmp = TempDir::new("flts_dictionary_list");
+        let library = Library::open(tmp.path.clone()).await.unwrap();
+        let card = Card::new_from_update(&update(&["can"]));
+        library
+            .card_store()
+            .save(&card, "spa", "eng")
+            .await
+            .unwrap();
+        let deck = library.card_store().deck_dir("spa", "eng");
+        std::fs::copy(
+            deck.join("poder.json"),
+            deck.join("poder.sync-conflict-20260520-153912-XYZ.json"),
+        )
+        .unwrap();
+        std::fs::create_dir_all(library.card_store().root().join("1-2")).unwrap();
+
+        assert_eq!(
+            library.list_dictionaries().await.unwrap(),
+            vec![DictionaryInfo {
+                source_language: Language::from_639_3("spa").unwrap(),
+                target_language: Language::from_639_3("eng").unwrap(),
+                entries: 1,
+                conflicts: 1,
+            }]
+        );
+    }
+}
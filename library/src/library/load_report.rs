@@ -0,0 +1,114 @@
+//! Per-directory health check of the library, for when a book silently
+//! drops out of [`Library::list_books`] or [`Library::get_book`] fails with
+//! an error that doesn't say which files are at fault.
+
+use std::path::PathBuf;
+
+use uuid::Uuid;
+
+use crate::library::{Library, LibraryBookMetadata};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BookLoadStatus {
+    Loaded,
+    /// Loaded after merging this many conflicting copies (of `book.dat`, a
+    /// translation or the chapter summaries) into their main files.
+    ConflictsMerged {
+        files: usize,
+    },
+    /// Loaded without touching this many conflicting copies, as
+    /// [auto-merging](Library::set_auto_merge_conflicts) is off.
+    ConflictsPending {
+        files: usize,
+    },
+    /// No `book.dat`; see [`Library::repair_book`].
+    Incomplete,
+    Failed {
+        error: String,
+    },
+}
+
+/// One book directory as [`Library::load_report`] found it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookLoadReport {
+    pub path: PathBuf,
+    /// `None` when the directory is neither named after a book id nor has a
+    /// readable `book.dat`.
+    pub id: Option<Uuid>,
+    pub title: Option<String>,
+    pub status: BookLoadStatus,
+}
+
+pub(super) fn conflict_count(metadata: &LibraryBookMetadata) -> usize {
+    metadata.conflicting_paths.len()
+        + metadata.chapter_summaries_conflicting_paths.len()
+        + metadata
+            .translations_metadata
+            .iter()
+            .map(|t| t.conflicting_paths.len())
+            .sum::<usize>()
+}
+
+impl Library {
+    /// Loads every book directory the way [`Library::get_book`] does and
+    /// records how it went. Like a normal load, this merges conflicting
+    /// copies into the main files unless auto-merging is off.
+    pub async fn load_report(&self) -> anyhow::Result<Vec<BookLoadReport>> {
+        let mut library_root_content = tokio::fs::read_dir(&self.library_root).await?;
+
+        let mut reports = Vec::new();
+        while let Some(entry) = library_root_content.next_entry().await? {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let dir_id = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| Uuid::parse_str(n).ok());
+            // Card decks, dictionaries and the like live next to the books.
+            if dir_id.is_none() && !tokio::fs::try_exists(path.join("book.dat")).await? {
+                continue;
+            }
+
+            let metadata = match LibraryBookMetadata::load(&path).await {
+                Ok(metadata) => metadata,
+                Err(err) => {
+                    reports.push(BookLoadReport {
+                        path,
+                        id: dir_id,
+                        title: None,
+                        status: BookLoadStatus::Failed {
+                            error: format!("{err:#}"),
+                        },
+                    });
+                    continue;
+                }
+            };
+
+            let status = if metadata.incomplete {
+                BookLoadStatus::Incomplete
+            } else {
+                let conflicts = conflict_count(&metadata);
+                match self.get_book(&metadata.id).await {
+                    Ok(_) if conflicts > 0 && self.auto_merge_conflicts() => {
+                        BookLoadStatus::ConflictsMerged { files: conflicts }
+                    }
+                    Ok(_) if conflicts > 0 => BookLoadStatus::ConflictsPending { files: conflicts },
+                    Ok(_) => BookLoadStatus::Loaded,
+                    Err(err) => BookLoadStatus::Failed {
+                        error: format!("{err:#}"),
+                    },
+                }
+            };
+            reports.push(BookLoadReport {
+                path,
+                id: Some(metadata.id),
+                title: Some(metadata.title),
+                status,
+            });
+        }
+
+        Ok(reports)
+    }
+}
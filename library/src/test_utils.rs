@@ -55,6 +55,7 @@ pub fn one_sentence_paragraph(
     translation_import::ParagraphTranslation {
         timestamp: 0,
         total_tokens: None,
+        token_usage: None,
         sentences: vec![translation_import::Sentence {
             full_translation: full_translation.into(),
             words,
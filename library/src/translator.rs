@@ -1,8 +1,20 @@
+pub mod fixture;
 pub(crate) mod gemini;
 pub mod gemini_cache;
+pub(crate) mod google_translate;
 pub(crate) mod openai;
-
-use std::{fmt::Display, sync::Arc, time::Duration};
+pub(crate) mod request_log;
+
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
 use async_trait::async_trait;
 use isolang::Language;
@@ -11,8 +23,11 @@ use strum::EnumIter;
 use uuid::Uuid;
 
 use crate::{
-    book::translation_import::ParagraphTranslation, cache::TranslationsCache,
-    translator::gemini::GeminiTranslator, translator::openai::OpenAITranslator,
+    book::translation_import::{ParagraphTranslation, Sentence},
+    cache::TranslationsCache,
+    translator::gemini::GeminiTranslator,
+    translator::google_translate::GoogleTranslateTranslator,
+    translator::openai::OpenAITranslator,
 };
 
 pub const TRANSLATION_REQUEST_TIMEOUT: Duration = Duration::from_secs(1200);
@@ -21,11 +36,20 @@ const TRANSLATION_TOTAL_TIMEOUT_BASE: Duration = Duration::from_secs(180);
 const TRANSLATION_TOTAL_TIMEOUT_PER_CHAR: Duration = Duration::from_millis(100);
 
 pub type ProgressCallback = dyn Fn(usize) + Send + Sync;
+/// Receives raw response text as it streams in, one chunk per call.
+pub type TokenCallback = dyn Fn(&str) + Send + Sync;
 
 pub fn total_stream_timeout(input_len: usize) -> Duration {
     TRANSLATION_TOTAL_TIMEOUT_BASE + TRANSLATION_TOTAL_TIMEOUT_PER_CHAR * (input_len as u32)
 }
 
+/// Number of concurrent workers that keeps `requests_per_minute` saturated
+/// when each request takes about `latency` (Little's law), at least one.
+pub fn suggested_worker_count(requests_per_minute: u32, latency: Duration) -> usize {
+    let in_flight = f64::from(requests_per_minute) * latency.as_secs_f64() / 60.0;
+    (in_flight.ceil() as usize).max(1)
+}
+
 /// Closed set of values the LLM may return in the grammar `pos` field.
 /// The schema's `enum` keyword is built from these tags, and the prompt
 /// renders each tag with its description so the LLM has guidance on
@@ -173,22 +197,85 @@ pub(crate) const PART_OF_SPEECH_VOCABULARY: &[(&str, &str)] = &[
     ),
 ];
 
-#[derive(Debug)]
-pub struct StreamChunkAccumulator {
+/// Tags of [`PART_OF_SPEECH_VOCABULARY`] (plus the short legacy tags older
+/// translations carry) that count as content words: nouns, lexical verbs
+/// and their participles/gerunds, adjectives and adverbs.
+const CONTENT_WORD_TAGS: &[&str] = &[
+    "common_noun",
+    "proper_noun",
+    "verb",
+    "participle_present",
+    "participle_past",
+    "gerund",
+    "adjective",
+    "adverb",
+    "noun",
+    "adj",
+    "adv",
+];
+
+/// Whether a word tagged `part_of_speech` is a content word. Noisy tags like
+/// `noun / adjective` count if any alternative does.
+pub fn is_content_word(part_of_speech: &str) -> bool {
+    part_of_speech
+        .split('/')
+        .map(|tag| tag.trim().to_lowercase())
+        .any(|tag| CONTENT_WORD_TAGS.contains(&tag.as_str()))
+}
+
+/// Whether a word tagged `part_of_speech` is one of the `visible` parts of
+/// speech. Both sides are compared case-insensitively with the legacy short
+/// tags spelled out, and an entry also matches the finer tags built on it:
+/// `noun` covers `common_noun` and `proper_noun`, `verb` covers
+/// `verb_auxiliary`. Noisy tags like `noun / adjective` match if any
+/// alternative does.
+pub fn is_visible_part_of_speech(part_of_speech: &str, visible: &HashSet<String>) -> bool {
+    let visible: Vec<_> = visible
+        .iter()
+        .map(|tag| normalize_part_of_speech(tag))
+        .collect();
+    part_of_speech
+        .split('/')
+        .map(normalize_part_of_speech)
+        .any(|tag| {
+            visible
+                .iter()
+                .any(|v| tag == *v || tag.split('_').any(|part| part == v))
+        })
+}
+
+fn normalize_part_of_speech(tag: &str) -> String {
+    let tag = tag.trim().to_lowercase();
+    match tag.as_str() {
+        "adj" => "adjective".to_owned(),
+        "adv" => "adverb".to_owned(),
+        _ => tag,
+    }
+}
+
+pub struct StreamChunkAccumulator<'a> {
     provider: &'static str,
     full_content: String,
     saw_chunk_error: bool,
+    token_sink: Option<&'a TokenCallback>,
 }
 
-impl StreamChunkAccumulator {
+impl<'a> StreamChunkAccumulator<'a> {
     pub fn new(provider: &'static str) -> Self {
         Self {
             provider,
             full_content: String::new(),
             saw_chunk_error: false,
+            token_sink: None,
         }
     }
 
+    /// Forwards every non-empty chunk to `sink` as it is accumulated.
+    pub fn with_token_sink(mut self, sink: Option<&'a TokenCallback>) -> Self {
+        self.token_sink = sink;
+        self
+    }
+
     pub fn handle_result(
         &mut self,
         result: anyhow::Result<Option<String>>,
@@ -203,6 +290,9 @@ impl StreamChunkAccumulator {
         match result {
             Ok(Some(text)) => {
                 if !text.is_empty() {
+                    if let Some(sink) = self.token_sink {
+                        sink(&text);
+                    }
                     self.full_content.push_str(&text);
                     if let Some(cb) = callback {
                         cb(self.full_content.len());
@@ -293,6 +383,10 @@ pub enum TranslationModel {
     DeepSeekV4Pro = 16,
 
     ZaiGlm52 = 17,
+
+    /// Google Cloud Translation: sentence-level drafts with placeholder
+    /// word grammar, for a cheap first read.
+    GoogleTranslate = 18,
 }
 
 impl TranslationModel {
@@ -305,7 +399,8 @@ impl TranslationModel {
             | TranslationModel::Gemini3Flash
             | TranslationModel::Gemini31Pro
             | TranslationModel::Gemini31FlashLite 
-            | TranslationModel::Gemini35Flash => Some(TranslationProvider::Google),
+            | TranslationModel::Gemini35Flash
+            | TranslationModel::GoogleTranslate => Some(TranslationProvider::Google),
 
             TranslationModel::OpenAIGpt52
             | TranslationModel::OpenAIGpt52Pro
@@ -345,6 +440,7 @@ impl From<usize> for TranslationModel {
             15 => TranslationModel::DeepSeekV4Flash,
             16 => TranslationModel::DeepSeekV4Pro,
             17 => TranslationModel::ZaiGlm52,
+            18 => TranslationModel::GoogleTranslate,
             _ => TranslationModel::Unknown,
         }
     }
@@ -383,6 +479,46 @@ impl TranslationProvider {
     }
 }
 
+/// How many contextual translations per word the prompt asks for, and keeps,
+/// unless the caller configures otherwise.
+pub const DEFAULT_MAX_CONTEXTUAL_TRANSLATIONS: usize = 3;
+
+/// Tuning knobs for the LLM translators built by [`get_translator`]. The
+/// default reproduces the behavior from before they were configurable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranslatorConfig {
+    pub max_contextual_translations: usize,
+    /// Sampling temperature for Gemini. `None` keeps the provider default.
+    pub temperature: Option<f32>,
+    /// Thinking budget in tokens for Gemini 2.5 models, `-1` for dynamic
+    /// thinking. `None` keeps the per-model default; Gemini 3 models, which
+    /// use thinking levels instead, ignore it.
+    pub thinking_budget: Option<i32>,
+    /// Append every request and its raw response to this file, with the API
+    /// key redacted. For debugging prompts; `None` disables logging.
+    pub request_log: Option<PathBuf>,
+}
+
+/// Caches and context source shared by the translators [`get_translator`]
+/// creates for one app session or CLI run.
+#[derive(Clone)]
+pub struct TranslatorServices {
+    pub cache: Arc<TranslationsCache>,
+    pub context_provider: Arc<dyn ChapterContextProvider>,
+    pub gemini_prompt_cache: Arc<gemini_cache::GeminiPromptCache>,
+}
+
+impl Default for TranslatorConfig {
+    fn default() -> Self {
+        Self {
+            max_contextual_translations: DEFAULT_MAX_CONTEXTUAL_TRANSLATIONS,
+            temperature: None,
+            thinking_budget: None,
+            request_log: None,
+        }
+    }
+}
+
 /// Per-paragraph translation request. Carries everything the translator
 /// needs to locate the paragraph in its surrounding chapter and call its
 /// `ChapterContextProvider`.
@@ -392,6 +528,7 @@ pub struct TranslationContext<'a> {
     pub chapter_id: usize,
     pub use_cache: bool,
     pub callback: Option<Box<ProgressCallback>>,
+    pub token_sink: Option<Box<TokenCallback>>,
 }
 
 /// Translator-facing view onto book-level chapter context. Lives on the
@@ -445,7 +582,49 @@ pub trait Translator: Send + Sync {
         ctx: TranslationContext<'_>,
     ) -> anyhow::Result<ParagraphTranslation>;
 
-    fn get_prompt(from: &str, to: &str) -> String
+    /// Like [`Translator::get_translation`], but hands the raw JSON response
+    /// to `sink` chunk by chunk as the backend streams it. Responses that
+    /// arrive in one piece (non-streaming backends, cache hits) are emitted
+    /// as a single chunk.
+    async fn stream_translation(
+        &self,
+        mut ctx: TranslationContext<'_>,
+        sink: Arc<TokenCallback>,
+    ) -> anyhow::Result<ParagraphTranslation> {
+        let streamed = Arc::new(AtomicBool::new(false));
+        ctx.token_sink = Some(Box::new({
+            let sink = sink.clone();
+            let streamed = streamed.clone();
+            move |chunk: &str| {
+                streamed.store(true, Ordering::Relaxed);
+                sink(chunk);
+            }
+        }));
+        let translation = self.get_translation(ctx).await?;
+        if !streamed.load(Ordering::Relaxed) {
+            sink(&serde_json::to_string(&translation)?);
+        }
+        Ok(translation)
+    }
+
+    /// Translates one sentence of a paragraph again, e.g. when only that
+    /// sentence came out wrong. `ctx.paragraph_text` holds just the sentence;
+    /// if the model splits it anyway, the parts are merged back into one.
+    async fn retranslate_sentence(&self, ctx: TranslationContext<'_>) -> anyhow::Result<Sentence> {
+        let translation = self.get_translation(ctx).await?;
+        let mut sentences = translation.sentences.into_iter();
+        let mut merged = sentences
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Translation contains no sentences"))?;
+        for sentence in sentences {
+            merged.full_translation.push(' ');
+            merged.full_translation.push_str(&sentence.full_translation);
+            merged.words.extend(sentence.words);
+        }
+        Ok(merged)
+    }
+
+    fn get_prompt(from: &str, to: &str, max_contextual_translations: usize) -> String
     where
         Self: Sized,
     {
@@ -503,6 +682,7 @@ pub trait Translator: Send + Sync {
         Translation forms — these two fields have distinct roles, do not conflate them:
             - 'targetInitialForm' is the CITATION-form translation in {to} for the same partOfSpeech tag the source carries (see the initial-forms rule above). Always populate this with a clean entry in that tag's citation form, even when the rendered sentence uses a different syntactic structure or restructures the meaning across multiple words. Illustrative examples (English → Russian): 'had witnessed' (tag: verb) → 'быть свидетелем' (a verb infinitive), NOT 'свидетелем'. 'the mourning lord' (tag: participle_present) → 'скорбящий' (a Russian participle), NOT 'скорбеть'. 'Swimming is fun' (tag: gerund) → 'плавание' (a verbal noun), NOT 'плавать'. Apply the same principle for any {from}/{to} pair. If the source word truly has no translatable meaning, leave 'targetInitialForm' empty — but always try first. EXCEPTION for `verb_auxiliary` / `verb_modal` / `verb_copula` when the form is a pro-verb, do-support, copular ellipsis, or modal-in-ellipsis: do NOT backfill the antecedent's meaning. Keep targetInitialForm anchored to the auxiliary/modal/copula itself; leave it empty and use 'note' if no clean target-language equivalent exists for the auxiliary in isolation. 'Oh, he does' (pro-verb for 'hates') → targetInitialForm is the auxiliary 'do' equivalent or empty, NEVER 'ненавидеть'.
             - 'contextualTranslations' are translation variants that fit the CURRENT sentence in-context. They are used to annotate the original text inside the reader UI, so fragments, oblique forms, or any rendering that matches how this specific occurrence appears in the translated sentence is welcome. Distinct purpose from targetInitialForm — these are for in-text help, that one is for flashcards.
+            - Give at most {max_contextual_translations} contextualTranslations per word, best fit first.
             - If the source word's meaning cannot be conveyed by a single {to} word — for example because the sentence restructures the idea — still produce a dictionary-form translation of the lemma in 'targetInitialForm' that best captures the source meaning, and use the 'note' field to explain the mismatch.
         {pos_block}
         Maintain consistency:
@@ -618,38 +798,68 @@ pub(crate) fn paragraph_translation_schema() -> serde_json::Value {
 }
 
 pub fn get_translator(
-    cache: Arc<TranslationsCache>,
-    context_provider: Arc<dyn ChapterContextProvider>,
-    gemini_prompt_cache: Arc<gemini_cache::GeminiPromptCache>,
+    services: TranslatorServices,
     provider: TranslationProvider,
     translation_model: TranslationModel,
     api_key: String,
     from: Language,
     to: Language,
+    config: TranslatorConfig,
 ) -> anyhow::Result<Box<dyn Translator>> {
     match provider {
+        TranslationProvider::Google if translation_model == TranslationModel::GoogleTranslate => {
+            Ok(Box::new(GoogleTranslateTranslator::create(
+                services.cache,
+                api_key,
+                &from,
+                &to,
+                config.request_log,
+            )?))
+        }
         TranslationProvider::Google => Ok(Box::new(GeminiTranslator::create(
-            cache,
-            context_provider,
-            gemini_prompt_cache,
+            services,
             translation_model,
             api_key,
             &from,
             &to,
+            config,
         )?)),
         TranslationProvider::Openai | TranslationProvider::Deepseek | TranslationProvider::Zai => {
             Ok(Box::new(OpenAITranslator::create(
-                cache,
-                context_provider,
+                services.cache,
+                services.context_provider,
                 translation_model,
                 api_key,
                 &from,
                 &to,
+                config,
             )?))
         }
     }
 }
 
+/// The language a book in `book_language` (ISO 639-3, as stored in
+/// `book.dat`) is translated from unless the caller asks for another one.
+pub fn book_source_language(book_language: &str) -> anyhow::Result<Language> {
+    Language::from_639_3(book_language)
+        .ok_or_else(|| anyhow::anyhow!("Unknown book language {book_language:?}"))
+}
+
+/// Fails when a book in `book_language` doesn't match the source language
+/// the translator is created with, so a German book isn't silently
+/// translated as if it were English.
+pub fn check_source_language(book_language: &str, source: &Language) -> anyhow::Result<()> {
+    let book_language = book_source_language(book_language)?;
+    if book_language != *source {
+        anyhow::bail!(
+            "Book is in {} but the source language is {}",
+            book_language.to_name(),
+            source.to_name()
+        );
+    }
+    Ok(())
+}
+
 fn is_reqwest_transient(re: &reqwest::Error) -> bool {
     re.is_timeout() || re.is_connect() || re.is_request()
 }
@@ -739,7 +949,51 @@ pub fn is_transient_translation_error(err: &anyhow::Error) -> bool {
 mod tests {
     use std::sync::{Arc, Mutex};
 
-    use super::{StreamChunkAccumulator, is_transient_translation_error};
+    use isolang::Language;
+
+    use super::{
+        StreamChunkAccumulator, book_source_language, check_source_language,
+        is_transient_translation_error, is_visible_part_of_speech,
+    };
+
+    #[test]
+    fn content_words_cover_lexical_tags_only() {
+        for tag in ["common_noun", "verb", "adverb", "Noun / adjective"] {
+            assert!(super::is_content_word(tag), "{tag}");
+        }
+        for tag in ["verb_auxiliary", "determiner_article", "other", ""] {
+            assert!(!super::is_content_word(tag), "{tag}");
+        }
+    }
+
+    #[test]
+    fn visible_parts_of_speech_cover_finer_tags() {
+        let visible = ["Noun", "verb", "adj"]
+            .into_iter()
+            .map(str::to_owned)
+            .collect();
+        for tag in ["common_noun", "proper_noun", "verb_modal", "adjective"] {
+            assert!(is_visible_part_of_speech(tag, &visible), "{tag}");
+        }
+        assert!(is_visible_part_of_speech("other / noun", &visible));
+        for tag in ["pronoun_personal", "determiner_article", "adverb", ""] {
+            assert!(!is_visible_part_of_speech(tag, &visible), "{tag}");
+        }
+    }
+
+    #[test]
+    fn source_language_mismatch_is_detected() {
+        let english = Language::from_639_3("eng").unwrap();
+        assert!(check_source_language("eng", &english).is_ok());
+
+        let err = check_source_language("deu", &english).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Book is in German but the source language is English"
+        );
+        assert!(check_source_language("xxx", &english).is_err());
+        assert_eq!(book_source_language("deu").unwrap().to_name(), "German");
+    }
 
     #[test]
     fn first_chunk_error_is_retried() {
@@ -859,6 +1113,15 @@ mod tests {
         assert_eq!(accumulator.tail(1000), "приём");
     }
 
+    #[test]
+    fn suggested_worker_count_follows_rate_and_latency() {
+        let secs = std::time::Duration::from_secs;
+        assert_eq!(super::suggested_worker_count(60, secs(20)), 20);
+        assert_eq!(super::suggested_worker_count(10, secs(30)), 5);
+        assert_eq!(super::suggested_worker_count(15, secs(1)), 1);
+        assert_eq!(super::suggested_worker_count(0, secs(20)), 1);
+    }
+
     #[test]
     fn total_stream_timeout_scales_with_input() {
         let short = super::total_stream_timeout(100);
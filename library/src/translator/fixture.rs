@@ -0,0 +1,144 @@
+//! Offline translator that replays canned responses. Each fixture is a
+//! `translation_import::ParagraphTranslation` JSON file named after the FNV
+//! hash of the paragraph text, so the same input always yields the same
+//! output without touching a provider API.
+
+use std::{
+    hash::Hasher,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+
+use crate::{
+    book::translation_import::ParagraphTranslation,
+    translator::{TranslationContext, TranslationModel, Translator},
+};
+
+pub struct FixtureTranslator {
+    fixtures_dir: PathBuf,
+}
+
+impl FixtureTranslator {
+    pub fn new(fixtures_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            fixtures_dir: fixtures_dir.into(),
+        }
+    }
+
+    /// Where the fixture for `paragraph_text` is expected, for recording new
+    /// fixtures or pointing at the missing one.
+    pub fn fixture_path(&self, paragraph_text: &str) -> PathBuf {
+        fixture_path(&self.fixtures_dir, paragraph_text)
+    }
+}
+
+fn fixture_path(fixtures_dir: &Path, paragraph_text: &str) -> PathBuf {
+    let mut hasher = fnv::FnvHasher::default();
+    hasher.write(paragraph_text.as_bytes());
+    fixtures_dir.join(format!("{:016x}.json", hasher.finish()))
+}
+
+#[async_trait]
+impl Translator for FixtureTranslator {
+    fn get_model(&self) -> TranslationModel {
+        TranslationModel::Unknown
+    }
+
+    async fn get_translation(
+        &self,
+        ctx: TranslationContext<'_>,
+    ) -> anyhow::Result<ParagraphTranslation> {
+        let path = self.fixture_path(ctx.paragraph_text);
+        let data = tokio::fs::read(&path)
+            .await
+            .map_err(|err| anyhow::anyhow!("No translation fixture at {path:?}: {err}"))?;
+        let mut translation: ParagraphTranslation = serde_json::from_slice(&data)?;
+        // The timestamp isn't part of the JSON; stamp it like a live
+        // response so history ordering behaves the same.
+        translation.timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        if let Some(callback) = ctx.callback {
+            callback(data.len());
+        }
+        Ok(translation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{book::translation_import, test_utils::TempDir};
+
+    fn context(text: &str) -> TranslationContext<'_> {
+        TranslationContext {
+            paragraph_text: text,
+            book_id: Uuid::nil(),
+            chapter_id: 0,
+            use_cache: false,
+            callback: None,
+            token_sink: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn replays_fixture_keyed_by_text() {
+        let tmp = TempDir::new("flts_fixture_translator");
+        let translator = FixtureTranslator::new(&tmp.path);
+        let canned = ParagraphTranslation {
+            timestamp: 0,
+            sentences: vec![translation_import::Sentence {
+                full_translation: "Hello.".into(),
+                words: vec![],
+            }],
+            total_tokens: None,
+            token_usage: None,
+        };
+        std::fs::write(
+            translator.fixture_path("Hola."),
+            serde_json::to_vec(&canned).unwrap(),
+        )
+        .unwrap();
+
+        let translation = translator.get_translation(context("Hola.")).await.unwrap();
+        assert_eq!(translation.sentences, canned.sentences);
+        assert!(translation.timestamp > 0);
+        assert!(translator.get_translation(context("Adiós.")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn stream_translation_emits_whole_response_once() {
+        let tmp = TempDir::new("flts_fixture_translator_stream");
+        let translator = FixtureTranslator::new(&tmp.path);
+        let canned = ParagraphTranslation {
+            timestamp: 0,
+            sentences: vec![],
+            total_tokens: None,
+            token_usage: None,
+        };
+        std::fs::write(
+            translator.fixture_path("Hola."),
+            serde_json::to_vec(&canned).unwrap(),
+        )
+        .unwrap();
+
+        let chunks = Arc::new(Mutex::new(Vec::new()));
+        let sink = {
+            let chunks = chunks.clone();
+            Arc::new(move |chunk: &str| chunks.lock().unwrap().push(chunk.to_owned()))
+        };
+        translator
+            .stream_translation(context("Hola."), sink)
+            .await
+            .unwrap();
+
+        let chunks = chunks.lock().unwrap();
+        assert_eq!(chunks.len(), 1);
+        let parsed: ParagraphTranslation = serde_json::from_str(&chunks[0]).unwrap();
+        assert_eq!(parsed.sentences, canned.sentences);
+    }
+}
@@ -15,16 +15,18 @@ use serde_json::Value;
 use tokio::time::timeout;
 
 use crate::{
-    book::translation_import::ParagraphTranslation,
+    book::translation_import::{ParagraphTranslation, TokenUsage},
     cache::TranslationsCache,
     translator::{
-        ChapterContextProvider, ProgressCallback, TranslationContext, TranslationErrors,
-        TranslationModel, Translator,
+        ChapterContextProvider, TranslationContext, TranslationErrors, TranslationModel,
+        Translator, TranslatorConfig, TranslatorServices,
         gemini_cache::{
             CacheContent, CacheKey, GeminiPromptCache, build_reference_material,
             is_cache_missing_error,
         },
-        paragraph_translation_schema, strip_additional_properties,
+        paragraph_translation_schema,
+        request_log::RequestLog,
+        strip_additional_properties,
     },
 };
 use uuid::Uuid;
@@ -140,31 +142,38 @@ pub struct GeminiTranslator {
     translation_model: TranslationModel,
     from: Language,
     to: Language,
+    config: TranslatorConfig,
+    request_log: Option<RequestLog>,
 }
 
 impl GeminiTranslator {
     pub fn create(
-        cache: Arc<TranslationsCache>,
-        context_provider: Arc<dyn ChapterContextProvider>,
-        prompt_cache: Arc<GeminiPromptCache>,
+        services: TranslatorServices,
         translation_model: TranslationModel,
         api_key: String,
         from: &Language,
         to: &Language,
+        config: TranslatorConfig,
     ) -> anyhow::Result<GeminiTranslator> {
         let model = gemini_model(translation_model)?;
+        let request_log = config
+            .request_log
+            .clone()
+            .map(|path| RequestLog::new(path, &api_key));
         let client = gemini_client(api_key, model.clone())?;
 
         Ok(Self {
-            cache,
-            context_provider,
-            prompt_cache,
+            cache: services.cache,
+            context_provider: services.context_provider,
+            prompt_cache: services.gemini_prompt_cache,
             client,
             schema: Arc::new(gemini_paragraph_schema()),
             model,
             translation_model,
             from: *from,
             to: *to,
+            config,
+            request_log,
         })
     }
 
@@ -179,6 +188,20 @@ impl GeminiTranslator {
     }
 
     fn thinking_config(&self) -> ThinkingConfig {
+        if let Some(budget) = self.config.thinking_budget
+            && matches!(
+                self.translation_model,
+                TranslationModel::Gemini25Flash
+                    | TranslationModel::Gemini25Pro
+                    | TranslationModel::Gemini25FlashLight
+            )
+        {
+            return ThinkingConfig {
+                thinking_budget: Some(budget),
+                include_thoughts: Some(false),
+                thinking_level: None,
+            };
+        }
         match &self.model {
             Model::Gemini25Flash => ThinkingConfig {
                 thinking_budget: Some(0),
@@ -198,24 +221,31 @@ impl GeminiTranslator {
     /// so a missing / expired server-side cache can be evicted and retried.
     async fn attempt_translation(
         &self,
-        paragraph: &str,
-        book_id: Uuid,
-        chapter_id: usize,
-        prior_summaries: String,
-        chapter_text: String,
-        callback: Option<&ProgressCallback>,
+        ctx: &TranslationContext<'_>,
+        prior_summaries: &str,
+        chapter_text: &str,
     ) -> anyhow::Result<ParagraphTranslation> {
+        let paragraph = ctx.paragraph_text;
+        let book_id = ctx.book_id;
+        let chapter_id = ctx.chapter_id;
+        let callback = ctx.callback.as_deref();
+        let token_sink = ctx.token_sink.as_deref();
         let from = self.from;
         let to = self.to;
+        let max_contextual_translations = self.config.max_contextual_translations;
         let key = self.cache_key(book_id, chapter_id);
 
         let cache_handle: Arc<CachedContentHandle> = timeout(
             CACHE_CREATE_TIMEOUT,
             self.prompt_cache
                 .get_or_create(&self.client, key.clone(), || {
-                    let reference = build_reference_material(&prior_summaries, &chapter_text);
+                    let reference = build_reference_material(prior_summaries, chapter_text);
                     CacheContent {
-                        system_instruction: Self::get_prompt(from.to_name(), to.to_name()),
+                        system_instruction: Self::get_prompt(
+                            from.to_name(),
+                            to.to_name(),
+                            max_contextual_translations,
+                        ),
                         user_reference_material: reference,
                     }
                 }),
@@ -224,26 +254,43 @@ impl GeminiTranslator {
         .map_err(|_| anyhow::anyhow!("Gemini cache creation timed out"))??;
 
         let user_message = format!("Translate this paragraph: {paragraph}");
-        let mut stream = timeout(
-            TRANSLATION_REQUEST_TIMEOUT,
-            self.client
-                .generate_content()
-                .with_cached_content(&cache_handle)
-                .with_user_message(user_message)
-                .with_response_mime_type("application/json")
-                .with_response_schema((*self.schema).clone())
-                .with_thinking_config(self.thinking_config())
-                .with_safety_settings(permissive_safety_settings())
-                .execute_stream(),
-        )
-        .await
-        .map_err(|_| anyhow::anyhow!("Gemini request timed out"))??;
+        // The chapter reference lives in the server-side cache and is left
+        // out; the system prompt is rebuilt here, as it shapes every answer.
+        let logged_request = self.request_log.as_ref().map(|_| {
+            serde_json::json!({
+                "bookId": book_id,
+                "chapterId": chapter_id,
+                "systemInstruction": Self::get_prompt(
+                    from.to_name(),
+                    to.to_name(),
+                    max_contextual_translations,
+                ),
+                "userMessage": user_message,
+                "temperature": self.config.temperature,
+                "thinkingBudget": self.config.thinking_budget,
+            })
+        });
+        let mut request = self
+            .client
+            .generate_content()
+            .with_cached_content(&cache_handle)
+            .with_user_message(user_message)
+            .with_response_mime_type("application/json")
+            .with_response_schema((*self.schema).clone())
+            .with_thinking_config(self.thinking_config())
+            .with_safety_settings(permissive_safety_settings());
+        if let Some(temperature) = self.config.temperature {
+            request = request.with_temperature(temperature);
+        }
+        let mut stream = timeout(TRANSLATION_REQUEST_TIMEOUT, request.execute_stream())
+            .await
+            .map_err(|_| anyhow::anyhow!("Gemini request timed out"))??;
 
         // The accumulator and stream metadata live OUTSIDE the timed future:
         // when a timeout fires the future is dropped, but the diagnostics
         // below still need to report how much arrived and what the server
         // last said (finish reason / usage), otherwise aborts are opaque.
-        let mut accumulator = StreamChunkAccumulator::new("Gemini");
+        let mut accumulator = StreamChunkAccumulator::new("Gemini").with_token_sink(token_sink);
         let mut last_finish_reason: Option<FinishReason> = None;
         let mut last_usage: Option<UsageMetadata> = None;
         let started = Instant::now();
@@ -297,6 +344,15 @@ impl GeminiTranslator {
         }
 
         let full_content = accumulator.finish()?;
+        if let (Some(log), Some(logged_request)) = (&self.request_log, logged_request) {
+            log.append(
+                "Gemini",
+                self.translation_model,
+                logged_request,
+                &full_content,
+            )
+            .await;
+        }
 
         // A MAX_TOKENS finish means the JSON is truncated. Bail before the
         // serde parse: a serde error is classified permanent and would kill
@@ -330,7 +386,16 @@ impl GeminiTranslator {
 
         let mut translation: ParagraphTranslation = serde_json::from_str(&full_content)?;
         translation.normalize_html_entities();
+        translation.truncate_contextual_translations(self.config.max_contextual_translations);
         translation.total_tokens = usage.and_then(|u| u.total_token_count).map(|c| c as u64);
+        translation.token_usage = usage.and_then(|u| {
+            let completion =
+                u.candidates_token_count.unwrap_or(0) + u.thoughts_token_count.unwrap_or(0);
+            Some(TokenUsage {
+                prompt_tokens: u.prompt_token_count? as u64,
+                completion_tokens: completion as u64,
+            })
+        });
         Ok(translation)
     }
 }
@@ -348,7 +413,12 @@ impl Translator for GeminiTranslator {
         if ctx.use_cache
             && let Some(cached_result) = self
                 .cache
-                .get(&self.from, &self.to, ctx.paragraph_text)
+                .get(
+                    &self.from,
+                    &self.to,
+                    self.translation_model,
+                    ctx.paragraph_text,
+                )
                 .await
                 .ok()
                 .flatten()
@@ -359,7 +429,6 @@ impl Translator for GeminiTranslator {
         let paragraph = ctx.paragraph_text;
         let book_id = ctx.book_id;
         let chapter_id = ctx.chapter_id;
-        let cb = ctx.callback.as_deref();
 
         // Block until the prerequisite per-chapter summaries are ready.
         // The UI gates translate buttons on the same predicate, so this
@@ -380,14 +449,7 @@ impl Translator for GeminiTranslator {
             .unwrap_or_default();
 
         let first = self
-            .attempt_translation(
-                paragraph,
-                book_id,
-                chapter_id,
-                prior_summaries.clone(),
-                chapter_text.clone(),
-                cb,
-            )
+            .attempt_translation(&ctx, &prior_summaries, &chapter_text)
             .await;
         let mut translation = match first {
             Ok(t) => t,
@@ -398,15 +460,8 @@ impl Translator for GeminiTranslator {
                 self.prompt_cache
                     .evict(&self.cache_key(book_id, chapter_id))
                     .await;
-                self.attempt_translation(
-                    paragraph,
-                    book_id,
-                    chapter_id,
-                    prior_summaries,
-                    chapter_text,
-                    cb,
-                )
-                .await?
+                self.attempt_translation(&ctx, &prior_summaries, &chapter_text)
+                    .await?
             }
             Err(err) => return Err(err),
         };
@@ -415,8 +470,13 @@ impl Translator for GeminiTranslator {
         let duration_since_epoch = now.duration_since(UNIX_EPOCH)?;
         translation.timestamp = duration_since_epoch.as_secs();
 
-        self.cache
-            .set(&self.from, &self.to, paragraph, &translation);
+        self.cache.set(
+            &self.from,
+            &self.to,
+            self.translation_model,
+            paragraph,
+            &translation,
+        );
 
         info!(
             "Gemini translation complete (paragraph {} chars, response {} chars)",
@@ -0,0 +1,266 @@
+//! Non-LLM draft translator backed by the Google Cloud Translation API (v2).
+//! It only produces sentence-level `full_translation`s. Words are split
+//! naively from the source text and their grammar fields are placeholders:
+//! part of speech `other`, the original as its own initial form, an empty
+//! target form and no contextual translations. The empty target form keeps
+//! these words out of the dictionary. Meant for a cheap first read before
+//! running an LLM pass over the book.
+
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use isolang::Language;
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    book::translation_import::{Grammar, ParagraphTranslation, Sentence, Word},
+    cache::TranslationsCache,
+    translator::{TranslationContext, TranslationModel, Translator, request_log::RequestLog},
+};
+
+use super::TRANSLATION_REQUEST_TIMEOUT;
+
+const TRANSLATE_ENDPOINT: &str = "https://translation.googleapis.com/language/translate/v2";
+
+pub struct GoogleTranslateTranslator {
+    cache: Arc<TranslationsCache>,
+    client: reqwest::Client,
+    api_key: String,
+    from: Language,
+    to: Language,
+    request_log: Option<RequestLog>,
+}
+
+impl GoogleTranslateTranslator {
+    pub fn create(
+        cache: Arc<TranslationsCache>,
+        api_key: String,
+        from: &Language,
+        to: &Language,
+        request_log: Option<PathBuf>,
+    ) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(TRANSLATION_REQUEST_TIMEOUT)
+            .build()?;
+        let request_log = request_log.map(|path| RequestLog::new(path, &api_key));
+        Ok(Self {
+            cache,
+            client,
+            api_key,
+            from: *from,
+            to: *to,
+            request_log,
+        })
+    }
+
+    async fn translate_sentences(&self, sentences: &[&str]) -> anyhow::Result<Vec<String>> {
+        let (Some(source), Some(target)) = (self.from.to_639_1(), self.to.to_639_1()) else {
+            anyhow::bail!(
+                "Google Translate needs ISO 639-1 codes, {} -> {} has none",
+                self.from.to_name(),
+                self.to.to_name()
+            );
+        };
+        let request = TranslateRequest {
+            q: sentences,
+            source,
+            target,
+            format: "text",
+        };
+        let resp = self
+            .client
+            .post(TRANSLATE_ENDPOINT)
+            .query(&[("key", self.api_key.as_str())])
+            .json(&request)
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            // Status encoded numerically so `is_transient_translation_error`
+            // can classify 429/5xx as retryable.
+            anyhow::bail!("Google Translate HTTP {}", resp.status().as_u16());
+        }
+        let raw = resp.text().await?;
+        if let Some(log) = &self.request_log {
+            log.append(
+                "Google Translate",
+                TranslationModel::GoogleTranslate,
+                serde_json::to_value(&request)?,
+                &raw,
+            )
+            .await;
+        }
+        let body: TranslateResponse = serde_json::from_str(&raw)?;
+        if body.data.translations.len() != sentences.len() {
+            anyhow::bail!(
+                "Google Translate returned {} translations for {} sentences",
+                body.data.translations.len(),
+                sentences.len()
+            );
+        }
+        Ok(body
+            .data
+            .translations
+            .into_iter()
+            .map(|t| t.translated_text)
+            .collect())
+    }
+}
+
+#[derive(Serialize)]
+struct TranslateRequest<'a> {
+    q: &'a [&'a str],
+    source: &'a str,
+    target: &'a str,
+    format: &'a str,
+}
+
+#[derive(Deserialize)]
+struct TranslateResponse {
+    data: TranslateResponseData,
+}
+
+#[derive(Deserialize)]
+struct TranslateResponseData {
+    translations: Vec<TranslatedText>,
+}
+
+#[derive(Deserialize)]
+struct TranslatedText {
+    #[serde(rename = "translatedText")]
+    translated_text: String,
+}
+
+fn split_sentences(paragraph: &str) -> Vec<&str> {
+    paragraph
+        .split_sentence_bounds()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Word with placeholder grammar: the draft backend knows nothing about
+/// lemmas or parts of speech.
+fn placeholder_word(original: &str) -> Word {
+    Word {
+        original: original.to_owned(),
+        contextual_translations: vec![],
+        note: None,
+        is_punctuation: !original.chars().any(char::is_alphanumeric),
+        grammar: Grammar {
+            original_initial_form: original.to_owned(),
+            target_initial_form: String::new(),
+            part_of_speech: "other".to_owned(),
+            plurality: None,
+            person: None,
+            tense: None,
+            case: None,
+            other: None,
+        },
+    }
+}
+
+pub(crate) fn draft_sentence(original: &str, full_translation: String) -> Sentence {
+    Sentence {
+        full_translation,
+        words: original
+            .split_word_bounds()
+            .filter(|w| !w.trim().is_empty())
+            .map(placeholder_word)
+            .collect(),
+    }
+}
+
+#[async_trait]
+impl Translator for GoogleTranslateTranslator {
+    fn get_model(&self) -> TranslationModel {
+        TranslationModel::GoogleTranslate
+    }
+
+    async fn get_translation(
+        &self,
+        ctx: TranslationContext<'_>,
+    ) -> anyhow::Result<ParagraphTranslation> {
+        if ctx.use_cache
+            && let Some(cached_result) = self
+                .cache
+                .get(
+                    &self.from,
+                    &self.to,
+                    TranslationModel::GoogleTranslate,
+                    ctx.paragraph_text,
+                )
+                .await
+                .ok()
+                .flatten()
+        {
+            return Ok(cached_result);
+        }
+
+        let sentences = split_sentences(ctx.paragraph_text);
+        let translated = if sentences.is_empty() {
+            vec![]
+        } else {
+            self.translate_sentences(&sentences).await?
+        };
+        if let Some(callback) = ctx.callback {
+            callback(translated.iter().map(String::len).sum());
+        }
+
+        let translation = ParagraphTranslation {
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            sentences: sentences
+                .into_iter()
+                .zip(translated)
+                .map(|(original, translation)| draft_sentence(original, translation))
+                .collect(),
+            total_tokens: None,
+            token_usage: None,
+        };
+
+        self.cache.set(
+            &self.from,
+            &self.to,
+            TranslationModel::GoogleTranslate,
+            ctx.paragraph_text,
+            &translation,
+        );
+
+        Ok(translation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draft_sentences_carry_placeholder_words() {
+        let sentences = split_sentences("¡Hola, mundo! ¿Qué tal?");
+        assert_eq!(sentences, vec!["¡Hola, mundo!", "¿Qué tal?"]);
+
+        let sentence = draft_sentence(sentences[0], "Hello, world!".into());
+        assert_eq!(sentence.full_translation, "Hello, world!");
+        assert_eq!(
+            sentence
+                .words
+                .iter()
+                .map(|w| (w.original.as_str(), w.is_punctuation))
+                .collect::<Vec<_>>(),
+            vec![
+                ("¡", true),
+                ("Hola", false),
+                (",", true),
+                ("mundo", false),
+                ("!", true)
+            ]
+        );
+        let grammar = &sentence.words[1].grammar;
+        assert_eq!(grammar.part_of_speech, "other");
+        assert!(grammar.target_initial_form.is_empty());
+    }
+}
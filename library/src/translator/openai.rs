@@ -5,8 +5,8 @@ use std::{
 
 use async_openai::types::chat::{
     ChatCompletionRequestMessage, ChatCompletionRequestSystemMessageArgs,
-    ChatCompletionRequestUserMessageArgs, CreateChatCompletionRequestArgs, ResponseFormat,
-    ResponseFormatJsonSchema,
+    ChatCompletionRequestUserMessageArgs, CreateChatCompletionRequest,
+    CreateChatCompletionRequestArgs, ResponseFormat, ResponseFormatJsonSchema,
 };
 use async_openai::{Client, config::OpenAIConfig};
 use async_trait::async_trait;
@@ -20,7 +20,8 @@ use crate::{
     cache::TranslationsCache,
     translator::{
         ChapterContextProvider, TranslationContext, TranslationErrors, TranslationModel,
-        TranslationProvider, Translator, paragraph_translation_schema,
+        TranslationProvider, Translator, TranslatorConfig, paragraph_translation_schema,
+        request_log::RequestLog,
     },
 };
 
@@ -38,6 +39,8 @@ pub struct OpenAITranslator {
     translation_model: TranslationModel,
     from: Language,
     to: Language,
+    max_contextual_translations: usize,
+    request_log: Option<RequestLog>,
 }
 
 pub(crate) const DEEPSEEK_BASE_URL: &str = "https://api.deepseek.com";
@@ -86,12 +89,16 @@ impl OpenAITranslator {
         api_key: String,
         from: &Language,
         to: &Language,
+        config: TranslatorConfig,
     ) -> anyhow::Result<Self> {
         let schema = paragraph_translation_schema();
         let model = openai_model_name(translation_model)?;
         let base_url = translation_model
             .provider()
             .and_then(openai_compat_base_url);
+        let request_log = config
+            .request_log
+            .map(|path| RequestLog::new(path, &api_key));
         let client = openai_client(api_key, base_url);
 
         Ok(Self {
@@ -103,8 +110,17 @@ impl OpenAITranslator {
             translation_model,
             from: *from,
             to: *to,
+            max_contextual_translations: config.max_contextual_translations,
+            request_log,
         })
     }
+
+    /// The request as JSON when request logging is on.
+    fn logged_request(&self, request: &CreateChatCompletionRequest) -> Option<Value> {
+        self.request_log
+            .as_ref()
+            .and_then(|_| serde_json::to_value(request).ok())
+    }
 }
 
 #[async_trait]
@@ -120,7 +136,12 @@ impl Translator for OpenAITranslator {
         if ctx.use_cache
             && let Some(cached_result) = self
                 .cache
-                .get(&self.from, &self.to, ctx.paragraph_text)
+                .get(
+                    &self.from,
+                    &self.to,
+                    self.translation_model,
+                    ctx.paragraph_text,
+                )
                 .await
                 .ok()
                 .flatten()
@@ -132,13 +153,18 @@ impl Translator for OpenAITranslator {
         let book_id = ctx.book_id;
         let chapter_id = ctx.chapter_id;
         let callback = ctx.callback;
+        let token_sink = ctx.token_sink;
         let is_deepseek = matches!(
             self.translation_model.provider(),
             Some(TranslationProvider::Deepseek) | Some(TranslationProvider::Zai)
         );
         let mut system_prompt = format!(
             "{}\n\nReturn ONLY a single JSON object that matches the requested schema. Do not wrap it in markdown.",
-            Self::get_prompt(self.from.to_name(), self.to.to_name())
+            Self::get_prompt(
+                self.from.to_name(),
+                self.to.to_name(),
+                self.max_contextual_translations,
+            )
         );
         // DeepSeek's JSON mode does not enforce a schema server-side — it only
         // guarantees valid JSON — so we inline the schema in the prompt so the
@@ -205,13 +231,19 @@ impl Translator for OpenAITranslator {
             Some(TranslationProvider::Zai)
         );
 
+        let mut request = CreateChatCompletionRequestArgs::default();
+        request
+            .model(self.model.as_ref())
+            .messages(messages)
+            .response_format(response_format);
+        // z.AI does not reliably support SSE streaming; use a single blocking call.
+        if !is_zai {
+            request.stream(true);
+        }
+        let request = request.build()?;
+        let logged_request = self.logged_request(&request);
+
         let full_content = if is_zai {
-            // z.AI does not reliably support SSE streaming; use a single blocking call.
-            let request = CreateChatCompletionRequestArgs::default()
-                .model(self.model.as_ref())
-                .messages(messages)
-                .response_format(response_format)
-                .build()?;
             let response =
                 timeout(TRANSLATION_REQUEST_TIMEOUT, self.client.chat().create(request))
                     .await
@@ -222,19 +254,14 @@ impl Translator for OpenAITranslator {
                 .and_then(|c| c.message.content.clone())
                 .unwrap_or_default()
         } else {
-            let request = CreateChatCompletionRequestArgs::default()
-                .model(self.model.as_ref())
-                .messages(messages)
-                .response_format(response_format)
-                .stream(true)
-                .build()?;
             let mut stream = timeout(
                 TRANSLATION_REQUEST_TIMEOUT,
                 self.client.chat().create_stream(request),
             )
             .await
             .map_err(|_| anyhow::anyhow!("OpenAI request timed out"))??;
-            let mut accumulator = StreamChunkAccumulator::new("OpenAI");
+            let mut accumulator =
+                StreamChunkAccumulator::new("OpenAI").with_token_sink(token_sink.as_deref());
 
             timeout(total_stream_timeout(paragraph.len()), async {
                 loop {
@@ -265,8 +292,19 @@ impl Translator for OpenAITranslator {
             .map_err(|_| anyhow::anyhow!("OpenAI total stream timeout"))??
         };
 
+        if let (Some(log), Some(logged_request)) = (&self.request_log, logged_request) {
+            log.append(
+                "OpenAI",
+                self.translation_model,
+                logged_request,
+                &full_content,
+            )
+            .await;
+        }
+
         let mut translation: ParagraphTranslation = serde_json::from_str(&full_content)?;
         translation.normalize_html_entities();
+        translation.truncate_contextual_translations(self.max_contextual_translations);
 
         // Note: Usage data might not be available in stream chunks easily or at all in some API versions for stream.
         // We'll skip setting usage for now or check if the final chunk has usage?
@@ -279,8 +317,13 @@ impl Translator for OpenAITranslator {
         let duration_since_epoch = now.duration_since(UNIX_EPOCH)?;
         translation.timestamp = duration_since_epoch.as_secs();
 
-        self.cache
-            .set(&self.from, &self.to, paragraph, &translation);
+        self.cache.set(
+            &self.from,
+            &self.to,
+            self.translation_model,
+            paragraph,
+            &translation,
+        );
 
         Ok(translation)
     }
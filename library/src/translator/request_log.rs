@@ -0,0 +1,168 @@
+//! Opt-in debugging aid: every translator request is appended, together with
+//! the raw model response, as one JSON line to a log file. The API key and
+//! any auth-looking field are redacted before anything is written.
+
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::warn;
+use serde_json::Value;
+use tokio::{io::AsyncWriteExt, sync::Mutex};
+
+use crate::translator::TranslationModel;
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Serializes appends from all translators in the process, so concurrent
+/// paragraphs never interleave their lines.
+static WRITE_LOCK: Mutex<()> = Mutex::const_new(());
+
+pub(crate) struct RequestLog {
+    path: PathBuf,
+    api_key: String,
+}
+
+impl RequestLog {
+    pub(crate) fn new(path: PathBuf, api_key: &str) -> Self {
+        Self {
+            path,
+            api_key: api_key.to_owned(),
+        }
+    }
+
+    /// Appends one entry. `response` is stored as JSON when it parses and as
+    /// a plain string otherwise, which is the interesting case when
+    /// debugging. Write failures are logged and otherwise ignored.
+    pub(crate) async fn append(
+        &self,
+        provider: &str,
+        model: TranslationModel,
+        request: Value,
+        response: &str,
+    ) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let response =
+            serde_json::from_str(response).unwrap_or_else(|_| Value::String(response.to_owned()));
+        let mut entry = serde_json::json!({
+            "timestamp": timestamp,
+            "provider": provider,
+            "model": model.to_string(),
+            "request": request,
+            "response": response,
+        });
+        redact(&mut entry, &self.api_key);
+
+        if let Err(err) = self.write_line(&entry).await {
+            warn!("Failed to append to request log {:?}: {err}", self.path);
+        }
+    }
+
+    async fn write_line(&self, entry: &Value) -> anyhow::Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        let _guard = WRITE_LOCK.lock().await;
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(&line).await?;
+        file.flush().await?;
+        Ok(())
+    }
+}
+
+fn is_secret_field(name: &str) -> bool {
+    let normalized: String = name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    matches!(
+        normalized.as_str(),
+        "authorization" | "key" | "apikey" | "xgoogapikey" | "accesstoken"
+    )
+}
+
+/// Replaces the values of auth-looking fields and every occurrence of
+/// `api_key` inside strings with a placeholder.
+fn redact(value: &mut Value, api_key: &str) {
+    match value {
+        Value::Object(map) => {
+            for (name, field) in map.iter_mut() {
+                if is_secret_field(name) {
+                    *field = Value::String(REDACTED.to_owned());
+                } else {
+                    redact(field, api_key);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| redact(item, api_key)),
+        Value::String(s) if !api_key.is_empty() && s.contains(api_key) => {
+            *s = s.replace(api_key, REDACTED);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn redact_scrubs_auth_fields_and_key_occurrences() {
+        let mut value = json!({
+            "headers": { "Authorization": "Bearer sk-123", "x-goog-api-key": "sk-123" },
+            "url": "https://example.com/v2?key=sk-123",
+            "messages": [{ "content": "Translate this paragraph: hola" }],
+        });
+        redact(&mut value, "sk-123");
+        assert_eq!(
+            value,
+            json!({
+                "headers": { "Authorization": REDACTED, "x-goog-api-key": REDACTED },
+                "url": "https://example.com/v2?key=[REDACTED]",
+                "messages": [{ "content": "Translate this paragraph: hola" }],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn append_writes_one_line_per_request() {
+        let tmp = crate::test_utils::TempDir::new("flts_request_log");
+        let path = tmp.path.join("requests.log");
+        let log = RequestLog::new(path.clone(), "sk-123");
+        log.append(
+            "OpenAI",
+            TranslationModel::OpenAIGpt5Mini,
+            json!({ "api_key": "sk-123" }),
+            "{\"s\": [",
+        )
+        .await;
+        log.append(
+            "OpenAI",
+            TranslationModel::OpenAIGpt5Mini,
+            json!({}),
+            "{\"s\": []}",
+        )
+        .await;
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<Value> = contents
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["request"]["api_key"], REDACTED);
+        assert_eq!(lines[0]["response"], "{\"s\": [");
+        assert_eq!(lines[1]["response"], json!({ "s": [] }));
+        assert!(!contents.contains("sk-123"));
+    }
+}
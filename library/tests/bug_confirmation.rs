@@ -75,6 +75,7 @@ fn overwrite_book_title(path: &Path, title: &str) {
 fn make_paragraph(ts: u64, text: &str) -> translation_import::ParagraphTranslation {
     translation_import::ParagraphTranslation {
         total_tokens: None,
+        token_usage: None,
         timestamp: ts,
         sentences: vec![translation_import::Sentence {
             full_translation: text.into(),
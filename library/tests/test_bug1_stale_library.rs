@@ -117,11 +117,13 @@ async fn test_bug1_stale_library_ref() {
     let book_a_id = library_a
         .create_book_plain("Book via stale ref", "Test paragraph", &en)
         .await
-        .unwrap();
+        .unwrap()
+        .id;
     let book_b_id = library_b
         .create_book_plain("Book via current ref", "Test paragraph", &en)
         .await
-        .unwrap();
+        .unwrap()
+        .id;
 
     let stale_books = library_a.list_books().await.unwrap();
     let current_books = library_b.list_books().await.unwrap();
@@ -48,7 +48,8 @@ async fn test_bug2_stale_snapshot_overwrites() {
     let _book_id = library
         .create_book_plain("F2 Test", "Original paragraph content", &en)
         .await
-        .unwrap();
+        .unwrap()
+        .id;
 
     // Shared FIFO event queue mimicking Tauri's event dispatch
     let event_queue: Arc<tokio::sync::Mutex<Vec<(String, usize)>>> =
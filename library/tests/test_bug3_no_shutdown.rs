@@ -35,6 +35,7 @@ impl Drop for TempDir {
 fn make_translation(text: &str) -> translation_import::ParagraphTranslation {
     translation_import::ParagraphTranslation {
         total_tokens: None,
+        token_usage: None,
         timestamp: 1000,
         sentences: vec![translation_import::Sentence {
             full_translation: text.into(),
@@ -69,7 +70,8 @@ async fn setup_dirty_library(lib_path: &Path) -> (Arc<Library>, uuid::Uuid) {
     let book_id = library
         .create_book_plain("F3 Test Book", "This is a test paragraph.", &en)
         .await
-        .unwrap();
+        .unwrap()
+        .id;
 
     // Add translation IN MEMORY ONLY — mirrors the window between
     // add_paragraph_translation and run_saver processing
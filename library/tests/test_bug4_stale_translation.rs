@@ -39,6 +39,7 @@ impl Drop for TempDir {
 fn make_translation(text: &str) -> translation_import::ParagraphTranslation {
     translation_import::ParagraphTranslation {
         total_tokens: None,
+        token_usage: None,
         timestamp: 2000,
         sentences: vec![translation_import::Sentence {
             full_translation: text.into(),
@@ -76,7 +77,8 @@ async fn test_bug4_stale_translation_stored() {
     let book_id = library
         .create_book_plain("F4 Test Book", "The cat sat on the mat.", &en)
         .await
-        .unwrap();
+        .unwrap()
+        .id;
 
     // Step 1: Worker reads paragraph (translation_queue.rs L244-254)
     // Lock acquired, paragraph read, lock released — this is the critical window.
@@ -167,7 +169,8 @@ async fn test_bug4_fix_detects_changed_paragraph() {
     let book_id = library
         .create_book_plain("F4 Fix Test", "Original paragraph text.", &en)
         .await
-        .unwrap();
+        .unwrap()
+        .id;
 
     // Simulate the worker's initial read (before translation API call)
     let snapshot_text = {
@@ -211,7 +214,8 @@ async fn test_bug4_fix_detects_changed_paragraph() {
     let book_id2 = library
         .create_book_plain("F4 Fix Test 2", "Version one text.", &en)
         .await
-        .unwrap();
+        .unwrap()
+        .id;
 
     let snapshot_text2 = {
         let book_handle = library.get_book(&book_id2).await.unwrap();
@@ -232,7 +236,8 @@ async fn test_bug4_fix_detects_changed_paragraph() {
             &en,
         )
         .await
-        .unwrap();
+        .unwrap()
+        .id;
 
     // Simulate: worker took snapshot from book3's paragraph 0 as "Version two text..."
     // but let's pretend the snapshot was "Version one text." (the old content)
@@ -257,7 +262,8 @@ async fn test_bug4_fix_detects_changed_paragraph() {
     let empty_book_id = library
         .create_book_plain("F4 Fix Test Empty", "", &en)
         .await
-        .unwrap();
+        .unwrap()
+        .id;
     let book_handle = library.get_book(&empty_book_id).await.unwrap();
     let book = book_handle.lock().await;
     let para_count = book.book.paragraphs_count();
@@ -70,6 +70,7 @@ fn sleep_for_mtime_tick() {
 fn make_paragraph(ts: u64, text: &str) -> translation_import::ParagraphTranslation {
     translation_import::ParagraphTranslation {
         total_tokens: None,
+        token_usage: None,
         timestamp: ts,
         sentences: vec![translation_import::Sentence {
             full_translation: text.into(),
@@ -45,6 +45,7 @@ impl Drop for TempDir {
 fn make_paragraph(ts: u64, text: &str) -> translation_import::ParagraphTranslation {
     translation_import::ParagraphTranslation {
         total_tokens: None,
+        token_usage: None,
         timestamp: ts,
         sentences: vec![translation_import::Sentence {
             full_translation: text.into(),
@@ -175,7 +176,8 @@ async fn trace_interaction_baseline() {
     let _new_book_id = library
         .create_book_plain("Second Book", "Some text content.", &en())
         .await
-        .unwrap();
+        .unwrap()
+        .id;
     span.end();
 
     // TauriComputeSnapshot: list_books
@@ -359,7 +361,8 @@ async fn trace_interaction_concurrent() {
         let _new = lib2
             .create_book_plain("Imported Book", "Fresh content.", &en())
             .await
-            .unwrap();
+            .unwrap()
+            .id;
         span.end();
 
         // TauriComputeSnapshot
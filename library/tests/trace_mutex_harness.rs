@@ -72,7 +72,8 @@ async fn trace_mutex_concurrent_save_and_list() {
     let book_id = library
         .create_book_plain("Test Book", "Hello world.\nGoodbye world.", &eng)
         .await
-        .unwrap();
+        .unwrap()
+        .id;
 
     // Pre-create a translation so the saver has something to iterate
     {
@@ -177,7 +178,8 @@ async fn trace_mutex_watcher_and_mark() {
     let book_id = library
         .create_book_plain("Watcher Test", "Paragraph one.\nParagraph two.", &eng)
         .await
-        .unwrap();
+        .unwrap()
+        .id;
 
     // Pre-create translation
     {
@@ -266,7 +268,8 @@ async fn trace_mutex_double_lock_pattern() {
     let book_id = library
         .create_book_plain("Double Lock Test", "Test paragraph.", &eng)
         .await
-        .unwrap();
+        .unwrap()
+        .id;
 
     // Pre-create translation
     {
@@ -1,4 +1,5 @@
 use std::{
+    collections::HashSet,
     error::Error,
     fmt::Display,
     fs,
@@ -12,13 +13,17 @@ use std::{
 use directories::ProjectDirs;
 use isolang::Language;
 use library::{
+    book::serialization::HashAlgorithm,
     cache::{GEMINI_PROMPT_CACHE_CAPACITY, TranslationsCache},
     library::{
         Library,
         file_watcher::{LibraryFileChange, LibraryWatcher},
     },
     translation_stats::TranslationSizeCache,
-    translator::{TranslationModel, gemini_cache::GeminiPromptCache},
+    translator::{
+        NoChapterContext, TranslationContext, TranslationModel, Translator, TranslatorServices,
+        book_source_language, gemini_cache::GeminiPromptCache, get_translator,
+    },
 };
 use log::{info, warn};
 use tokio::sync::{Mutex, watch};
@@ -27,8 +32,12 @@ use uuid::Uuid;
 use tauri::Emitter;
 
 use crate::app::{
-    anki_sync::AnkiSyncTask, chapter_context::SummaryBackedChapterContext, config::Config,
-    summary_generation_queue::SummaryGenerationQueue, translation_queue::TranslationQueue,
+    anki_sync::AnkiSyncTask,
+    chapter_context::SummaryBackedChapterContext,
+    config::{AnnotationDensity, Config},
+    library_view::{LibraryView, ParagraphView},
+    summary_generation_queue::SummaryGenerationQueue,
+    translation_queue::{TranslationQueue, TranslatorFactory},
 };
 
 const EXIT_STOP_QUEUE_TIMEOUT: Duration = Duration::from_secs(2);
@@ -123,7 +132,9 @@ fn resolve_config_dir(app: Option<&tauri::AppHandle>) -> anyhow::Result<PathBuf>
 /// [`resolve_config_dir`]'s Android handling; non-Android keeps the historical
 /// `ProjectDirs::from("", "TS", "FLTS").cache_dir()` (empty qualifier) so
 /// existing installs' cache locations don't move.
-fn resolve_cache_dir(app: Option<&tauri::AppHandle>) -> anyhow::Result<PathBuf> {
+fn resolve_cache_dir<R: tauri::Runtime>(
+    app: Option<&tauri::AppHandle<R>>,
+) -> anyhow::Result<PathBuf> {
     #[cfg(target_os = "android")]
     {
         use tauri::Manager;
@@ -233,6 +244,7 @@ pub struct AppState {
     config: watch::Sender<Config>,
     library: Arc<watch::Sender<Option<Arc<Library>>>>,
     translation_queue: watch::Sender<Option<Arc<TranslationQueue>>>,
+    translator_factory: Option<TranslatorFactory>,
     translation_queue_init_lock: Mutex<()>,
     summary_generation_queue: watch::Sender<Option<Arc<SummaryGenerationQueue>>>,
     summary_generation_queue_init_lock: Mutex<()>,
@@ -248,12 +260,23 @@ pub struct AppState {
     translations_cache: tokio::sync::OnceCell<Arc<TranslationsCache>>,
     stats_cache: tokio::sync::OnceCell<Arc<TranslationSizeCache>>,
     gemini_prompt_cache: tokio::sync::OnceCell<Arc<GeminiPromptCache>>,
+    /// Prompt cache of one-off translations made without chapter context.
+    /// Kept apart from `gemini_prompt_cache`, whose entry for the same
+    /// book/chapter key carries the chapter's summaries and text.
+    standalone_prompt_cache: tokio::sync::OnceCell<Arc<GeminiPromptCache>>,
+    chapter_render_cache: crate::app::library_view::render_cache::ChapterRenderCache,
     pub lyrics_state: crate::app::lyrics::LyricsState,
     pub spotify_web: Arc<crate::app::spotify::web::SpotifyWebState>,
 }
 
 impl AppState {
-    pub fn new(app: tauri::AppHandle, watcher: Arc<Mutex<LibraryWatcher>>) -> anyhow::Result<Self> {
+    /// `translator_factory` replaces the provider-backed translators of the
+    /// translation queue; `None` outside of tests.
+    pub fn new(
+        app: tauri::AppHandle,
+        watcher: Arc<Mutex<LibraryWatcher>>,
+        translator_factory: Option<TranslatorFactory>,
+    ) -> anyhow::Result<Self> {
         info!("Startup!");
 
         let config_dir = resolve_config_dir(Some(&app))?;
@@ -285,6 +308,7 @@ impl AppState {
             config: watch::channel(config).0,
             library: Arc::new(watch::channel::<Option<Arc<Library>>>(None).0),
             translation_queue: watch::channel(None).0,
+            translator_factory,
             translation_queue_init_lock: Mutex::new(()),
             summary_generation_queue: watch::channel(None).0,
             summary_generation_queue_init_lock: Mutex::new(()),
@@ -299,11 +323,20 @@ impl AppState {
             translations_cache: tokio::sync::OnceCell::new(),
             stats_cache: tokio::sync::OnceCell::new(),
             gemini_prompt_cache: tokio::sync::OnceCell::new(),
+            standalone_prompt_cache: tokio::sync::OnceCell::new(),
+            chapter_render_cache: Default::default(),
             lyrics_state: crate::app::lyrics::LyricsState::new(),
             spotify_web: Arc::new(crate::app::spotify::web::SpotifyWebState::new()),
         })
     }
 
+    /// Rendered annotated chapters, shared across `LibraryView`s.
+    pub fn chapter_render_cache(
+        &self,
+    ) -> &crate::app::library_view::render_cache::ChapterRenderCache {
+        &self.chapter_render_cache
+    }
+
     pub fn subscribe_config(&self) -> watch::Receiver<Config> {
         self.config.subscribe()
     }
@@ -373,6 +406,31 @@ impl AppState {
         Ok(())
     }
 
+    /// Persist the reader's part-of-speech filter and ask open readers to
+    /// re-fetch their paragraph segments. Doesn't touch the translation queue
+    /// or reopen the library, unlike a full `update_config`.
+    pub async fn set_visible_pos(
+        &self,
+        visible_pos: Option<HashSet<String>>,
+    ) -> anyhow::Result<()> {
+        let mut config = self.config.borrow().clone();
+        config.visible_pos = visible_pos;
+        config.save(&self.config_path)?;
+        self.config.send_replace(config);
+        self.app.emit("reader_settings_updated", ())?;
+        Ok(())
+    }
+
+    /// Like [`AppState::set_visible_pos`], for the annotation density.
+    pub async fn set_annotation_density(&self, density: AnnotationDensity) -> anyhow::Result<()> {
+        let mut config = self.config.borrow().clone();
+        config.annotation_density = density;
+        config.save(&self.config_path)?;
+        self.config.send_replace(config);
+        self.app.emit("reader_settings_updated", ())?;
+        Ok(())
+    }
+
     pub fn subscribe_library(&self) -> watch::Receiver<Option<Arc<Library>>> {
         self.library.subscribe()
     }
@@ -430,7 +488,11 @@ impl AppState {
             });
     }
 
-    pub async fn update_config(&self, config: Config) -> anyhow::Result<()> {
+    pub async fn update_config(&self, mut config: Config) -> anyhow::Result<()> {
+        // Reject unknown language codes up front: a bad id would otherwise
+        // only surface later as empty lists in the UI.
+        config.normalize_language_ids()?;
+
         // Translator settings (provider/key/model) are captured when the translation queue is created.
         // Reset it so the next translation uses the latest config.
         self.stop_translation_queue().await;
@@ -453,8 +515,15 @@ impl AppState {
         let library_root = resolve_library_root(Some(&self.app))?;
         info!("library_root = {library_root:?}");
         self.migrate_legacy_library(&config, &library_root).await?;
+        if let Some(parent) = library_root.parent() {
+            fs::create_dir_all(parent)?;
+        }
 
         let library = Arc::new(Library::open(library_root.clone()).await?);
+        library.set_auto_merge_conflicts(config.auto_merge_conflicts);
+        if config.sha256_checksums {
+            library.set_hash_algorithm(HashAlgorithm::Sha256);
+        }
         self.library.send_replace(Some(library.clone()));
 
         if std::env::var_os("FLTS_ENABLE_CARD_BACKFILL").is_some_and(|v| !v.is_empty()) {
@@ -670,6 +739,17 @@ impl AppState {
             .cloned()
     }
 
+    async fn get_standalone_prompt_cache(&self) -> anyhow::Result<Arc<GeminiPromptCache>> {
+        self.standalone_prompt_cache
+            .get_or_try_init(|| async {
+                let cache_dir =
+                    resolve_cache_dir(Some(&self.app))?.join("gemini_standalone_caches");
+                GeminiPromptCache::open(&cache_dir, GEMINI_PROMPT_CACHE_CAPACITY).await
+            })
+            .await
+            .cloned()
+    }
+
     pub async fn shutdown(&self) {
         // Best effort only: do not let app exit hang forever on any shutdown step.
         run_exit_step(
@@ -710,6 +790,11 @@ impl AppState {
         // preserve the prior gating for those variants only.
         match event {
             LibraryFileChange::BookChanged { modified: _, uuid } if had_effect => {
+                // The book content itself was replaced from disk, not just a
+                // translation: `book_reloaded` lets an open reader re-anchor
+                // its position on top of the usual `book_updated` refresh.
+                info!("Emitting \"book_reloaded\" for {uuid}");
+                self.app.emit("book_reloaded", uuid)?;
                 info!("Emitting \"book_updated\" for {uuid}");
                 self.app.emit("book_updated", uuid)?;
                 self.notify_library_changed();
@@ -774,6 +859,7 @@ impl AppState {
             gemini_prompt_cache,
             context_provider,
             &config,
+            self.translator_factory.clone(),
             self.app.clone(),
             self.library_sender(),
         )
@@ -811,6 +897,7 @@ impl AppState {
         paragraph_id: usize,
         model: TranslationModel,
         use_cache: bool,
+        stream_tokens: bool,
     ) -> anyhow::Result<usize> {
         let library = self
             .library
@@ -819,7 +906,7 @@ impl AppState {
             .ok_or(AppError::NoLibraryError)?;
         let queue = self.get_or_init_translation_queue(library).await?;
         queue
-            .translate(book_id, paragraph_id, model, use_cache)
+            .translate(book_id, paragraph_id, model, use_cache, stream_tokens)
             .await
     }
 
@@ -868,7 +955,7 @@ impl AppState {
             // Dedup-on-enqueue is handled by TranslationQueue::translate.
             // Swallow per-item errors so one bad paragraph doesn't abandon the rest.
             if let Err(err) = queue
-                .translate(book_id, *paragraph_id, model, use_cache)
+                .translate(book_id, *paragraph_id, model, use_cache, false)
                 .await
             {
                 warn!("translate_chapter: failed to enqueue paragraph {paragraph_id}: {err}");
@@ -877,6 +964,241 @@ impl AppState {
         Ok(untranslated.len())
     }
 
+    /// Translates the book's title into the configured target language with
+    /// `model`, outside the paragraph queue, and stores it in the book's user
+    /// state. Returns the translated title.
+    pub async fn translate_title(
+        &self,
+        book_id: Uuid,
+        model: TranslationModel,
+    ) -> anyhow::Result<String> {
+        let library = self
+            .library
+            .borrow()
+            .clone()
+            .ok_or(AppError::NoLibraryError)?;
+        let config = self.config.borrow().clone();
+        let target_language =
+            Language::from_639_3(&config.target_language_id).ok_or_else(|| {
+                anyhow::anyhow!("invalid target language: {}", config.target_language_id)
+            })?;
+
+        let book = library.get_book(&book_id).await?;
+        let (title, source_language) = {
+            let book = book.lock().await;
+            (book.book.title.clone(), book.book.language.clone())
+        };
+        let source_language = book_source_language(&source_language)?;
+
+        let translator = self
+            .standalone_translator(&config, model, source_language, target_language)
+            .await?;
+        let translation = translator
+            .get_translation(TranslationContext {
+                paragraph_text: &title,
+                book_id,
+                chapter_id: 0,
+                use_cache: true,
+                callback: None,
+                token_sink: None,
+            })
+            .await?;
+        let translated_title = translation
+            .sentences
+            .iter()
+            .map(|s| s.full_translation.trim())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        book.lock()
+            .await
+            .update_translated_title(&target_language, translated_title.clone())
+            .await?;
+        self.notify_library_changed();
+        Ok(translated_title)
+    }
+
+    /// Translates sentence `sentence_id` of `paragraph_id` again with
+    /// `model`, outside the paragraph queue but with the context of the
+    /// paragraph's chapter, and saves the result as a new version of the
+    /// paragraph with only that sentence replaced.
+    pub async fn retranslate_sentence(
+        &self,
+        book_id: Uuid,
+        paragraph_id: usize,
+        sentence_id: usize,
+        model: TranslationModel,
+    ) -> anyhow::Result<()> {
+        let library = self
+            .library
+            .borrow()
+            .clone()
+            .ok_or(AppError::NoLibraryError)?;
+        let config = self.config.borrow().clone();
+        let target_language =
+            Language::from_639_3(&config.target_language_id).ok_or_else(|| {
+                anyhow::anyhow!("invalid target language: {}", config.target_language_id)
+            })?;
+
+        let original = library
+            .sentence_original(&book_id, &target_language, paragraph_id, sentence_id)
+            .await?;
+        let book = library.get_book(&book_id).await?;
+        let (source_language, chapter_id) = {
+            let book = book.lock().await;
+            (
+                book.book.language.clone(),
+                book.book.chapter_for_paragraph(paragraph_id).unwrap_or(0),
+            )
+        };
+        let source_language = book_source_language(&source_language)?;
+
+        let translator = self
+            .chapter_translator(
+                library.clone(),
+                &config,
+                model,
+                source_language,
+                target_language,
+            )
+            .await?;
+        let sentence = translator
+            .retranslate_sentence(TranslationContext {
+                paragraph_text: &original,
+                book_id,
+                chapter_id,
+                use_cache: false,
+                callback: None,
+                token_sink: None,
+            })
+            .await?;
+
+        let paragraph = {
+            let book = book.lock().await;
+            let translation = book
+                .get_translation(&target_language)
+                .await
+                .ok_or_else(|| anyhow::anyhow!("Book {book_id} has no translation"))?;
+            let mut translation = translation.lock().await;
+            translation.replace_sentence(paragraph_id, sentence_id, sentence, model)?;
+            translation
+                .paragraph_view(paragraph_id)
+                .map(|p| p.to_import())
+        };
+        if let Some(paragraph) = paragraph {
+            library
+                .apply_paragraph_to_cards(book_id, paragraph_id, &paragraph, target_language)
+                .await?;
+        }
+
+        translation_queue::save_and_emit(
+            library,
+            self.app.clone(),
+            &self.library,
+            book_id,
+            paragraph_id,
+        )
+        .await
+    }
+
+    /// Translates `paragraph_id` with `model` through the paragraph queue and
+    /// returns once the result is stored and the book saved, for callers that
+    /// want the translation rather than a request id to poll.
+    pub async fn translate_paragraph_now(
+        &self,
+        book_id: Uuid,
+        paragraph_id: usize,
+        model: TranslationModel,
+        use_cache: bool,
+    ) -> anyhow::Result<()> {
+        let library = self
+            .library
+            .borrow()
+            .clone()
+            .ok_or(AppError::NoLibraryError)?;
+        let queue = self.get_or_init_translation_queue(library).await?;
+        queue
+            .translate_and_wait(book_id, paragraph_id, model, use_cache)
+            .await
+    }
+
+    /// Translator for one-off requests made outside the paragraph queue,
+    /// without chapter context. It gets its own prompt cache: a context-free
+    /// cache stored under a chapter's key would otherwise be reused by every
+    /// queued translation of that chapter.
+    async fn standalone_translator(
+        &self,
+        config: &Config,
+        model: TranslationModel,
+        source_language: Language,
+        target_language: Language,
+    ) -> anyhow::Result<Box<dyn Translator>> {
+        if let Some(factory) = &self.translator_factory {
+            return factory(source_language, target_language);
+        }
+        let services = TranslatorServices {
+            cache: self.get_translations_cache().await?,
+            context_provider: Arc::new(NoChapterContext),
+            gemini_prompt_cache: self.get_standalone_prompt_cache().await?,
+        };
+        self.provider_translator(services, config, model, source_language, target_language)
+    }
+
+    /// Translator for one-off requests about a paragraph outside the
+    /// paragraph queue, with the same chapter context and prompt cache as
+    /// the queue.
+    async fn chapter_translator(
+        &self,
+        library: Arc<Library>,
+        config: &Config,
+        model: TranslationModel,
+        source_language: Language,
+        target_language: Language,
+    ) -> anyhow::Result<Box<dyn Translator>> {
+        if let Some(factory) = &self.translator_factory {
+            return factory(source_language, target_language);
+        }
+        let summary_queue = self
+            .get_or_init_summary_generation_queue(library.clone())
+            .await?;
+        let services = TranslatorServices {
+            cache: self.get_translations_cache().await?,
+            context_provider: Arc::new(SummaryBackedChapterContext {
+                queue: summary_queue,
+                library,
+            }),
+            gemini_prompt_cache: self.get_gemini_prompt_cache().await?,
+        };
+        self.provider_translator(services, config, model, source_language, target_language)
+    }
+
+    fn provider_translator(
+        &self,
+        services: TranslatorServices,
+        config: &Config,
+        model: TranslationModel,
+        source_language: Language,
+        target_language: Language,
+    ) -> anyhow::Result<Box<dyn Translator>> {
+        let provider = model
+            .provider()
+            .ok_or_else(|| anyhow::anyhow!("Unknown model provider"))?;
+        let api_key = config
+            .api_keys()
+            .for_provider(provider)
+            .ok_or_else(|| anyhow::anyhow!("no api key for provider {provider:?}"))?
+            .to_owned();
+        get_translator(
+            services,
+            provider,
+            model,
+            api_key,
+            source_language,
+            target_language,
+            translation_queue::translator_config(config, &self.app),
+        )
+    }
+
     pub async fn get_paragraph_translation_activity(
         &self,
         book_id: Uuid,
@@ -943,6 +1265,18 @@ impl AppState {
                 info!("Gemini prompt cache closed");
             }
         }
+        if let Some(cache) = self.standalone_prompt_cache.get() {
+            info!("Closing standalone Gemini prompt cache");
+            if run_exit_step(
+                "standalone gemini prompt cache close",
+                EXIT_CACHE_CLOSE_TIMEOUT,
+                cache.close(),
+            )
+            .await
+            {
+                info!("Standalone Gemini prompt cache closed");
+            }
+        }
     }
 }
 
@@ -1088,6 +1422,28 @@ pub async fn update_config(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn set_visible_pos(
+    state: tauri::State<'_, Arc<AppState>>,
+    visible_pos: Option<Vec<String>>,
+) -> Result<(), String> {
+    state
+        .set_visible_pos(visible_pos.map(|pos| pos.into_iter().collect()))
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn set_annotation_density(
+    state: tauri::State<'_, Arc<AppState>>,
+    density: AnnotationDensity,
+) -> Result<(), String> {
+    state
+        .set_annotation_density(density)
+        .await
+        .map_err(|err| err.to_string())
+}
+
 #[tauri::command]
 pub async fn get_config(state: tauri::State<'_, Arc<AppState>>) -> Result<Config, String> {
     Ok(state.config.borrow().clone())
@@ -1116,6 +1472,13 @@ pub async fn purge_gemini_caches(state: tauri::State<'_, Arc<AppState>>) -> Resu
         .purge_all(&api_key)
         .await
         .map_err(|err| err.to_string())?;
+    // The purge deleted the standalone caches' server side as well.
+    state
+        .get_standalone_prompt_cache()
+        .await
+        .map_err(|err| err.to_string())?
+        .clear_local()
+        .await;
     info!(
         "Gemini cache purge: {} deleted, {} failed",
         report.deleted,
@@ -1181,7 +1544,54 @@ pub async fn translate_paragraph(
     use_cache: bool,
 ) -> Result<usize, String> {
     state
-        .translate_paragraph(book_id, paragraph_id, model, use_cache)
+        .translate_paragraph(book_id, paragraph_id, model, use_cache, false)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Translates the paragraph and returns it rendered once the translation is
+/// saved, instead of a request id to follow through events. The reader keeps
+/// using the queued `translate_paragraph`.
+#[tauri::command]
+pub async fn translate_paragraph_sync(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+    paragraph_id: usize,
+    model: TranslationModel,
+    use_cache: bool,
+) -> Result<ParagraphView, String> {
+    state
+        .translate_paragraph_now(book_id, paragraph_id, model, use_cache)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    let library = state
+        .library
+        .borrow()
+        .clone()
+        .ok_or("Library is not configured")?;
+    let target_language_id = { state.config.borrow().target_language_id.clone() };
+    let target_language = Language::from_639_3(&target_language_id)
+        .ok_or_else(|| format!("invalid target language: {target_language_id}"))?;
+    LibraryView::create(state.inner().clone(), library)
+        .get_paragraph_view(book_id, paragraph_id, &target_language)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Same as `translate_paragraph`, but the response is also pushed to the UI
+/// as it arrives, through `translation_token` events carrying raw JSON chunks.
+/// The parsed translation is saved as usual when the stream completes.
+#[tauri::command]
+pub async fn stream_paragraph_translation(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+    paragraph_id: usize,
+    model: TranslationModel,
+    use_cache: bool,
+) -> Result<usize, String> {
+    state
+        .translate_paragraph(book_id, paragraph_id, model, use_cache, true)
         .await
         .map_err(|err| err.to_string())
 }
@@ -1200,6 +1610,32 @@ pub async fn translate_chapter(
         .map_err(|err| err.to_string())
 }
 
+#[tauri::command]
+pub async fn translate_title(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+    model: TranslationModel,
+) -> Result<String, String> {
+    state
+        .translate_title(book_id, model)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn retranslate_sentence(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+    paragraph_id: usize,
+    sentence_id: usize,
+    model: TranslationModel,
+) -> Result<(), String> {
+    state
+        .retranslate_sentence(book_id, paragraph_id, sentence_id, model)
+        .await
+        .map_err(|err| err.to_string())
+}
+
 #[tauri::command]
 pub async fn get_paragraph_translation_activity(
     state: tauri::State<'_, Arc<AppState>>,
@@ -1214,7 +1650,18 @@ pub async fn get_paragraph_translation_activity(
 
 #[tauri::command]
 pub async fn get_system_definition(
-    #[allow(unused_variables)] app: tauri::AppHandle,
+    app: tauri::AppHandle,
+    word: String,
+    source_lang: String,
+    target_lang: String,
+) -> Result<Option<library::system_dictionary::SystemDefinition>, String> {
+    lookup_system_definition(&app, word, source_lang, target_lang)
+}
+
+/// Platform dictionary lookup. The macOS dictionary services must be called
+/// on the main thread; other platforms have no lookup and return `None`.
+pub(crate) fn lookup_system_definition(
+    #[allow(unused_variables)] app: &tauri::AppHandle,
     #[allow(unused_variables)] word: String,
     #[allow(unused_variables)] source_lang: String,
     #[allow(unused_variables)] target_lang: String,
@@ -1224,10 +1671,6 @@ pub async fn get_system_definition(
         use std::sync::mpsc::channel;
         let (tx, rx) = channel();
 
-        let word = word.clone();
-        let source_lang = source_lang.clone();
-        let target_lang = target_lang.clone();
-
         app.run_on_main_thread(move || {
             let result = library::system_dictionary::system_macos::get_definition(
                 &word,
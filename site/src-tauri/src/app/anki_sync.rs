@@ -286,6 +286,9 @@ mod tests {
             translations,
             examples: vec![],
             anki_data: None,
+            usage: Default::default(),
+            last_seen: Default::default(),
+            usage_sources: Default::default(),
         };
         library
             .card_store()
@@ -334,6 +337,9 @@ mod tests {
             translations: translations2,
             examples: vec![],
             anki_data: None,
+            usage: Default::default(),
+            last_seen: Default::default(),
+            usage_sources: Default::default(),
         };
         library
             .card_store()
@@ -1,6 +1,9 @@
-use std::{fs::File, path::Path};
+use std::{collections::HashSet, fs::File, path::Path};
 
-use library::translator::{TranslationModel, TranslationProvider};
+use library::library::library_book::DEFAULT_LOOKUP_HISTORY_LIMIT;
+use library::translator::{
+    DEFAULT_MAX_CONTEXTUAL_TRANSLATIONS, TranslationModel, TranslationProvider, is_content_word,
+};
 use log::{info, warn};
 use serde::{Deserialize, Serialize};
 use std::fs::OpenOptions;
@@ -14,6 +17,28 @@ pub struct Model {
     provider: Option<TranslationProvider>,
 }
 
+/// How many words the reader annotates with an inline translation.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum AnnotationDensity {
+    /// No inline translations; words are still clickable.
+    None,
+    /// Nouns, lexical verbs, adjectives and adverbs only.
+    ContentWords,
+    #[default]
+    All,
+}
+
+impl AnnotationDensity {
+    pub fn annotates(self, part_of_speech: &str) -> bool {
+        match self {
+            AnnotationDensity::None => false,
+            AnnotationDensity::ContentWords => is_content_word(part_of_speech),
+            AnnotationDensity::All => true,
+        }
+    }
+}
+
 #[derive(Serialize)]
 pub struct ProviderMeta {
     pub id: TranslationProvider,
@@ -43,6 +68,7 @@ fn model_pretty_name(model: TranslationModel) -> &'static str {
         TranslationModel::DeepSeekV4Flash => "DeepSeek V4 Flash",
         TranslationModel::DeepSeekV4Pro => "DeepSeek V4 Pro",
         TranslationModel::ZaiGlm52 => "z.AI GLM-5.2",
+        TranslationModel::GoogleTranslate => "Google Translate (draft)",
         TranslationModel::Unknown => "Not set",
     }
 }
@@ -144,6 +170,10 @@ impl ApiKeys {
 pub struct Config {
     #[serde(rename = "targetLanguageId")]
     pub target_language_id: String,
+    /// Source language assumed by imports that don't name one. `None` means
+    /// every import has to pick its language explicitly.
+    #[serde(rename = "defaultSourceLanguageId", default)]
+    pub default_source_language_id: Option<String>,
     #[serde(rename = "translationProvider")]
     #[serde(default)]
     pub translation_provider: TranslationProvider,
@@ -192,6 +222,51 @@ pub struct Config {
         default = "default_translation_concurrency"
     )]
     pub translation_concurrency: u32,
+    /// Contextual translations requested (and kept) per word, best fit first.
+    #[serde(
+        rename = "maxContextualTranslations",
+        default = "default_max_contextual_translations"
+    )]
+    pub max_contextual_translations: u32,
+    /// Gemini sampling temperature. `None` keeps the model's default.
+    #[serde(rename = "temperature", default)]
+    pub temperature: Option<f32>,
+    /// Gemini 2.5 thinking budget in tokens, `-1` for dynamic. `None` keeps
+    /// the per-model default (thinking off for Flash).
+    #[serde(rename = "thinkingBudget", default)]
+    pub thinking_budget: Option<i32>,
+    /// Parts of speech whose in-text translations the reader shows: tags the
+    /// translator emits (e.g. `common_noun`, `verb_modal`) or the families
+    /// they share (`noun`, `verb`, `pronoun`). `None` shows every word; use
+    /// this to hide annotations for function words such as articles and
+    /// prepositions.
+    #[serde(rename = "visiblePos", default)]
+    pub visible_pos: Option<HashSet<String>>,
+    /// Which words carry an in-text translation, applied on top of
+    /// `visible_pos`.
+    #[serde(rename = "annotationDensity", default)]
+    pub annotation_density: AnnotationDensity,
+    /// Append every translator request and raw response to
+    /// `translator_requests.log` in the cache dir, API keys redacted.
+    #[serde(rename = "logRequests", default)]
+    pub log_requests: bool,
+    /// Word lookups remembered per book; older ones are dropped.
+    #[serde(
+        rename = "lookupHistoryLimit",
+        default = "default_lookup_history_limit"
+    )]
+    pub lookup_history_limit: u32,
+    /// Merge sync-conflict copies into a book's main files when it loads.
+    /// Off leaves them on disk for `merge_conflicts`.
+    #[serde(
+        rename = "autoMergeConflicts",
+        default = "default_auto_merge_conflicts"
+    )]
+    pub auto_merge_conflicts: bool,
+    /// Checksum book and translation files with SHA-256 instead of FNV-1.
+    /// Existing files switch over the next time they are saved.
+    #[serde(rename = "sha256Checksums", default)]
+    pub sha256_checksums: bool,
 }
 
 fn default_preload_count() -> u32 {
@@ -202,6 +277,18 @@ fn default_translation_concurrency() -> u32 {
     8
 }
 
+fn default_max_contextual_translations() -> u32 {
+    DEFAULT_MAX_CONTEXTUAL_TRANSLATIONS as u32
+}
+
+fn default_lookup_history_limit() -> u32 {
+    DEFAULT_LOOKUP_HISTORY_LIMIT as u32
+}
+
+fn default_auto_merge_conflicts() -> bool {
+    true
+}
+
 fn default_show_next_track() -> bool {
     true
 }
@@ -210,6 +297,7 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             target_language_id: "eng".to_owned(),
+            default_source_language_id: None,
             translation_provider: TranslationProvider::Google,
             gemini_api_key: None,
             openai_api_key: None,
@@ -225,6 +313,15 @@ impl Default for Config {
             sync_enabled: false,
             sync_device_name: None,
             translation_concurrency: default_translation_concurrency(),
+            max_contextual_translations: default_max_contextual_translations(),
+            temperature: None,
+            thinking_budget: None,
+            visible_pos: None,
+            annotation_density: AnnotationDensity::default(),
+            log_requests: false,
+            lookup_history_limit: default_lookup_history_limit(),
+            auto_merge_conflicts: default_auto_merge_conflicts(),
+            sha256_checksums: false,
         }
     }
 }
@@ -239,6 +336,19 @@ impl Config {
         }
     }
 
+    /// Checks that `target_language_id` and `default_source_language_id`
+    /// name known languages and rewrites ISO 639-1 codes (`"en"`) to the
+    /// 639-3 form (`"eng"`) the rest of the app looks languages up by.
+    pub fn normalize_language_ids(&mut self) -> anyhow::Result<()> {
+        self.target_language_id = normalize_language_id(&self.target_language_id)?;
+        self.default_source_language_id = self
+            .default_source_language_id
+            .as_deref()
+            .map(normalize_language_id)
+            .transpose()?;
+        Ok(())
+    }
+
     pub fn load(path: &Path) -> anyhow::Result<Self> {
         let file = File::open(path)?;
         Ok(match serde_json::from_reader::<_, Self>(file) {
@@ -264,6 +374,14 @@ impl Config {
     }
 }
 
+fn normalize_language_id(id: &str) -> anyhow::Result<String> {
+    let id = id.trim().to_ascii_lowercase();
+    isolang::Language::from_639_3(&id)
+        .or_else(|| isolang::Language::from_639_1(&id))
+        .map(|language| language.to_639_3().to_owned())
+        .ok_or_else(|| anyhow::anyhow!("unknown language code: {id:?}"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -329,4 +447,81 @@ mod tests {
         let parsed: Config = serde_json::from_value(legacy).unwrap();
         assert_eq!(parsed.translation_concurrency, 8);
     }
+
+    #[test]
+    fn config_loads_legacy_file_with_defaults_for_newer_fields() {
+        // A config persisted before any of these fields existed must fall back
+        // to each field's default rather than failing to parse.
+        let legacy = serde_json::json!({
+            "targetLanguageId": "eng",
+            "translationProvider": "google",
+            "geminiApiKey": null,
+            "openaiApiKey": null,
+            "model": 0,
+            "libraryPath": null,
+        });
+        let parsed: Config = serde_json::from_value(legacy).unwrap();
+        assert!(parsed.default_source_language_id.is_none());
+        assert_eq!(
+            parsed.max_contextual_translations,
+            DEFAULT_MAX_CONTEXTUAL_TRANSLATIONS as u32
+        );
+        assert_eq!(parsed.temperature, None);
+        assert_eq!(parsed.thinking_budget, None);
+        assert!(parsed.visible_pos.is_none());
+        assert_eq!(parsed.annotation_density, AnnotationDensity::All);
+        assert!(!parsed.log_requests);
+        assert_eq!(
+            parsed.lookup_history_limit,
+            DEFAULT_LOOKUP_HISTORY_LIMIT as u32
+        );
+        assert!(parsed.auto_merge_conflicts);
+        assert!(!parsed.sha256_checksums);
+    }
+
+    #[test]
+    fn config_serializes_annotation_density_in_camel_case() {
+        let json = serde_json::to_string(&Config {
+            annotation_density: AnnotationDensity::ContentWords,
+            ..Config::default()
+        })
+        .unwrap();
+        assert!(json.contains("\"annotationDensity\":\"contentWords\""));
+    }
+
+    #[test]
+    fn normalize_language_ids_rejects_unknown_and_upgrades_639_1() {
+        let mut config = Config {
+            target_language_id: "xx".to_owned(),
+            ..Config::default()
+        };
+        assert!(config.normalize_language_ids().is_err());
+
+        config.target_language_id = "de".to_owned();
+        config.normalize_language_ids().unwrap();
+        assert_eq!(config.target_language_id, "deu");
+
+        config.target_language_id = "rus".to_owned();
+        config.normalize_language_ids().unwrap();
+        assert_eq!(config.target_language_id, "rus");
+
+        config.default_source_language_id = Some("es".to_owned());
+        config.normalize_language_ids().unwrap();
+        assert_eq!(config.default_source_language_id.as_deref(), Some("spa"));
+
+        config.default_source_language_id = Some("nope".to_owned());
+        assert!(config.normalize_language_ids().is_err());
+    }
+
+    #[test]
+    fn config_round_trips_visible_pos() {
+        let original = Config {
+            visible_pos: Some(HashSet::from(["noun".to_owned(), "verb".to_owned()])),
+            ..Config::default()
+        };
+        let json = serde_json::to_string(&original).unwrap();
+        assert!(json.contains("\"visiblePos\""));
+        let parsed: Config = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.visible_pos, original.visible_pos);
+    }
 }
@@ -1,7 +1,10 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use isolang::Language;
+use library::book::book::ParagraphStorage;
 use library::epub_importer::EpubBook;
+use library::library::CreatedBook;
 use uuid::Uuid;
 
 use crate::app::AppState;
@@ -13,20 +16,31 @@ pub async fn import_plain_text(
     state: tauri::State<'_, Arc<AppState>>,
     title: String,
     text: String,
-    source_language_id: String,
-) -> Result<Uuid, String> {
+    source_language_id: Option<String>,
+    chapter_heading_pattern: Option<String>,
+    preserve_line_breaks: Option<bool>,
+) -> Result<CreatedBook, String> {
     let library = state
         .library
         .borrow()
         .clone()
         .ok_or("Library is not configured")?;
 
+    let source_language_id = source_language_id
+        .or_else(|| state.config.borrow().default_source_language_id.clone())
+        .ok_or("No source language given and no default source language configured")?;
     let source_language = Language::from_639_3(&source_language_id)
         .ok_or_else(|| format!("Failed to resolve source language: {}", source_language_id))?;
 
     let mut library_view = LibraryView::create(state.inner().clone(), library);
     library_view
-        .import_plain_text(&title, &text, &source_language)
+        .import_plain_text(
+            &title,
+            &text,
+            &source_language,
+            chapter_heading_pattern.as_deref(),
+            preserve_line_breaks.unwrap_or(false),
+        )
         .await
         .map_err(|err| err.to_string())
 }
@@ -36,6 +50,34 @@ pub async fn import_epub(
     state: tauri::State<'_, Arc<AppState>>,
     book: EpubBook,
     source_language_id: String,
+    paragraph_storage: Option<ParagraphStorage>,
+) -> Result<Uuid, String> {
+    let library = state
+        .library
+        .borrow()
+        .clone()
+        .ok_or("Library is not configured")?;
+
+    let source_language = Language::from_639_3(&source_language_id)
+        .ok_or_else(|| format!("Failed to resolve source language: {}", source_language_id))?;
+
+    let mut library_view = LibraryView::create(state.inner().clone(), library);
+    library_view
+        .import_epub(
+            &book,
+            &source_language,
+            paragraph_storage.unwrap_or_default(),
+        )
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn import_epub_path(
+    state: tauri::State<'_, Arc<AppState>>,
+    path: PathBuf,
+    source_language_id: String,
+    paragraph_storage: Option<ParagraphStorage>,
 ) -> Result<Uuid, String> {
     let library = state
         .library
@@ -48,7 +90,11 @@ pub async fn import_epub(
 
     let mut library_view = LibraryView::create(state.inner().clone(), library);
     library_view
-        .import_epub(&book, &source_language)
+        .import_epub_path(
+            &path,
+            &source_language,
+            paragraph_storage.unwrap_or_default(),
+        )
         .await
         .map_err(|err| err.to_string())
 }
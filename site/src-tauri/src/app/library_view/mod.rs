@@ -1,23 +1,37 @@
 use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::Path;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use htmlentity::entity::{ICodedDataTrait, decode};
 use isolang::Language;
 use library::card;
 use library::epub_importer::EpubBook;
 use library::library::file_watcher::LibraryFileChange;
-use library::translator::TranslationModel;
+use library::system_dictionary::SystemDefinition;
+use library::translator::{TranslationModel, is_visible_part_of_speech};
 use library::{
-    book::translation::ParagraphTranslationView,
-    library::{Library, library_book::BookReadingState},
+    book::{
+        book::ParagraphStorage,
+        translation::{self, ParagraphTranslationView},
+    },
+    library::{
+        CreatedBook, Library, LibraryBookMetadata,
+        library_book::{BookReadingState, StudySet},
+    },
 };
+use tauri::Emitter;
+use unicode_segmentation::UnicodeSegmentation;
 use uuid::Uuid;
 
-use crate::app::AppState;
+use crate::app::{AppState, config::AnnotationDensity, translation_queue::ParagraphUpdatedEvent};
+use render_cache::ChapterRenderKey;
 
 pub mod imports;
 pub mod mutations;
 pub mod queries;
+pub mod render_cache;
 
 pub use imports::*;
 pub use mutations::*;
@@ -27,12 +41,26 @@ pub use queries::*;
 pub struct LibraryBookMetadataView {
     id: Uuid,
     title: String,
+    /// `title` in the target language, once `translate_title` has run.
+    #[serde(rename = "translatedTitle")]
+    translated_title: Option<String>,
     #[serde(rename = "chaptersCount")]
     chapters_count: usize,
     #[serde(rename = "paragraphsCount")]
     paragraphs_count: usize,
     #[serde(rename = "translationRatio")]
     translation_ratio: f64,
+    /// Ratio for every translation of the book, keyed by target language
+    /// tag (`<lang>[-<variant>]`), not just the configured target language.
+    #[serde(rename = "translationRatios")]
+    translation_ratios: HashMap<String, f64>,
+    /// Milliseconds since the Unix epoch at which the target-language
+    /// translation file was last written, if there is one.
+    #[serde(rename = "translationLastModified")]
+    translation_last_modified: Option<u64>,
+    /// The book's `book.dat` is missing; see `repair_book`.
+    #[serde(rename = "incomplete")]
+    incomplete: bool,
     #[serde(rename = "path")]
     path: Vec<String>,
 }
@@ -41,8 +69,41 @@ pub struct LibraryBookMetadataView {
 pub struct ChapterView {
     id: usize,
     title: String,
+    /// Start of the chapter's first paragraph, to tell untitled chapters
+    /// apart. Empty for a chapter without paragraphs.
+    preview: String,
+    #[serde(rename = "paragraphCount")]
+    paragraph_count: usize,
     #[serde(rename = "translationRatio")]
     translation_ratio: f64,
+    #[serde(flatten)]
+    adjacent: AdjacentChaptersView,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct AdjacentChaptersView {
+    #[serde(rename = "prevId")]
+    prev_id: Option<usize>,
+    #[serde(rename = "nextId")]
+    next_id: Option<usize>,
+    #[serde(rename = "hasPrev")]
+    has_prev: bool,
+    #[serde(rename = "hasNext")]
+    has_next: bool,
+}
+
+impl AdjacentChaptersView {
+    /// Neighbours of chapter `idx` in a book of `chapter_count` chapters.
+    fn new(idx: usize, chapter_count: usize) -> Self {
+        let prev_id = idx.checked_sub(1);
+        let next_id = Some(idx + 1).filter(|&next| next < chapter_count);
+        Self {
+            prev_id,
+            next_id,
+            has_prev: prev_id.is_some(),
+            has_next: next_id.is_some(),
+        }
+    }
 }
 
 #[derive(Clone, serde::Serialize)]
@@ -65,6 +126,52 @@ pub struct ParagraphView {
     segments: Option<Vec<ParagraphSegment>>,
 }
 
+/// One hit of [`LibraryView::search_translation`], addressed like
+/// `get_word_info`.
+#[derive(Clone, serde::Serialize)]
+pub struct LemmaMatchView {
+    #[serde(rename = "paragraphId")]
+    paragraph_id: usize,
+    #[serde(rename = "sentenceId")]
+    sentence_id: usize,
+    #[serde(rename = "wordId")]
+    word_id: usize,
+    /// The inflected form as it appears in the text.
+    original: String,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct VisibleWordView {
+    #[serde(rename = "paragraphId")]
+    paragraph_id: usize,
+    #[serde(rename = "wordIndex")]
+    word_index: usize,
+    /// Seconds since the Unix epoch; `None` for words revealed before reveal
+    /// times were recorded.
+    #[serde(rename = "revealedAt")]
+    revealed_at: Option<u64>,
+}
+
+/// Payload of `import_progress`, emitted while an EPUB is imported from disk.
+/// `stage` is `parsing` and then `chapters`, with `done` of `total` chapters
+/// added so far.
+#[derive(Clone, serde::Serialize)]
+pub struct ImportProgressEvent {
+    path: String,
+    stage: &'static str,
+    done: usize,
+    total: usize,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct TranslationTotalsView {
+    #[serde(rename = "translatedParagraphs")]
+    translated_paragraphs: usize,
+    sentences: usize,
+    /// Excludes punctuation.
+    words: usize,
+}
+
 #[derive(Clone, serde::Serialize)]
 pub struct ParagraphOriginal {
     id: usize,
@@ -110,6 +217,45 @@ pub struct WordView {
     translation_model: TranslationModel,
     #[serde(rename = "sourceLanguage")]
     source_language: String,
+    /// Platform dictionary entry, set only on the fallback for words of an
+    /// untranslated paragraph.
+    #[serde(rename = "systemDefinition", skip_serializing_if = "Option::is_none")]
+    system_definition: Option<SystemDefinition>,
+}
+
+impl WordView {
+    /// Stand-in for a word the model hasn't annotated yet: the platform
+    /// dictionary's definition goes in `note`, everything else is empty.
+    fn from_system_definition(
+        original: String,
+        source_language: String,
+        definition: SystemDefinition,
+    ) -> Self {
+        Self {
+            note: definition.definition.clone(),
+            is_punctuation: false,
+            grammar: GrammarView {
+                original_initial_form: original.clone(),
+                target_initial_form: String::new(),
+                part_of_speech: String::new(),
+                plurality: None,
+                person: None,
+                tense: None,
+                case: None,
+                other: None,
+                fields: vec![GrammarFieldView {
+                    label: "Initial form",
+                    value: Some(original.clone()),
+                }],
+            },
+            original,
+            contextual_translations: vec![],
+            full_sentence_translation: String::new(),
+            translation_model: TranslationModel::Unknown,
+            source_language,
+            system_definition: Some(definition),
+        }
+    }
 }
 
 #[derive(Clone, serde::Serialize)]
@@ -132,6 +278,18 @@ impl From<BookReadingState> for BookReadingStateView {
     }
 }
 
+#[derive(Clone, serde::Serialize)]
+pub struct WordLookupView {
+    #[serde(rename = "paragraphId")]
+    paragraph_id: usize,
+    #[serde(rename = "sentenceId")]
+    sentence_id: usize,
+    #[serde(rename = "wordId")]
+    word_id: usize,
+    /// Seconds since the Unix epoch.
+    timestamp: u64,
+}
+
 #[derive(Clone, serde::Serialize)]
 pub struct GrammarView {
     #[serde(rename = "originalInitialForm")]
@@ -145,6 +303,109 @@ pub struct GrammarView {
     tense: Option<String>,
     case: Option<String>,
     other: Option<String>,
+    /// The fields above, labeled and in display order.
+    fields: Vec<GrammarFieldView>,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct GrammarFieldView {
+    label: &'static str,
+    value: Option<String>,
+}
+
+impl From<&translation::GrammarView<'_>> for GrammarView {
+    fn from(grammar: &translation::GrammarView<'_>) -> Self {
+        Self {
+            original_initial_form: grammar.original_initial_form.to_string(),
+            target_initial_form: grammar.target_initial_form.to_string(),
+            part_of_speech: grammar.part_of_speech.to_string(),
+            plurality: grammar.plurality.as_ref().map(|p| p.to_string()),
+            person: grammar.person.as_ref().map(|p| p.to_string()),
+            tense: grammar.tense.as_ref().map(|t| t.to_string()),
+            case: grammar.case.as_ref().map(|c| c.to_string()),
+            other: grammar.other.as_ref().map(|o| o.to_string()),
+            fields: grammar
+                .fields()
+                .into_iter()
+                .map(|(label, value)| GrammarFieldView {
+                    label,
+                    value: value.map(|v| v.into_owned()),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Characters of the first paragraph shown in [`ChapterView::preview`].
+const CHAPTER_PREVIEW_CHARS: usize = 60;
+
+/// Example sentences [`LibraryView::get_word_examples`] returns per lemma.
+const WORD_EXAMPLES_LIMIT: usize = 5;
+
+/// The first [`CHAPTER_PREVIEW_CHARS`] characters of `text` on one line,
+/// with an ellipsis if it was cut.
+fn chapter_preview(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.char_indices().nth(CHAPTER_PREVIEW_CHARS) {
+        Some((end, _)) => format!("{}…", text[..end].trim_end()),
+        None => text,
+    }
+}
+
+/// `done / total`, or 0.0 for an empty total: NaN and infinity serialize as
+/// `null` and break the frontend's progress bars.
+fn progress_ratio(done: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        done as f64 / total as f64
+    }
+}
+
+fn metadata_view(
+    b: LibraryBookMetadata,
+    target_language: Option<&Language>,
+) -> LibraryBookMetadataView {
+    let translation = target_language.and_then(|tl| {
+        b.translations_metadata
+            .iter()
+            .find(|t| t.target_language == tl.to_639_3() && t.target_variant.is_none())
+    });
+
+    let translation_ratio = translation
+        .map(|t| progress_ratio(t.translated_paragraphs_count, b.paragraphs_count))
+        .unwrap_or(0.0);
+    let translation_last_modified = translation
+        .and_then(|t| t.last_modified)
+        .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64);
+    let translated_title =
+        target_language.and_then(|tl| b.translated_titles.get(tl.to_639_3()).cloned());
+    let translation_ratios = b
+        .translations_metadata
+        .iter()
+        .map(|t| {
+            let tag = match &t.target_variant {
+                Some(variant) => format!("{}-{variant}", t.target_language),
+                None => t.target_language.clone(),
+            };
+            let ratio = progress_ratio(t.translated_paragraphs_count, b.paragraphs_count);
+            (tag, ratio)
+        })
+        .collect();
+
+    LibraryBookMetadataView {
+        id: b.id,
+        title: b.title,
+        translated_title,
+        chapters_count: b.chapters_count,
+        paragraphs_count: b.paragraphs_count,
+        translation_ratio,
+        translation_ratios,
+        translation_last_modified,
+        incomplete: b.incomplete,
+        path: b.folder_path.clone(),
+    }
 }
 
 pub struct LibraryView {
@@ -173,9 +434,14 @@ impl LibraryView {
 
         let src_lang = Language::from_639_3(&book.book.language).unwrap();
         let card_store = self.library.card_store();
+        let known_words = self.library.known_words().get(&src_lang).await?;
 
         let bt = book_translation.lock().await;
         let t_view = bt.paragraph_view(paragraph_id);
+        let (visible_pos, density) = {
+            let config = self.state.config.borrow();
+            (config.visible_pos.clone(), config.annotation_density)
+        };
 
         let segments = if let Some(t) = t_view.as_ref() {
             let mut slug_set: HashSet<String> = HashSet::new();
@@ -184,7 +450,15 @@ impl LibraryView {
             let fam = card_store
                 .familiarities(src_lang.to_639_3(), target_language.to_639_3(), &slugs)
                 .await;
-            Some(paragraph_to_segments(&original, t, &fam, src_lang))
+            Some(paragraph_to_segments(
+                &original,
+                t,
+                &fam,
+                &known_words,
+                src_lang,
+                visible_pos.as_ref(),
+                density,
+            ))
         } else {
             None
         };
@@ -213,6 +487,28 @@ impl LibraryView {
             .collect())
     }
 
+    pub async fn has_unsaved_changes(&self, book_id: Uuid) -> anyhow::Result<bool> {
+        let book = self.library.get_book(&book_id).await?;
+        let book = book.lock().await;
+        Ok(book.is_dirty().await)
+    }
+
+    /// Character ranges of the words in a paragraph's plain original text,
+    /// for click-to-lookup on the original rather than the translation.
+    pub async fn get_paragraph_word_boundaries(
+        &self,
+        book_id: Uuid,
+        paragraph_id: usize,
+    ) -> anyhow::Result<Vec<(usize, usize)>> {
+        let book = self.library.get_book(&book_id).await?;
+        let book = book.lock().await;
+        anyhow::ensure!(
+            paragraph_id < book.book.paragraphs_count(),
+            "Paragraph {paragraph_id} not found"
+        );
+        Ok(book.book.paragraph_view(paragraph_id).word_boundaries())
+    }
+
     pub async fn get_paragraph_translations_batch(
         &self,
         book_id: Uuid,
@@ -227,6 +523,7 @@ impl LibraryView {
 
         let src_lang = Language::from_639_3(&book.book.language).unwrap();
         let card_store = self.library.card_store();
+        let known_words = self.library.known_words().get(&src_lang).await?;
 
         // First pass: resolve each paragraph's original text + translation
         // view, and accumulate the union of lemma slugs across the whole batch
@@ -251,12 +548,24 @@ impl LibraryView {
             .await;
 
         // Second pass: build segments against the shared familiarity map.
+        let (visible_pos, density) = {
+            let config = self.state.config.borrow();
+            (config.visible_pos.clone(), config.annotation_density)
+        };
         let out = prepared
             .iter()
             .map(|(id, original, t_view)| {
-                let segments = t_view
-                    .as_ref()
-                    .map(|t| paragraph_to_segments(original, t, &fam, src_lang));
+                let segments = t_view.as_ref().map(|t| {
+                    paragraph_to_segments(
+                        original,
+                        t,
+                        &fam,
+                        &known_words,
+                        src_lang,
+                        visible_pos.as_ref(),
+                        density,
+                    )
+                });
                 ParagraphTranslationSlice {
                     id: *id,
                     segments,
@@ -273,29 +582,28 @@ impl LibraryView {
         let books = self.library.list_books().await?;
         Ok(books
             .into_iter()
-            .map(|b| {
-                let translation = target_language.and_then(|tl| {
-                    b.translations_metadata
-                        .iter()
-                        .find(|t| t.target_language == tl.to_639_3())
-                });
+            .map(|b| metadata_view(b, target_language))
+            .collect())
+    }
 
-                let translation_ratio = translation
-                    .map(|t| t.translated_paragraphs_count as f64 / b.paragraphs_count as f64)
-                    .unwrap_or(0.0);
-
-                LibraryBookMetadataView {
-                    id: b.id,
-                    title: b.title,
-                    chapters_count: b.chapters_count,
-                    paragraphs_count: b.paragraphs_count,
-                    translation_ratio,
-                    path: b.folder_path.clone(),
-                }
-            })
+    pub async fn list_books_in_folder(
+        &self,
+        path: &[String],
+        target_language: Option<&Language>,
+    ) -> anyhow::Result<Vec<LibraryBookMetadataView>> {
+        let books = self.library.books_in_folder(path).await?;
+        Ok(books
+            .into_iter()
+            .map(|b| metadata_view(b, target_language))
             .collect())
     }
 
+    pub fn get_book_directory_path(&self, book_id: Uuid) -> Option<String> {
+        self.library
+            .book_directory(&book_id)
+            .map(|path| path.to_string_lossy().into_owned())
+    }
+
     pub async fn list_book_chapters(
         &mut self,
         book_id: Uuid,
@@ -312,6 +620,7 @@ impl LibraryView {
                 Some(arc) => Some(arc.lock().await),
                 None => None,
             };
+            let chapter_count = book_guard.book.chapter_count();
             book_guard
                 .book
                 .chapter_views()
@@ -325,20 +634,25 @@ impl LibraryView {
                     } else {
                         0
                     };
-                    let translation_ratio = if total == 0 {
-                        0.0
-                    } else {
-                        translated as f64 / total as f64
-                    };
+                    let translation_ratio = progress_ratio(translated, total);
                     let id = chapter.idx;
                     let title = chapter
                         .title
+                        .as_ref()
                         .map(|s| s.to_string())
                         .unwrap_or("<no title>".to_owned());
+                    let preview = if total > 0 {
+                        chapter_preview(&chapter.paragraph_view(0).original_text)
+                    } else {
+                        String::new()
+                    };
                     ChapterView {
                         id,
                         title,
+                        preview,
+                        paragraph_count: total,
                         translation_ratio,
+                        adjacent: AdjacentChaptersView::new(id, chapter_count),
                     }
                 })
                 .collect()
@@ -355,6 +669,52 @@ impl LibraryView {
         Ok(chapters)
     }
 
+    pub async fn get_adjacent_chapters(
+        &self,
+        book_id: Uuid,
+        chapter_id: usize,
+    ) -> anyhow::Result<AdjacentChaptersView> {
+        let book = self.library.get_book(&book_id).await?;
+        let book = book.lock().await;
+        let chapter_count = book.book.chapter_count();
+        if chapter_id >= chapter_count {
+            anyhow::bail!("Chapter {chapter_id} is out of range ({chapter_count} chapters)");
+        }
+        Ok(AdjacentChaptersView::new(chapter_id, chapter_count))
+    }
+
+    /// The paragraph's plain `original_text`, never its HTML, for copying.
+    /// Fails unless the paragraph belongs to `chapter_id`.
+    pub async fn get_paragraph_original_text(
+        &self,
+        book_id: Uuid,
+        chapter_id: usize,
+        paragraph_id: usize,
+    ) -> anyhow::Result<String> {
+        let book = self.library.get_book(&book_id).await?;
+        let book = book.lock().await;
+        let chapter_count = book.book.chapter_count();
+        if chapter_id >= chapter_count {
+            anyhow::bail!("Chapter {chapter_id} is out of range ({chapter_count} chapters)");
+        }
+        book.book
+            .chapter_view(chapter_id)
+            .paragraphs()
+            .find(|p| p.id == paragraph_id)
+            .map(|p| p.original_text.into_owned())
+            .ok_or_else(|| {
+                anyhow::anyhow!("Paragraph {paragraph_id} not found in chapter {chapter_id}")
+            })
+    }
+
+    /// First chapter whose title matches `title` case-insensitively, for
+    /// deep links that name a chapter rather than its index.
+    pub async fn find_chapter(&self, book_id: Uuid, title: &str) -> anyhow::Result<Option<usize>> {
+        let book = self.library.get_book(&book_id).await?;
+        let book = book.lock().await;
+        Ok(book.book.chapter_index_by_title(title))
+    }
+
     pub async fn list_book_chapter_paragraph_ids(
         &self,
         book_id: Uuid,
@@ -370,6 +730,271 @@ impl LibraryView {
             .collect())
     }
 
+    /// Every paragraph of a chapter in one call. With `annotated` off the
+    /// paragraphs carry only their original HTML and no segments, so the
+    /// reader can show clean text without word spans.
+    pub async fn get_book_chapter_paragraphs(
+        &self,
+        book_id: Uuid,
+        chapter_id: usize,
+        annotated: bool,
+        target_language: &Language,
+    ) -> anyhow::Result<Vec<ParagraphView>> {
+        let paragraph_ids = self
+            .list_book_chapter_paragraph_ids(book_id, chapter_id)
+            .await?;
+        if !annotated {
+            let originals = self
+                .get_paragraph_originals_batch(book_id, paragraph_ids)
+                .await?;
+            return Ok(originals
+                .into_iter()
+                .map(|p| ParagraphView {
+                    id: p.id,
+                    original: p.original,
+                    segments: None,
+                })
+                .collect());
+        }
+
+        let render = || async {
+            let originals = self
+                .get_paragraph_originals_batch(book_id, paragraph_ids.clone())
+                .await?;
+            let translations = self
+                .get_paragraph_translations_batch(book_id, paragraph_ids.clone(), target_language)
+                .await?;
+            Ok::<Vec<ParagraphView>, anyhow::Error>(
+                originals
+                    .into_iter()
+                    .zip(translations)
+                    .map(|(p, t)| ParagraphView {
+                        id: p.id,
+                        original: p.original,
+                        segments: t.segments,
+                    })
+                    .collect(),
+            )
+        };
+        let Some(key) = self
+            .chapter_render_key(book_id, chapter_id, &paragraph_ids, target_language)
+            .await?
+        else {
+            return render().await;
+        };
+        let paragraphs = self
+            .state
+            .chapter_render_cache()
+            .get_or_render(key, render)
+            .await?;
+        Ok(paragraphs.as_ref().clone())
+    }
+
+    /// Cache key for the annotated paragraphs of a chapter, or `None` when
+    /// the translation has unsaved changes its mtime doesn't reflect yet.
+    async fn chapter_render_key(
+        &self,
+        book_id: Uuid,
+        chapter_id: usize,
+        paragraph_ids: &[usize],
+        target_language: &Language,
+    ) -> anyhow::Result<Option<ChapterRenderKey>> {
+        let book = self.library.get_book(&book_id).await?;
+        let book = book.lock().await;
+        let Some(translation) = book.get_translation(target_language).await else {
+            return Ok(None);
+        };
+        let translation = translation.lock().await;
+        let Some(translation_last_modified) = translation
+            .last_modified()
+            .filter(|_| !translation.is_changed())
+        else {
+            return Ok(None);
+        };
+
+        let src_lang = Language::from_639_3(&book.book.language).unwrap();
+        let mut slug_set: HashSet<String> = HashSet::new();
+        for &id in paragraph_ids {
+            if let Some(t) = translation.paragraph_view(id) {
+                collect_paragraph_slugs(&t, src_lang, &mut slug_set);
+            }
+        }
+        let slugs: Vec<String> = slug_set.into_iter().collect();
+        let fam = self
+            .library
+            .card_store()
+            .familiarities(src_lang.to_639_3(), target_language.to_639_3(), &slugs)
+            .await;
+        let known_words = self.library.known_words().get(&src_lang).await?;
+
+        let (visible_pos, density) = {
+            let config = self.state.config.borrow();
+            (config.visible_pos.clone(), config.annotation_density)
+        };
+        let visible_pos = visible_pos.map(|pos| {
+            let mut pos: Vec<String> = pos.into_iter().collect();
+            pos.sort();
+            pos
+        });
+
+        Ok(Some(ChapterRenderKey {
+            book_id,
+            chapter_id,
+            target_language: target_language.to_639_3().to_owned(),
+            translation_last_modified,
+            visible_pos,
+            density,
+            familiarity: familiarity_fingerprint(&fam),
+            known_words: known_words_fingerprint(&known_words),
+        }))
+    }
+
+    /// Renders the whole book, or only `chapter_id`, as a self-contained HTML
+    /// page for sharing. Annotated paragraphs carry each word's translation
+    /// above it; otherwise every paragraph is followed by its full
+    /// translation. Untranslated paragraphs are exported as the original only.
+    pub async fn export_book_html(
+        &self,
+        book_id: Uuid,
+        chapter_id: Option<usize>,
+        target_language: &Language,
+        annotated: bool,
+    ) -> anyhow::Result<String> {
+        let book = self.library.get_book(&book_id).await?;
+        let book = book.lock().await;
+        if let Some(chapter_id) = chapter_id {
+            anyhow::ensure!(
+                chapter_id < book.book.chapter_count(),
+                "Chapter {chapter_id} not found"
+            );
+        }
+        let src_lang = Language::from_639_3(&book.book.language).unwrap();
+        let known_words = self.library.known_words().get(&src_lang).await?;
+        let (visible_pos, density) = {
+            let config = self.state.config.borrow();
+            (config.visible_pos.clone(), config.annotation_density)
+        };
+        let translation = book.get_translation(target_language).await;
+        let translation = match &translation {
+            Some(translation) => Some(translation.lock().await),
+            None => None,
+        };
+
+        let mut body = String::new();
+        for chapter in book.book.chapter_views() {
+            if chapter_id.is_some_and(|id| id != chapter.idx) {
+                continue;
+            }
+            if let Some(title) = &chapter.title {
+                body.push_str(&format!("<h2>{}</h2>\n", escape_html(title)));
+            }
+            for paragraph in chapter.paragraphs() {
+                let original = paragraph.original_html.unwrap_or(paragraph.original_text);
+                let t_view = translation
+                    .as_ref()
+                    .and_then(|t| t.paragraph_view(paragraph.id));
+                body.push_str("<div class=\"paragraph\">");
+                match t_view {
+                    Some(t) if annotated => {
+                        let segments = paragraph_to_segments(
+                            &original,
+                            &t,
+                            &HashMap::new(),
+                            &known_words,
+                            src_lang,
+                            visible_pos.as_ref(),
+                            density,
+                        );
+                        body.push_str(&segments_to_html(&segments));
+                    }
+                    Some(t) => {
+                        let full_translation: Vec<_> = t
+                            .sentences()
+                            .map(|s| s.full_translation.into_owned())
+                            .collect();
+                        body.push_str(&original);
+                        body.push_str("<p class=\"paragraph-translation\">");
+                        body.push_str(&escape_html(&full_translation.join(" ")));
+                        body.push_str("</p>");
+                    }
+                    None => body.push_str(&original),
+                }
+                body.push_str("</div>\n");
+            }
+        }
+
+        Ok(format!(
+            "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{}</title>\n<style>\n{EXPORT_HTML_STYLE}</style>\n</head>\n\
+             <body>\n<h1>{}</h1>\n{body}</body>\n</html>\n",
+            src_lang.to_639_1().unwrap_or(src_lang.to_639_3()),
+            escape_html(&book.book.title),
+            escape_html(&book.book.title),
+        ))
+    }
+
+    /// Every word of the book's `target_language` translation whose
+    /// dictionary form is `lemma`, in reading order. Unlike a plain-text
+    /// search this finds inflected forms as well.
+    pub async fn search_translation(
+        &self,
+        book_id: Uuid,
+        lemma: &str,
+        target_language: &Language,
+    ) -> anyhow::Result<Vec<LemmaMatchView>> {
+        let translation = {
+            let book = self.library.get_book(&book_id).await?;
+            let book = book.lock().await;
+            book.get_translation(target_language).await
+        };
+        let Some(translation) = translation else {
+            return Ok(vec![]);
+        };
+
+        let translation = translation.lock().await;
+        Ok(translation
+            .search_lemma(lemma)
+            .into_iter()
+            .filter_map(|(paragraph_id, sentence_id, word_id)| {
+                let paragraph = translation.paragraph_view(paragraph_id)?;
+                let original = paragraph
+                    .sentence_view(sentence_id)
+                    .word_view(word_id)
+                    .original
+                    .to_string();
+                Some(LemmaMatchView {
+                    paragraph_id,
+                    sentence_id,
+                    word_id,
+                    original,
+                })
+            })
+            .collect())
+    }
+
+    /// Up to [`WORD_EXAMPLES_LIMIT`] translated sentences of the book that
+    /// use `lemma`, for studying the word in context.
+    pub async fn get_word_examples(
+        &self,
+        book_id: Uuid,
+        lemma: &str,
+        target_language: &Language,
+    ) -> anyhow::Result<Vec<String>> {
+        let translation = {
+            let book = self.library.get_book(&book_id).await?;
+            let book = book.lock().await;
+            book.get_translation(target_language).await
+        };
+        let Some(translation) = translation else {
+            return Ok(vec![]);
+        };
+
+        Ok(translation
+            .lock()
+            .await
+            .examples_for_lemma(lemma, WORD_EXAMPLES_LIMIT))
+    }
+
     pub async fn get_word_info(
         &mut self,
         book_id: Uuid,
@@ -399,19 +1024,11 @@ impl LibraryView {
                         .contextual_translations()
                         .map(|ct| ct.translation.to_string())
                         .collect(),
-                    grammar: GrammarView {
-                        original_initial_form: word.grammar.original_initial_form.to_string(),
-                        target_initial_form: word.grammar.target_initial_form.to_string(),
-                        part_of_speech: word.grammar.part_of_speech.to_string(),
-                        plurality: word.grammar.plurality.map(|p| p.to_string()),
-                        person: word.grammar.person.map(|p| p.to_string()),
-                        tense: word.grammar.tense.map(|t| t.to_string()),
-                        case: word.grammar.case.map(|c| c.to_string()),
-                        other: word.grammar.other.map(|o| o.to_string()),
-                    },
+                    grammar: GrammarView::from(&word.grammar),
                     full_sentence_translation: sentence.full_translation.to_string(),
                     translation_model: paragraph.model,
                     source_language: source_language_code,
+                    system_definition: None,
                 })
             } else {
                 None
@@ -419,15 +1036,71 @@ impl LibraryView {
         )
     }
 
+    /// The `word_id`-th word of the paragraph's original text, counted by
+    /// [`get_paragraph_word_boundaries`](Self::get_paragraph_word_boundaries),
+    /// with the book's language. For looking up words of untranslated
+    /// paragraphs.
+    pub async fn get_paragraph_word(
+        &self,
+        book_id: Uuid,
+        paragraph_id: usize,
+        word_id: usize,
+    ) -> anyhow::Result<Option<(String, String)>> {
+        let book = self.library.get_book(&book_id).await?;
+        let book = book.lock().await;
+        anyhow::ensure!(
+            paragraph_id < book.book.paragraphs_count(),
+            "Paragraph {paragraph_id} not found"
+        );
+        let paragraph = book.book.paragraph_view(paragraph_id);
+        Ok(paragraph
+            .word_boundaries()
+            .get(word_id)
+            .map(|&(start, end)| {
+                let word = paragraph
+                    .original_text
+                    .chars()
+                    .skip(start)
+                    .take(end - start)
+                    .collect();
+                (word, book.book.language.clone())
+            }))
+    }
+
     pub async fn import_plain_text(
         &mut self,
         title: &str,
         text: &str,
         source_language: &Language,
+        chapter_heading_pattern: Option<&str>,
+        preserve_line_breaks: bool,
+    ) -> anyhow::Result<CreatedBook> {
+        let created = self
+            .library
+            .create_book_plain_with_options(
+                title,
+                text,
+                source_language,
+                chapter_heading_pattern,
+                preserve_line_breaks,
+            )
+            .await?;
+
+        self.state.notify_library_changed();
+        self.enqueue_summary_generation(created.id).await;
+
+        Ok(created)
+    }
+
+    pub async fn import_epub(
+        &mut self,
+        book: &EpubBook,
+        source_language: &Language,
+        storage: ParagraphStorage,
     ) -> anyhow::Result<Uuid> {
         let id = self
             .library
-            .create_book_plain(title, text, source_language)
+            .create_book_epub(book, source_language, storage)
             .await?;
 
         self.state.notify_library_changed();
@@ -436,12 +1109,36 @@ impl LibraryView {
         Ok(id)
     }
 
-    pub async fn import_epub(
+    /// Loads and imports the EPUB at `path` without handing it through the
+    /// webview, emitting `import_progress` along the way.
+    pub async fn import_epub_path(
         &mut self,
-        book: &EpubBook,
+        path: &Path,
         source_language: &Language,
+        storage: ParagraphStorage,
     ) -> anyhow::Result<Uuid> {
-        let id = self.library.create_book_epub(book, source_language).await?;
+        let path_label = path.to_string_lossy().into_owned();
+        let progress = |stage, done, total| {
+            let _ = self.state.app.emit(
+                "import_progress",
+                ImportProgressEvent {
+                    path: path_label.clone(),
+                    stage,
+                    done,
+                    total,
+                },
+            );
+        };
+
+        progress("parsing", 0, 0);
+        let owned_path = path.to_owned();
+        let book = tokio::task::spawn_blocking(move || EpubBook::load(&owned_path)).await??;
+        let id = self
+            .library
+            .create_book_epub_with_progress(&book, source_language, storage, |done, total| {
+                progress("chapters", done, total)
+            })
+            .await?;
 
         self.state.notify_library_changed();
         self.enqueue_summary_generation(id).await;
@@ -488,6 +1185,169 @@ impl LibraryView {
         .await
     }
 
+    pub async fn record_word_lookup(
+        &self,
+        book_id: Uuid,
+        paragraph_id: usize,
+        sentence_id: usize,
+        word_id: usize,
+    ) -> anyhow::Result<()> {
+        let limit = self.state.config.borrow().lookup_history_limit as usize;
+        let book = self.library.get_book(&book_id).await?;
+        let mut book = book.lock().await;
+        book.record_word_lookup(paragraph_id, sentence_id, word_id, limit)
+            .await
+    }
+
+    /// Looked-up words of `book_id`, oldest first.
+    pub async fn get_lookup_history(&self, book_id: Uuid) -> anyhow::Result<Vec<WordLookupView>> {
+        let book = self.library.get_book(&book_id).await?;
+        let mut book = book.lock().await;
+        Ok(book
+            .lookup_history()
+            .await?
+            .into_iter()
+            .map(
+                |(paragraph_id, sentence_id, word_id, timestamp)| WordLookupView {
+                    paragraph_id,
+                    sentence_id,
+                    word_id,
+                    timestamp,
+                },
+            )
+            .collect())
+    }
+
+    pub async fn mark_word_visible(
+        &self,
+        book_id: Uuid,
+        paragraph_id: usize,
+        word_index: usize,
+        target_language: &Language,
+    ) -> anyhow::Result<()> {
+        let book = self.library.get_book(&book_id).await?;
+        let mut book = book.lock().await;
+        let Some(translation) = book.get_translation(target_language).await else {
+            return Ok(());
+        };
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        translation
+            .lock()
+            .await
+            .mark_word_visible(paragraph_id, word_index, timestamp);
+        self.state.chapter_render_cache().invalidate_book(book_id);
+        book.save().await
+    }
+
+    pub async fn set_paragraph_visible(
+        &self,
+        book_id: Uuid,
+        paragraph_id: usize,
+        visible: bool,
+        target_language: &Language,
+    ) -> anyhow::Result<()> {
+        let book = self.library.get_book(&book_id).await?;
+        let mut book = book.lock().await;
+        let Some(translation) = book.get_translation(target_language).await else {
+            return Ok(());
+        };
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        translation
+            .lock()
+            .await
+            .set_all_words_visible(paragraph_id, visible, timestamp);
+        self.state.chapter_render_cache().invalidate_book(book_id);
+        book.save().await
+    }
+
+    /// Marks `lemma` as known in the language of `book_id`, so it's no longer
+    /// annotated in any book of that language. Returns `false` if it already
+    /// was known.
+    pub async fn add_known_word(&self, book_id: Uuid, lemma: &str) -> anyhow::Result<bool> {
+        let src_lang = self.book_language(book_id).await?;
+        self.library.known_words().add(&src_lang, lemma).await
+    }
+
+    /// Annotates `lemma` again. Returns `false` if it wasn't known.
+    pub async fn remove_known_word(&self, book_id: Uuid, lemma: &str) -> anyhow::Result<bool> {
+        let src_lang = self.book_language(book_id).await?;
+        self.library.known_words().remove(&src_lang, lemma).await
+    }
+
+    async fn book_language(&self, book_id: Uuid) -> anyhow::Result<Language> {
+        let book = self.library.get_book(&book_id).await?;
+        let book = book.lock().await;
+        Language::from_639_3(&book.book.language)
+            .ok_or_else(|| anyhow::anyhow!("Unknown book language {}", book.book.language))
+    }
+
+    pub async fn get_translation_totals(
+        &self,
+        book_id: Uuid,
+        target_language: &Language,
+    ) -> anyhow::Result<TranslationTotalsView> {
+        let book = self.library.get_book(&book_id).await?;
+        let book = book.lock().await;
+        let Some(translation) = book.get_translation(target_language).await else {
+            return Ok(TranslationTotalsView {
+                translated_paragraphs: 0,
+                sentences: 0,
+                words: 0,
+            });
+        };
+        let translation = translation.lock().await;
+        Ok(TranslationTotalsView {
+            translated_paragraphs: translation.translated_paragraphs_count(),
+            sentences: translation.total_sentences(),
+            words: translation.total_words(),
+        })
+    }
+
+    pub async fn export_visible_words(
+        &self,
+        book_id: Uuid,
+        target_language: &Language,
+    ) -> anyhow::Result<Vec<VisibleWordView>> {
+        let book = self.library.get_book(&book_id).await?;
+        let book = book.lock().await;
+        let Some(translation) = book.get_translation(target_language).await else {
+            return Ok(vec![]);
+        };
+        let translation = translation.lock().await;
+
+        let mut words = Vec::new();
+        for paragraph_id in 0..book.book.paragraphs_count() {
+            let Some(paragraph) = translation.paragraph_view(paragraph_id) else {
+                continue;
+            };
+            let mut indexes: Vec<usize> = paragraph.visible_words().iter().copied().collect();
+            indexes.sort_unstable();
+            words.extend(indexes.into_iter().map(|word_index| VisibleWordView {
+                paragraph_id,
+                word_index,
+                revealed_at: paragraph.revealed_at().get(&word_index).copied(),
+            }));
+        }
+        Ok(words)
+    }
+
+    pub async fn export_studied_words(
+        &self,
+        book_id: Uuid,
+        target_language: &Language,
+    ) -> anyhow::Result<StudySet> {
+        let book = self.library.get_book(&book_id).await?;
+        let book = book.lock().await;
+        let translation = book.get_translation(target_language).await.ok_or_else(|| {
+            anyhow::anyhow!(
+                "Book {book_id} has no {} translation",
+                target_language.to_639_3()
+            )
+        })?;
+        let study_set = translation.lock().await.export_studied();
+        Ok(study_set)
+    }
+
     pub async fn move_book(&self, book_id: Uuid, new_path: Vec<String>) -> anyhow::Result<()> {
         let book = self.library.get_book(&book_id).await?;
         {
@@ -499,6 +1359,59 @@ impl LibraryView {
         Ok(())
     }
 
+    pub async fn update_paragraph_original(
+        &self,
+        book_id: Uuid,
+        paragraph_id: usize,
+        text: &str,
+        html: Option<&str>,
+    ) -> anyhow::Result<bool> {
+        let book = self.library.get_book(&book_id).await?;
+        let stale = book
+            .lock()
+            .await
+            .update_paragraph_original(paragraph_id, text, html)
+            .await?;
+
+        self.state.chapter_render_cache().invalidate_book(book_id);
+        self.state.app.emit(
+            "paragraph_updated",
+            ParagraphUpdatedEvent {
+                book_id,
+                paragraph_id,
+            },
+        )?;
+        self.state.notify_library_changed();
+        Ok(stale)
+    }
+
+    pub async fn repair_book(&self, book_id: Uuid, remove: bool) -> anyhow::Result<()> {
+        self.library.repair_book(&book_id, remove).await?;
+        self.state.notify_library_changed();
+        Ok(())
+    }
+
+    /// Conflict files the book was loaded without merging.
+    pub async fn get_book_conflicts(&self, book_id: Uuid) -> anyhow::Result<Vec<String>> {
+        let book = self.library.get_book(&book_id).await?;
+        let book = book.lock().await;
+        Ok(book
+            .conflicting_paths()
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect())
+    }
+
+    pub async fn merge_conflicts(&self, book_id: Uuid) -> anyhow::Result<usize> {
+        let merged = self.library.merge_conflicts(&book_id).await?;
+        if merged > 0 {
+            self.state.chapter_render_cache().invalidate_book(book_id);
+            self.state.app.emit("book_updated", book_id)?;
+            self.state.notify_library_changed();
+        }
+        Ok(merged)
+    }
+
     pub async fn delete_book(&self, book_id: Uuid) -> anyhow::Result<()> {
         self.library.delete_book(&book_id).await?;
         self.state.notify_library_changed();
@@ -540,11 +1453,132 @@ fn collect_paragraph_slugs(
     }
 }
 
+/// Order-independent hash of a familiarity map, so a reviewed card
+/// invalidates the cached chapters showing it.
+fn familiarity_fingerprint(fam: &HashMap<String, f32>) -> u64 {
+    let mut entries: Vec<_> = fam.iter().map(|(slug, f)| (slug, f.to_bits())).collect();
+    entries.sort_unstable();
+    let mut hasher = DefaultHasher::new();
+    entries.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Order-independent hash of the known-words set, so marking a word as known
+/// invalidates the cached chapters annotating it.
+fn known_words_fingerprint(known_words: &HashSet<String>) -> u64 {
+    let mut slugs: Vec<_> = known_words.iter().collect();
+    slugs.sort_unstable();
+    let mut hasher = DefaultHasher::new();
+    slugs.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Character ranges `[start, end)` that bound the word search of each
+/// translated sentence. `weights` holds, per translated sentence, how many
+/// source characters its words cover. When the UAX #29 sentence split of
+/// `original` yields exactly one sentence per translated one, each gets its
+/// own span. When the model merged or split sentences, the paragraph is cut at
+/// the sentence boundaries (or, if there are too few, at clause punctuation)
+/// closest to where the weights put the sentence breaks; the weights count
+/// source-side words, so a target language that runs longer doesn't skew the
+/// cuts. Only without enough candidate cuts is every sentence bounded by the
+/// whole paragraph.
+fn sentence_spans(original: &str, weights: &[usize]) -> Vec<(usize, usize)> {
+    let count = weights.len();
+    let mut bounds = Vec::new();
+    let mut total = 0;
+    for sentence in original.split_sentence_bounds() {
+        total += sentence.chars().count();
+        bounds.push(total);
+    }
+    // The paragraph end closes the last span, it's never a cut.
+    bounds.pop();
+    if bounds.len() + 1 == count {
+        return spans_between(&bounds, total);
+    }
+    if bounds.len() + 1 < count {
+        bounds.extend(clause_bounds(original));
+        bounds.sort_unstable();
+        bounds.dedup();
+    }
+    if count == 0 || bounds.len() + 1 < count {
+        return vec![(0, total); count];
+    }
+
+    // Equal weights when no sentence has any words to weigh.
+    let weights = if weights.iter().all(|&w| w == 0) {
+        vec![1; count]
+    } else {
+        weights.to_vec()
+    };
+    let weight_sum: usize = weights.iter().sum();
+
+    let mut cuts = Vec::with_capacity(count - 1);
+    let mut next_candidate = 0;
+    let mut cumulative = 0;
+    for (k, weight) in weights[..count - 1].iter().enumerate() {
+        cumulative += weight;
+        let target = total * cumulative / weight_sum;
+        // Leave a candidate for each of the cuts still to come.
+        let last_candidate = bounds.len() - (count - 1 - k);
+        let best = (next_candidate..=last_candidate)
+            .min_by_key(|&i| bounds[i].abs_diff(target))
+            .unwrap();
+        cuts.push(bounds[best]);
+        next_candidate = best + 1;
+    }
+    spans_between(&cuts, total)
+}
+
+/// Spans from 0 to `total` split at the ascending offsets `cuts`.
+fn spans_between(cuts: &[usize], total: usize) -> Vec<(usize, usize)> {
+    let mut spans = Vec::with_capacity(cuts.len() + 1);
+    let mut start = 0;
+    for &cut in cuts.iter().chain([&total]) {
+        spans.push((start, cut));
+        start = cut;
+    }
+    spans
+}
+
+/// Character offsets just past clause punctuation and the whitespace after
+/// it, the fallback cuts for a model that split one sentence in several.
+fn clause_bounds(original: &str) -> Vec<usize> {
+    let chars: Vec<char> = original.chars().collect();
+    let mut bounds = Vec::new();
+    for (i, c) in chars.iter().enumerate() {
+        if !matches!(c, ',' | ';' | ':' | '—' | '–' | '、' | '，' | '；' | '：') {
+            continue;
+        }
+        let mut end = i + 1;
+        while chars.get(end).is_some_and(|c| c.is_whitespace()) {
+            end += 1;
+        }
+        if end < chars.len() {
+            bounds.push(end);
+        }
+    }
+    bounds
+}
+
+/// Splits `original` into word and gap segments aligned with `translation`.
+/// The segments tile `original` exactly: concatenating their text and gap
+/// html reproduces it character for character, whatever the alignment finds.
+/// Each translated sentence is aligned within its own span of `original` (see
+/// [`sentence_spans`]), so a word that fails to match can't push the cursor
+/// into the following sentences.
+/// When `visible_pos` is set, only words whose part of speech is in the set
+/// (see [`is_visible_part_of_speech`]) carry an in-text translation, and `density` narrows that further; words
+/// whose lemma slug is in `known_words` never do. The rest are still emitted
+/// as words (so they stay clickable) but with `translation: None`.
 fn paragraph_to_segments(
     original: &str,
     translation: &ParagraphTranslationView,
     card_familiarity: &HashMap<String, f32>,
+    known_words: &HashSet<String>,
     src_lang: Language,
+    visible_pos: Option<&HashSet<String>>,
+    density: AnnotationDensity,
 ) -> Vec<ParagraphSegment> {
     let mut segments: Vec<ParagraphSegment> = Vec::new();
 
@@ -565,6 +1599,11 @@ fn paragraph_to_segments(
             .unwrap_or_else(|_| value.to_owned())
     };
 
+    let weights: Vec<usize> = translation
+        .sentences()
+        .map(|sentence| sentence.words().map(|w| w.original.chars().count()).sum())
+        .collect();
+    let spans = sentence_spans(original, &weights);
     let original: Vec<char> = original.chars().collect();
 
     let mut p_idx = 0_usize;
@@ -572,6 +1611,13 @@ fn paragraph_to_segments(
     let mut flat_index = 0_usize;
 
     for sentence in translation.sentences() {
+        let (span_start, span_end) = spans[sentence_idx];
+        if p_idx < span_start {
+            let gap = String::from_iter(original[p_idx..span_start].iter());
+            push_gap(&mut segments, gap);
+            p_idx = span_start;
+        }
+
         let mut word_idx = 0;
         for word in sentence.words() {
             if word.is_punctuation {
@@ -586,11 +1632,11 @@ fn paragraph_to_segments(
             let w = decode_lossy(&w_raw);
             let len = w.chars().count();
             let mut offset = 0_usize;
-            while p_idx + offset < original.len() {
+            while p_idx + offset < span_end {
                 let start = p_idx + offset;
                 let mut clamped_end = p_idx + offset + len;
-                if clamped_end >= original.len() {
-                    clamped_end = original.len();
+                if clamped_end >= span_end {
+                    clamped_end = span_end;
                 }
 
                 if start >= clamped_end {
@@ -600,7 +1646,9 @@ fn paragraph_to_segments(
                 let p_word_raw = String::from_iter(original[start..clamped_end].iter());
                 let p_word = decode_lossy(&p_word_raw);
 
-                if w.len() <= 2 {
+                // A single CJK character is within one edit of any other
+                // character, so short words must match exactly.
+                if len <= 2 {
                     if w.to_lowercase() == p_word.to_lowercase() {
                         break;
                     }
@@ -611,42 +1659,54 @@ fn paragraph_to_segments(
                 offset += 1;
             }
 
+            let mut match_len = len;
+            if p_idx + offset >= span_end
+                && let Some((start, end)) = match_by_edge_chars(&original, p_idx, span_end, &w)
+            {
+                offset = start - p_idx;
+                match_len = end - start;
+            }
+
             if offset > 0 {
-                let end = (p_idx + offset).min(original.len());
+                let end = (p_idx + offset).min(span_end);
                 let gap = String::from_iter(original[p_idx..end].iter());
                 push_gap(&mut segments, gap);
             }
 
             p_idx += offset;
 
-            let mut clamped_end = p_idx + len;
-            if clamped_end >= original.len() {
-                clamped_end = original.len();
+            let mut clamped_end = p_idx + match_len;
+            if clamped_end >= span_end {
+                clamped_end = span_end;
             }
 
             if p_idx < clamped_end {
                 let text = String::from_iter(original[p_idx..clamped_end].iter());
-                let translation_text = word
-                    .contextual_translations()
-                    .next()
-                    .map(|ct| sanitize_translation_text(ct.translation.as_ref()))
-                    .filter(|t| !t.is_empty());
 
                 let lemma_canonical =
                     card::canonicalize_lemma(&word.grammar.original_initial_form, src_lang);
-                let familiarity = if lemma_canonical.is_empty() {
+                let slug = if lemma_canonical.is_empty() {
                     None
                 } else {
-                    let slug = card::lemma_slug(&lemma_canonical);
-                    if slug.is_empty() {
-                        None
-                    } else {
-                        // A missing map entry means the card is dormant
-                        // (Suspended/Deleted). A never-synced card is mapped
-                        // to Some(0.0) by `LibraryCardStore::familiarities`.
-                        card_familiarity.get(&slug).copied()
-                    }
+                    Some(card::lemma_slug(&lemma_canonical)).filter(|slug| !slug.is_empty())
                 };
+                let known = slug.as_ref().is_some_and(|slug| known_words.contains(slug));
+
+                let pos = &*word.grammar.part_of_speech;
+                let annotated = !known
+                    && visible_pos.is_none_or(|set| is_visible_part_of_speech(pos, set))
+                    && density.annotates(pos);
+                let translation_text = word
+                    .contextual_translations()
+                    .next()
+                    .filter(|_| annotated)
+                    .map(|ct| sanitize_translation_text(ct.translation.as_ref()))
+                    .filter(|t| !t.is_empty());
+
+                // A missing map entry means the card is dormant
+                // (Suspended/Deleted). A never-synced card is mapped
+                // to Some(0.0) by `LibraryCardStore::familiarities`.
+                let familiarity = slug.and_then(|slug| card_familiarity.get(&slug).copied());
 
                 segments.push(ParagraphSegment::Word {
                     text,
@@ -673,6 +1733,103 @@ fn paragraph_to_segments(
     segments
 }
 
+/// Fallback for [`paragraph_to_segments`] when no window of `word`'s length
+/// is within one edit of it: the first span of `original[from..to]` that
+/// starts with the word's first character and ends with its last, at most
+/// two characters shorter or longer than the word. CJK text has no spaces to
+/// resync on, so this keeps a word spelled differently inside (a full-width
+/// Latin name, a variant kanji) attached instead of turning the rest of the
+/// sentence into a gap.
+fn match_by_edge_chars(
+    original: &[char],
+    from: usize,
+    to: usize,
+    word: &str,
+) -> Option<(usize, usize)> {
+    let word: Vec<char> = word.chars().collect();
+    let (&first, &last) = (word.first()?, word.last()?);
+    let len = word.len();
+    let lengths = [
+        Some(len),
+        len.checked_sub(1),
+        Some(len + 1),
+        len.checked_sub(2),
+        Some(len + 2),
+    ];
+    (from..to)
+        .filter(|&start| same_letter(original[start], first))
+        .find_map(|start| {
+            lengths
+                .into_iter()
+                .flatten()
+                .filter(|&l| l > 0)
+                .map(|l| start + l)
+                .find(|&end| end <= to && same_letter(original[end - 1], last))
+                .map(|end| (start, end))
+        })
+}
+
+/// Case- and width-insensitive comparison: full-width forms of ASCII
+/// characters (U+FF01..U+FF5E) equal their ASCII counterparts.
+fn same_letter(a: char, b: char) -> bool {
+    let fold = |c: char| {
+        let c = match c {
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            _ => c,
+        };
+        c.to_lowercase()
+    };
+    fold(a).eq(fold(b))
+}
+
+/// Inline styles for [`LibraryView::export_book_html`]: each annotated word
+/// stacks its translation in small type above the original.
+const EXPORT_HTML_STYLE: &str = "\
+body { max-width: 40em; margin: 2em auto; padding: 0 1em; font-family: Georgia, serif; line-height: 1.8; }
+.word-span { display: inline-flex; flex-direction: column; align-items: center; vertical-align: bottom; line-height: 1.2; }
+.word-translation { font-size: 0.55em; color: #8a6d1a; white-space: nowrap; }
+.paragraph-translation { color: #666; font-style: italic; }
+";
+
+/// Renders segments from [`paragraph_to_segments`] as static HTML. Gaps and
+/// word text are slices of the paragraph's original markup and are emitted
+/// verbatim; translations are escaped.
+fn segments_to_html(segments: &[ParagraphSegment]) -> String {
+    let mut html = String::new();
+    for segment in segments {
+        match segment {
+            ParagraphSegment::Gap { html: gap } => html.push_str(gap),
+            ParagraphSegment::Word {
+                text, translation, ..
+            } => {
+                html.push_str("<span class=\"word-span\">");
+                if let Some(translation) = translation {
+                    html.push_str("<span class=\"word-translation\">");
+                    html.push_str(&escape_html(translation));
+                    html.push_str("</span>");
+                }
+                html.push_str(text);
+                html.push_str("</span>");
+            }
+        }
+    }
+    html
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 fn sanitize_translation_text(value: &str) -> String {
     value
         .split_whitespace()
@@ -751,12 +1908,16 @@ fn levenshtein_distance_lt_2(str1: &str, str2: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::{ParagraphSegment, paragraph_to_segments};
+    use super::{
+        AdjacentChaptersView, AnnotationDensity, CHAPTER_PREVIEW_CHARS, ParagraphSegment, WordView,
+        chapter_preview, metadata_view, paragraph_to_segments, segments_to_html, sentence_spans,
+    };
 
     use isolang::Language;
     use library::book::translation_import;
+    use library::system_dictionary::SystemDefinition;
     use library::{book::translation::ParagraphTranslationView, translator::TranslationModel};
-    use std::collections::HashMap;
+    use std::collections::{HashMap, HashSet};
 
     fn grammar_stub(original: &str) -> translation_import::Grammar {
         translation_import::Grammar {
@@ -795,6 +1956,7 @@ mod tests {
             timestamp: 0,
             sentences,
             total_tokens: None,
+            token_usage: None,
         }
     }
 
@@ -832,6 +1994,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn chapter_preview_cuts_on_a_char_boundary() {
+        assert_eq!(chapter_preview("  Short\n text "), "Short text");
+
+        let long = "ё".repeat(100);
+        let preview = chapter_preview(&long);
+        assert_eq!(preview.chars().count(), CHAPTER_PREVIEW_CHARS + 1);
+        assert!(preview.ends_with("ё…"));
+    }
+
+    #[test]
+    fn segments_to_html_stacks_escaped_translations_over_words() {
+        let segments = vec![
+            word_seg("Hola", 0, 0, 0, Some("Hi <there>")),
+            gap_seg(", "),
+            word_seg("mundo", 0, 1, 1, None),
+        ];
+        assert_eq!(
+            segments_to_html(&segments),
+            "<span class=\"word-span\"><span class=\"word-translation\">Hi &lt;there&gt;</span>Hola</span>, \
+             <span class=\"word-span\">mundo</span>"
+        );
+    }
+
     #[test]
     fn wraps_words_and_preserves_raw_translation() {
         let original = "Hello, world!";
@@ -852,7 +2038,10 @@ mod tests {
             original,
             &view,
             &HashMap::new(),
+            &HashSet::new(),
             Language::from_639_3("eng").unwrap(),
+            None,
+            AnnotationDensity::All,
         );
 
         // Translation text is preserved raw (no HTML escaping on the backend);
@@ -868,6 +2057,115 @@ mod tests {
         );
     }
 
+    fn concat_segments(segments: &[ParagraphSegment]) -> String {
+        segments
+            .iter()
+            .map(|s| match s {
+                ParagraphSegment::Gap { html } => html.as_str(),
+                ParagraphSegment::Word { text, .. } => text.as_str(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keeps_every_character_of_trailing_punctuation() {
+        let cases: [(&str, Vec<translation_import::Word>); 3] = [
+            (
+                "¿Vienes mañana…!?",
+                vec![
+                    word("¿", &[], true),
+                    word("Vienes", &["coming"], false),
+                    word("mañana", &["tomorrow"], false),
+                    word("…", &[], true),
+                    word("!", &[], true),
+                    word("?", &[], true),
+                ],
+            ),
+            // The last word only matches fuzzily, and its window takes in
+            // part of the punctuation run.
+            (
+                "Ven aqu…!?",
+                vec![
+                    word("Ven", &["come"], false),
+                    word("aquí", &["here"], false),
+                ],
+            ),
+            // The translation drops the trailing punctuation altogether.
+            ("Sí…!?", vec![word("Sí", &["yes"], false)]),
+        ];
+
+        for (original, words) in cases {
+            let pt = make_paragraph_translation(vec![translation_import::Sentence {
+                full_translation: "ignored".to_owned(),
+                words,
+            }]);
+            let mut t = library::book::translation::Translation::create("spa", "eng");
+            let view = view_from_import(&mut t, 0, &pt);
+            let segments = paragraph_to_segments(
+                original,
+                &view,
+                &HashMap::new(),
+                &HashSet::new(),
+                Language::from_639_3("spa").unwrap(),
+                None,
+                AnnotationDensity::All,
+            );
+            assert_eq!(concat_segments(&segments), original);
+        }
+    }
+
+    #[test]
+    fn misaligned_sentence_does_not_leak_into_the_next() {
+        let original = "Me gusta el té. Ella lee libros.";
+        let pt = make_paragraph_translation(vec![
+            // "mucho" is not in the original: without per-sentence bounds the
+            // failed search runs the cursor to the end of the paragraph.
+            translation_import::Sentence {
+                full_translation: "I like tea a lot.".to_owned(),
+                words: vec![
+                    word("Me", &["me"], false),
+                    word("gusta", &["like"], false),
+                    word("mucho", &["a lot"], false),
+                    word("el", &["the"], false),
+                    word("té", &["tea"], false),
+                    word(".", &[], true),
+                ],
+            },
+            translation_import::Sentence {
+                full_translation: "She reads books.".to_owned(),
+                words: vec![
+                    word("Ella", &["she"], false),
+                    word("lee", &["reads"], false),
+                    word("libros", &["books"], false),
+                    word(".", &[], true),
+                ],
+            },
+        ]);
+        let mut t = library::book::translation::Translation::create("spa", "eng");
+        let view = view_from_import(&mut t, 0, &pt);
+        let segments = paragraph_to_segments(
+            original,
+            &view,
+            &HashMap::new(),
+            &HashSet::new(),
+            Language::from_639_3("spa").unwrap(),
+            None,
+            AnnotationDensity::All,
+        );
+
+        assert_eq!(concat_segments(&segments), original);
+        let second_sentence: Vec<&str> = segments
+            .iter()
+            .filter_map(|s| match s {
+                ParagraphSegment::Word { text, sentence, .. } if *sentence == 1 => {
+                    Some(text.as_str())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(second_sentence, vec!["Ella", "lee", "libros"]);
+    }
+
     #[test]
     fn empty_contextual_translation_yields_none() {
         let original = "Just words";
@@ -883,7 +2181,10 @@ mod tests {
             original,
             &view,
             &HashMap::new(),
+            &HashSet::new(),
             Language::from_639_3("eng").unwrap(),
+            None,
+            AnnotationDensity::All,
         );
 
         assert_eq!(
@@ -915,7 +2216,10 @@ mod tests {
             original,
             &view,
             &HashMap::new(),
+            &HashSet::new(),
             Language::from_639_3("eng").unwrap(),
+            None,
+            AnnotationDensity::All,
         );
 
         // The &amp; entity is carried verbatim inside a gap segment between the two words.
@@ -947,7 +2251,10 @@ mod tests {
             original,
             &view,
             &HashMap::new(),
+            &HashSet::new(),
             Language::from_639_3("eng").unwrap(),
+            None,
+            AnnotationDensity::All,
         );
 
         assert_eq!(
@@ -960,6 +2267,125 @@ mod tests {
         );
     }
 
+    #[test]
+    fn full_width_latin_name_in_japanese_still_attaches() {
+        // No window matches the model's "Maria" against the full-width
+        // original, so it is found by its first and last letters; the words
+        // after it must still attach.
+        let original = "私はＭａｒｉａと東京へ行った。";
+        let pt = make_paragraph_translation(vec![translation_import::Sentence {
+            full_translation: "I went to Tokyo with Maria.".to_owned(),
+            words: vec![
+                word("私", &["I"], false),
+                word("は", &["(topic)"], false),
+                word("Maria", &["Maria"], false),
+                word("と", &["with"], false),
+                word("東京", &["Tokyo"], false),
+                word("へ", &["to"], false),
+                word("行った", &["went"], false),
+                word("。", &[], true),
+            ],
+        }]);
+
+        let mut t = library::book::translation::Translation::create("jpn", "eng");
+        let view = view_from_import(&mut t, 0, &pt);
+        let segments = paragraph_to_segments(
+            original,
+            &view,
+            &HashMap::new(),
+            &HashSet::new(),
+            Language::from_639_3("jpn").unwrap(),
+            None,
+            AnnotationDensity::All,
+        );
+
+        assert_eq!(
+            segments,
+            vec![
+                word_seg("私", 0, 0, 0, Some("I")),
+                word_seg("は", 0, 1, 1, Some("(topic)")),
+                word_seg("Ｍａｒｉａ", 0, 2, 2, Some("Maria")),
+                word_seg("と", 0, 3, 3, Some("with")),
+                word_seg("東京", 0, 4, 4, Some("Tokyo")),
+                word_seg("へ", 0, 5, 5, Some("to")),
+                word_seg("行った", 0, 6, 6, Some("went")),
+                gap_seg("。"),
+            ]
+        );
+    }
+
+    #[test]
+    fn merged_sentences_are_aligned_within_their_own_span() {
+        // The model merged the first two sentences into one, and its "Qué"
+        // isn't in the original: the failed search must stop at the end of
+        // the merged span instead of swallowing the last sentence.
+        let original = "Hola amigo. ¿Cómo estás? Bien, gracias.";
+        let pt = make_paragraph_translation(vec![
+            translation_import::Sentence {
+                full_translation: "Hi friend, how are you?".to_owned(),
+                words: vec![
+                    word("Hola", &["hi"], false),
+                    word("amigo", &["friend"], false),
+                    word("Qué", &["what"], false),
+                    word("¿", &[], true),
+                    word("Cómo", &["how"], false),
+                    word("estás", &["are you"], false),
+                    word("?", &[], true),
+                ],
+            },
+            translation_import::Sentence {
+                full_translation: "Fine, thanks.".to_owned(),
+                words: vec![
+                    word("Bien", &["fine"], false),
+                    word(",", &[], true),
+                    word("gracias", &["thanks"], false),
+                    word(".", &[], true),
+                ],
+            },
+        ]);
+        let mut t = library::book::translation::Translation::create("spa", "eng");
+        let view = view_from_import(&mut t, 0, &pt);
+        let segments = paragraph_to_segments(
+            original,
+            &view,
+            &HashMap::new(),
+            &HashSet::new(),
+            Language::from_639_3("spa").unwrap(),
+            None,
+            AnnotationDensity::All,
+        );
+
+        assert_eq!(
+            segments,
+            vec![
+                word_seg("Hola", 0, 0, 0, Some("hi")),
+                gap_seg(" "),
+                word_seg("amigo", 0, 1, 1, Some("friend")),
+                gap_seg(". ¿Cómo estás? "),
+                word_seg("Bien", 1, 0, 5, Some("fine")),
+                gap_seg(", "),
+                word_seg("gracias", 1, 2, 6, Some("thanks")),
+                gap_seg("."),
+            ]
+        );
+    }
+
+    #[test]
+    fn sentence_spans_cut_where_the_weights_put_the_breaks() {
+        // Three original sentences, two translated ones: the cut goes to the
+        // boundary closest to the first sentence's share of the words.
+        let original = "Uno dos. Tres. Cuatro cinco seis.";
+        assert_eq!(sentence_spans(original, &[6, 16]), vec![(0, 9), (9, 33)]);
+        assert_eq!(sentence_spans(original, &[12, 16]), vec![(0, 15), (15, 33)]);
+
+        // One original sentence split in two falls back to clause punctuation.
+        let original = "Llegó tarde, pero nadie lo notó.";
+        assert_eq!(sentence_spans(original, &[10, 14]), vec![(0, 13), (13, 32)]);
+
+        // Nowhere to cut: every sentence gets the whole paragraph.
+        assert_eq!(sentence_spans("Hola", &[2, 2]), vec![(0, 4), (0, 4)]);
+    }
+
     #[test]
     fn supports_multiple_sentences_with_distinct_sentence_indices() {
         let original = "Hello world. Bye world.";
@@ -989,7 +2415,10 @@ mod tests {
             original,
             &view,
             &HashMap::new(),
+            &HashSet::new(),
             Language::from_639_3("eng").unwrap(),
+            None,
+            AnnotationDensity::All,
         );
 
         assert_eq!(
@@ -1027,7 +2456,10 @@ mod tests {
             original,
             &view,
             &HashMap::new(),
+            &HashSet::new(),
             Language::from_639_3("eng").unwrap(),
+            None,
+            AnnotationDensity::All,
         );
 
         let texts: Vec<&str> = segments
@@ -1056,7 +2488,10 @@ mod tests {
             original,
             &view,
             &HashMap::new(),
+            &HashSet::new(),
             Language::from_639_3("eng").unwrap(),
+            None,
+            AnnotationDensity::All,
         );
 
         assert_eq!(segments, vec![gap_seg("...")]);
@@ -1080,8 +2515,15 @@ mod tests {
         let mut fam = HashMap::new();
         fam.insert("hola".to_string(), 0.5_f32);
 
-        let segments =
-            paragraph_to_segments(original, &view, &fam, Language::from_639_3("spa").unwrap());
+        let segments = paragraph_to_segments(
+            original,
+            &view,
+            &fam,
+            &HashSet::new(),
+            Language::from_639_3("spa").unwrap(),
+            None,
+            AnnotationDensity::All,
+        );
 
         let familiarities: Vec<Option<f32>> = segments
             .iter()
@@ -1093,4 +2535,229 @@ mod tests {
 
         assert_eq!(familiarities, vec![Some(0.5), None]);
     }
+
+    #[test]
+    fn visible_pos_hides_translations_for_other_parts_of_speech() {
+        let original = "the cat";
+
+        let mut article = word("the", &["-"], false);
+        article.grammar.part_of_speech = "determiner_article".to_owned();
+        // The filter's `noun` covers the translator's finer noun tags.
+        let mut noun = word("cat", &["кот"], false);
+        noun.grammar.part_of_speech = "common_noun".to_owned();
+        let pt = make_paragraph_translation(vec![translation_import::Sentence {
+            full_translation: "ignored".to_owned(),
+            words: vec![article, noun],
+        }]);
+
+        let mut t = library::book::translation::Translation::create("eng", "rus");
+        let view = view_from_import(&mut t, 0, &pt);
+        let visible_pos = HashSet::from(["noun".to_owned()]);
+        let segments = paragraph_to_segments(
+            original,
+            &view,
+            &HashMap::new(),
+            &HashSet::new(),
+            Language::from_639_3("eng").unwrap(),
+            Some(&visible_pos),
+            AnnotationDensity::All,
+        );
+
+        assert_eq!(
+            segments,
+            vec![
+                word_seg("the", 0, 0, 0, None),
+                gap_seg(" "),
+                word_seg("cat", 0, 1, 1, Some("кот")),
+            ]
+        );
+    }
+
+    #[test]
+    fn known_words_are_left_without_translation() {
+        let original = "the cat";
+
+        let pt = make_paragraph_translation(vec![translation_import::Sentence {
+            full_translation: "ignored".to_owned(),
+            words: vec![word("the", &["-"], false), word("cat", &["кот"], false)],
+        }]);
+
+        let mut t = library::book::translation::Translation::create("eng", "rus");
+        let view = view_from_import(&mut t, 0, &pt);
+        let known_words = HashSet::from(["the".to_owned()]);
+        let segments = paragraph_to_segments(
+            original,
+            &view,
+            &HashMap::new(),
+            &known_words,
+            Language::from_639_3("eng").unwrap(),
+            None,
+            AnnotationDensity::All,
+        );
+
+        assert_eq!(
+            segments,
+            vec![
+                word_seg("the", 0, 0, 0, None),
+                gap_seg(" "),
+                word_seg("cat", 0, 1, 1, Some("кот")),
+            ]
+        );
+    }
+
+    #[test]
+    fn annotation_density_selects_annotated_words() {
+        let original = "the old cat";
+
+        let mut article = word("the", &["-"], false);
+        article.grammar.part_of_speech = "determiner_article".to_owned();
+        let mut adjective = word("old", &["старый"], false);
+        adjective.grammar.part_of_speech = "adjective".to_owned();
+        let mut noun = word("cat", &["кот"], false);
+        noun.grammar.part_of_speech = "common_noun".to_owned();
+        let pt = make_paragraph_translation(vec![translation_import::Sentence {
+            full_translation: "ignored".to_owned(),
+            words: vec![article, adjective, noun],
+        }]);
+
+        let mut t = library::book::translation::Translation::create("eng", "rus");
+        let view = view_from_import(&mut t, 0, &pt);
+        let translations = |density| -> Vec<Option<String>> {
+            paragraph_to_segments(
+                original,
+                &view,
+                &HashMap::new(),
+                &HashSet::new(),
+                Language::from_639_3("eng").unwrap(),
+                None,
+                density,
+            )
+            .into_iter()
+            .filter_map(|s| match s {
+                ParagraphSegment::Word { translation, .. } => Some(translation),
+                ParagraphSegment::Gap { .. } => None,
+            })
+            .collect()
+        };
+
+        let some = |s: &str| Some(s.to_owned());
+        assert_eq!(translations(AnnotationDensity::None), [None, None, None]);
+        assert_eq!(
+            translations(AnnotationDensity::ContentWords),
+            [None, some("старый"), some("кот")]
+        );
+        assert_eq!(
+            translations(AnnotationDensity::All),
+            [some("-"), some("старый"), some("кот")]
+        );
+    }
+
+    #[tokio::test]
+    async fn empty_book_has_finite_translation_ratio() {
+        let root = std::env::temp_dir().join(format!("flts_empty_ratio_{}", uuid::Uuid::new_v4()));
+        let library = library::library::Library::open(root.clone()).await.unwrap();
+        let eng = Language::from_639_3("eng").unwrap();
+        let rus = Language::from_639_3("rus").unwrap();
+
+        let book_id = library
+            .create_book_plain("Empty", "", &eng)
+            .await
+            .unwrap()
+            .id;
+        {
+            let book = library.get_book(&book_id).await.unwrap();
+            let mut book = book.lock().await;
+            book.get_or_create_translation(&rus).await;
+            book.save().await.unwrap();
+        }
+
+        let books = library.list_books().await.unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(books.len(), 1);
+        assert_eq!(books[0].paragraphs_count, 0);
+        assert_eq!(books[0].translations_metadata.len(), 1);
+        let view = metadata_view(books.into_iter().next().unwrap(), Some(&rus));
+        assert!(view.translation_ratio.is_finite());
+        assert_eq!(view.translation_ratio, 0.0);
+    }
+
+    #[tokio::test]
+    async fn translation_ratios_cover_every_target_language() {
+        let root = std::env::temp_dir().join(format!("flts_ratios_{}", uuid::Uuid::new_v4()));
+        let library = library::library::Library::open(root.clone()).await.unwrap();
+        let spa = Language::from_639_3("spa").unwrap();
+        let rus = Language::from_639_3("rus").unwrap();
+        let deu = Language::from_639_3("deu").unwrap();
+
+        let book_id = library
+            .create_book_plain("Ratios", "Hola.\nAdiós.", &spa)
+            .await
+            .unwrap()
+            .id;
+        {
+            let book = library.get_book(&book_id).await.unwrap();
+            let mut book = book.lock().await;
+            book.get_or_create_translation(&rus)
+                .await
+                .lock()
+                .await
+                .add_paragraph_translation(
+                    0,
+                    &translation_import::ParagraphTranslation {
+                        timestamp: 1,
+                        sentences: vec![],
+                        total_tokens: None,
+                        token_usage: None,
+                    },
+                    TranslationModel::Gemini25Flash,
+                );
+            book.get_or_create_translation(&deu).await;
+            book.save().await.unwrap();
+        }
+
+        let books = library.list_books().await.unwrap();
+        let _ = std::fs::remove_dir_all(&root);
+
+        let view = metadata_view(books.into_iter().next().unwrap(), Some(&deu));
+        assert_eq!(view.translation_ratio, 0.0);
+        assert_eq!(
+            view.translation_ratios,
+            HashMap::from([("rus".to_owned(), 0.5), ("deu".to_owned(), 0.0)])
+        );
+    }
+
+    #[test]
+    fn system_definition_fallback_fills_note() {
+        let view = WordView::from_system_definition(
+            "Haus".to_owned(),
+            "deu".to_owned(),
+            SystemDefinition {
+                definition: "house; building".to_owned(),
+                transcription: Some("haʊs".to_owned()),
+            },
+        );
+        let json = serde_json::to_value(&view).unwrap();
+        assert_eq!(json["original"], "Haus");
+        assert_eq!(json["note"], "house; building");
+        assert_eq!(json["grammar"]["originalInitialForm"], "Haus");
+        assert_eq!(json["systemDefinition"]["transcription"], "haʊs");
+    }
+
+    #[test]
+    fn adjacent_chapters_are_bounded_by_the_book() {
+        let first = AdjacentChaptersView::new(0, 3);
+        assert_eq!((first.prev_id, first.next_id), (None, Some(1)));
+        assert!(!first.has_prev && first.has_next);
+
+        let middle = AdjacentChaptersView::new(1, 3);
+        assert_eq!((middle.prev_id, middle.next_id), (Some(0), Some(2)));
+
+        let last = AdjacentChaptersView::new(2, 3);
+        assert_eq!((last.prev_id, last.next_id), (Some(1), None));
+        assert!(last.has_prev && !last.has_next);
+
+        let only = AdjacentChaptersView::new(0, 1);
+        assert!(!only.has_prev && !only.has_next);
+    }
 }
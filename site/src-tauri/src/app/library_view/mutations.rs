@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use isolang::Language;
 use uuid::Uuid;
 
 use crate::app::AppState;
@@ -26,6 +27,123 @@ pub async fn save_book_reading_state(
         .map_err(|err| err.to_string())
 }
 
+#[tauri::command]
+pub async fn record_word_lookup(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+    paragraph_id: usize,
+    sentence_id: usize,
+    word_id: usize,
+) -> Result<(), String> {
+    let library = state
+        .library
+        .borrow()
+        .clone()
+        .ok_or("Library is not configured")?;
+
+    LibraryView::create(state.inner().clone(), library)
+        .record_word_lookup(book_id, paragraph_id, sentence_id, word_id)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn mark_word_visible(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+    paragraph_id: usize,
+    word_index: usize,
+) -> Result<(), String> {
+    let library = state
+        .library
+        .borrow()
+        .clone()
+        .ok_or("Library is not configured")?;
+    let target_language_id = { state.config.borrow().target_language_id.clone() };
+    let target_language =
+        Language::from_639_3(&target_language_id).ok_or("Target language is not configured")?;
+
+    LibraryView::create(state.inner().clone(), library)
+        .mark_word_visible(book_id, paragraph_id, word_index, &target_language)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn add_known_word(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+    lemma: String,
+) -> Result<bool, String> {
+    let library = state
+        .library
+        .borrow()
+        .clone()
+        .ok_or("Library is not configured")?;
+
+    LibraryView::create(state.inner().clone(), library)
+        .add_known_word(book_id, &lemma)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn remove_known_word(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+    lemma: String,
+) -> Result<bool, String> {
+    let library = state
+        .library
+        .borrow()
+        .clone()
+        .ok_or("Library is not configured")?;
+
+    LibraryView::create(state.inner().clone(), library)
+        .remove_known_word(book_id, &lemma)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn reveal_paragraph(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+    paragraph_id: usize,
+) -> Result<(), String> {
+    set_paragraph_visible(state, book_id, paragraph_id, true).await
+}
+
+#[tauri::command]
+pub async fn hide_paragraph(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+    paragraph_id: usize,
+) -> Result<(), String> {
+    set_paragraph_visible(state, book_id, paragraph_id, false).await
+}
+
+async fn set_paragraph_visible(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+    paragraph_id: usize,
+    visible: bool,
+) -> Result<(), String> {
+    let library = state
+        .library
+        .borrow()
+        .clone()
+        .ok_or("Library is not configured")?;
+    let target_language_id = { state.config.borrow().target_language_id.clone() };
+    let target_language =
+        Language::from_639_3(&target_language_id).ok_or("Target language is not configured")?;
+
+    LibraryView::create(state.inner().clone(), library)
+        .set_paragraph_visible(book_id, paragraph_id, visible, &target_language)
+        .await
+        .map_err(|err| err.to_string())
+}
+
 #[tauri::command]
 pub async fn move_book(
     state: tauri::State<'_, Arc<AppState>>,
@@ -44,6 +162,63 @@ pub async fn move_book(
         .map_err(|err| err.to_string())
 }
 
+/// Fixes a paragraph's original text. Returns `true` if translations of the
+/// paragraph were made against the old text and may need redoing.
+#[tauri::command]
+pub async fn update_paragraph_original(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+    paragraph_id: usize,
+    text: String,
+    html: Option<String>,
+) -> Result<bool, String> {
+    let library = state
+        .library
+        .borrow()
+        .clone()
+        .ok_or("Library is not configured")?;
+
+    LibraryView::create(state.inner().clone(), library)
+        .update_paragraph_original(book_id, paragraph_id, &text, html.as_deref())
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn repair_book(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+    remove: bool,
+) -> Result<(), String> {
+    let library = state
+        .library
+        .borrow()
+        .clone()
+        .ok_or("Library is not configured")?;
+
+    LibraryView::create(state.inner().clone(), library)
+        .repair_book(book_id, remove)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn merge_conflicts(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+) -> Result<usize, String> {
+    let library = state
+        .library
+        .borrow()
+        .clone()
+        .ok_or("Library is not configured")?;
+
+    LibraryView::create(state.inner().clone(), library)
+        .merge_conflicts(book_id)
+        .await
+        .map_err(|err| err.to_string())
+}
+
 #[tauri::command]
 pub async fn delete_book(
     state: tauri::State<'_, Arc<AppState>>,
@@ -1,13 +1,16 @@
 use std::sync::Arc;
 
 use isolang::Language;
+use library::library::library_book::StudySet;
+use library::library::library_dictionary::{DictionaryEntry, RecentDictionaryEntry};
 use uuid::Uuid;
 
 use crate::app::AppState;
 
 use super::{
-    BookReadingStateView, BookSummaryStatusView, ChapterView, LibraryBookMetadataView,
-    LibraryView, ParagraphOriginal, ParagraphTranslationSlice, ParagraphView, WordView,
+    AdjacentChaptersView, BookReadingStateView, BookSummaryStatusView, ChapterView, LemmaMatchView,
+    LibraryBookMetadataView, LibraryView, ParagraphOriginal, ParagraphTranslationSlice,
+    ParagraphView, TranslationTotalsView, VisibleWordView, WordLookupView, WordView,
 };
 
 #[tauri::command]
@@ -28,6 +31,68 @@ pub async fn list_books(
         .map_err(|err| err.to_string())
 }
 
+/// Number of books in the library, without loading any of them.
+#[tauri::command]
+pub async fn get_books_count(state: tauri::State<'_, Arc<AppState>>) -> Result<usize, String> {
+    let library = state.library.borrow().clone();
+    let Some(library) = library else {
+        return Ok(0);
+    };
+
+    library.books_count().await.map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn list_books_in_folder(
+    state: tauri::State<'_, Arc<AppState>>,
+    path: Vec<String>,
+) -> Result<Vec<LibraryBookMetadataView>, String> {
+    let target_language_id = { state.config.borrow().target_language_id.clone() };
+    let target_language = Language::from_639_3(&target_language_id);
+    let library = state.library.borrow().clone();
+
+    let Some(library) = library else {
+        return Ok(vec![]);
+    };
+
+    LibraryView::create(state.inner().clone(), library)
+        .list_books_in_folder(&path, target_language.as_ref())
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Folder to reveal in the file manager; `None` without a library or for a
+/// book that has no directory (yet).
+#[tauri::command]
+pub async fn get_book_directory_path(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+) -> Result<Option<String>, String> {
+    let library = state.library.borrow().clone();
+
+    let Some(library) = library else {
+        return Ok(None);
+    };
+
+    Ok(LibraryView::create(state.inner().clone(), library).get_book_directory_path(book_id))
+}
+
+#[tauri::command]
+pub async fn get_book_conflicts(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+) -> Result<Vec<String>, String> {
+    let library = state.library.borrow().clone();
+    let Some(library) = library else {
+        return Ok(vec![]);
+    };
+
+    LibraryView::create(state.inner().clone(), library)
+        .get_book_conflicts(book_id)
+        .await
+        .map_err(|err| err.to_string())
+}
+
 #[tauri::command]
 pub async fn list_book_chapters(
     state: tauri::State<'_, Arc<AppState>>,
@@ -47,6 +112,64 @@ pub async fn list_book_chapters(
         .map_err(|err| err.to_string())
 }
 
+#[tauri::command]
+pub async fn get_adjacent_chapters(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+    chapter_id: usize,
+) -> Result<AdjacentChaptersView, String> {
+    let library = state
+        .library
+        .borrow()
+        .clone()
+        .ok_or("Library is not configured")?;
+
+    LibraryView::create(state.inner().clone(), library)
+        .get_adjacent_chapters(book_id, chapter_id)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Plain original text of a paragraph, for "copy original".
+#[tauri::command]
+pub async fn get_paragraph_original_text(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+    chapter_id: usize,
+    paragraph_id: usize,
+) -> Result<String, String> {
+    let library = state
+        .library
+        .borrow()
+        .clone()
+        .ok_or("Library is not configured")?;
+
+    LibraryView::create(state.inner().clone(), library)
+        .get_paragraph_original_text(book_id, chapter_id, paragraph_id)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Chapter index for a deep link naming the chapter by title; with duplicate
+/// titles the first chapter wins.
+#[tauri::command]
+pub async fn find_chapter(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+    title: String,
+) -> Result<Option<usize>, String> {
+    let library = state
+        .library
+        .borrow()
+        .clone()
+        .ok_or("Library is not configured")?;
+
+    LibraryView::create(state.inner().clone(), library)
+        .find_chapter(book_id, &title)
+        .await
+        .map_err(|err| err.to_string())
+}
+
 #[tauri::command]
 pub async fn get_book_chapter_paragraph_ids(
     state: tauri::State<'_, Arc<AppState>>,
@@ -64,6 +187,215 @@ pub async fn get_book_chapter_paragraph_ids(
         .map_err(|err| err.to_string())
 }
 
+/// Whole-chapter paragraphs; `annotated: false` is the plain reading mode.
+#[tauri::command]
+pub async fn get_book_chapter_paragraphs(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+    chapter_id: usize,
+    annotated: bool,
+) -> Result<Vec<ParagraphView>, String> {
+    let library = state.library.borrow().clone();
+    let Some(library) = library else {
+        return Ok(vec![]);
+    };
+
+    let target_language_id = { state.config.borrow().target_language_id.clone() };
+    let Some(target_language) = Language::from_639_3(&target_language_id) else {
+        return Err("Library is not configured".into());
+    };
+
+    LibraryView::create(state.inner().clone(), library)
+        .get_book_chapter_paragraphs(book_id, chapter_id, annotated, &target_language)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Sentence and word totals of the book's translation into the configured
+/// target language.
+#[tauri::command]
+pub async fn get_translation_totals(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+) -> Result<TranslationTotalsView, String> {
+    let library = state
+        .library
+        .borrow()
+        .clone()
+        .ok_or("Library is not configured")?;
+    let target_language_id = { state.config.borrow().target_language_id.clone() };
+    let target_language =
+        Language::from_639_3(&target_language_id).ok_or("Target language is not configured")?;
+
+    LibraryView::create(state.inner().clone(), library)
+        .get_translation_totals(book_id, &target_language)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Dictionary-level translations for a lemma, drawn from every book in the
+/// library rather than the current paragraph.
+#[tauri::command]
+pub async fn dictionary_suggestions(
+    state: tauri::State<'_, Arc<AppState>>,
+    word: String,
+    source_language: String,
+    target_language: String,
+) -> Result<Vec<DictionaryEntry>, String> {
+    let library = state.library.borrow().clone();
+    let Some(library) = library else {
+        return Ok(vec![]);
+    };
+    let (Some(source_language), Some(target_language)) = (
+        Language::from_639_3(&source_language),
+        Language::from_639_3(&target_language),
+    ) else {
+        return Ok(vec![]);
+    };
+
+    library
+        .dictionary(source_language, target_language)
+        .lookup(&word)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// The `n` dictionary pairs a translation produced most recently.
+#[tauri::command]
+pub async fn recent_dictionary_entries(
+    state: tauri::State<'_, Arc<AppState>>,
+    source_language: String,
+    target_language: String,
+    n: usize,
+) -> Result<Vec<RecentDictionaryEntry>, String> {
+    let library = state.library.borrow().clone();
+    let Some(library) = library else {
+        return Ok(vec![]);
+    };
+    let (Some(source_language), Some(target_language)) = (
+        Language::from_639_3(&source_language),
+        Language::from_639_3(&target_language),
+    ) else {
+        return Ok(vec![]);
+    };
+
+    library
+        .dictionary(source_language, target_language)
+        .recent_entries(n)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Every revealed word of the book with its first reveal time, in paragraph
+/// order.
+#[tauri::command]
+pub async fn export_visible_words(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+) -> Result<Vec<VisibleWordView>, String> {
+    let library = state.library.borrow().clone();
+    let Some(library) = library else {
+        return Ok(vec![]);
+    };
+    let target_language_id = { state.config.borrow().target_language_id.clone() };
+    let Some(target_language) = Language::from_639_3(&target_language_id) else {
+        return Ok(vec![]);
+    };
+
+    LibraryView::create(state.inner().clone(), library)
+        .export_visible_words(book_id, &target_language)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Revealed words of the book with their grammar, translation and sentence,
+/// for sharing study progress without the whole translation.
+#[tauri::command]
+pub async fn export_studied_words(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+) -> Result<StudySet, String> {
+    let library = state
+        .library
+        .borrow()
+        .clone()
+        .ok_or("Library is not configured")?;
+    let target_language_id = { state.config.borrow().target_language_id.clone() };
+    let target_language = Language::from_639_3(&target_language_id)
+        .ok_or_else(|| format!("Failed to resolve target language: {target_language_id}"))?;
+
+    LibraryView::create(state.inner().clone(), library)
+        .export_studied_words(book_id, &target_language)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn export_book_html(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+    chapter_id: Option<usize>,
+    target_language_id: String,
+    annotated: bool,
+) -> Result<String, String> {
+    let library = state
+        .library
+        .borrow()
+        .clone()
+        .ok_or("Library is not configured")?;
+    let target_language = Language::from_639_3(&target_language_id)
+        .ok_or_else(|| format!("Failed to resolve target language: {target_language_id}"))?;
+
+    LibraryView::create(state.inner().clone(), library)
+        .export_book_html(book_id, chapter_id, &target_language, annotated)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn search_translation(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+    lemma: String,
+    target_language_id: String,
+) -> Result<Vec<LemmaMatchView>, String> {
+    let library = state
+        .library
+        .borrow()
+        .clone()
+        .ok_or("Library is not configured")?;
+    let target_language = Language::from_639_3(&target_language_id)
+        .ok_or_else(|| format!("Failed to resolve target language: {target_language_id}"))?;
+
+    LibraryView::create(state.inner().clone(), library)
+        .search_translation(book_id, &lemma, &target_language)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Translated sentences of the book using `lemma`, in reading order.
+#[tauri::command]
+pub async fn get_word_examples(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+    lemma: String,
+) -> Result<Vec<String>, String> {
+    let library = state.library.borrow().clone();
+    let Some(library) = library else {
+        return Ok(vec![]);
+    };
+
+    let target_language_id = { state.config.borrow().target_language_id.clone() };
+    let Some(target_language) = Language::from_639_3(&target_language_id) else {
+        return Ok(vec![]);
+    };
+
+    LibraryView::create(state.inner().clone(), library)
+        .get_word_examples(book_id, &lemma, &target_language)
+        .await
+        .map_err(|err| err.to_string())
+}
+
 #[tauri::command]
 pub async fn get_word_info(
     state: tauri::State<'_, Arc<AppState>>,
@@ -71,6 +403,7 @@ pub async fn get_word_info(
     paragraph_id: usize,
     sentence_id: usize,
     word_id: usize,
+    system_fallback: Option<bool>,
 ) -> Result<Option<WordView>, String> {
     let library = state.library.borrow().clone();
     let Some(library) = library else {
@@ -83,7 +416,7 @@ pub async fn get_word_info(
     };
 
     let mut library_view = LibraryView::create(state.inner().clone(), library);
-    library_view
+    let word = library_view
         .get_word_info(
             book_id,
             paragraph_id,
@@ -92,7 +425,27 @@ pub async fn get_word_info(
             &target_language,
         )
         .await
-        .map_err(|err| err.to_string())
+        .map_err(|err| err.to_string())?;
+    if word.is_some() || !system_fallback.unwrap_or(false) {
+        return Ok(word);
+    }
+
+    // Not annotated yet: look the original word up in the platform
+    // dictionary instead.
+    let Some((original, source_language)) = library_view
+        .get_paragraph_word(book_id, paragraph_id, word_id)
+        .await
+        .map_err(|err| err.to_string())?
+    else {
+        return Ok(None);
+    };
+    let definition = crate::app::lookup_system_definition(
+        &state.app,
+        original.clone(),
+        source_language.clone(),
+        target_language_id,
+    )?;
+    Ok(definition.map(|d| WordView::from_system_definition(original, source_language, d)))
 }
 
 #[tauri::command]
@@ -136,6 +489,42 @@ pub async fn get_paragraph_originals_batch(
         .map_err(|err| err.to_string())
 }
 
+#[tauri::command]
+pub async fn has_unsaved_changes(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+) -> Result<bool, String> {
+    let library = state
+        .library
+        .borrow()
+        .clone()
+        .ok_or("Library is not configured")?;
+
+    LibraryView::create(state.inner().clone(), library)
+        .has_unsaved_changes(book_id)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+pub async fn get_paragraph_word_boundaries(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+    paragraph_id: usize,
+) -> Result<Vec<(usize, usize)>, String> {
+    let library = state
+        .library
+        .borrow()
+        .clone()
+        .ok_or("Library is not configured")?;
+
+    let library_view = LibraryView::create(state.inner().clone(), library);
+    library_view
+        .get_paragraph_word_boundaries(book_id, paragraph_id)
+        .await
+        .map_err(|err| err.to_string())
+}
+
 #[tauri::command]
 pub async fn get_paragraph_translations_batch(
     state: tauri::State<'_, Arc<AppState>>,
@@ -201,3 +590,20 @@ pub async fn get_book_reading_state(
         .await
         .map_err(|err| err.to_string())
 }
+
+#[tauri::command]
+pub async fn get_lookup_history(
+    state: tauri::State<'_, Arc<AppState>>,
+    book_id: Uuid,
+) -> Result<Vec<WordLookupView>, String> {
+    let library = state
+        .library
+        .borrow()
+        .clone()
+        .ok_or("Library is not configured")?;
+
+    LibraryView::create(state.inner().clone(), library)
+        .get_lookup_history(book_id)
+        .await
+        .map_err(|err| err.to_string())
+}
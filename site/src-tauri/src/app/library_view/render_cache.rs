@@ -0,0 +1,150 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use uuid::Uuid;
+
+use super::ParagraphView;
+use crate::app::config::AnnotationDensity;
+
+/// Chapters kept rendered; enough for flipping back and forth between a few
+/// chapters of a couple of books.
+const CAPACITY: usize = 16;
+
+/// Everything the annotated paragraphs of a chapter are rendered from. The
+/// translation's mtime stands in for its content, so entries are only made
+/// for translations with no unsaved changes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChapterRenderKey {
+    pub book_id: Uuid,
+    pub chapter_id: usize,
+    pub target_language: String,
+    pub translation_last_modified: SystemTime,
+    /// Sorted, so equal sets compare equal.
+    pub visible_pos: Option<Vec<String>>,
+    pub density: AnnotationDensity,
+    /// Hash of the familiarity map the segments were colored with.
+    pub familiarity: u64,
+    /// Hash of the known words left without a translation.
+    pub known_words: u64,
+}
+
+/// Small LRU of rendered chapters shared by all `LibraryView`s.
+#[derive(Default)]
+pub struct ChapterRenderCache {
+    entries: Mutex<VecDeque<(ChapterRenderKey, Arc<Vec<ParagraphView>>)>>,
+}
+
+impl ChapterRenderCache {
+    /// The cached paragraphs for `key`, rendering and caching them on a miss.
+    /// The lock isn't held while rendering, so two concurrent misses may both
+    /// render; the later one wins.
+    pub async fn get_or_render<F>(
+        &self,
+        key: ChapterRenderKey,
+        render: impl FnOnce() -> F,
+    ) -> anyhow::Result<Arc<Vec<ParagraphView>>>
+    where
+        F: Future<Output = anyhow::Result<Vec<ParagraphView>>>,
+    {
+        {
+            let mut entries = self.entries.lock().unwrap();
+            if let Some(pos) = entries.iter().position(|(k, _)| *k == key) {
+                let entry = entries.remove(pos).unwrap();
+                let paragraphs = entry.1.clone();
+                entries.push_back(entry);
+                return Ok(paragraphs);
+            }
+        }
+
+        let paragraphs = Arc::new(render().await?);
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|(k, _)| *k != key);
+        if entries.len() >= CAPACITY {
+            entries.pop_front();
+        }
+        entries.push_back((key, paragraphs.clone()));
+        Ok(paragraphs)
+    }
+
+    /// Drops every chapter of `book_id`, e.g. after a word was revealed.
+    pub fn invalidate_book(&self, book_id: Uuid) {
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|(key, _)| key.book_id != book_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::*;
+
+    fn key(book_id: Uuid, mtime_secs: u64) -> ChapterRenderKey {
+        ChapterRenderKey {
+            book_id,
+            chapter_id: 0,
+            target_language: "eng".to_owned(),
+            translation_last_modified: UNIX_EPOCH + Duration::from_secs(mtime_secs),
+            visible_pos: None,
+            density: AnnotationDensity::All,
+            familiarity: 0,
+            known_words: 0,
+        }
+    }
+
+    async fn render(calls: &AtomicUsize) -> anyhow::Result<Vec<ParagraphView>> {
+        calls.fetch_add(1, Ordering::SeqCst);
+        Ok(vec![ParagraphView {
+            id: 0,
+            original: "<p>Hello</p>".to_owned(),
+            segments: None,
+        }])
+    }
+
+    #[tokio::test]
+    async fn cache_hit_skips_rendering() {
+        let cache = ChapterRenderCache::default();
+        let calls = AtomicUsize::new(0);
+        let book_id = Uuid::new_v4();
+
+        let first = cache
+            .get_or_render(key(book_id, 1), || render(&calls))
+            .await
+            .unwrap();
+        let second = cache
+            .get_or_render(key(book_id, 1), || render(&calls))
+            .await
+            .unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(Arc::ptr_eq(&first, &second));
+    }
+
+    #[tokio::test]
+    async fn newer_translation_or_invalidation_renders_again() {
+        let cache = ChapterRenderCache::default();
+        let calls = AtomicUsize::new(0);
+        let book_id = Uuid::new_v4();
+
+        cache
+            .get_or_render(key(book_id, 1), || render(&calls))
+            .await
+            .unwrap();
+        cache
+            .get_or_render(key(book_id, 2), || render(&calls))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        cache.invalidate_book(book_id);
+        cache
+            .get_or_render(key(book_id, 2), || render(&calls))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+}
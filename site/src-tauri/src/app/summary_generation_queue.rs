@@ -158,14 +158,19 @@ async fn load_or_init(
     }
     // Need to materialize. We do the I/O without holding the map lock so
     // a slow load doesn't block other books.
-    let (book_path, chapter_count) = {
+    let (book_path, chapter_count, unmerged) = {
         let book = library.get_book(&book_id).await?;
         let book = book.lock().await;
-        (book.path().to_path_buf(), book.book.chapter_count())
+        (
+            book.path().to_path_buf(),
+            book.book.chapter_count(),
+            book.conflicting_paths().to_vec(),
+        )
     };
     let path = chapter_summaries_path(&book_path);
     let loaded = if tokio::fs::try_exists(&path).await? {
-        // Discover any conflict files left by an interrupted save.
+        // Discover any conflict files left by an interrupted save. Ones the
+        // book was loaded without wait for `merge_conflicts` like the rest.
         let mut conflicts = Vec::new();
         let mut dir = tokio::fs::read_dir(&book_path).await?;
         while let Some(entry) = dir.next_entry().await? {
@@ -173,6 +178,7 @@ async fn load_or_init(
             if let Some(name) = p.file_name().and_then(|n| n.to_str())
                 && name.starts_with("chapter_summaries~")
                 && name.ends_with(".dat")
+                && !unmerged.contains(&p)
             {
                 conflicts.push(p);
             }
@@ -9,22 +9,29 @@ use std::{
 
 use isolang::Language;
 use library::{
+    book::translation_import::ParagraphTranslation,
     cache::TranslationsCache,
     library::Library,
     translation_stats::TranslationSizeCache,
     translator::{
-        ChapterContextProvider, TranslationContext, TranslationModel,
-        gemini_cache::GeminiPromptCache, get_translator, is_transient_translation_error,
+        ChapterContextProvider, TranslationContext, TranslationModel, Translator, TranslatorConfig,
+        TranslatorServices, book_source_language, gemini_cache::GeminiPromptCache, get_translator,
+        is_transient_translation_error,
     },
 };
 use log::{info, warn};
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
-use tokio::sync::{Mutex, Semaphore, watch};
+use tokio::sync::{Mutex, Semaphore, oneshot, watch};
 use tokio::task::JoinSet;
 use uuid::Uuid;
 
 use crate::app::config::Config;
-use tauri::Emitter;
+use tauri::{AppHandle, Emitter, Runtime, Wry};
+
+/// Builds the translator for a source → target language pair in place of the
+/// configured provider, e.g. a `FixtureTranslator` in tests.
+pub type TranslatorFactory =
+    Arc<dyn Fn(Language, Language) -> anyhow::Result<Box<dyn Translator>> + Send + Sync>;
 
 const TRANSLATION_PROGRESS_UPDATE_INTERVAL: Duration = Duration::from_millis(500);
 
@@ -40,11 +47,34 @@ struct TranslationRequest {
     paragraph_id: usize,
     model: TranslationModel,
     use_cache: bool,
+    /// Forward the raw response to the UI as `translation_token` events.
+    stream_tokens: bool,
     /// 0 on first enqueue; incremented each time the worker requeues this
     /// paragraph after a transient failure.
     attempt: u32,
 }
 
+/// The translator settings of `config`, shared by the queue and one-off
+/// translations outside of it.
+pub fn translator_config<R: Runtime>(config: &Config, app: &AppHandle<R>) -> TranslatorConfig {
+    TranslatorConfig {
+        max_contextual_translations: config.max_contextual_translations.max(1) as usize,
+        temperature: config.temperature,
+        thinking_budget: config.thinking_budget,
+        request_log: if config.log_requests {
+            match super::resolve_cache_dir(Some(app)) {
+                Ok(dir) => Some(dir.join("translator_requests.log")),
+                Err(err) => {
+                    warn!("Request logging disabled, no cache dir: {err}");
+                    None
+                }
+            }
+        } else {
+            None
+        },
+    }
+}
+
 /// Decide whether a failed request should be restarted (re-enqueued) rather
 /// than surfaced as a terminal error. Pure so it can be unit-tested without a
 /// running queue.
@@ -97,6 +127,7 @@ async fn handle_translation_failure(
             paragraph_id: request.paragraph_id,
             model: request.model,
             use_cache: request.use_cache,
+            stream_tokens: request.stream_tokens,
             attempt: next_attempt,
         });
         if requeued.is_ok() {
@@ -114,11 +145,11 @@ async fn handle_translation_failure(
         "Failed to translate {}/{}: {}",
         request.book_id, request.paragraph_id, err
     );
-    state
-        .lock()
-        .await
-        .active_translations
-        .remove(&(request.book_id, request.paragraph_id));
+    state.lock().await.finish(
+        (request.book_id, request.paragraph_id),
+        request.request_id,
+        Err(err.to_string()),
+    );
     FailureDisposition::Terminal
 }
 
@@ -130,11 +161,11 @@ struct SaveNotify {
 }
 
 #[derive(Clone, serde::Serialize)]
-struct ParagraphUpdatedEvent {
+pub(crate) struct ParagraphUpdatedEvent {
     #[serde(rename = "bookId")]
-    book_id: Uuid,
+    pub(crate) book_id: Uuid,
     #[serde(rename = "paragraphId")]
-    paragraph_id: usize,
+    pub(crate) paragraph_id: usize,
 }
 
 #[derive(Clone, serde::Serialize)]
@@ -163,6 +194,17 @@ struct ParagraphTranslationProgressEvent {
     expected_chars: usize,
 }
 
+#[derive(Clone, serde::Serialize)]
+struct TranslationTokenEvent<'a> {
+    #[serde(rename = "bookId")]
+    book_id: Uuid,
+    #[serde(rename = "paragraphId")]
+    paragraph_id: usize,
+    #[serde(rename = "requestId")]
+    request_id: usize,
+    chunk: &'a str,
+}
+
 #[derive(Clone, serde::Serialize)]
 struct ParagraphTranslationFinishedEvent {
     #[serde(rename = "bookId")]
@@ -186,6 +228,20 @@ pub struct ParagraphTranslationActivity {
 
 struct TranslationQueueState {
     active_translations: HashMap<(Uuid, usize), ParagraphTranslationActivity>,
+    /// Callers of `translate_and_wait` by request id, told the outcome once
+    /// the request leaves `active_translations`. Dropped senders mean the
+    /// queue shut down first.
+    waiters: HashMap<usize, Vec<oneshot::Sender<Result<(), String>>>>,
+}
+
+impl TranslationQueueState {
+    /// Ends the request for `key` and tells its waiters `outcome`.
+    fn finish(&mut self, key: (Uuid, usize), request_id: usize, outcome: Result<(), String>) {
+        self.active_translations.remove(&key);
+        for waiter in self.waiters.remove(&request_id).unwrap_or_default() {
+            let _ = waiter.send(outcome.clone());
+        }
+    }
 }
 
 struct TranslationQueueTasks {
@@ -205,17 +261,17 @@ impl TranslationQueueTasks {
     }
 }
 
-pub struct TranslationQueue {
+pub struct TranslationQueue<R: Runtime = Wry> {
     next_request_index: AtomicUsize,
     translate_tx: UnboundedSender<TranslationRequest>,
 
     state: Arc<Mutex<TranslationQueueState>>,
-    app: tauri::AppHandle,
+    app: AppHandle<R>,
 
     tasks: Mutex<Option<TranslationQueueTasks>>,
 }
 
-impl Drop for TranslationQueue {
+impl<R: Runtime> Drop for TranslationQueue<R> {
     fn drop(&mut self) {
         if let Ok(mut tasks) = self.tasks.try_lock()
             && let Some(tasks) = tasks.take()
@@ -226,7 +282,10 @@ impl Drop for TranslationQueue {
     }
 }
 
-impl TranslationQueue {
+impl<R: Runtime> TranslationQueue<R> {
+    /// With `translator_factory` set, every request is translated by the
+    /// translator it builds, regardless of the requested model and without
+    /// needing an API key.
     pub fn init(
         library: Arc<Library>,
         cache: Arc<TranslationsCache>,
@@ -234,18 +293,21 @@ impl TranslationQueue {
         gemini_prompt_cache: Arc<GeminiPromptCache>,
         context_provider: Arc<dyn ChapterContextProvider>,
         config: &Config,
-        app: tauri::AppHandle,
+        translator_factory: Option<TranslatorFactory>,
+        app: AppHandle<R>,
         library_tx: Arc<watch::Sender<Option<Arc<Library>>>>,
     ) -> Option<Arc<Self>> {
         let api_keys = config.api_keys();
         let target_language = Language::from_639_3(&config.target_language_id)?;
         // Clamp so a stray 0 can never deadlock the semaphore.
         let concurrency = config.translation_concurrency.max(1) as usize;
+        let translator_config = translator_config(config, &app);
 
         let (tx_save, rx_save) = unbounded_channel::<SaveNotify>();
 
         let state = Arc::new(Mutex::new(TranslationQueueState {
             active_translations: HashMap::new(),
+            waiters: HashMap::new(),
         }));
 
         let saver_task = tokio::spawn(run_saver(
@@ -308,30 +370,46 @@ impl TranslationQueue {
                     let state = state.clone();
                     let tx_save = tx_save.clone();
                     let tx_retry = tx_retry.clone();
+                    let translator_config = translator_config.clone();
+                    let translator_factory = translator_factory.clone();
 
                     join_set.spawn(async move {
                         let _permit = permit;
                         let outcome = async {
-                            let provider = request
-                                .model
-                                .provider()
-                                .ok_or_else(|| anyhow::anyhow!("Unknown model provider"))?;
-                            let api_key = api_keys
-                                .for_provider(provider)
-                                .ok_or_else(|| anyhow::anyhow!("no api key for provider {provider:?}"))?
-                                .to_owned();
-                            let model = request.model;
-                            let make_translator = move |source_language: Language| {
-                                get_translator(
-                                    cache,
-                                    context_provider,
-                                    gemini_prompt_cache,
-                                    provider,
-                                    model,
-                                    api_key,
-                                    source_language,
-                                    target_language,
-                                )
+                            let make_translator: Box<
+                                dyn FnOnce(Language) -> anyhow::Result<Box<dyn Translator>> + Send,
+                            > = match translator_factory {
+                                Some(factory) => Box::new(move |source_language: Language| {
+                                    factory(source_language, target_language)
+                                }),
+                                None => {
+                                    let provider = request
+                                        .model
+                                        .provider()
+                                        .ok_or_else(|| anyhow::anyhow!("Unknown model provider"))?;
+                                    let api_key = api_keys
+                                        .for_provider(provider)
+                                        .ok_or_else(|| {
+                                            anyhow::anyhow!("no api key for provider {provider:?}")
+                                        })?
+                                        .to_owned();
+                                    let model = request.model;
+                                    Box::new(move |source_language: Language| {
+                                        get_translator(
+                                            TranslatorServices {
+                                                cache,
+                                                context_provider,
+                                                gemini_prompt_cache,
+                                            },
+                                            provider,
+                                            model,
+                                            api_key,
+                                            source_language,
+                                            target_language,
+                                            translator_config,
+                                        )
+                                    })
+                                }
                             };
                             handle_request(
                                 library,
@@ -407,6 +485,7 @@ impl TranslationQueue {
         // each stranded entry or its spinner survives the queue forever.
         let stranded: Vec<_> = {
             let mut state = self.state.lock().await;
+            state.waiters.clear();
             state.active_translations.drain().collect()
         };
         for ((book_id, paragraph_id), activity) in stranded {
@@ -426,12 +505,51 @@ impl TranslationQueue {
         paragraph_id: usize,
         model: TranslationModel,
         use_cache: bool,
+        stream_tokens: bool,
+    ) -> anyhow::Result<usize> {
+        self.enqueue(book_id, paragraph_id, model, use_cache, stream_tokens, None)
+            .await
+    }
+
+    /// Like [`translate`](Self::translate), but returns once the translation
+    /// is stored and the book saved, or with the error the request failed
+    /// with. Joins a request already queued for the paragraph.
+    pub async fn translate_and_wait(
+        &self,
+        book_id: Uuid,
+        paragraph_id: usize,
+        model: TranslationModel,
+        use_cache: bool,
+    ) -> anyhow::Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.enqueue(book_id, paragraph_id, model, use_cache, false, Some(tx))
+            .await?;
+        match rx.await {
+            Ok(outcome) => outcome.map_err(|err| anyhow::anyhow!(err)),
+            Err(_) => Err(anyhow::anyhow!("translation cancelled")),
+        }
+    }
+
+    async fn enqueue(
+        &self,
+        book_id: Uuid,
+        paragraph_id: usize,
+        model: TranslationModel,
+        use_cache: bool,
+        stream_tokens: bool,
+        waiter: Option<oneshot::Sender<Result<(), String>>>,
     ) -> anyhow::Result<usize> {
         // Hold lock across check + insert to prevent TOCTOU race where two
         // concurrent calls both pass the dedup check and send duplicate requests.
+        // The waiter is registered under the same lock, so it can't miss a
+        // request finishing in between.
         let mut state = self.state.lock().await;
         if let Some(activity) = state.active_translations.get(&(book_id, paragraph_id)) {
-            return Ok(activity.request_id);
+            let request_id = activity.request_id;
+            if let Some(waiter) = waiter {
+                state.waiters.entry(request_id).or_default().push(waiter);
+            }
+            return Ok(request_id);
         }
 
         let request_id = self.next_request_index.fetch_add(1, Ordering::SeqCst);
@@ -443,6 +561,9 @@ impl TranslationQueue {
                 expected_chars: 0,
             },
         );
+        if let Some(waiter) = waiter {
+            state.waiters.entry(request_id).or_default().push(waiter);
+        }
         drop(state);
 
         // Announce activity at enqueue, not when the worker picks the request
@@ -467,13 +588,14 @@ impl TranslationQueue {
             paragraph_id,
             model,
             use_cache,
+            stream_tokens,
             attempt: 0,
         }) {
-            self.state
-                .lock()
-                .await
-                .active_translations
-                .remove(&(book_id, paragraph_id));
+            self.state.lock().await.finish(
+                (book_id, paragraph_id),
+                request_id,
+                Err("translation queue closed".to_string()),
+            );
             return Err(err.into());
         }
 
@@ -494,29 +616,27 @@ impl TranslationQueue {
     }
 }
 
-async fn handle_request(
+async fn handle_request<R: Runtime>(
     library: Arc<Library>,
-    make_translator: impl FnOnce(Language) -> anyhow::Result<Box<dyn library::translator::Translator>>,
+    make_translator: impl FnOnce(Language) -> anyhow::Result<Box<dyn Translator>>,
     stats_cache: Arc<TranslationSizeCache>,
     target_language: Language,
-    app: tauri::AppHandle,
+    app: AppHandle<R>,
     state: Arc<Mutex<TranslationQueueState>>,
     save_notify: &UnboundedSender<SaveNotify>,
     request: &TranslationRequest,
 ) -> anyhow::Result<()> {
-    let (translation, paragraph_text, source_language, chapter_id) = {
+    let (paragraph_text, source_language, chapter_id) = {
         let book = library.get_book(&request.book_id).await?;
-        let mut book = book.lock().await;
-        let translation = book.get_or_create_translation(&target_language).await;
+        let book = book.lock().await;
         let paragraph = book.book.paragraph_view(request.paragraph_id);
         let chapter_id = book
             .book
             .chapter_for_paragraph(request.paragraph_id)
             .unwrap_or(0);
         (
-            translation,
             paragraph.original_text.to_string(),
-            Language::from_639_3(&book.book.language).unwrap(),
+            book_source_language(&book.book.language)?,
             chapter_id,
         )
     };
@@ -626,15 +746,34 @@ async fn handle_request(
         })
     };
 
-    let p_translation = translator
-        .get_translation(TranslationContext {
-            paragraph_text: &paragraph_text,
-            book_id: request.book_id,
-            chapter_id,
-            use_cache: request.use_cache,
-            callback: Some(callback),
-        })
-        .await?;
+    let ctx = TranslationContext {
+        paragraph_text: &paragraph_text,
+        book_id: request.book_id,
+        chapter_id,
+        use_cache: request.use_cache,
+        callback: Some(callback),
+        token_sink: None,
+    };
+    let p_translation = if request.stream_tokens {
+        let app = app.clone();
+        let book_id = request.book_id;
+        let paragraph_id = request.paragraph_id;
+        let request_id = request.request_id;
+        let sink = Arc::new(move |chunk: &str| {
+            let _ = app.emit(
+                "translation_token",
+                TranslationTokenEvent {
+                    book_id,
+                    paragraph_id,
+                    request_id,
+                    chunk,
+                },
+            );
+        });
+        translator.stream_translation(ctx, sink).await?
+    } else {
+        translator.get_translation(ctx).await?
+    };
     info!("Translated paragraph {}", request.paragraph_id);
 
     // Measure actual translation JSON size and update stats
@@ -651,59 +790,74 @@ async fn handle_request(
         actual_size as f64 / source_len as f64
     );
 
-    // F4 fix: Re-read paragraph text and verify it hasn't changed since we started translating.
-    // Between our initial read and now, the book could have been reloaded (e.g., file watcher
-    // picked up a sync update), which would make this translation stale.
+    store_paragraph_translation(
+        &library,
+        request.book_id,
+        request.paragraph_id,
+        &paragraph_text,
+        p_translation,
+        request.model,
+        target_language,
+    )
+    .await?;
+
+    save_notify.send(SaveNotify {
+        request_id: request.request_id,
+        book_id: request.book_id,
+        paragraph_id: request.paragraph_id,
+    })?;
+
+    Ok(())
+}
+
+/// Stores a finished translation of `paragraph_id`, made from
+/// `paragraph_text`, in the book's translation into `target_language` and
+/// updates the cards. The translation is discarded if the paragraph changed
+/// since (e.g. the file watcher picked up a sync update). Saving and
+/// notifying is left to [`save_and_emit`].
+async fn store_paragraph_translation(
+    library: &Library,
+    book_id: Uuid,
+    paragraph_id: usize,
+    paragraph_text: &str,
+    mut paragraph: ParagraphTranslation,
+    model: TranslationModel,
+    target_language: Language,
+) -> anyhow::Result<()> {
     {
-        let book_handle = library.get_book(&request.book_id).await?;
-        let book = book_handle.lock().await;
-        if request.paragraph_id >= book.book.paragraphs_count() {
+        let book_handle = library.get_book(&book_id).await?;
+        let mut book = book_handle.lock().await;
+        if paragraph_id >= book.book.paragraphs_count() {
             return Err(anyhow::anyhow!(
                 "Paragraph {} no longer exists (book now has {} paragraphs) — discarding stale translation",
-                request.paragraph_id,
+                paragraph_id,
                 book.book.paragraphs_count()
             ));
         }
-        let current_text = book
-            .book
-            .paragraph_view(request.paragraph_id)
-            .original_text
-            .to_string();
-        if current_text != paragraph_text {
+        if book.book.paragraph_view(paragraph_id).original_text != paragraph_text {
             return Err(anyhow::anyhow!(
                 "Paragraph {} content changed during translation — discarding stale translation",
-                request.paragraph_id
+                paragraph_id
             ));
         }
+        if let Err(err) = book.apply_footnotes(paragraph_id, &mut paragraph).await {
+            warn!("Failed to attach footnotes to paragraph {paragraph_id}: {err}");
+        }
+        let translation = book.get_or_create_translation(&target_language).await;
+        translation
+            .lock()
+            .await
+            .add_paragraph_translation(paragraph_id, &paragraph, model);
     }
 
-    translation.lock().await.add_paragraph_translation(
-        request.paragraph_id,
-        &p_translation,
-        request.model,
-    );
-
     library
-        .apply_paragraph_to_cards(
-            request.book_id,
-            request.paragraph_id,
-            &p_translation,
-            target_language,
-        )
-        .await?;
-
-    save_notify.send(SaveNotify {
-        request_id: request.request_id,
-        book_id: request.book_id,
-        paragraph_id: request.paragraph_id,
-    })?;
-
-    Ok(())
+        .apply_paragraph_to_cards(book_id, paragraph_id, &paragraph, target_language)
+        .await
 }
 
-async fn run_saver(
+async fn run_saver<R: Runtime>(
     library: Arc<Library>,
-    app: tauri::AppHandle,
+    app: AppHandle<R>,
     library_tx: Arc<watch::Sender<Option<Arc<Library>>>>,
     state: Arc<Mutex<TranslationQueueState>>,
     mut rx: UnboundedReceiver<SaveNotify>,
@@ -719,9 +873,15 @@ async fn run_saver(
             std::collections::hash_map::Entry::Vacant(_) => {
                 // No existing saver, save immediately
                 drop(savers_guard); // Drop lock before await
-                save_and_emit(library.clone(), app.clone(), &library_tx, msg)
-                    .await
-                    .unwrap_or_else(|err| warn!("Failed to autosave book {book_id}: {err}"));
+                save_and_emit(
+                    library.clone(),
+                    app.clone(),
+                    &library_tx,
+                    msg.book_id,
+                    msg.paragraph_id,
+                )
+                .await
+                .unwrap_or_else(|err| warn!("Failed to autosave book {book_id}: {err}"));
                 finalize_request(&state, &app, msg).await;
             }
             std::collections::hash_map::Entry::Occupied(_) => {
@@ -734,11 +894,15 @@ async fn run_saver(
                     let state = state.clone();
                     async move {
                         tokio::time::sleep(Duration::from_secs(1)).await;
-                        save_and_emit(library.clone(), app.clone(), &library_tx, msg)
-                            .await
-                            .unwrap_or_else(|err| {
-                                warn!("Failed to autosave book {book_id}: {err}")
-                            });
+                        save_and_emit(
+                            library.clone(),
+                            app.clone(),
+                            &library_tx,
+                            msg.book_id,
+                            msg.paragraph_id,
+                        )
+                        .await
+                        .unwrap_or_else(|err| warn!("Failed to autosave book {book_id}: {err}"));
                         savers.lock().await.remove(&book_id);
                         finalize_request(&state, &app, msg).await;
                     }
@@ -751,21 +915,20 @@ async fn run_saver(
     }
 }
 
-async fn finalize_request(
+async fn finalize_request<R: Runtime>(
     state: &Arc<Mutex<TranslationQueueState>>,
-    app: &tauri::AppHandle,
+    app: &AppHandle<R>,
     msg: SaveNotify,
 ) {
     state
         .lock()
         .await
-        .active_translations
-        .remove(&(msg.book_id, msg.paragraph_id));
+        .finish((msg.book_id, msg.paragraph_id), msg.request_id, Ok(()));
     emit_finished(app, msg.book_id, msg.paragraph_id, msg.request_id, None);
 }
 
-fn emit_finished(
-    app: &tauri::AppHandle,
+fn emit_finished<R: Runtime>(
+    app: &AppHandle<R>,
     book_id: Uuid,
     paragraph_id: usize,
     request_id: usize,
@@ -788,29 +951,31 @@ async fn save_book(library: Arc<Library>, book_id: Uuid) -> anyhow::Result<()> {
     book.save().await
 }
 
-async fn save_and_emit(
+/// Saves the book and tells the UI `paragraph_id` changed.
+pub(crate) async fn save_and_emit<R: Runtime>(
     library: Arc<Library>,
-    app: tauri::AppHandle,
+    app: AppHandle<R>,
     library_tx: &watch::Sender<Option<Arc<Library>>>,
-    msg: SaveNotify,
+    book_id: Uuid,
+    paragraph_id: usize,
 ) -> anyhow::Result<()> {
-    save_book(library, msg.book_id).await?;
+    save_book(library, book_id).await?;
     info!(
         "Emitting \"paragraph_updated\" and \"book_updated\" for {}/{}",
-        msg.book_id, msg.paragraph_id
+        book_id, paragraph_id
     );
     app.emit(
         "paragraph_updated",
         ParagraphUpdatedEvent {
-            book_id: msg.book_id,
-            paragraph_id: msg.paragraph_id,
+            book_id,
+            paragraph_id,
         },
     )?;
     // The file-watcher TranslationChanged path won't fire `book_updated` for
     // our own writes (reload_translations sees in-memory == disk and returns
     // had_effect=false), so emit directly here. Chapter-list `Resource`s in
     // the frontend subscribe to this to refresh per-chapter translation %.
-    app.emit("book_updated", msg.book_id)?;
+    app.emit("book_updated", book_id)?;
     library_tx.send_modify(|_| {});
     Ok(())
 }
@@ -867,6 +1032,7 @@ mod tests {
         );
         Arc::new(Mutex::new(TranslationQueueState {
             active_translations,
+            waiters: HashMap::new(),
         }))
     }
 
@@ -877,6 +1043,7 @@ mod tests {
             paragraph_id,
             model: TranslationModel::Gemini25Flash,
             use_cache: true,
+            stream_tokens: false,
             attempt,
         }
     }
@@ -953,6 +1120,23 @@ mod tests {
         assert!(state.lock().await.active_translations.is_empty());
     }
 
+    #[tokio::test]
+    async fn terminal_failure_is_reported_to_waiters() {
+        let book_id = Uuid::new_v4();
+        let state = state_with_entry(book_id, 3, 7);
+        let (waiter, outcome) = oneshot::channel();
+        state.lock().await.waiters.insert(7, vec![waiter]);
+        let (tx, _rx) = unbounded_channel::<TranslationRequest>();
+        let err = anyhow::anyhow!(
+            "Paragraph 3 content changed during translation — discarding stale translation"
+        );
+
+        handle_translation_failure(&state, &tx, &request(book_id, 3, 0), &err).await;
+
+        assert_eq!(outcome.await.unwrap(), Err(err.to_string()));
+        assert!(state.lock().await.waiters.is_empty());
+    }
+
     #[tokio::test]
     async fn closed_queue_degrades_to_terminal_failure() {
         let book_id = Uuid::new_v4();
@@ -967,4 +1151,86 @@ mod tests {
         assert_eq!(disposition, FailureDisposition::Terminal);
         assert!(state.lock().await.active_translations.is_empty());
     }
+
+    /// Covers the queue on its own: `AppState` needs a real app handle, so
+    /// its factory wiring and `translate_paragraph` aren't exercised here.
+    #[tokio::test]
+    async fn queue_translates_and_saves_paragraph_with_injected_translator() {
+        use library::book::translation_import::{ParagraphTranslation, Sentence};
+        use library::translator::{NoChapterContext, fixture::FixtureTranslator};
+
+        let root = std::env::temp_dir().join(format!("flts_queue_injected_{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let library = Arc::new(Library::open(root.join("library")).await.unwrap());
+        let spa = Language::from_639_3("spa").unwrap();
+        let eng = Language::from_639_3("eng").unwrap();
+        let book_id = library
+            .create_book_plain("Test Book", "Hola.", &spa)
+            .await
+            .unwrap()
+            .id;
+
+        let fixtures = root.join("fixtures");
+        std::fs::create_dir_all(&fixtures).unwrap();
+        let canned = ParagraphTranslation {
+            timestamp: 0,
+            sentences: vec![Sentence {
+                full_translation: "Hello.".into(),
+                words: vec![],
+            }],
+            total_tokens: None,
+            token_usage: None,
+        };
+        std::fs::write(
+            FixtureTranslator::new(&fixtures).fixture_path("Hola."),
+            serde_json::to_vec(&canned).unwrap(),
+        )
+        .unwrap();
+        let factory: TranslatorFactory = Arc::new(move |_, _| {
+            Ok(Box::new(FixtureTranslator::new(fixtures.clone())) as Box<dyn Translator>)
+        });
+
+        let cache_dir = root.join("cache");
+        let app = tauri::test::mock_app();
+        let queue = TranslationQueue::init(
+            library.clone(),
+            Arc::new(TranslationsCache::create(&cache_dir).await.unwrap()),
+            Arc::new(TranslationSizeCache::create(&cache_dir).await.unwrap()),
+            GeminiPromptCache::open(&cache_dir, 1 << 20).await.unwrap(),
+            Arc::new(NoChapterContext),
+            &Config::default(),
+            Some(factory),
+            app.handle().clone(),
+            Arc::new(watch::channel(Some(library.clone())).0),
+        )
+        .unwrap();
+
+        queue
+            .translate(book_id, 0, TranslationModel::Unknown, false, false)
+            .await
+            .unwrap();
+        // The activity is cleared once the book is saved, or on failure.
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while !queue.state.lock().await.active_translations.is_empty() {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .unwrap();
+        queue.shutdown().await;
+
+        let reopened = Library::open(root.join("library")).await.unwrap();
+        let book = reopened.get_book(&book_id).await.unwrap();
+        let translation = book.lock().await.get_translation(&eng).await.unwrap();
+        let translation = translation.lock().await;
+        let stored: Vec<_> = translation
+            .paragraph_view(0)
+            .unwrap()
+            .sentences()
+            .map(|s| s.full_translation.into_owned())
+            .collect();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(stored, ["Hello."]);
+    }
 }
@@ -48,6 +48,7 @@ pub fn run() {
             let app_state = Arc::new(crate::app::AppState::new(
                 app.handle().clone(),
                 watcher.clone(),
+                None,
             )?);
             info!("App created");
             app.manage(app_state.clone());
@@ -140,6 +141,8 @@ pub fn run() {
             app::get_library_root,
             app::reveal_library_root,
             app::update_config,
+            app::set_visible_pos,
+            app::set_annotation_density,
             app::purge_gemini_caches,
             app::get_anki_sync_status,
             app::sync_anki_now,
@@ -154,23 +157,56 @@ pub fn run() {
             app::sync::sync_add_device,
             app::sync::sync_remove_device,
             app::translate_paragraph,
+            app::translate_paragraph_sync,
+            app::stream_paragraph_translation,
             app::translate_chapter,
+            app::translate_title,
+            app::retranslate_sentence,
             app::get_paragraph_translation_activity,
             app::library_view::list_books,
+            app::library_view::get_books_count,
+            app::library_view::list_books_in_folder,
+            app::library_view::get_book_directory_path,
+            app::library_view::get_book_conflicts,
             app::library_view::list_book_chapters,
+            app::library_view::get_adjacent_chapters,
             app::library_view::get_book_chapter_paragraph_ids,
+            app::library_view::get_book_chapter_paragraphs,
+            app::library_view::get_paragraph_original_text,
+            app::library_view::find_chapter,
+            app::library_view::get_translation_totals,
             app::library_view::get_paragraph_view,
             app::library_view::get_paragraph_originals_batch,
             app::library_view::get_paragraph_translations_batch,
+            app::library_view::get_paragraph_word_boundaries,
+            app::library_view::has_unsaved_changes,
             app::config::get_translation_providers,
             app::library_view::get_word_info,
+            app::library_view::dictionary_suggestions,
+            app::library_view::recent_dictionary_entries,
+            app::library_view::export_visible_words,
+            app::library_view::export_studied_words,
+            app::library_view::export_book_html,
+            app::library_view::search_translation,
+            app::library_view::get_word_examples,
+            app::library_view::mark_word_visible,
+            app::library_view::add_known_word,
+            app::library_view::remove_known_word,
+            app::library_view::reveal_paragraph,
+            app::library_view::hide_paragraph,
             app::library_view::import_plain_text,
             app::library_view::import_epub,
+            app::library_view::import_epub_path,
             app::library_view::get_book_reading_state,
+            app::library_view::get_lookup_history,
             app::library_view::get_book_summary_status,
             app::library_view::save_book_reading_state,
+            app::library_view::record_word_lookup,
             app::library_view::move_book,
             app::library_view::delete_book,
+            app::library_view::update_paragraph_original,
+            app::library_view::repair_book,
+            app::library_view::merge_conflicts,
             app::get_system_definition,
             app::show_system_dictionary,
             app::lyrics::start_spotify_watcher,
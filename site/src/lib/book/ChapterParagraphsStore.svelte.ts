@@ -62,6 +62,17 @@ export class ChapterParagraphsStore {
                 this.#scheduleCardsRefresh();
             },
         );
+
+        // Reader display settings (e.g. the part-of-speech filter) change
+        // which translations the backend puts into segments; refresh the
+        // cached ones the same way a card update does.
+        eventHub.subscribe<null>(
+            "reader_settings_updated",
+            () => true,
+            () => {
+                this.#scheduleCardsRefresh();
+            },
+        );
     }
 
     #scheduleCardsRefresh(): void {
@@ -22,8 +22,11 @@ export type Language = {
     localName?: string,
 }
 
+export type AnnotationDensity = 'none' | 'contentWords' | 'all';
+
 export type Config = {
     targetLanguageId?: string,
+    defaultSourceLanguageId?: string | null,
     translationProvider: TranslationProvider,
     geminiApiKey?: string,
     openaiApiKey?: string,
@@ -31,6 +34,9 @@ export type Config = {
     zaiApiKey?: string,
     model: number,
     translationConcurrency?: number,
+    maxContextualTranslations?: number,
+    temperature?: number,
+    thinkingBudget?: number,
     spotifyClientId?: string,
     spotifyPreloadCount?: number,
     spotifyShowNextTrack?: boolean,
@@ -38,6 +44,12 @@ export type Config = {
     ankiApiKey?: string,
     syncEnabled?: boolean,
     syncDeviceName?: string,
+    visiblePos?: string[] | null,
+    annotationDensity?: AnnotationDensity,
+    logRequests?: boolean,
+    lookupHistoryLimit?: number,
+    autoMergeConflicts?: boolean,
+    sha256Checksums?: boolean,
 }
 
 export async function getModels(): Promise<Model[]> {
@@ -59,6 +71,14 @@ export async function setConfig(config: Config) {
     await invoke("update_config", { config: config });
 }
 
+export async function setVisiblePos(visiblePos: string[] | null) {
+    await invoke("set_visible_pos", { visiblePos });
+}
+
+export async function setAnnotationDensity(density: AnnotationDensity) {
+    await invoke("set_annotation_density", { density });
+}
+
 export async function getConfig() {
     return await invoke<Config>("get_config");
 }
@@ -14,6 +14,13 @@ type Grammar = {
     tense?: string | null,
     case?: string | null,
     other?: string | null,
+    // The fields above, labeled and in display order
+    fields: GrammarField[],
+}
+
+type GrammarField = {
+    label: string,
+    value?: string | null,
 }
 
 export type SentenceWordTranslation = {
@@ -25,6 +32,49 @@ export type SentenceWordTranslation = {
     readonly fullSentenceTranslation: string,
     readonly translationModel: number,
     readonly sourceLanguage: string,
+    // Only set when the word came from the system dictionary fallback
+    readonly systemDefinition?: SystemDefinition,
+}
+
+export type DictionaryEntry = {
+    readonly targetForm: string,
+    readonly partOfSpeech: string,
+    readonly usageCount: number,
+    readonly lastSeen: number | null,
+}
+
+export type RecentDictionaryEntry = {
+    readonly lemma: string,
+    readonly targetForm: string,
+    readonly partOfSpeech: string,
+    readonly lastSeen: number,
+}
+
+export type StudiedGrammar = {
+    readonly originalInitialForm: string,
+    readonly targetInitialForm: string,
+    readonly partOfSpeech: string,
+    readonly plurality?: string,
+    readonly person?: string,
+    readonly tense?: string,
+    readonly case?: string,
+    readonly other?: string,
+}
+
+export type StudiedWord = {
+    readonly paragraphId: number,
+    readonly wordIndex: number,
+    readonly original: string,
+    readonly grammar: StudiedGrammar,
+    readonly translation: string | null,
+    readonly sentence: string,
+    readonly revealedAt?: number,
+}
+
+export type StudySet = {
+    readonly sourceLanguage: string,
+    readonly targetLanguage: string,
+    readonly words: StudiedWord[],
 }
 
 export type ParagraphSegment =
@@ -44,14 +94,55 @@ export type ParagraphOriginal = {
     original: string,
 }
 
+export type ChapterParagraph = {
+    id: number,
+    original: string,
+    segments: ParagraphSegment[] | null,
+}
+
+export type TranslationTotals = {
+    translatedParagraphs: number,
+    sentences: number,
+    words: number,
+}
+
 export type ParagraphTranslationSlice = {
     id: number,
     segments?: ParagraphSegment[],
 }
 
-export type ChapterMetaView = {
+export type CreatedBook = {
+    id: UUID,
+    language: string,
+    chaptersCount: number,
+    paragraphsCount: number,
+}
+
+// What an EPUB import keeps of each paragraph; "htmlOnly" derives the text
+// from the HTML, "textOnly" drops the formatting
+export type ParagraphStorage = "both" | "htmlOnly" | "textOnly";
+
+// Payload of the `import_progress` event emitted by importEpubPath
+export type ImportProgress = {
+    path: string,
+    stage: "parsing" | "chapters",
+    done: number,
+    total: number,
+}
+
+export type AdjacentChapters = {
+    prevId: number | null,
+    nextId: number | null,
+    hasPrev: boolean,
+    hasNext: boolean,
+}
+
+export type ChapterMetaView = AdjacentChapters & {
     id: number,
     title: string,
+    // Start of the first paragraph, empty for a chapter without paragraphs
+    preview: string,
+    paragraphCount: number,
     translationRatio: number,
 }
 
@@ -59,6 +150,8 @@ export interface BookMeta {
     readonly uid: UUID,
     readonly chapterCount: number;
     readonly translationRatio: number;
+    readonly translationRatios: Record<string, number>;
+    readonly translationLastModified: number | null;
     readonly title: string;
     path: string[];
 }
@@ -66,18 +159,39 @@ export interface BookMeta {
 export type LibraryBookMetadataView = {
     id: UUID,
     title: string,
+    // Title in the target language, once translateTitle has run
+    translatedTitle: string | null,
     chaptersCount: number,
     paragraphsCount: number,
     translationRatio: number,
+    // Every translation of the book, keyed by "<lang>[-<variant>]"
+    translationRatios: Record<string, number>,
+    translationLastModified: number | null,
+    // book.dat is missing (partial sync); see repairBook
+    incomplete: boolean,
     path: string[],
 }
 
+export type LemmaMatch = {
+    paragraphId: number,
+    sentenceId: number,
+    wordId: number,
+    original: string,
+}
+
 export type BookReadingState = {
     chapterId: number,
     paragraphId: number,
     pageOffset: number,
 }
 
+export type WordLookup = {
+    paragraphId: number,
+    sentenceId: number,
+    wordId: number,
+    timestamp: number,
+}
+
 export type LibraryFolder = {
     name?: string,
     folders: LibraryFolder[],
@@ -113,6 +227,8 @@ export function buildLibraryFolder(books: LibraryBookMetadataView[]): LibraryFol
             uid: book.id,
             chapterCount: book.chaptersCount,
             translationRatio: book.translationRatio,
+            translationRatios: book.translationRatios,
+            translationLastModified: book.translationLastModified,
             title: book.title,
             path: [...folderPath],
         });
@@ -131,6 +247,25 @@ export class Library {
         );
     }
 
+    // Book count from a directory scan, available before the metadata loads
+    getBooksCount(): Resource<number> {
+        return new Resource<number>(
+            "get_books_count",
+            {},
+            [{ name: "library_updated", filter: () => true }],
+            0,
+        );
+    }
+
+    getLibraryBooksInFolder(path: string[]): Resource<LibraryBookMetadataView[]> {
+        return new Resource<LibraryBookMetadataView[]>(
+            "list_books_in_folder",
+            { path },
+            [{ name: "library_updated", filter: () => true }],
+            [],
+        );
+    }
+
     getBookChapters(bookId: UUID): Resource<ChapterMetaView[]> {
         return new Resource<ChapterMetaView[]>(
             "list_book_chapters",
@@ -140,6 +275,10 @@ export class Library {
         );
     }
 
+    async getAdjacentChapters(bookId: UUID, chapterId: number): Promise<AdjacentChapters> {
+        return await invoke<AdjacentChapters>("get_adjacent_chapters", { bookId, chapterId });
+    }
+
     getBookChapterParagraphIds(bookId: UUID, chapterId: number): Resource<number[]> {
         return new Resource<number[]>(
             "get_book_chapter_paragraph_ids",
@@ -149,14 +288,72 @@ export class Library {
         );
     }
 
-    getWordInfo(bookId: UUID, paragraphId: number, sentenceId: number, wordId: number): Resource<SentenceWordTranslation | undefined> {
+    async getParagraphOriginalText(bookId: UUID, chapterId: number, paragraphId: number): Promise<string> {
+        return await invoke<string>("get_paragraph_original_text", { bookId, chapterId, paragraphId });
+    }
+
+    // Index of the first chapter titled `title` (case-insensitive), or null
+    async findChapter(bookId: UUID, title: string): Promise<number | null> {
+        return await invoke<number | null>("find_chapter", { bookId, title });
+    }
+
+    getBookChapterParagraphs(bookId: UUID, chapterId: number, annotated: boolean): Resource<ChapterParagraph[]> {
+        return new Resource<ChapterParagraph[]>(
+            "get_book_chapter_paragraphs",
+            { bookId, chapterId, annotated },
+            [{ name: "book_updated", filter: (updatedId: UUID) => updatedId === bookId }],
+            [],
+        );
+    }
+
+    getTranslationTotals(bookId: UUID): Resource<TranslationTotals> {
+        return new Resource<TranslationTotals>(
+            "get_translation_totals",
+            { bookId },
+            [{ name: "book_updated", filter: (updatedId: UUID) => updatedId === bookId }],
+            { translatedParagraphs: 0, sentences: 0, words: 0 },
+        );
+    }
+
+    // [start, end) character ranges of the words in a paragraph's original text
+    getParagraphWordBoundaries(bookId: UUID, paragraphId: number): Resource<[number, number][]> {
+        return new Resource<[number, number][]>(
+            "get_paragraph_word_boundaries",
+            { bookId, paragraphId },
+            [{ name: "book_updated", filter: (updatedId: UUID) => updatedId === bookId }],
+            [],
+        );
+    }
+
+    // With systemFallback, words of untranslated paragraphs are looked up in the system dictionary
+    getWordInfo(bookId: UUID, paragraphId: number, sentenceId: number, wordId: number, systemFallback = false): Resource<SentenceWordTranslation | undefined> {
         return new Resource<SentenceWordTranslation | undefined>(
             "get_word_info",
-            { bookId, paragraphId, sentenceId, wordId },
+            { bookId, paragraphId, sentenceId, wordId, systemFallback },
             [{ name: "book_updated", filter: (updatedId: UUID) => updatedId === bookId }],
         );
     }
 
+    // Translations of a lemma collected from the whole library, most used first
+    getDictionarySuggestions(word: string, sourceLanguage: string, targetLanguage: string): Resource<DictionaryEntry[]> {
+        return new Resource<DictionaryEntry[]>(
+            "dictionary_suggestions",
+            { word, sourceLanguage, targetLanguage },
+            [{ name: "cards_updated", filter: () => true }],
+            [],
+        );
+    }
+
+    // Dictionary pairs most recently produced by a translation, newest first
+    getRecentDictionaryEntries(sourceLanguage: string, targetLanguage: string, n: number): Resource<RecentDictionaryEntry[]> {
+        return new Resource<RecentDictionaryEntry[]>(
+            "recent_dictionary_entries",
+            { sourceLanguage, targetLanguage, n },
+            [{ name: "cards_updated", filter: () => true }],
+            [],
+        );
+    }
+
     // Get system dictionary definition for a word (macOS Dictionary Services)
     getSystemDefinition(word: string, sourceLang: string, targetLang: string): Resource<SystemDefinition | null> {
         return new Resource<SystemDefinition | null>(
@@ -167,12 +364,59 @@ export class Library {
         );
     }
 
-    async importEpub(book: EpubBook, sourceLanguageId: string) {
-        await invoke<UUID>("import_epub", { book, sourceLanguageId });
+    async importEpub(book: EpubBook, sourceLanguageId: string, paragraphStorage?: ParagraphStorage) {
+        await invoke<UUID>("import_epub", { book, sourceLanguageId, paragraphStorage });
     }
 
-    async importText(title: string, text: string, sourceLanguageId: string) {
-        await invoke<UUID>("import_plain_text", { title, text, sourceLanguageId });
+    // Parses the EPUB in Rust, so large books don't pass through the webview
+    async importEpubPath(path: string, sourceLanguageId: string, paragraphStorage?: ParagraphStorage) {
+        return await invoke<UUID>("import_epub_path", { path, sourceLanguageId, paragraphStorage });
+    }
+
+    // With preserveLineBreaks, blank lines separate paragraphs and single
+    // line breaks inside them are kept (verse, code samples)
+    async importText(
+        title: string,
+        text: string,
+        sourceLanguageId?: string,
+        preserveLineBreaks?: boolean,
+    ): Promise<CreatedBook> {
+        return await invoke<CreatedBook>("import_plain_text", {
+            title,
+            text,
+            sourceLanguageId,
+            preserveLineBreaks,
+        });
+    }
+
+    // Whether the book or any of its translations has changes not yet on disk
+    async hasUnsavedChanges(bookId: UUID) {
+        return await invoke<boolean>("has_unsaved_changes", { bookId });
+    }
+
+    // Revealed words with their grammar, translation and sentence
+    async exportStudiedWords(bookId: UUID) {
+        return await invoke<StudySet>("export_studied_words", { bookId });
+    }
+
+    // Self-contained HTML page for the whole book, or just one chapter
+    async exportBookHtml(bookId: UUID, targetLanguageId: string, annotated: boolean, chapterId?: number) {
+        return await invoke<string>("export_book_html", { bookId, chapterId, targetLanguageId, annotated });
+    }
+
+    // Every word whose dictionary form is `lemma`, inflected forms included
+    async searchTranslation(bookId: UUID, lemma: string, targetLanguageId: string) {
+        return await invoke<LemmaMatch[]>("search_translation", { bookId, lemma, targetLanguageId });
+    }
+
+    // Translated sentences of the book that use the lemma, for vocabulary study
+    getWordExamples(bookId: UUID, lemma: string): Resource<string[]> {
+        return new Resource<string[]>(
+            "get_word_examples",
+            { bookId, lemma },
+            [{ name: "book_updated", filter: (updatedId: UUID) => updatedId === bookId }],
+            [],
+        );
     }
 
     async translateParagraph(bookId: UUID, paragraphId: number, model: number | undefined = undefined, useCache: boolean = true) {
@@ -180,11 +424,37 @@ export class Library {
         return await invoke<number>("translate_paragraph", { bookId, paragraphId, model: model ?? config.model, useCache });
     }
 
+    // Waits for the translation instead of returning a request id; for simple
+    // views that don't follow the translation events
+    async translateParagraphSync(bookId: UUID, paragraphId: number, model: number | undefined = undefined, useCache: boolean = true) {
+        let config = await getConfig();
+        return await invoke<ChapterParagraph>("translate_paragraph_sync", { bookId, paragraphId, model: model ?? config.model, useCache });
+    }
+
+    async streamParagraphTranslation(bookId: UUID, paragraphId: number, model: number | undefined = undefined, useCache: boolean = true) {
+        let config = await getConfig();
+        return await invoke<number>("stream_paragraph_translation", { bookId, paragraphId, model: model ?? config.model, useCache });
+    }
+
     async translateChapter(bookId: UUID, chapterId: number, model: number | undefined = undefined, useCache: boolean = true) {
         let config = await getConfig();
         return await invoke<number>("translate_chapter", { bookId, chapterId, model: model ?? config.model, useCache });
     }
 
+    async translateTitle(bookId: UUID, model: number | undefined = undefined) {
+        let config = await getConfig();
+        return await invoke<string>("translate_title", { bookId, model: model ?? config.model });
+    }
+
+    async retranslateSentence(bookId: UUID, paragraphId: number, sentenceId: number, model: number | undefined = undefined) {
+        let config = await getConfig();
+        await invoke("retranslate_sentence", { bookId, paragraphId, sentenceId, model: model ?? config.model });
+    }
+
+    async getBookDirectoryPath(bookId: UUID) {
+        return await invoke<string | null>("get_book_directory_path", { bookId });
+    }
+
     getParagraphTranslationActivity(bookId: UUID, paragraphId: number): ParagraphTranslationActivityResource {
         return new ParagraphTranslationActivityResource(bookId, paragraphId);
     }
@@ -227,10 +497,49 @@ export class Library {
         });
     }
 
+    // Remembers a looked-up word in the book's lookup history
+    async recordWordLookup(bookId: UUID, paragraphId: number, sentenceId: number, wordId: number) {
+        await invoke("record_word_lookup", { bookId, paragraphId, sentenceId, wordId });
+    }
+
+    async getLookupHistory(bookId: UUID): Promise<WordLookup[]> {
+        return await invoke<WordLookup[]>("get_lookup_history", { bookId });
+    }
+
     async deleteBook(bookUid: UUID) {
         await invoke('delete_book', { bookId: bookUid });
     }
 
+    // Known words are left without an in-text translation in every book of the same language
+    async addKnownWord(bookUid: UUID, lemma: string) {
+        return await invoke<boolean>("add_known_word", { bookId: bookUid, lemma });
+    }
+
+    async removeKnownWord(bookUid: UUID, lemma: string) {
+        return await invoke<boolean>("remove_known_word", { bookId: bookUid, lemma });
+    }
+
+    // Fixes a paragraph's original text; true if its translations may now be stale
+    async updateParagraphOriginal(bookUid: UUID, paragraphId: number, text: string, html: string | null = null) {
+        return await invoke<boolean>("update_paragraph_original", { bookId: bookUid, paragraphId, text, html });
+    }
+
+    // Conflict files left unmerged because automatic merging is off
+    async getBookConflicts(bookUid: UUID) {
+        return await invoke<string[]>("get_book_conflicts", { bookId: bookUid });
+    }
+
+    // Merges them into the main files; returns how many there were
+    async mergeConflicts(bookUid: UUID) {
+        return await invoke<number>("merge_conflicts", { bookId: bookUid });
+    }
+
+    // Restores a missing book.dat from a conflict copy, or removes the
+    // orphaned directory
+    async repairBook(bookUid: UUID, remove: boolean) {
+        await invoke('repair_book', { bookId: bookUid, remove });
+    }
+
     async moveBook(bookUid: UUID, newPath: string[]) {
         await invoke("move_book", { bookId: bookUid, path: newPath });
     }
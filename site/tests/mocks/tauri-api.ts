@@ -29,6 +29,7 @@ type Model = {
 
 type Config = {
   targetLanguageId?: string;
+  defaultSourceLanguageId?: string | null;
   translationProvider: TranslationProvider;
   geminiApiKey?: string;
   openaiApiKey?: string;
@@ -93,6 +94,7 @@ type MockParagraph = {
 type ChapterMetaView = {
   id: number;
   title: string;
+  paragraphCount: number;
   translationRatio: number;
 };
 
@@ -892,11 +894,17 @@ export function invoke<T>(cmd: string, args?: InvokeArgs): Promise<T> {
         chaptersCount: book.chaptersCount,
         paragraphsCount: book.paragraphsCount,
         translationRatio: book.translationRatio,
+        translationRatios: {},
+        translationLastModified: null,
+        incomplete: false,
         path: book.path,
       }));
       return Promise.resolve(books as T);
     }
 
+    case 'get_books_count':
+      return Promise.resolve(mockLibrary.size as T);
+
     case 'import_epub': {
       const id = `mock-book-${++bookIdCounter}`;
       // Frontend ships `{ title, chapters: [{ title, paragraphs: [{ html }] }] }`.
@@ -983,6 +991,7 @@ export function invoke<T>(cmd: string, args?: InvokeArgs): Promise<T> {
         return {
           id: idx,
           title: chapter.title || `Chapter ${idx + 1}`,
+          paragraphCount: total,
           translationRatio: total === 0 ? 0 : translated / total,
         };
       });